@@ -0,0 +1,49 @@
+//! Move generation benchmarks, a slower result than the saved baseline is reported as a regression.
+//!
+//! Usage: `cargo bench --bench movegen`
+
+use bitschess::bench::{BENCH_POSITIONS, PERFT_SUITE};
+use bitschess::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+fn legal_moves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("legal_moves");
+    for (idx, fen) in BENCH_POSITIONS.iter().enumerate() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(fen).expect("valid fen");
+        group.bench_with_input(BenchmarkId::from_parameter(idx), &board, |b, board| b.iter(|| board.get_legal_moves()));
+    }
+    group.finish();
+}
+
+fn perft(c: &mut Criterion) {
+    let mut group = c.benchmark_group("perft");
+    group.sample_size(10);
+    for (idx, &(fen, depth, nodes)) in PERFT_SUITE.iter().enumerate() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(fen).expect("valid fen");
+        group.throughput(Throughput::Elements(nodes));
+        group.bench_function(BenchmarkId::from_parameter(idx), |b| b.iter(|| board.perft(depth, false)));
+    }
+    group.finish();
+}
+
+fn perft_divide(c: &mut Criterion) {
+    let mut board = ChessBoard::new();
+    board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    c.bench_function("perft_divide", |b| b.iter(|| board.perft_divide(3)));
+}
+
+fn perft_hashed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("perft_hashed");
+    group.sample_size(10);
+    let (fen, depth, nodes) = PERFT_SUITE[1];
+    let mut board = ChessBoard::new();
+    board.parse_fen(fen).expect("valid fen");
+    group.throughput(Throughput::Elements(nodes));
+    group.bench_function("kiwipete", |b| b.iter(|| board.perft_hashed(depth, 1024, false)));
+    group.finish();
+}
+
+criterion_group!(benches, legal_moves, perft, perft_divide, perft_hashed);
+criterion_main!(benches);
@@ -0,0 +1,156 @@
+//! A clickable board drawn with egui on top of [BoardUiAdapter]: click a piece to see its moves and click a target to move it.
+//! The previous move and a king in check are highlighted, promotions ask for the piece.
+//!
+//! Usage: `cargo run --release --example egui_board --features egui`
+
+use bitschess::prelude::*;
+use eframe::egui;
+
+const LIGHT: egui::Color32 = egui::Color32::from_rgb(240, 217, 181);
+const DARK: egui::Color32 = egui::Color32::from_rgb(181, 136, 99);
+const LAST_MOVE: egui::Color32 = egui::Color32::from_rgba_premultiplied(155, 199, 0, 105);
+const SELECTED: egui::Color32 = egui::Color32::from_rgba_premultiplied(20, 85, 30, 120);
+const CHECK: egui::Color32 = egui::Color32::from_rgba_premultiplied(200, 30, 30, 160);
+
+struct BoardApp {
+    board: ChessBoard,
+    selected: Option<i32>,
+    /// (from, to) of a promotion waiting for the piece to be picked.
+    promotion: Option<(i32, i32)>,
+}
+
+impl BoardApp {
+    fn new() -> Self {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        Self { board, selected: None, promotion: None }
+    }
+
+    fn click(&mut self, square: i32) {
+        if self.promotion.is_some() {
+            return;
+        }
+
+        if let Some(from) = self.selected.take() {
+            if self.board.legal_targets(from) & (1u64 << square) != 0 {
+                if self.board.is_promotion(from, square) {
+                    self.promotion = Some((from, square));
+                } else {
+                    self.board.apply_move(from, square, None).expect("legal target");
+                }
+                return;
+            }
+        }
+
+        let piece = self.board.piece_at(square);
+        if !piece.is_none() && piece.get_color() == self.board.side_to_move() {
+            self.selected = Some(square);
+        }
+    }
+
+    fn draw_board(&mut self, ui: &mut egui::Ui) {
+        let size = ui.available_width().min(ui.available_height());
+        let (response, painter) = ui.allocate_painter(egui::Vec2::splat(size), egui::Sense::click());
+        let origin = response.rect.min;
+        let square_size = size / 8.0;
+        // White at the bottom, a1 in the lower left corner
+        let square_rect = |square: i32| {
+            let (file, rank) = (square % 8, 7 - square / 8);
+            let min = origin + egui::vec2(file as f32 * square_size, rank as f32 * square_size);
+            egui::Rect::from_min_size(min, egui::Vec2::splat(square_size))
+        };
+
+        let targets = self.selected.map_or(0, |square| self.board.legal_targets(square));
+        let last_move = self.board.last_move();
+        for square in 0..64 {
+            let rect = square_rect(square);
+            let light = (square % 8 + square / 8) % 2 == 1;
+            painter.rect_filled(rect, 0.0, if light { LIGHT } else { DARK });
+
+            if last_move.is_some_and(|m| m.get_from_idx() == square || m.get_to_idx() == square) {
+                painter.rect_filled(rect, 0.0, LAST_MOVE);
+            }
+            if self.selected == Some(square) {
+                painter.rect_filled(rect, 0.0, SELECTED);
+            }
+            if self.board.checked_king() == Some(square) {
+                painter.circle_filled(rect.center(), square_size * 0.48, CHECK);
+            }
+
+            let piece = self.board.piece_at(square);
+            if !piece.is_none() {
+                let (fill, text) = if piece.get_color() == PieceColor::White {
+                    (egui::Color32::WHITE, egui::Color32::BLACK)
+                } else {
+                    (egui::Color32::BLACK, egui::Color32::WHITE)
+                };
+                painter.circle(rect.center(), square_size * 0.38, fill, egui::Stroke::new(1.5, egui::Color32::DARK_GRAY));
+                let letter = piece.to_char().to_ascii_uppercase().to_string();
+                painter.text(rect.center(), egui::Align2::CENTER_CENTER, letter, egui::FontId::proportional(square_size * 0.45), text);
+            }
+            if targets & (1u64 << square) != 0 {
+                painter.circle_filled(rect.center(), square_size * 0.12, SELECTED);
+            }
+        }
+
+        if response.clicked() {
+            if let Some(position) = response.interact_pointer_pos() {
+                let offset = (position - origin) / square_size;
+                let (file, rank) = (offset.x as i32, 7 - offset.y as i32);
+                if (0..8).contains(&file) && (0..8).contains(&rank) {
+                    self.click(rank * 8 + file);
+                }
+            }
+        }
+    }
+
+    fn draw_status(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if let Some((from, to)) = self.promotion {
+                ui.label("Promote to:");
+                for (name, piece_type) in [("Queen", PieceType::Queen), ("Rook", PieceType::Rook), ("Bishop", PieceType::Bishop), ("Knight", PieceType::Knight)] {
+                    if ui.button(name).clicked() {
+                        self.board.apply_move(from, to, Some(piece_type)).expect("legal promotion");
+                        self.promotion = None;
+                    }
+                }
+                if ui.button("Cancel").clicked() {
+                    self.promotion = None;
+                }
+                return;
+            }
+
+            let state = match self.board.get_game_state() {
+                GameState::Ongoing => { format!("{:?} to move", self.board.side_to_move()) }
+                state => { format!("{state:?}") }
+            };
+            ui.label(state);
+            if ui.add_enabled(!self.board.get_move_history().is_empty(), egui::Button::new("Undo")).clicked() {
+                self.selected = None;
+                self.board.undo();
+            }
+            if ui.add_enabled(!self.board.redo_moves().is_empty(), egui::Button::new("Redo")).clicked() {
+                self.selected = None;
+                self.board.redo();
+            }
+            if ui.button("New game").clicked() {
+                *self = Self::new();
+            }
+        });
+    }
+}
+
+impl eframe::App for BoardApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::TopBottomPanel::bottom("status").show(ctx, |ui| self.draw_status(ui));
+        egui::CentralPanel::default().show(ctx, |ui| self.draw_board(ui));
+    }
+}
+
+fn main() -> eframe::Result {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([480.0, 520.0]),
+        ..Default::default()
+    };
+    eframe::run_native("BitsChess", options, Box::new(|_| Ok(Box::new(BoardApp::new()))))
+}
@@ -0,0 +1,34 @@
+//! Skeleton of an engine built on top of the crate.
+//! Without arguments it speaks UCI on stdin/stdout, with a FEN argument it prints the best move of that position.
+//!
+//! Usage: `cargo run --release --example engine -- [fen]`
+
+use std::sync::atomic::AtomicBool;
+
+use bitschess::prelude::*;
+use bitschess::search::{search, SearchLimits};
+use bitschess::Uci;
+
+fn main() {
+    let fen = std::env::args().skip(1).collect::<Vec<String>>().join(" ");
+    if fen.is_empty() {
+        Uci::new().run();
+        return;
+    }
+
+    let mut board = ChessBoard::new();
+    if let Err(e) = board.parse_fen(&fen) {
+        eprintln!("invalid fen '{fen}': {e:?}");
+        std::process::exit(1);
+    }
+
+    let limits = SearchLimits { depth: Some(5), ..Default::default() };
+    let result = search(&mut board, limits, &AtomicBool::new(false));
+    let pv: Vec<String> = result.pv.iter().map(|m| m.to_uci()).collect();
+    println!("score {} depth {} nodes {}", result.score, result.depth, result.nodes);
+    println!("pv {}", pv.join(" "));
+    match result.best {
+        Some(best) => { println!("bestmove {}", board.move_to_san(best)); }
+        None => { println!("no legal moves: {:?}", board.get_game_state()); }
+    }
+}
@@ -0,0 +1,39 @@
+//! Validates FENs read from stdin, one per line, and reports the errors and counter inconsistencies.
+//!
+//! Usage: `echo "<fen>" | cargo run --example fen_validator`
+
+use std::io::BufRead;
+
+use bitschess::prelude::*;
+
+fn main() {
+    let mut board = ChessBoard::new();
+    let mut invalid = 0;
+
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else { break; };
+        let fen = line.trim();
+        if fen.is_empty() {
+            continue;
+        }
+
+        let options = FenParseOptions { normalize_counters: true, lenient: true };
+        match board.parse_fen_with_options(fen, options) {
+            Ok(warnings) if warnings.is_empty() => {
+                println!("ok      {fen}");
+            }
+            Ok(warnings) => {
+                println!("warning {fen}: {warnings:?}");
+                println!("        normalized: {}", board.to_fen());
+            }
+            Err(e) => {
+                println!("error   {fen}: {e:?}");
+                invalid += 1;
+            }
+        }
+    }
+
+    if invalid > 0 {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,84 @@
+//! Adapter between a click based GUI and the board: the first click selects a piece and highlights its moves,
+//! the second one moves it. Promotions default to a queen.
+//!
+//! Usage: `cargo run --example gui_adapter -- [square ...]`, e.g. `-- e2 e4 e7 e5`
+
+use bitschess::prelude::*;
+
+#[derive(Debug, PartialEq, Eq)]
+enum ClickEvent {
+    /// A piece was selected, contains the squares it can move to.
+    Selected(Vec<i32>),
+    Moved(Move),
+    Deselected,
+    Ignored,
+}
+
+struct ClickAdapter {
+    board: ChessBoard,
+    selected: Option<i32>,
+}
+
+impl ClickAdapter {
+    fn new() -> Self {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        Self { board, selected: None }
+    }
+
+    fn click(&mut self, square: i32) -> ClickEvent {
+        if let Some(from) = self.selected.take() {
+            if self.board.legal_targets(from) & (1u64 << square) != 0 {
+                let promotion = self.board.is_promotion(from, square).then_some(PieceType::Queen);
+                let m = self.board.apply_move(from, square, promotion).expect("legal target");
+                return ClickEvent::Moved(m);
+            }
+            if from == square {
+                return ClickEvent::Deselected;
+            }
+        }
+
+        let piece = self.board.piece_at(square);
+        if piece.is_none() || piece.get_color() != self.board.side_to_move() {
+            return ClickEvent::Ignored;
+        }
+        self.selected = Some(square);
+        let mut targets = self.board.legal_targets(square);
+        let mut squares = vec![];
+        while targets != 0 {
+            squares.push(BoardHelper::pop_lsb(&mut targets));
+        }
+        ClickEvent::Selected(squares)
+    }
+}
+
+fn main() {
+    let clicks: Vec<String> = std::env::args().skip(1).collect();
+    let clicks = if clicks.is_empty() {
+        ["e2", "e4", "g8", "f6", "e4", "e5", "d7", "d5", "e5", "d6"].map(String::from).to_vec()
+    } else {
+        clicks
+    };
+
+    let mut adapter = ClickAdapter::new();
+    for click in &clicks {
+        let square = match BoardHelper::text_to_square(click) {
+            Ok(square) => { square as i32 }
+            Err(e) => {
+                println!("{click}: {e}");
+                continue;
+            }
+        };
+
+        match adapter.click(square) {
+            ClickEvent::Selected(targets) => {
+                let targets: Vec<String> = targets.into_iter().map(BoardHelper::square_to_string).collect();
+                println!("{click}: selected, can move to {targets:?}");
+            }
+            ClickEvent::Moved(m) => { println!("{click}: moved {}", m.to_uci()); }
+            ClickEvent::Deselected => { println!("{click}: deselected"); }
+            ClickEvent::Ignored => { println!("{click}: ignored"); }
+        }
+    }
+    println!("{}", adapter.board);
+}
@@ -0,0 +1,38 @@
+//! Counts the leaf nodes of the move tree, the standard way of verifying move generation.
+//!
+//! Usage: `cargo run --release --example perft -- <depth> [fen]`
+
+use bitschess::prelude::*;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(Ok(depth)) = args.first().map(|d| d.parse::<u32>()) else {
+        eprintln!("usage: perft <depth> [fen]");
+        std::process::exit(1);
+    };
+    let fen = if args.len() > 1 { args[1..].join(" ") } else { STARTPOS_FEN.to_string() };
+
+    let mut board = ChessBoard::new();
+    if let Err(e) = board.parse_fen(&fen) {
+        eprintln!("invalid fen '{fen}': {e:?}");
+        std::process::exit(1);
+    }
+    if depth == 0 {
+        println!("Nodes searched: 1");
+        return;
+    }
+
+    // Divide, nodes for each of the root moves
+    for m in board.get_legal_moves() {
+        board.make_move(m, true);
+        let nodes = if depth == 1 { 1 } else { board.perft(depth - 1, false) };
+        let _ = board.unmake_move();
+        println!("{}: {nodes}", m.to_uci());
+    }
+
+    let threads = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+    let begin = std::time::Instant::now();
+    let nodes = board.perft_parallel(depth, threads);
+    println!();
+    println!("Nodes searched: {nodes} ({threads} threads, {:?})", begin.elapsed());
+}
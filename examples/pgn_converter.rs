@@ -0,0 +1,47 @@
+//! Converts PGN games read from stdin into UCI move lists and the final position of every game.
+//!
+//! Usage: `cargo run --example pgn_converter < games.pgn`
+
+use std::io::Read;
+
+use bitschess::prelude::*;
+
+fn main() {
+    let mut contents = String::new();
+    if let Err(e) = std::io::stdin().read_to_string(&mut contents) {
+        eprintln!("couldn't read stdin: {e}");
+        std::process::exit(1);
+    }
+
+    let mut board = ChessBoard::new();
+    for (i, game) in PgnParser::new(&contents).enumerate() {
+        let game = match game {
+            Ok(game) => { game }
+            Err(e) => {
+                eprintln!("game {}: {e:?}", i + 1);
+                std::process::exit(1);
+            }
+        };
+
+        let fen = game.get_tag("FEN").cloned().unwrap_or_else(|| STARTPOS_FEN.to_string());
+        if let Err(e) = board.parse_fen(&fen) {
+            eprintln!("game {}: invalid fen '{fen}': {e:?}", i + 1);
+            continue;
+        }
+
+        let mut uci_moves = vec![];
+        for san in game.get_moves() {
+            match board.make_move_pgn(san) {
+                Ok(m) => { uci_moves.push(m.to_uci()); }
+                Err(e) => {
+                    eprintln!("game {}: couldn't play '{san}': {e:?}", i + 1);
+                    break;
+                }
+            }
+        }
+
+        println!("game {}: {}", i + 1, uci_moves.join(" "));
+        println!("fen: {}", board.to_fen());
+        println!("state: {:?}", board.get_game_state());
+    }
+}
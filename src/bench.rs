@@ -0,0 +1,272 @@
+//! Fixed position benchmark, the searched node count acts as a signature for verifying that refactors
+//! don't change the behaviour of the search. [run_pgn] measures the throughput of importing PGN games.
+//!
+//! [perft_suite] and [movegen_nps] measure the speed of move generation, they're also run by the criterion
+//! benches with `cargo bench`.
+
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+use crate::bitschess::board::ChessBoard;
+use crate::bitschess::board::pgn::PgnParser;
+use crate::fixtures;
+use crate::search::{search, SearchLimits};
+
+/// Default depth used by [run], keeps the whole suite at about a second on release builds.
+pub const BENCH_DEPTH: u32 = 9;
+
+/// Positions used by the benchmark, changing these changes the signature!
+pub const BENCH_POSITIONS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "r1bq1rk1/pp2ppbp/2np1np1/8/3NP3/2N1BP2/PPPQ2PP/R3KB1R w KQ - 3 9",
+    "8/k7/3p4/p2P1p2/P2P1P2/8/8/K7 w - - 0 1",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchResult {
+    /// Nodes searched in each of the [BENCH_POSITIONS].
+    pub position_nodes: Vec<u64>,
+    /// The signature, sum of all the nodes.
+    pub nodes: u64,
+    pub duration: Duration,
+}
+
+impl BenchResult {
+    /// Nodes per second.
+    #[must_use]
+    pub fn nps(&self) -> u64 {
+        let micros = self.duration.as_micros().max(1);
+        ((self.nodes as u128) * 1_000_000 / micros) as u64
+    }
+}
+
+/// Searches every position of [BENCH_POSITIONS] to `depth` with the default [SearchOptions](crate::search::SearchOptions).
+/// The search has no time limit, so the node counts are the same on every run.
+///
+/// # Examples
+/// ```rust
+/// let result = bitschess::bench::run(2);
+/// assert_eq!(result.position_nodes, bitschess::bench::run(2).position_nodes);
+/// assert_eq!(result.nodes, result.position_nodes.iter().sum::<u64>());
+/// ```
+#[must_use]
+pub fn run(depth: u32) -> BenchResult {
+    let mut board = ChessBoard::new();
+    let mut position_nodes = Vec::with_capacity(BENCH_POSITIONS.len());
+
+    let begin = Instant::now();
+    for fen in BENCH_POSITIONS {
+        board.parse_fen(fen).expect("valid fen");
+        let limits = SearchLimits { depth: Some(depth), ..Default::default() };
+        position_nodes.push(search(&mut board, limits, &AtomicBool::new(false)).nodes);
+    }
+    let duration = begin.elapsed();
+
+    BenchResult {
+        nodes: position_nodes.iter().sum(),
+        position_nodes,
+        duration,
+    }
+}
+
+/// Standard perft positions with their depth and node count used by [perft_suite].
+/// <https://www.chessprogramming.org/Perft_Results>
+pub const PERFT_SUITE: [(&str, u32, u64); 6] = [
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 4, 197_281),
+    ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 3, 97_862),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 5, 674_624),
+    ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 3, 9_467),
+    ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", 3, 62_379),
+    ("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", 3, 89_890),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PerftResult {
+    pub fen: &'static str,
+    pub depth: u32,
+    pub nodes: u64,
+    /// Node count the position should have, see [PERFT_SUITE].
+    pub expected: u64,
+    pub duration: Duration,
+}
+
+impl PerftResult {
+    /// Nodes per second.
+    #[must_use]
+    pub fn nps(&self) -> u64 {
+        let micros = self.duration.as_micros().max(1);
+        ((self.nodes as u128) * 1_000_000 / micros) as u64
+    }
+
+    /// If the node count matches the expected one, a mismatch is a bug in the move generation.
+    #[must_use]
+    pub const fn is_correct(&self) -> bool {
+        self.nodes == self.expected
+    }
+}
+
+/// Runs perft on every position of [PERFT_SUITE] and times each of them.
+///
+/// # Examples
+/// ```rust
+/// let results = bitschess::bench::perft_suite();
+/// assert!(results.iter().all(|result| result.is_correct()));
+/// for result in &results {
+///     println!("{}: {} nodes, {} nps", result.fen, result.nodes, result.nps());
+/// }
+/// ```
+#[must_use]
+pub fn perft_suite() -> Vec<PerftResult> {
+    let mut board = ChessBoard::new();
+    PERFT_SUITE.iter().map(|&(fen, depth, expected)| {
+        board.parse_fen(fen).expect("valid fen");
+        let begin = Instant::now();
+        let nodes = board.perft(depth, false);
+        PerftResult { fen, depth, nodes, expected, duration: begin.elapsed() }
+    }).collect()
+}
+
+/// Depth of the perft repeated by [movegen_nps], low enough for checking the time often.
+const MOVEGEN_NPS_DEPTH: u32 = 3;
+
+/// Leaf nodes per second of move generation on the `board`, repeating a perft of the position for about `seconds`.
+/// Returns 0 if the position has no legal moves.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// let mut board = ChessBoard::new();
+/// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+/// assert!(bitschess::bench::movegen_nps(&board, 0.05) > 0);
+/// ```
+#[must_use]
+pub fn movegen_nps(board: &ChessBoard, seconds: f64) -> u64 {
+    let mut board = board.clone();
+    let limit = Duration::from_secs_f64(seconds.max(0.0));
+
+    let mut nodes = 0u64;
+    let begin = Instant::now();
+    loop {
+        let perft = board.perft(MOVEGEN_NPS_DEPTH, false);
+        nodes += perft;
+        if perft == 0 || begin.elapsed() >= limit {
+            break;
+        }
+    }
+
+    let micros = begin.elapsed().as_micros().max(1);
+    ((nodes as u128) * 1_000_000 / micros) as u64
+}
+
+/// Copies of every [fixtures] game in the default corpus of [run_pgn].
+pub const PGN_BENCH_COPIES: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgnBenchResult {
+    pub games: usize,
+    /// Moves played over all the games.
+    pub moves: usize,
+    /// Games which couldn't be parsed or had an illegal move.
+    pub errors: usize,
+    pub duration: Duration,
+}
+
+impl PgnBenchResult {
+    /// Games imported per second.
+    #[must_use]
+    pub fn games_per_second(&self) -> u64 {
+        let micros = self.duration.as_micros().max(1);
+        ((self.games as u128) * 1_000_000 / micros) as u64
+    }
+}
+
+/// A multi-game PGN of every [fixtures] game repeated `copies` times.
+#[must_use]
+pub fn pgn_corpus(copies: usize) -> String {
+    let mut corpus = String::new();
+    for _ in 0..copies {
+        for fixture in fixtures::GAMES {
+            corpus.push_str(fixture.pgn);
+            corpus.push_str("\n\n");
+        }
+    }
+    corpus
+}
+
+/// Parses every game of the `pgn` and plays its moves on a board, like importing the games into a database would.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::bench::{pgn_corpus, run_pgn};
+/// let result = run_pgn(&pgn_corpus(2));
+/// assert_eq!(result.games, 10);
+/// assert_eq!(result.errors, 0);
+/// ```
+#[must_use]
+pub fn run_pgn(pgn: &str) -> PgnBenchResult {
+    let mut board = ChessBoard::new();
+    let mut result = PgnBenchResult { games: 0, moves: 0, errors: 0, duration: Duration::ZERO };
+
+    let begin = Instant::now();
+    for game in PgnParser::new(pgn) {
+        result.games += 1;
+        let Ok(game) = game else {
+            result.errors += 1;
+            continue;
+        };
+
+        if board.parse_fen(game.starting_fen()).is_err() {
+            result.errors += 1;
+            continue;
+        }
+        for san in game.get_moves() {
+            if board.make_move_pgn(san).is_err() {
+                result.errors += 1;
+                break;
+            }
+            result.moves += 1;
+        }
+    }
+    result.duration = begin.elapsed();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_signature() {
+        let result = run(4);
+        assert_eq!(result.position_nodes, [772, 4828, 463, 2974, 1175, 13661, 1605, 113]);
+        assert_eq!(result.nodes, 25591);
+    }
+
+    #[test]
+    fn test_perft_suite() {
+        let results = perft_suite();
+        assert_eq!(results.len(), PERFT_SUITE.len());
+        for result in &results {
+            assert!(result.is_correct(), "{}: {} != {}", result.fen, result.nodes, result.expected);
+        }
+
+        let mut board = ChessBoard::new();
+        board.parse_fen("k7/1Q6/1K6/8/8/8/8/8 b - - 0 1").expect("valid fen");
+        assert_eq!(movegen_nps(&board, 1.0), 0);
+    }
+
+    #[test]
+    fn test_bench_pgn() {
+        let result = run_pgn(&pgn_corpus(3));
+        let moves: usize = fixtures::GAMES.iter().map(|fixture| fixtures::pgn(fixture.name).expect("known game").get_moves().len()).sum();
+        assert_eq!((result.games, result.moves, result.errors), (15, 3 * moves, 0));
+
+        let result = run_pgn("1. e4 e5 2. Ke3 *\n\n1. d4 {unterminated");
+        assert_eq!((result.games, result.moves, result.errors), (2, 2, 2));
+    }
+}
@@ -12,7 +12,9 @@ pub const HG_FILE: u64 = H_FILE | G_FILE;
 pub const NOT_H_FILE: u64 = !H_FILE;
 pub const NOT_HG_FILE: u64 = !HG_FILE;
 
-use crate::piece::PieceColor;
+use crate::bitschess::board::magics::{get_bishop_magic, get_rook_magic};
+use crate::piece::{PieceColor, PieceType};
+use alloc::string::{String, ToString};
 
 pub const PAWN_ATTACKS: [[u64; 64]; 2] = {
     let mut map = [[0; 64]; 2];
@@ -23,6 +25,17 @@ pub const PAWN_ATTACKS: [[u64; 64]; 2] = {
     map
 };
 
+/// Squares on the adjacent files in front of a pawn, see [BitBoard::attack_span].
+/// Indexed by `[color][square]`.
+pub const PAWN_ATTACK_SPANS: [[u64; 64]; 2] = {
+    let mut map = [[0; 64]; 2];
+    const_for!(square in 0..64 => {
+        map[0][square] = BitBoard::attack_span(square as i32, PieceColor::White);
+        map[1][square] = BitBoard::attack_span(square as i32, PieceColor::Black);
+    });
+    map
+};
+
 pub const KNIGHT_ATTACKS: [u64; 64] = {
     let mut map = [0; 64];
     const_for!(square in 0..64 => {
@@ -64,7 +77,267 @@ pub const BETWEENS: [[u64; 64]; 64] = {
     map
 };
 
-pub struct BitBoard;
+/// Every square on the rank, file or diagonal going through both squares from edge to edge, including the squares themselves.
+/// Empty if the squares aren't on the same line, see [BETWEENS] for only the squares in between.
+#[allow(clippy::large_const_arrays)]
+pub const LINE: [[u64; 64]; 64] = {
+    let mut map = [[0u64; 64]; 64];
+
+    const_for!(from in 0..64 => {
+        const_for!(to in 0..64 => {
+            let ends = (1u64 << from) | (1u64 << to);
+            if from == to {
+                // Not a line
+            }
+            else if (BitBoard::get_bishop_attack_mask(from, 0) & (1u64 << to)) != 0 {
+                map[from as usize][to as usize] = (BitBoard::get_bishop_attack_mask(from, 0) & BitBoard::get_bishop_attack_mask(to, 0)) | ends;
+            }
+            else if (BitBoard::get_rook_attack_mask(from, 0) & (1u64 << to)) != 0 {
+                map[from as usize][to as usize] = (BitBoard::get_rook_attack_mask(from, 0) & BitBoard::get_rook_attack_mask(to, 0)) | ends;
+            }
+        });
+    });
+
+    map
+};
+
+/// Chebyshev distance between the squares, i.e. the amount of king moves, see [BitBoard::distance].
+pub const DISTANCES: [[u8; 64]; 64] = {
+    let mut map = [[0u8; 64]; 64];
+    const_for!(from in 0..64 => {
+        const_for!(to in 0..64 => {
+            let files = (from as i32 % 8 - to as i32 % 8).abs();
+            let ranks = (from as i32 / 8 - to as i32 / 8).abs();
+            map[from as usize][to as usize] = if files > ranks { files as u8 } else { ranks as u8 };
+        });
+    });
+    map
+};
+
+/// Manhattan distance between the squares, i.e. the amount of rook moves on an empty board along single steps, see [BitBoard::manhattan_distance].
+pub const MANHATTAN_DISTANCES: [[u8; 64]; 64] = {
+    let mut map = [[0u8; 64]; 64];
+    const_for!(from in 0..64 => {
+        const_for!(to in 0..64 => {
+            let files = (from as i32 % 8 - to as i32 % 8).abs();
+            let ranks = (from as i32 / 8 - to as i32 / 8).abs();
+            map[from as usize][to as usize] = (files + ranks) as u8;
+        });
+    });
+    map
+};
+
+/// One of the 8 directions a queen can move to, from White's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North, Direction::NorthEast, Direction::East, Direction::SouthEast,
+        Direction::South, Direction::SouthWest, Direction::West, Direction::NorthWest,
+    ];
+
+    /// Added to a square index for moving a single step in the direction.
+    #[must_use]
+    pub const fn offset(self) -> i32 {
+        match self {
+            Direction::North => { 8 }
+            Direction::NorthEast => { 9 }
+            Direction::East => { 1 }
+            Direction::SouthEast => { -7 }
+            Direction::South => { -8 }
+            Direction::SouthWest => { -9 }
+            Direction::West => { -1 }
+            Direction::NorthWest => { 7 }
+        }
+    }
+
+    #[must_use]
+    pub const fn is_diagonal(self) -> bool {
+        matches!(self, Direction::NorthEast | Direction::SouthEast | Direction::SouthWest | Direction::NorthWest)
+    }
+
+    #[must_use]
+    pub const fn opposite(self) -> Direction {
+        match self {
+            Direction::North => { Direction::South }
+            Direction::NorthEast => { Direction::SouthWest }
+            Direction::East => { Direction::West }
+            Direction::SouthEast => { Direction::NorthWest }
+            Direction::South => { Direction::North }
+            Direction::SouthWest => { Direction::NorthEast }
+            Direction::West => { Direction::East }
+            Direction::NorthWest => { Direction::SouthEast }
+        }
+    }
+}
+
+/// A set of squares, the bit `n` is set for the square `n` (0 = a1, 63 = h8).
+/// The bitboard helpers taking raw `u64`s are associated functions of it as well.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// let mut board = ChessBoard::new();
+/// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+/// let knights = board.bitboards[PieceType::Knight.get_side_index(PieceColor::White)];
+/// assert_eq!(knights.count(), 2);
+/// assert_eq!(knights.iter_squares().collect::<Vec<i32>>(), [Square::B1 as i32, Square::G1 as i32]);
+/// assert_eq!(knights & board.side_bitboards[PieceColor::White as usize], knights);
+/// assert!((knights & board.side_bitboards[PieceColor::Black as usize]).is_empty());
+/// assert_eq!(u64::from(!BitBoard::EMPTY), u64::MAX);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct BitBoard(pub u64);
+
+impl BitBoard {
+    pub const EMPTY: Self = Self(0);
+
+    /// Bitboard with only the `square` set.
+    #[must_use]
+    #[inline(always)]
+    pub const fn from_square(square: i32) -> Self {
+        Self(1u64 << square)
+    }
+
+    /// Number of squares set.
+    #[must_use]
+    #[inline(always)]
+    pub const fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn contains(self, square: i32) -> bool {
+        self.0 & (1u64 << square) != 0
+    }
+
+    /// The squares set, from a1 to h8.
+    #[must_use]
+    #[inline(always)]
+    pub const fn iter_squares(self) -> Squares {
+        Squares(self.0)
+    }
+}
+
+/// Iterator over the squares of a [BitBoard], see [BitBoard::iter_squares].
+#[derive(Debug, Clone)]
+pub struct Squares(u64);
+
+impl Iterator for Squares {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+        let square = self.0.trailing_zeros() as i32;
+        self.0 &= self.0 - 1;
+        Some(square)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.0.count_ones() as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Squares {}
+
+impl From<u64> for BitBoard {
+    #[inline(always)]
+    fn from(bits: u64) -> Self {
+        Self(bits)
+    }
+}
+
+impl From<BitBoard> for u64 {
+    #[inline(always)]
+    fn from(bitboard: BitBoard) -> Self {
+        bitboard.0
+    }
+}
+
+impl core::ops::BitAnd for BitBoard {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl core::ops::BitOr for BitBoard {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl core::ops::BitXor for BitBoard {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitxor(self, rhs: Self) -> Self {
+        Self(self.0 ^ rhs.0)
+    }
+}
+
+impl core::ops::Not for BitBoard {
+    type Output = Self;
+
+    #[inline(always)]
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+impl core::ops::BitAndAssign for BitBoard {
+    #[inline(always)]
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl core::ops::BitOrAssign for BitBoard {
+    #[inline(always)]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl core::ops::BitXorAssign for BitBoard {
+    #[inline(always)]
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.0 ^= rhs.0;
+    }
+}
+
+impl core::fmt::Display for BitBoard {
+    fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        formatter.pad(Self::pretty(self.0).as_str())
+    }
+}
 
 impl BitBoard {
     pub fn pretty(bits: u64) -> String {
@@ -85,6 +358,204 @@ impl BitBoard {
         str
     }
 
+    /// Squares attacked by a `piece_type` of `color` standing on the `square`, sliding pieces are blocked by the pieces in `occupancy`.
+    /// The piece doesn't need to be on the board, the `color` only matters for pawns.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let e4 = Square::E4 as i32;
+    /// let pawn = BitBoard::attacks(PieceType::Pawn, PieceColor::Black, e4, 0);
+    /// assert_eq!(pawn, (1u64 << Square::D3 as u32) | (1u64 << Square::F3 as u32));
+    ///
+    /// // Blocked by the piece on e6, which is attacked
+    /// let rook = BitBoard::attacks(PieceType::Rook, PieceColor::White, e4, 1u64 << Square::E6 as u32);
+    /// assert_eq!(rook.count_ones(), 7 + 3 + 2);
+    /// assert_eq!(BitBoard::attacks(PieceType::Queen, PieceColor::White, e4, 0), BitBoard::attacks(PieceType::Rook, PieceColor::White, e4, 0) | BitBoard::attacks(PieceType::Bishop, PieceColor::White, e4, 0));
+    /// ```
+    #[must_use]
+    pub const fn attacks(piece_type: PieceType, color: PieceColor, square: i32, occupancy: u64) -> u64 {
+        match piece_type {
+            PieceType::Pawn => { PAWN_ATTACKS[color as usize][square as usize] }
+            PieceType::Knight => { KNIGHT_ATTACKS[square as usize] }
+            PieceType::Bishop => { get_bishop_magic(square, occupancy) }
+            PieceType::Rook => { get_rook_magic(square, occupancy) }
+            PieceType::Queen => { get_bishop_magic(square, occupancy) | get_rook_magic(square, occupancy) }
+            PieceType::King => { KING_ATTACKS[square as usize] }
+            PieceType::None => { 0 }
+        }
+    }
+
+    /// Chebyshev distance, the amount of king moves between the squares.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// assert_eq!(BitBoard::distance(Square::A1 as i32, Square::H8 as i32), 7);
+    /// assert_eq!(BitBoard::distance(Square::E4 as i32, Square::F6 as i32), 2);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn distance(from: i32, to: i32) -> i32 {
+        DISTANCES[from as usize][to as usize] as i32
+    }
+
+    /// Manhattan distance, the amount of files plus the amount of ranks between the squares.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// assert_eq!(BitBoard::manhattan_distance(Square::A1 as i32, Square::H8 as i32), 14);
+    /// assert_eq!(BitBoard::manhattan_distance(Square::E4 as i32, Square::F6 as i32), 3);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn manhattan_distance(from: i32, to: i32) -> i32 {
+        MANHATTAN_DISTANCES[from as usize][to as usize] as i32
+    }
+
+    /// Direction of the line going from `from` to `to`, [None] if the squares are the same or not on the same rank, file or diagonal.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// assert_eq!(BitBoard::direction_between(Square::E1 as i32, Square::A5 as i32), Some(Direction::NorthWest));
+    /// assert_eq!(BitBoard::direction_between(Square::E1 as i32, Square::E8 as i32), Some(Direction::North));
+    /// assert_eq!(BitBoard::direction_between(Square::E1 as i32, Square::F3 as i32), None);
+    /// ```
+    #[must_use]
+    pub const fn direction_between(from: i32, to: i32) -> Option<Direction> {
+        let files = (to % 8) - (from % 8);
+        let ranks = (to / 8) - (from / 8);
+        if files == 0 && ranks == 0 {
+            return None;
+        }
+        if files != 0 && ranks != 0 && files.abs() != ranks.abs() {
+            return None;
+        }
+
+        let direction = match (files.signum(), ranks.signum()) {
+            (0, 1) => { Direction::North }
+            (1, 1) => { Direction::NorthEast }
+            (1, 0) => { Direction::East }
+            (1, -1) => { Direction::SouthEast }
+            (0, -1) => { Direction::South }
+            (-1, -1) => { Direction::SouthWest }
+            (-1, 0) => { Direction::West }
+            _ => { Direction::NorthWest }
+        };
+        Some(direction)
+    }
+
+    /// If the 3 squares are on the same rank, file or diagonal, see [LINE].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// // A piece on d2 pinned by a bishop on a5 against the king on e1 can still capture the bishop
+    /// assert!(BitBoard::aligned(Square::E1 as i32, Square::D2 as i32, Square::A5 as i32));
+    /// assert!(!BitBoard::aligned(Square::E1 as i32, Square::D2 as i32, Square::D3 as i32));
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn aligned(a: i32, b: i32, c: i32) -> bool {
+        (LINE[a as usize][b as usize] & (1u64 << c)) != 0
+    }
+
+    /// Flips the bitboard vertically, the 1st rank becomes the 8th.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let e2 = 1u64 << Square::E2 as u32;
+    /// assert_eq!(BitBoard::flip_vertical(e2), 1u64 << Square::E7 as u32);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn flip_vertical(bits: u64) -> u64 {
+        bits.swap_bytes()
+    }
+
+    /// Mirrors the bitboard horizontally, the a-file becomes the h-file.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let b2 = 1u64 << Square::B2 as u32;
+    /// assert_eq!(BitBoard::mirror_horizontal(b2), 1u64 << Square::G2 as u32);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn mirror_horizontal(bits: u64) -> u64 {
+        // Reversing the bits flips both ways, swapping the bytes undoes the vertical flip
+        bits.reverse_bits().swap_bytes()
+    }
+
+    /// Every bit is copied to all the squares above it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let e4 = 1u64 << Square::E4 as u32;
+    /// assert_eq!(BitBoard::fill_north(e4), 0x1010_1010_1000_0000);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn fill_north(mut bits: u64) -> u64 {
+        bits |= bits << 8;
+        bits |= bits << 16;
+        bits |= bits << 32;
+        bits
+    }
+
+    /// Every bit is copied to all the squares below it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let e4 = 1u64 << Square::E4 as u32;
+    /// assert_eq!(BitBoard::fill_south(e4), 0x0000_0000_1010_1010);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn fill_south(mut bits: u64) -> u64 {
+        bits |= bits >> 8;
+        bits |= bits >> 16;
+        bits |= bits >> 32;
+        bits
+    }
+
+    /// Squares in front of a pawn of `color` on the `square`, on its own file.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// assert_eq!(BitBoard::front_span(Square::E6 as i32, PieceColor::White), (1u64 << Square::E7 as u32) | (1u64 << Square::E8 as u32));
+    /// assert_eq!(BitBoard::front_span(Square::E2 as i32, PieceColor::Black), 1u64 << Square::E1 as u32);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn front_span(square: i32, color: PieceColor) -> u64 {
+        let bit = 1u64 << square;
+        if color.eq_const(PieceColor::White) { Self::fill_north(bit << 8) } else { Self::fill_south(bit >> 8) }
+    }
+
+    /// Squares on the adjacent files in front of a pawn of `color` on the `square`,
+    /// i.e. every square the pawn can attack while advancing.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let span = BitBoard::attack_span(Square::A6 as i32, PieceColor::White);
+    /// assert_eq!(span, (1u64 << Square::B7 as u32) | (1u64 << Square::B8 as u32));
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn attack_span(square: i32, color: PieceColor) -> u64 {
+        let front = Self::front_span(square, color);
+        ((front << 1) & NOT_A_FILE) | ((front >> 1) & NOT_H_FILE)
+    }
+
     const fn get_pawn_attack(side: PieceColor, square: i32) -> u64 {
         let mut attacks = 0u64;
         let bitboard = 1u64 << square;
@@ -253,3 +724,88 @@ impl BitBoard {
         attacks
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitboard_spans() {
+        // Nothing in front of a pawn on its last rank
+        assert_eq!(BitBoard::front_span(60, PieceColor::White), 0);
+        assert_eq!(BitBoard::front_span(4, PieceColor::Black), 0);
+        assert_eq!(BitBoard::attack_span(63, PieceColor::White), 0);
+
+        // Spans don't wrap around the edge files
+        assert_eq!(BitBoard::attack_span(7, PieceColor::White), BitBoard::front_span(6, PieceColor::White));
+        assert_eq!(BitBoard::attack_span(56, PieceColor::Black), BitBoard::front_span(57, PieceColor::Black));
+
+        assert_eq!(BitBoard::fill_north(0x81) | BitBoard::fill_south(0x8100_0000_0000_0000), A_FILE | (A_FILE << 7));
+    }
+
+    #[test]
+    fn test_lines_and_distances() {
+        for from in 0..64 {
+            for to in 0..64 {
+                let line = LINE[from as usize][to as usize];
+                assert_eq!(line, LINE[to as usize][from as usize]);
+                assert_eq!(line != 0, BitBoard::direction_between(from, to).is_some());
+                // Squares in between are on the line
+                assert_eq!(BETWEENS[from as usize][to as usize] & !line, 0);
+                assert_eq!(BitBoard::distance(from, to), BitBoard::distance(to, from));
+                assert!(BitBoard::distance(from, to) <= BitBoard::manhattan_distance(from, to));
+
+                if let Some(direction) = BitBoard::direction_between(from, to) {
+                    assert_eq!(BitBoard::direction_between(to, from), Some(direction.opposite()));
+                    assert_eq!(from + direction.offset() * BitBoard::distance(from, to), to);
+                    if !direction.is_diagonal() {
+                        assert_eq!(line.count_ones(), 8);
+                    }
+                }
+            }
+        }
+
+        // a1-h8 diagonal
+        assert_eq!(LINE[9][18], 0x8040_2010_0804_0201);
+        assert_eq!(LINE[0][7], 0xFF);
+        assert_eq!(LINE[0][0], 0);
+        assert_eq!(BitBoard::direction_between(0, 63), Some(Direction::NorthEast));
+        assert_eq!(Direction::ALL.iter().map(|direction| direction.offset()).sum::<i32>(), 0);
+    }
+
+    #[test]
+    fn test_bitboard_attacks() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        for square in 0..64 {
+            let occupancy = rng.u64(..) & rng.u64(..);
+            let queen = BitBoard::get_rook_attack_mask(square, occupancy) | BitBoard::get_bishop_attack_mask(square, occupancy);
+            assert_eq!(BitBoard::attacks(PieceType::Queen, PieceColor::Black, square, occupancy), queen);
+            assert_eq!(BitBoard::attacks(PieceType::Knight, PieceColor::White, square, occupancy), KNIGHT_ATTACKS[square as usize]);
+            assert_eq!(BitBoard::attacks(PieceType::King, PieceColor::White, square, occupancy), KING_ATTACKS[square as usize]);
+            assert_eq!(BitBoard::attacks(PieceType::None, PieceColor::White, square, occupancy), 0);
+        }
+    }
+
+    #[test]
+    fn test_bitboard_newtype() {
+        let corners = BitBoard::from_square(0) | BitBoard::from_square(7) | BitBoard::from_square(56) | BitBoard::from(1u64 << 63);
+        assert_eq!(corners.count(), 4);
+        assert_eq!(corners.iter_squares().len(), 4);
+        assert_eq!(corners.iter_squares().collect::<alloc::vec::Vec<i32>>(), [0, 7, 56, 63]);
+        assert!(corners.contains(63) && !corners.contains(62));
+
+        let a_file = BitBoard(A_FILE);
+        assert_eq!(corners & a_file, BitBoard::from_square(0) | BitBoard::from_square(56));
+        assert_eq!(corners ^ a_file, BitBoard(A_FILE ^ 0x8100_0000_0000_0081));
+        assert_eq!((!a_file & corners).count(), 2);
+
+        let mut bits = BitBoard::EMPTY;
+        bits |= a_file;
+        bits &= !BitBoard::from_square(0);
+        bits ^= BitBoard::from_square(8);
+        assert_eq!(u64::from(bits), A_FILE & !0x0101);
+        assert!(BitBoard::EMPTY.is_empty());
+        assert_eq!(BitBoard::EMPTY.iter_squares().next(), None);
+        assert_eq!(alloc::format!("{}", BitBoard::from_square(0)), BitBoard::pretty(1));
+    }
+}
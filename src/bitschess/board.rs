@@ -1,28 +1,93 @@
+pub mod attack_maps;
+pub mod builder;
+pub mod crazyhouse;
+pub mod diff;
+pub mod edit;
 pub mod fen;
+pub mod game_state;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod magics;
+pub mod material;
 pub mod move_generation;
+#[cfg(feature = "std")]
+pub mod notation;
+pub mod observer;
+pub mod outposts;
 pub mod perft;
+#[cfg(feature = "std")]
 pub mod pgn;
-pub mod repetition_table;
+pub mod position_key;
+pub mod square_control;
+pub mod state;
+pub mod transform;
+pub mod transposition_table;
+pub mod ui_adapter;
+pub mod variant;
 pub mod zobrist;
 
-use move_generation::MoveGenerator;
-use repetition_table::RepetitionTable;
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+use crazyhouse::Pockets;
+use move_generation::{MoveGenInfo, MoveGenerator};
+use observer::Observers;
+use state::BoardState;
+use variant::VariantKind;
 use super::bitboard::BitBoard;
 
 use crate::board_helper::{BoardHelper, Square};
 use crate::chess_move::{Move, MoveFlag, ReversibleMove, MoveContainer};
 use crate::piece::{Piece, PieceType, PieceColor};
+use alloc::{format, string::{String, ToString}, sync::Arc, vec, vec::Vec};
 
 /// A Chessboard is 8x8 
 pub const CHESSBOARD_WIDTH: i32 = 8;
 
+/// Why a move given by the user couldn't be made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    /// The move is not legal in the current position.
+    IllegalMove,
+    /// More than 1 legal move matches the SAN move, e.g. "Nd7" when both knights can move to d7.
+    AmbiguousSan,
+    /// The move is not in the expected notation.
+    ParseError,
+    /// The piece being moved belongs to the side not to move.
+    WrongSideToMove,
+    /// A pawn move to the last rank without the piece to promote to, see [PromotionPolicy::Prompt].
+    MissingPromotion,
+}
+
+/// What [ChessBoard::make_move_uci_with] does when a pawn moves to the last rank without a promotion piece, e.g. "e7e8".
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PromotionPolicy {
+    /// The move is illegal, as in [ChessBoard::make_move_uci].
+    Reject,
+    /// Promotes to a queen, what most user interfaces expect.
+    #[default]
+    Queen,
+    /// Fails with [MoveError::MissingPromotion], so the user can be asked for the piece.
+    Prompt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawClaim {
+    ThreefoldRepetition,
+    FiftyMoveRule,
+}
+
+/// Read-only queries, such as move generation, SAN conversion and evaluation, take `&self`,
+/// so one board can be analyzed from many threads at once.
+/// Boards are `Send + Sync` and clones share the move history until it's changed, so cloning one for every search thread is cheap.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChessBoard {
     // Board representation
     // "masks" for every different type of piece
-    pub bitboards: [u64; 12], // 0 = white pawns, 1 = white knights ... 6 = black pawns, etc
-    pub side_bitboards: [u64; 2],
+    pub bitboards: [BitBoard; 12], // 0 = white pawns, 1 = white knights ... 6 = black pawns, etc
+    pub side_bitboards: [BitBoard; 2],
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::piece_array"))]
     pub board: [Piece; 64],
 
     // flags
@@ -33,11 +98,55 @@ pub struct ChessBoard {
     pub half_move: u8,
     pub full_move: u16,
     pub zobrist_hash: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    variant: VariantKind,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pockets: Pockets,
+    /// Pieces promoted from pawns, tracked in variants with [drops](variant::Variant::drops).
+    #[cfg_attr(feature = "serde", serde(default))]
+    promoted: u64,
+
+    /// Shared between clones until either of them changes it, so cloning a board for a search thread doesn't copy the whole game.
+    move_history: Arc<Vec<ReversibleMove>>,
+    /// Moves taken back with [ChessBoard::undo], the next one to [ChessBoard::redo] last.
+    #[cfg_attr(feature = "serde", serde(default))]
+    redo_moves: Vec<Move>,
+    /// Pieces removed by the [variant's explosions](variant::Variant::explosion) as (index in the move history, square, piece).
+    #[cfg_attr(feature = "serde", serde(default))]
+    exploded: Vec<(usize, i32, Piece)>,
+    /// Promoted pieces before a move changed them as (index in the move history, promoted pieces).
+    #[cfg_attr(feature = "serde", serde(default))]
+    promoted_history: Vec<(usize, u64)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    observers: Observers,
+    /// Squares attacked by the piece on every square, kept up to date by [ChessBoard::put_piece].
+    #[cfg(feature = "incremental-attacks")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_impls::u64_array"))]
+    attack_maps: [u64; 64],
+    /// Filled by [ChessBoard::legal_moves_cached] and cleared whenever the position changes.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    legal_moves_cache: LegalMovesCache,
+}
+
+/// Legal moves of the position, cloning gives an empty cache so clones stay cheap.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct LegalMovesCache(std::sync::OnceLock<Box<MoveContainer>>);
 
-    repetitions: RepetitionTable,
-    move_history: Vec<ReversibleMove>,
+#[cfg(feature = "std")]
+impl Clone for LegalMovesCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
 }
 
+// Boards are moved and shared between search threads
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ChessBoard>();
+};
+
 impl PartialEq for ChessBoard {
     /// !Square look up tables are not being compared, because they are not guaranteed to be in the same order.
     /// Equality is mostly used in tests anyways...
@@ -50,14 +159,17 @@ impl PartialEq for ChessBoard {
         self.turn == other.turn && 
         self.en_passant == other.en_passant && 
         self.castling_rights == other.castling_rights && 
+        self.variant == other.variant &&
+        self.pockets == other.pockets &&
+        self.promoted == other.promoted &&
         self.move_history == other.move_history &&
         self.half_move == other.half_move &&
         self.full_move == other.full_move
     }
 }
 
-impl std::fmt::Display for ChessBoard {
-    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for ChessBoard {
+    fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let mut str = String::from("");
         
         str.push_str("   a b c d e f g h\n");
@@ -84,10 +196,10 @@ impl std::fmt::Display for ChessBoard {
         str.push_str(format!("half move: {}\n", self.half_move).as_str());
         str.push_str(format!("full move: {}\n", self.full_move).as_str());
         str.push_str(format!("zobrist: {}\n", self.zobrist_hash).as_str());
-        str.push_str(format!("repetitions: {}\n", self.repetitions).as_str());
+        str.push_str(format!("repetitions: {}\n", self.repetition_count()).as_str());
         str.push_str("move_history: ");
         str.push('[');
-        for m in &self.move_history {
+        for m in self.move_history.iter() {
             str.push_str(format!("'{}', ", &m.board_move.to_uci()).as_str());
         }
         str.push(']');
@@ -108,8 +220,8 @@ impl ChessBoard {
     pub fn new() -> Self {
         let mut x = Self {
             board: [Piece::new(0x0); 64],
-            bitboards: [0u64; 12],
-            side_bitboards: [0u64; 2],
+            bitboards: [BitBoard::EMPTY; 12],
+            side_bitboards: [BitBoard::EMPTY; 2],
 
             turn: PieceColor::White,
             en_passant: -1,
@@ -117,22 +229,41 @@ impl ChessBoard {
             half_move: 0,
             full_move: 1,
             zobrist_hash: 0,
-
-            repetitions: RepetitionTable::new(),
-            move_history: vec![],
+            variant: VariantKind::Standard,
+            pockets: Pockets::default(),
+            promoted: 0,
+
+            move_history: Arc::default(),
+            redo_moves: vec![],
+            exploded: vec![],
+            promoted_history: vec![],
+            observers: Observers::default(),
+            #[cfg(feature = "incremental-attacks")]
+            attack_maps: [0; 64],
+            #[cfg(feature = "std")]
+            legal_moves_cache: LegalMovesCache::default(),
         };
         x.new_game();
         x
     }
 
     pub fn clear(&mut self) {
+        self.reset();
+        self.notify_observers(|observer, board| observer.on_position_set(board));
+    }
+
+    fn reset(&mut self) {
         for idx in 0..64 {
             let _ = self.set_piece(idx, Piece::new(0));
         }
         
         self.turn = PieceColor::White;
-        self.move_history.clear();
-        self.repetitions.clear();
+        self.move_history = Arc::default();
+        self.redo_moves.clear();
+        self.exploded.clear();
+        self.promoted_history.clear();
+        self.pockets = Pockets::default();
+        self.promoted = 0;
         self.en_passant = -1;
         self.full_move = 1;
         self.half_move = 0;
@@ -145,38 +276,222 @@ impl ChessBoard {
     }
 
     /// Only does legal moves.
-    #[must_use]
-    pub fn make_move_uci(&mut self, uci: &str) -> Option<()> {
-        let from = BoardHelper::text_to_square(&uci[0..2]);
-        let legal_moves = self.get_legal_moves_for_square(from);
-        let mut filtered_moves: Vec<Move> = legal_moves.into_iter().filter(|m| { m.to_uci() == uci}).collect();
-        if filtered_moves.is_empty() {
-            return None;
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.make_move_uci("e2e5"), Err(MoveError::IllegalMove));
+    /// assert_eq!(board.make_move_uci("e7e5"), Err(MoveError::WrongSideToMove));
+    /// assert_eq!(board.make_move_uci("e2"), Err(MoveError::ParseError));
+    /// assert!(board.make_move_uci("e2e4").is_ok());
+    /// ```
+    pub fn make_move_uci(&mut self, uci: &str) -> Result<Move, MoveError> {
+        self.make_move_uci_with(uci, PromotionPolicy::Reject)
+    }
+
+    /// Same as [ChessBoard::make_move_uci], but a promotion without the piece is handled with the `policy`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("7k/4P3/8/8/8/8/8/4K3 w - - 0 1").expect("valid fen");
+    /// assert_eq!(board.make_move_uci("e7e8"), Err(MoveError::IllegalMove));
+    /// assert_eq!(board.make_move_uci_with("e7e8", PromotionPolicy::Prompt), Err(MoveError::MissingPromotion));
+    ///
+    /// let m = board.make_move_uci_with("e7e8", PromotionPolicy::Queen).expect("legal move");
+    /// assert_eq!(m.get_flag(), MoveFlag::PromoteQueen);
+    /// ```
+    pub fn make_move_uci_with(&mut self, uci: &str, policy: PromotionPolicy) -> Result<Move, MoveError> {
+        // Drops, e.g. "N@f3"
+        if uci.as_bytes().get(1) == Some(&b'@') {
+            let m: Move = uci.parse().map_err(|_| MoveError::ParseError)?;
+            if !self.get_legal_moves().contains(m) {
+                return Err(MoveError::IllegalMove);
+            }
+            self.make_move(m, false);
+            return Ok(m);
+        }
+
+        if !BoardHelper::is_valid_uci_move(uci) {
+            return Err(MoveError::ParseError);
         }
-        let m = filtered_moves.pop().expect("?");
+
+        // Matched by the squares and the flag, instead of formatting every legal move
+        let bytes = uci.as_bytes();
+        let from = BoardHelper::chars_to_square(bytes[0] as char, bytes[1] as char).map_err(|_| MoveError::ParseError)? as i32;
+        let to = BoardHelper::chars_to_square(bytes[2] as char, bytes[3] as char).map_err(|_| MoveError::ParseError)? as i32;
+        self.check_side_to_move(from)?;
+
+        let moves = self.get_legal_moves_for_square(from);
+        let mut promotion = match bytes.get(4).map(u8::to_ascii_lowercase) {
+            Some(b'q') => { Some(MoveFlag::PromoteQueen) }
+            Some(b'r') => { Some(MoveFlag::PromoteRook) }
+            Some(b'b') => { Some(MoveFlag::PromoteBishop) }
+            Some(b'n') => { Some(MoveFlag::PromoteKnight) }
+            _ => { None }
+        };
+        if promotion.is_none() && moves.iter().any(|m| m.get_to_idx() == to && m.is_promotion()) {
+            match policy {
+                PromotionPolicy::Reject => {}
+                PromotionPolicy::Queen => { promotion = Some(MoveFlag::PromoteQueen); }
+                PromotionPolicy::Prompt => { return Err(MoveError::MissingPromotion); }
+            }
+        }
+
+        let m = moves.into_iter()
+            .filter(|m| m.get_to_idx() == to)
+            .find(|m| match promotion {
+                Some(flag) => { m.get_flag() == flag }
+                None => { !m.is_promotion() }
+            })
+            .ok_or(MoveError::IllegalMove)?;
         self.make_move(m, false);
-        
-        Some(())
+        Ok(m)
+    }
+
+    /// Makes every move of a whitespace separated list of UCI moves, like the one of UCI's `position ... moves` command.
+    /// Stops at the first move which can't be made, the moves before it stay made.
+    ///
+    /// # Errors
+    /// See [ChessBoard::make_move_uci].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// board.apply_uci_moves("e2e4 e7e5  g1f3\nb8c6").expect("legal moves");
+    /// assert_eq!(board.to_fen(), "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+    ///
+    /// assert_eq!(board.apply_uci_moves("f1c4 c4b5"), Err(MoveError::WrongSideToMove));
+    /// assert_eq!(board.get_move_history().len(), 5);
+    /// ```
+    pub fn apply_uci_moves(&mut self, moves: &str) -> Result<(), MoveError> {
+        for uci in moves.split_ascii_whitespace() {
+            self.make_move_uci(uci)?;
+        }
+        Ok(())
     }
 
     /// Before doing the move, checks legality.
+    pub fn make_move_checked(&mut self, chess_move: Move) -> Result<Move, MoveError> {
+        if !chess_move.is_drop() {
+            self.check_side_to_move(chess_move.get_from_idx())?;
+        }
+        if !self.is_legal(chess_move) {
+            return Err(MoveError::IllegalMove);
+        }
+        self.make_move(chess_move, false);
+        Ok(chess_move)
+    }
+
+    /// See [MoveGenerator::is_legal], in a [variant](ChessBoard::set_variant) the move is looked up from its legal moves.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert!(board.is_legal(Move::from_uci("g1f3")));
+    /// assert!(!board.is_legal(Move::from_uci("g1g3")));
+    /// ```
     #[must_use]
-    #[allow(dead_code)]
-    pub fn make_move_checked(&mut self, chess_move: Move) -> bool {
-        let legal_moves = self.get_legal_moves_for_square(chess_move.get_from_idx());
-        let mut filtered_moves: Vec<Move> = legal_moves.into_iter().filter(|m| { m == &chess_move }).collect();
-        if filtered_moves.is_empty() {
-            return false;
+    #[inline(always)]
+    pub fn is_legal(&self, m: Move) -> bool {
+        match self.variant {
+            VariantKind::Standard => { MoveGenerator::is_legal(self, m) }
+            _ => { self.get_legal_moves().contains(m) }
         }
-        let m = filtered_moves.pop().expect("?");
-        self.make_move(m, false);
-        true
     }
 
+    /// Checks, pins and attacks of the position, see [MoveGenInfo].
+    #[must_use]
+    #[inline(always)]
+    pub fn movegen_info(&self) -> MoveGenInfo {
+        MoveGenInfo::new(self)
+    }
+
+    /// See [MoveGenerator::gives_check].
+    #[must_use]
+    #[inline(always)]
+    pub fn gives_check(&self, m: Move) -> bool {
+        MoveGenerator::gives_check(self, m)
+    }
+
+    /// See [MoveGenerator::is_pseudo_legal].
+    #[must_use]
+    #[inline(always)]
+    pub fn is_pseudo_legal(&self, m: Move) -> bool {
+        MoveGenerator::is_pseudo_legal(self, m)
+    }
+
+    const fn check_side_to_move(&self, from: i32) -> Result<(), MoveError> {
+        let piece = self.get_piece(from);
+        if piece.is_none() {
+            return Err(MoveError::IllegalMove);
+        }
+        if piece.get_color() as u8 != self.turn as u8 {
+            return Err(MoveError::WrongSideToMove);
+        }
+        Ok(())
+    }
+
+    /// Legal moves by the rules of the board's [variant](ChessBoard::set_variant).
     #[must_use]
     #[inline(always)]
     pub fn get_legal_moves(&self) -> MoveContainer { 
-        MoveGenerator::get_legal_moves(self, true)
+        self.rules().legal_moves(self)
+    }
+
+    /// Same moves as [ChessBoard::get_legal_moves], generated on the first call and kept until the position changes,
+    /// so user interfaces can ask for the moves of every clicked square without generating them again.
+    /// Making or taking back moves, [ChessBoard::set_piece], [ChessBoard::set_turn] and [ChessBoard::edit] clear it,
+    /// writing to the public fields directly doesn't. Clones start with an empty cache.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::from_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.legal_moves_cached().len(), 20);
+    ///
+    /// board.make_move_uci("g1f3").expect("legal move");
+    /// assert!(!board.legal_moves_cached().contains(Move::from_uci("g1f3")));
+    /// assert!(board.legal_moves_cached().contains(Move::from_uci("g8f6")));
+    /// ```
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn legal_moves_cached(&self) -> &MoveContainer {
+        self.legal_moves_cache.0.get_or_init(|| Box::new(self.get_legal_moves()))
+    }
+
+    #[inline(always)]
+    pub(crate) fn clear_legal_moves_cache(&mut self) {
+        #[cfg(feature = "std")]
+        {
+            let _ = self.legal_moves_cache.0.take();
+        }
+    }
+
+    /// Same moves as [ChessBoard::get_legal_moves], but generated lazily one piece at a time, captures first.
+    /// See [MoveGenerator::legal_moves_iter].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").expect("valid fen");
+    /// assert!(board.legal_moves_iter().next().is_none()); // stalemate
+    ///
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// let knight_move = board.legal_moves_iter().find(|m| m.to_uci() == "g1f3");
+    /// assert_eq!(knight_move, Some(Move::from_uci("g1f3")));
+    /// ```
+    #[inline(always)]
+    pub fn legal_moves_iter(&self) -> impl Iterator<Item = Move> + '_ {
+        MoveGenerator::legal_moves_iter(self)
     }
 
     #[must_use]
@@ -186,12 +501,27 @@ impl ChessBoard {
         MoveGenerator::get_legal_moves(self, false)
     }
 
+    /// Legal moves of the piece on the `square`. Taken from [ChessBoard::legal_moves_cached] if it has been filled for the position,
+    /// otherwise only the moves of the piece are generated.
     #[must_use]
     #[inline(always)]
     pub fn get_legal_moves_for_square(&self, square: i32) -> MoveContainer { 
-        MoveGenerator::get_legal_moves_for_square(self, square)
+        #[cfg(feature = "std")]
+        if let Some(moves) = self.legal_moves_cache.0.get() {
+            return moves.iter().copied().filter(|m| !m.is_drop() && m.get_from_idx() == square).collect();
+        }
+
+        match self.variant {
+            VariantKind::Standard => { MoveGenerator::get_legal_moves_for_square(self, square) }
+            _ => {
+                let mut moves = self.get_legal_moves();
+                moves.retain(|m| !m.is_drop() && m.get_from_idx() == square);
+                moves
+            }
+        }
     }
 
+    #[cfg(feature = "std")]
     pub fn print_legal_moves_for_square(&self, square: i32) {
         let moves = self.get_legal_moves_for_square(square);
         let mut str = String::from("");
@@ -222,11 +552,36 @@ impl ChessBoard {
     }
 
     pub fn make_move(&mut self, chess_move: Move, is_in_search: bool) {
+        if !chess_move.is_drop() && self.get_piece(chess_move.get_from_idx()).is_none() { return; }
+
+        // A move played outside of a search continues the redo line only if it's the next move in it
+        if !is_in_search {
+            if self.redo_moves.last() == Some(&chess_move) {
+                self.redo_moves.pop();
+            } else {
+                self.redo_moves.clear();
+            }
+        }
+
+        let state = self.make_move_inner(chess_move);
+        if !state.captured.is_none() {
+            self.explode(chess_move.get_to_idx());
+        }
+        if self.rules().drops() {
+            self.track_hand(chess_move, state.captured);
+        }
+        let reversible = ReversibleMove::new(chess_move, state.captured, state.en_passant_square, state.castling, state.half_move, state.zobrist_hash, state.zobrist_hash ^ self.zobrist_hash);
+        Arc::make_mut(&mut self.move_history).push(reversible);
+        self.notify_observers(|observer, board| observer.on_move_made(board, chess_move));
+    }
+
+    /// Makes the move without saving it anywhere, returns what's needed for [ChessBoard::unmake_move_inner].
+    /// There has to be a piece on the from square.
+    fn make_move_inner(&mut self, chess_move: Move) -> BoardState {
+        self.clear_legal_moves_cache();
         let from = chess_move.get_from_idx();
         let to = chess_move.get_to_idx();
         let mut moving_piece = self.get_piece(from);
-        
-        if moving_piece.is_none() { return; }
 
         // Handle en passant
         let en_passant_hold = self.en_passant;
@@ -238,6 +593,20 @@ impl ChessBoard {
         self.full_move += self.turn as u16; // white = 0, black = 1
         self.turn.flip();
         self.zobrist_hash ^= zobrist::ZOBRIST_KEYS[zobrist::ZOBRIST_TURN];
+
+        // Piece from the hand
+        if let Some(piece_type) = chess_move.dropped_piece() {
+            let color = self.turn.flipped();
+            self.remove_from_pocket(color, piece_type);
+            let _ = self.put_piece(to, Piece::from_type(piece_type, color));
+
+            if piece_type == PieceType::Pawn {
+                self.half_move = 0;
+            } else {
+                self.half_move += 1;
+            }
+            return BoardState { captured: Piece::new(0), en_passant_square: en_passant_hold, castling: castling_hold, half_move: half_move_hold, zobrist_hash: zobrist_hold };
+        }
         
         match chess_move.get_flag() {
             MoveFlag::None => { }
@@ -245,18 +614,14 @@ impl ChessBoard {
                 let en_passant_dir = if moving_piece.get_color() == PieceColor::Black { 8 } else { -8 };
 
                 // Move
-                let _ = self.set_piece(from, Piece::new(0));
-                let _ = self.set_piece(to, moving_piece);
+                let _ = self.put_piece(from, Piece::new(0));
+                let _ = self.put_piece(to, moving_piece);
                 
                 // Capture
-                let captured = self.set_piece(to + en_passant_dir, Piece::new(0));
+                let captured = self.put_piece(to + en_passant_dir, Piece::new(0));
 
-                // Save to history
-                let save_repetition = if is_in_search { self.repetitions.increment_existing_repetition(self.zobrist_hash) } else { self.repetitions.increment_repetition(self.zobrist_hash) };
-                let reversible = ReversibleMove::new(chess_move, captured, en_passant_hold, self.castling_rights, self.half_move, self.zobrist_hash, save_repetition);
-                self.move_history.push(reversible);
                 self.half_move = 0;
-                return;
+                return BoardState { captured, en_passant_square: en_passant_hold, castling: castling_hold, half_move: half_move_hold, zobrist_hash: zobrist_hold };
             }
             MoveFlag::PawnTwoUp => {
                 let en_passant_dir = if moving_piece.get_color() == PieceColor::White { 8 } else { -8 };
@@ -266,24 +631,24 @@ impl ChessBoard {
                 match Square::from_u32(to as u32) {
                     // White king side
                     Square::G1 => {
-                        let rook = self.set_piece(Square::H1 as i32, Piece::new(0));
-                        let _ = self.set_piece(Square::F1 as i32, rook);
+                        let rook = self.put_piece(Square::H1 as i32, Piece::new(0));
+                        let _ = self.put_piece(Square::F1 as i32, rook);
                     }
                     // White queen side
                     Square::C1 => {
-                        let rook = self.set_piece(Square::A1 as i32, Piece::new(0));
-                        let _ = self.set_piece(Square::D1 as i32, rook);
+                        let rook = self.put_piece(Square::A1 as i32, Piece::new(0));
+                        let _ = self.put_piece(Square::D1 as i32, rook);
                     }
 
                     // Black king side
                     Square::G8 => {
-                        let rook = self.set_piece(Square::H8 as i32, Piece::new(0));
-                        let _ = self.set_piece(Square::F8 as i32, rook);
+                        let rook = self.put_piece(Square::H8 as i32, Piece::new(0));
+                        let _ = self.put_piece(Square::F8 as i32, rook);
                     }
                     // Black queen side
                     Square::C8 => {
-                        let rook = self.set_piece(Square::A8 as i32, Piece::new(0));
-                        let _ = self.set_piece(Square::D8 as i32, rook);
+                        let rook = self.put_piece(Square::A8 as i32, Piece::new(0));
+                        let _ = self.put_piece(Square::D8 as i32, rook);
                     }
 
                     _ => { panic!("huh????? {}", to); }
@@ -300,16 +665,11 @@ impl ChessBoard {
         }
         
         // Move & Capture
-        let _ = self.set_piece(from, Piece::new(0));
-        let captured = self.set_piece(to, moving_piece);
+        let _ = self.put_piece(from, Piece::new(0));
+        let captured = self.put_piece(to, moving_piece);
 
         // Half move
         if !captured.is_none() || moving_piece.get_piece_type() == PieceType::Pawn {
-            if !is_in_search {
-                // to even more reduce hash collisions, delete after every half move reset,
-                // because there's no way the same position is going to be achieved anymore.
-                self.repetitions.clear();
-            }
             self.half_move = 0;
         } else {
             self.half_move += 1
@@ -375,112 +735,402 @@ impl ChessBoard {
             _ => {}
         }
 
-        // Save to history
-        let save_repetition = if is_in_search { self.repetitions.increment_existing_repetition(self.zobrist_hash) } else { self.repetitions.increment_repetition(self.zobrist_hash) };
-        let reversible = ReversibleMove::new(chess_move, captured, en_passant_hold, castling_hold, half_move_hold, zobrist_hold, save_repetition);
-        self.move_history.push(reversible);
+        // Capturing a rook on its starting square
+        if captured.get_piece_type() == PieceType::Rook {
+            let right = match Square::from_u32(to as u32) {
+                Square::H1 => { Some(0) }
+                Square::A1 => { Some(1) }
+                Square::H8 => { Some(2) }
+                Square::A8 => { Some(3) }
+                _ => { None }
+            };
+            if let Some(right) = right {
+                if self.castling_rights[right] {
+                    self.castling_rights[right] = false;
+                    self.zobrist_hash ^= zobrist::ZOBRIST_KEYS[zobrist::ZOBRIST_CASTLING+right];
+                }
+            }
+        }
+
+        BoardState { captured, en_passant_square: en_passant_hold, castling: castling_hold, half_move: half_move_hold, zobrist_hash: zobrist_hold }
     }
 
     // Not able to move not counted here.
     #[must_use]
-    #[allow(dead_code)]
-    pub const fn is_draw(&self) -> bool {
-        // 50 move rule
-        if self.half_move == 100 {
-            return true;
+    #[deprecated(note = "use `can_claim_draw` or `is_draw_automatic`")]
+    pub fn is_draw(&self) -> bool {
+        self.can_claim_draw()
+    }
+
+    /// If the game is drawn without either player claiming it, by the 75-move rule or a fivefold repetition (FIDE Laws 9.6).
+    /// A checkmate on the 150th half move still wins. Not being able to move isn't counted here.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/8/8/8/8/8/8/R3K3 w - - 149 100").expect("valid fen");
+    /// assert!(board.can_claim_draw() && !board.is_draw_automatic());
+    /// board.make_move_uci("a1a2").expect("legal move");
+    /// assert!(board.is_draw_automatic());
+    /// ```
+    #[must_use]
+    pub fn is_draw_automatic(&self) -> bool {
+        (self.half_move >= 150 && !self.is_check_mate()) || self.repetition_count() >= 5
+    }
+
+    /// If the player to move can claim a draw, by the 50-move rule or a threefold repetition (FIDE Laws 9.2 and 9.3), see [ChessBoard::get_draw_claim].
+    #[must_use]
+    pub fn can_claim_draw(&self) -> bool {
+        self.get_draw_claim().is_some()
+    }
+
+    /// Returns the rule which allows the player to move to claim a draw, if any.
+    #[must_use]
+    pub fn get_draw_claim(&self) -> Option<DrawClaim> {
+        if self.half_move >= 100 {
+            return Some(DrawClaim::FiftyMoveRule);
         }
 
-        if let Some(reps) = self.repetitions.get_repetitions(self.zobrist_hash) {
-            // 3-fold repetition
-            if reps >= 3u8 {
-                return true;
-            }
+        if self.repetition_count() >= 3 {
+            return Some(DrawClaim::ThreefoldRepetition);
         }
-        false
+        None
+    }
+
+    /// How many times the current position has occurred in the game, including the current occurrence.
+    /// Walks the move history back to the last irreversible move comparing the zobrist hashes of the positions with the same side to move,
+    /// positions before the one the board was set up from aren't known.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.repetition_count(), 1);
+    /// for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+    ///     board.make_move_uci(uci).expect("legal move");
+    /// }
+    /// assert_eq!(board.repetition_count(), 2);
+    /// ```
+    #[must_use]
+    pub fn repetition_count(&self) -> u8 {
+        let reversible_plies = (self.half_move as usize).min(self.move_history.len());
+        let earlier = self.move_history.iter().rev()
+            .take(reversible_plies)
+            .skip(1).step_by(2)
+            .filter(|m| m.zobrist_hash == self.zobrist_hash)
+            .count();
+        1 + earlier as u8
+    }
+
+    /// Moves made on the board, oldest first.
+    #[must_use]
+    pub fn get_move_history(&self) -> &[ReversibleMove] {
+        &self.move_history
+    }
+
+    /// Every position reached in the game, from the position the board was set up from to the current one.
+    /// The board itself isn't changed, the moves are replayed on a copy of it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// board.make_move_uci("e2e4").expect("legal move");
+    /// board.make_move_uci("e7e5").expect("legal move");
+    ///
+    /// let positions: Vec<GamePosition> = board.positions().collect();
+    /// assert_eq!(positions.len(), 3);
+    /// assert_eq!(positions[0].fen, STARTPOS_FEN);
+    /// assert_eq!(positions[0].last_move, None);
+    /// assert_eq!(positions[2].last_move.map(|m| m.to_uci()), Some(String::from("e7e5")));
+    /// assert_eq!(positions[2].zobrist_hash, board.zobrist_hash);
+    /// ```
+    #[must_use]
+    pub fn positions(&self) -> Positions<'_> {
+        let mut board = self.clone();
+        let _ = board.unmake_n(self.move_history.len());
+        Positions { board, moves: &self.move_history, ply: 0 }
+    }
+
+    /// The move made last, if any.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.last_move(), None);
+    /// let m = board.make_move_uci("e2e4").expect("legal move");
+    /// assert_eq!(board.last_move(), Some(m));
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub fn last_move(&self) -> Option<Move> {
+        self.move_history.last().map(|last| last.board_move)
+    }
+
+    /// Half moves played since the start of the game, derived from the full move number and the side to move.
+    /// Unlike [ChessBoard::half_move] it's never reset, and it accounts for the moves before a position set up from a FEN.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 12").expect("valid fen");
+    /// assert_eq!(board.ply_count(), 23);
+    /// board.make_move_uci("e8d7").expect("legal move");
+    /// assert_eq!(board.ply_count(), 24);
+    /// assert_eq!(board.full_move, 13);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn ply_count(&self) -> u32 {
+        self.full_move.saturating_sub(1) as u32 * 2 + self.turn as u32
+    }
+
+    /// Ply count of the position the board was set up from, before any of the moves in [ChessBoard::get_move_history].
+    #[must_use]
+    #[inline(always)]
+    pub fn start_ply(&self) -> u32 {
+        self.ply_count().saturating_sub(self.move_history.len() as u32)
+    }
+
+    /// If no moves have been made since the position was set up, e.g. from a FEN.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert!(board.is_first_move_of_game());
+    /// board.make_move_uci("e2e4").expect("legal move");
+    /// assert!(!board.is_first_move_of_game());
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub fn is_first_move_of_game(&self) -> bool {
+        self.move_history.is_empty()
     }
 
     #[must_use]
     pub fn is_check_mate(&self) -> bool {
-        self.is_king_in_check(self.turn) && self.get_legal_moves().is_empty()
+        self.is_king_in_check(self.turn) && self.legal_moves_iter().next().is_none()
     }
 
     #[must_use]
     pub fn unmake_move(&mut self) -> Option<Move> {
         if self.move_history.is_empty() { return None; }
         
-        let move_made = self.move_history.pop().expect("?");
-        if move_made.repetition_saved {
-            self.repetitions.decrement_repetition(self.zobrist_hash);
+        let move_made = Arc::make_mut(&mut self.move_history).pop().expect("?");
+        self.restore_explosion(self.move_history.len());
+        if self.rules().drops() {
+            self.untrack_hand(self.move_history.len(), move_made.board_move, move_made.captured);
         }
+        self.unmake_move_inner(move_made.board_move, &move_made.state());
+        self.notify_observers(|observer, board| observer.on_move_unmade(board, move_made.board_move));
+        Some(move_made.board_move)
+    }
+
+    /// Takes back the move made by [ChessBoard::make_move_inner], `state` being what it returned.
+    fn unmake_move_inner(&mut self, board_move: Move, state: &BoardState) {
+        self.clear_legal_moves_cache();
 
-        // Undo capture
-        let mut moving_piece = self.set_piece(move_made.board_move.get_to_idx(), move_made.captured);
+        // Back to the hand
+        if let Some(piece_type) = board_move.dropped_piece() {
+            let dropped = self.put_piece(board_move.get_to_idx(), Piece::new(0));
+            self.add_to_pocket(dropped.get_color(), piece_type);
+        } else {
+            // Undo capture
+            let mut moving_piece = self.put_piece(board_move.get_to_idx(), state.captured);
         
-        // Do flags
-        match move_made.board_move.get_flag() {
-            MoveFlag::EnPassant => { 
-                let en_passant_dir = if moving_piece.get_color() == PieceColor::Black { 8 } else { -8 };
-                let captured_square = move_made.board_move.get_to_idx() + en_passant_dir;
+            // Do flags
+            match board_move.get_flag() {
+                MoveFlag::EnPassant => { 
+                    let en_passant_dir = if moving_piece.get_color() == PieceColor::Black { 8 } else { -8 };
+                    let captured_square = board_move.get_to_idx() + en_passant_dir;
+
+                    // Correctly undo capture
+                    let _ = self.put_piece(board_move.get_to_idx(), Piece::new(0));
+                    let _ = self.put_piece(captured_square, state.captured);
+                }
+                MoveFlag::Castle => {
+                    match Square::from_u32(board_move.get_to_idx() as u32) {
+                        // White king side
+                        Square::G1 => {
+                            let rook = self.put_piece(Square::F1 as i32, Piece::new(0));
+                            let _ = self.put_piece(Square::H1 as i32, rook);
+                        }
+                        // White queen side
+                        Square::C1 => {
+                            let rook = self.put_piece(Square::D1 as i32, Piece::new(0));
+                            let _ = self.put_piece(Square::A1 as i32, rook);
+                        }
 
-                // Correctly undo capture
-                let _ = self.set_piece(move_made.board_move.get_to_idx(), Piece::new(0));
-                let _ = self.set_piece(captured_square, move_made.captured);
-            }
-            MoveFlag::Castle => {
-                match Square::from_u32(move_made.board_move.get_to_idx() as u32) {
-                    // White king side
-                    Square::G1 => {
-                        let rook = self.set_piece(Square::F1 as i32, Piece::new(0));
-                        let _ = self.set_piece(Square::H1 as i32, rook);
-                    }
-                    // White queen side
-                    Square::C1 => {
-                        let rook = self.set_piece(Square::D1 as i32, Piece::new(0));
-                        let _ = self.set_piece(Square::A1 as i32, rook);
-                    }
+                        // Black king side
+                        Square::G8 => {
+                            let rook = self.put_piece(Square::F8 as i32, Piece::new(0));
+                            let _ = self.put_piece(Square::H8 as i32, rook);
+                        }
+                        // Black queen side
+                        Square::C8 => {
+                            let rook = self.put_piece(Square::D8 as i32, Piece::new(0));
+                            let _ = self.put_piece(Square::A8 as i32, rook);
+                        }
 
-                    // Black king side
-                    Square::G8 => {
-                        let rook = self.set_piece(Square::F8 as i32, Piece::new(0));
-                        let _ = self.set_piece(Square::H8 as i32, rook);
+                        _ => { panic!("huh????? {board_move:?}"); }
                     }
-                    // Black queen side
-                    Square::C8 => {
-                        let rook = self.set_piece(Square::D8 as i32, Piece::new(0));
-                        let _ = self.set_piece(Square::A8 as i32, rook);
-                    }
-
-                    _ => { panic!("huh????? {move_made:?}"); }
                 }
+                MoveFlag::PromoteQueen | MoveFlag::PromoteRook | MoveFlag::PromoteBishop | MoveFlag::PromoteKnight => { moving_piece.set_piece(PieceType::Pawn); }
+                _ => { }
             }
-            MoveFlag::PromoteQueen | MoveFlag::PromoteRook | MoveFlag::PromoteBishop | MoveFlag::PromoteKnight => { moving_piece.set_piece(PieceType::Pawn); }
-            _ => { }
-        }
 
-        let _ = self.set_piece(move_made.board_move.get_from_idx(), moving_piece);
+            let _ = self.put_piece(board_move.get_from_idx(), moving_piece);
+        }
 
         /* Board flags */
-        self.en_passant = move_made.en_passant_square;
-        self.castling_rights = move_made.castling;
-        self.half_move = move_made.half_move;
+        self.en_passant = state.en_passant_square;
+        self.castling_rights = state.castling;
+        self.half_move = state.half_move;
         self.turn.flip();
-        self.zobrist_hash = move_made.zobrist_hash;
+        self.zobrist_hash = state.zobrist_hash;
         if self.turn == PieceColor::Black { 
             self.full_move -= 1;
         }
+    }
 
-        Some(move_made.board_move)
+    /// Takes back the last `n` moves, either all of them or none if fewer than `n` moves have been made.
+    /// Returns the moves taken back in the order they were played.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// board.make_move_uci("e2e4").expect("legal move");
+    /// board.make_move_uci("e7e5").expect("legal move");
+    ///
+    /// assert_eq!(board.unmake_n(3), None);
+    /// let moves = board.unmake_n(2).expect("two moves were made");
+    /// assert_eq!(moves.iter().map(|m| m.to_uci()).collect::<Vec<_>>(), ["e2e4", "e7e5"]);
+    /// assert_eq!(board.to_fen(), STARTPOS_FEN);
+    /// ```
+    #[must_use]
+    pub fn unmake_n(&mut self, n: usize) -> Option<Vec<Move>> {
+        if n > self.move_history.len() {
+            return None;
+        }
+
+        let mut moves: Vec<Move> = (0..n).map(|_| self.unmake_move().expect("enough moves in history")).collect();
+        moves.reverse();
+        Some(moves)
+    }
+
+    /// Takes back moves until only the first `ply` moves of the history remain, see [ChessBoard::unmake_n].
+    /// Returns [None] without changing anything if fewer than `ply` moves have been made.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// for uci in ["e2e4", "e7e5", "g1f3"] {
+    ///     board.make_move_uci(uci).expect("legal move");
+    /// }
+    ///
+    /// let moves = board.unmake_to_ply(1).expect("three moves were made");
+    /// assert_eq!(moves.iter().map(|m| m.to_uci()).collect::<Vec<_>>(), ["e7e5", "g1f3"]);
+    /// assert_eq!(board.get_move_history().len(), 1);
+    /// assert_eq!(board.unmake_to_ply(2), None);
+    /// ```
+    #[must_use]
+    pub fn unmake_to_ply(&mut self, ply: usize) -> Option<Vec<Move>> {
+        let n = self.move_history.len().checked_sub(ply)?;
+        self.unmake_n(n)
+    }
+
+    /// Takes back the last move like [ChessBoard::unmake_move], but keeps it for [ChessBoard::redo].
+    /// Meant for takebacks in a GUI, searches should keep using [ChessBoard::unmake_move].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// let m = board.make_move_uci("e2e4").expect("legal move");
+    ///
+    /// assert_eq!(board.undo(), Some(m));
+    /// assert_eq!(board.to_fen(), STARTPOS_FEN);
+    /// assert_eq!(board.redo(), Some(m));
+    /// assert_eq!(board.redo(), None);
+    /// ```
+    pub fn undo(&mut self) -> Option<Move> {
+        let m = self.unmake_move()?;
+        self.redo_moves.push(m);
+        Some(m)
+    }
+
+    /// Takes back the last `n` moves like [ChessBoard::unmake_n], but keeps them for [ChessBoard::redo].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// for uci in ["e2e4", "e7e5", "g1f3"] {
+    ///     board.make_move_uci(uci).expect("legal move");
+    /// }
+    ///
+    /// assert_eq!(board.undo_n(4), None);
+    /// board.undo_n(3).expect("three moves were made");
+    /// assert_eq!(board.redo().map(|m| m.to_uci()), Some(String::from("e2e4")));
+    /// assert_eq!(board.redo_moves().len(), 2);
+    ///
+    /// // Playing another move discards the rest of the undone moves
+    /// board.make_move_uci("c7c5").expect("legal move");
+    /// assert!(board.redo_moves().is_empty());
+    /// ```
+    pub fn undo_n(&mut self, n: usize) -> Option<Vec<Move>> {
+        let moves = self.unmake_n(n)?;
+        self.redo_moves.extend(moves.iter().rev());
+        Some(moves)
+    }
+
+    /// Makes the move taken back last with [ChessBoard::undo] again.
+    /// Returns [None] if there's nothing to redo, e.g. a different move has been made since.
+    pub fn redo(&mut self) -> Option<Move> {
+        let m = *self.redo_moves.last()?;
+        self.make_move(m, false);
+        Some(m)
+    }
+
+    /// Moves which can be made again with [ChessBoard::redo], the next one last.
+    #[must_use]
+    #[inline(always)]
+    pub fn redo_moves(&self) -> &[Move] {
+        &self.redo_moves
     }
 
     #[must_use]
     #[inline(always)]
     pub const fn get_king_square(&self, king_color: PieceColor) -> i32 {
-        BoardHelper::bitscan_forward(self.bitboards[PieceType::King.get_side_index(king_color)])
+        BoardHelper::bitscan_forward(self.bitboards[PieceType::King.get_side_index(king_color)].0)
     }
 
-    // returns the piece that was on the square before
+    /// Returns the piece that was on the square before.
+    /// Only the pieces and their zobrist keys are updated, castling rights, the en passant square and the move history are left as is.
+    /// Use [ChessBoard::edit] for keeping those consistent.
     #[must_use]
     pub fn set_piece(&mut self, square: i32, piece: Piece) -> Piece {
+        self.clear_legal_moves_cache();
+        self.put_piece(square, piece)
+    }
+
+    /// [ChessBoard::set_piece] without clearing the legal move cache, for making and taking back moves which clear it once.
+    #[must_use]
+    pub(super) fn put_piece(&mut self, square: i32, piece: Piece) -> Piece {
         // Remove the captured piece from all bitboards
         let captured = self.board[square as usize];
         if !captured.is_none() {
@@ -492,6 +1142,9 @@ impl ChessBoard {
         if !piece.is_none() {
             self.add_to_bitboards(piece, square);
         }
+
+        #[cfg(feature = "incremental-attacks")]
+        self.update_attack_maps(square);
         captured
     }
 
@@ -501,9 +1154,98 @@ impl ChessBoard {
         self.board[square as usize]
     }
 
+    /// Same as [ChessBoard::get_piece], but with a typed square, an empty piece for [Square::INVALID].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.piece_on(Square::E1).to_char(), 'K');
+    /// assert!(board.piece_on(Square::E4).is_none());
+    /// assert!(board.piece_on(Square::INVALID).is_none());
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn piece_on(&self, square: Square) -> Piece {
+        match Square::from_index(square.index()) {
+            Some(square) => { self.board[square as usize] }
+            None => { Piece::new(0) }
+        }
+    }
+
+    /// The piece the `m` moves, for drops the dropped piece of the side to move.
+    /// The move is expected to be made in this position, i.e. it isn't made yet.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let board = ChessBoard::from_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.moved_piece(Move::from_uci("g1f3")).to_char(), 'N');
+    /// assert_eq!(board.moved_piece(Move::from_uci("P@e4")).to_char(), 'P');
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn moved_piece(&self, m: Move) -> Piece {
+        match m.dropped_piece() {
+            Some(piece_type) => { Piece::from_type(piece_type, self.turn) }
+            None => { self.board[m.get_from_idx() as usize] }
+        }
+    }
+
+    /// The piece the `m` captures, the pawn behind the to square for en passant.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let board = ChessBoard::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").expect("valid fen");
+    /// let en_passant = Move::new(Square::E5 as i32, Square::D6 as i32, MoveFlag::EnPassant);
+    /// assert_eq!(board.captured_piece(en_passant).map(Piece::to_char), Some('p'));
+    /// assert_eq!(board.captured_piece(Move::from_uci("e5e6")), None);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn captured_piece(&self, m: Move) -> Option<Piece> {
+        if m.is_drop() {
+            return None;
+        }
+        if m.is_en_passant() {
+            let color = self.board[m.get_from_idx() as usize].get_color().flipped();
+            return Some(Piece::from_type(PieceType::Pawn, color));
+        }
+
+        let piece = self.board[m.get_to_idx() as usize];
+        if piece.is_none() { None } else { Some(piece) }
+    }
+
+    /// If the `m` captures a piece, en passant included.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_capture(&self, m: Move) -> bool {
+        self.captured_piece(m).is_some()
+    }
+
+    /// If the `m` neither captures nor promotes, which moves quiescence searches leave out.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_quiet(&self, m: Move) -> bool {
+        !self.is_capture(m) && !m.is_promotion()
+    }
+
+    /// Same as [ChessBoard::set_piece], but with a typed square.
+    ///
+    /// # Panics
+    /// If the `square` is [Square::INVALID].
+    #[must_use]
+    pub fn set_piece_on(&mut self, square: Square, piece: Piece) -> Piece {
+        assert!(square.index() >= 0, "invalid square");
+        self.set_piece(square.index(), piece)
+    }
+
     #[inline(always)]
     pub fn set_turn(&mut self, turn: PieceColor) { 
         self.turn = turn; 
+        self.clear_legal_moves_cache();
     }
 
     #[must_use]
@@ -517,33 +1259,87 @@ impl ChessBoard {
         assert!(!piece.is_none());
 
         // Bitboard & Zobrist
-        self.bitboards[piece.get_piece_index()] &= !(0b1 << square);
-        self.side_bitboards[piece.get_color() as usize] &= !(0b1 << square);
+        self.bitboards[piece.get_piece_index()] &= !BitBoard::from_square(square);
+        self.side_bitboards[piece.get_color() as usize] &= !BitBoard::from_square(square);
         self.zobrist_hash ^= piece.get_hash(square);
     }
 
     #[inline(always)]
     const fn get_side_mask(&self, side: PieceColor) -> u64 {
-        self.side_bitboards[side as usize]
+        self.side_bitboards[side as usize].0
     }
 
     #[inline(always)]
     fn add_to_bitboards(&mut self, piece: Piece, square: i32) {
         // Bitboard
-        self.bitboards[piece.get_piece_index()] |= 1u64 << square;
-        self.side_bitboards[piece.get_color() as usize] |= 1u64 << square;
+        self.bitboards[piece.get_piece_index()] |= BitBoard::from_square(square);
+        self.side_bitboards[piece.get_color() as usize] |= BitBoard::from_square(square);
         self.zobrist_hash ^= piece.get_hash(square);
     }
 }
 
+/// A position reached in a game, see [ChessBoard::positions].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GamePosition {
+    /// Moves made before the position, 0 for the position the board was set up from.
+    pub ply: usize,
+    /// The move leading to the position.
+    pub last_move: Option<Move>,
+    pub zobrist_hash: u64,
+    pub fen: String,
+}
+
+/// Iterator over the positions of a game, see [ChessBoard::positions].
+pub struct Positions<'a> {
+    board: ChessBoard,
+    moves: &'a [ReversibleMove],
+    ply: usize,
+}
+
+impl Iterator for Positions<'_> {
+    type Item = GamePosition;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ply > self.moves.len() {
+            return None;
+        }
+
+        let last_move = self.ply.checked_sub(1).map(|i| self.moves[i].board_move);
+        if let Some(m) = last_move {
+            self.board.make_move(m, false);
+        }
+        self.ply += 1;
+        Some(GamePosition { ply: self.ply - 1, last_move, zobrist_hash: self.board.zobrist_hash, fen: self.board.to_fen() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.moves.len() + 1).saturating_sub(self.ply);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Positions<'_> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use super::fen::{STARTPOS_FEN, STARTPOS_FEN_BLACK};
-    use crate::board_helper::BoardHelper;
 
     const TEST_PROMOTION_FEN: &str = "4k3/2P5/4K3/8/8/8/5p2/8 b - - 0 1";
     
+    #[test]
+    fn test_chessboard_hash_delta() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k2r/1P6/8/8/3p4/8/4P3/R3K2R w KQkq - 0 1").expect("valid fen");
+        // Quiet, double push, en passant, castling, promotion with capture
+        for uci in ["h1h2", "e8g8", "e2e4", "d4e3", "e1c1", "g8g7", "b7a8q"] {
+            let before = board.zobrist_hash;
+            board.make_move_uci(uci).expect("legal move");
+            let delta = board.get_move_history().last().expect("move was made").hash_delta();
+            assert_eq!(before ^ delta, board.zobrist_hash, "{uci}");
+        }
+    }
+
     /* MakeMove Tests */
     #[test]
     fn test_chessboard_make_move_queen_promotion() {
@@ -551,7 +1347,7 @@ mod tests {
         board.parse_fen(TEST_PROMOTION_FEN).expect("valid fen");
 
         board.make_move(Move::from_uci("f2f1q"), false);
-        let piece = board.get_piece(BoardHelper::text_to_square("f1"));
+        let piece = board.piece_on(Square::F1);
         assert_eq!(piece.get_piece_type(), PieceType::Queen);
     }
 
@@ -562,7 +1358,7 @@ mod tests {
         board.set_turn(PieceColor::White);
 
         board.make_move(Move::from_uci("f2f1r"), false);
-        let piece = board.get_piece(BoardHelper::text_to_square("f1"));
+        let piece = board.piece_on(Square::F1);
         assert_eq!(piece.get_piece_type(), PieceType::Rook);
     }
 
@@ -572,7 +1368,7 @@ mod tests {
         board.parse_fen(TEST_PROMOTION_FEN).expect("valid fen");
 
         board.make_move(Move::from_uci("f2f1b"), false);
-        let piece = board.get_piece(BoardHelper::text_to_square("f1"));
+        let piece = board.piece_on(Square::F1);
         assert_eq!(piece.get_piece_type(), PieceType::Bishop);
     }
 
@@ -582,7 +1378,7 @@ mod tests {
         board.parse_fen(TEST_PROMOTION_FEN).expect("valid fen");
 
         board.make_move(Move::from_uci("f2f1n"), false);
-        let piece = board.get_piece(BoardHelper::text_to_square("f1"));
+        let piece = board.piece_on(Square::F1);
         assert_eq!(piece.get_piece_type(), PieceType::Knight);
     }
 
@@ -591,7 +1387,7 @@ mod tests {
         let mut board = ChessBoard::new();
         board.parse_fen("4k3/6p1/8/5P2/8/8/8/4K3 b - - 0 1").expect("valid fen");
         board.make_move_uci("g7g5").unwrap();
-        assert_eq!(board.en_passant, BoardHelper::text_to_square("g6"));
+        assert_eq!(board.en_passant, Square::G6 as i32);
     }
 
     #[test]
@@ -601,7 +1397,7 @@ mod tests {
         board.make_move_uci("f5g6").unwrap();
 
         assert_eq!(board.en_passant, -1);
-        assert_eq!(board.get_piece(BoardHelper::text_to_square("g5")).is_none(), true); // Captured
+        assert!(board.piece_on(Square::G5).is_none()); // Captured
     }
 
     #[test]
@@ -611,7 +1407,7 @@ mod tests {
         board.make_move_uci("d4e3").unwrap();
 
         assert_eq!(board.en_passant, -1);
-        assert_eq!(board.get_piece(BoardHelper::text_to_square("e4")).is_none(), true); // Captured
+        assert!(board.piece_on(Square::E4).is_none()); // Captured
     }
 
     /* UnMakeMove Tests */
@@ -683,4 +1479,222 @@ mod tests {
     fn test_chessboard_unmake_move_knight_promotion() {
         _test_unmake_move(TEST_PROMOTION_FEN, "f2f1n");
     }
+
+    #[test]
+    fn test_chessboard_unmake_n() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k2r/1P6/8/8/3p4/8/4P3/R3K2R w KQkq - 0 1").expect("valid fen");
+        let start = board.clone();
+        let played = ["e2e4", "d4e3", "e1c1", "e8g8", "b7a8q"];
+        for uci in played {
+            board.make_move_uci(uci).expect("legal move");
+        }
+
+        let after = board.clone();
+        assert_eq!(board.unmake_n(6), None);
+        assert_eq!(board.unmake_to_ply(6), None);
+        assert_eq!(board, after);
+
+        assert_eq!(board.unmake_n(0), Some(vec![]));
+        let moves = board.unmake_n(played.len()).expect("enough moves");
+        assert_eq!(moves.iter().map(|m| m.to_uci()).collect::<Vec<_>>(), played);
+        assert_eq!(board, start);
+    }
+
+    #[test]
+    fn test_chessboard_undo_redo() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k2r/1P6/8/8/3p4/8/4P3/R3K2R w KQkq - 0 1").expect("valid fen");
+        let start = board.clone();
+        let played = ["e2e4", "d4e3", "e1c1", "e8g8", "b7a8q"];
+        for uci in played {
+            board.make_move_uci(uci).expect("legal move");
+        }
+        let after = board.clone();
+
+        board.undo_n(played.len()).expect("enough moves");
+        assert_eq!(board, start);
+        assert_eq!(board.last_move(), None);
+        while board.redo().is_some() {}
+        assert_eq!(board, after);
+        assert_eq!(board.zobrist_hash, after.zobrist_hash);
+
+        // Searching doesn't touch the redo moves, playing the next redo move keeps the rest
+        board.undo_n(2).expect("enough moves");
+        board.perft(2, false);
+        assert_eq!(board.redo_moves().len(), 2);
+        board.make_move_uci("e8g8").expect("legal move");
+        assert_eq!(board.redo_moves().len(), 1);
+        board.make_move_uci("b7b8q").expect("legal move");
+        assert!(board.redo_moves().is_empty());
+        assert_eq!(board.redo(), None);
+
+        board.undo();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        assert!(board.redo_moves().is_empty());
+    }
+
+    #[test]
+    fn test_chessboard_positions() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k2r/1P6/8/8/3p4/8/4P3/R3K2R w KQkq - 0 1").expect("valid fen");
+        let mut expected = vec![(board.zobrist_hash, board.to_fen())];
+        for uci in ["e2e4", "d4e3", "e1c1", "e8g8", "b7a8q"] {
+            board.make_move_uci(uci).expect("legal move");
+            expected.push((board.zobrist_hash, board.to_fen()));
+        }
+
+        let before = board.clone();
+        let positions = board.positions();
+        assert_eq!(positions.len(), expected.len());
+        let positions: Vec<(u64, String)> = positions.map(|position| (position.zobrist_hash, position.fen)).collect();
+        assert_eq!(positions, expected);
+        assert_eq!(board, before);
+    }
+
+    #[test]
+    fn test_chessboard_repetition_count() {
+        const SHUFFLE: [&str; 4] = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+
+        for expected in 2..=4 {
+            for uci in SHUFFLE {
+                board.make_move_uci(uci).expect("legal move");
+            }
+            assert_eq!(board.repetition_count(), expected);
+        }
+        assert!(board.can_claim_draw());
+        assert!(!board.is_draw_automatic());
+        assert_eq!(board.get_draw_claim(), Some(DrawClaim::ThreefoldRepetition));
+
+        for uci in SHUFFLE {
+            board.make_move_uci(uci).expect("legal move");
+        }
+        assert_eq!(board.repetition_count(), 5);
+        assert!(board.is_draw_automatic());
+
+        // Nothing before a pawn move can repeat
+        board.make_move_uci("e2e4").expect("legal move");
+        assert_eq!(board.repetition_count(), 1);
+        for uci in ["g8f6", "g1f3", "f6g8", "f3g1", "g8f6"] {
+            board.make_move_uci(uci).expect("legal move");
+        }
+        assert_eq!(board.repetition_count(), 2);
+        assert_eq!(board.get_draw_claim(), None);
+
+        // Moves made in search count as well and taking them back restores the count
+        for uci in ["g1f3", "f6g8", "f3g1", "g8f6"] {
+            board.make_move(Move::from_uci(uci), true);
+        }
+        assert_eq!(board.repetition_count(), 3);
+        for _ in 0..4 {
+            let _ = board.unmake_move();
+        }
+        assert_eq!(board.repetition_count(), 2);
+
+        // Positions before the one the board was set up from are unknown
+        let fen = board.to_fen();
+        board.parse_fen(&fen).expect("valid fen");
+        assert_eq!(board.repetition_count(), 1);
+    }
+
+    #[test]
+    fn test_chessboard_shared_between_threads() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        let board = &board;
+
+        let sans: Vec<(String, i32)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = board.get_legal_moves().into_iter().map(|m| scope.spawn(move || {
+                let san = board.move_to_san(m);
+                assert_eq!(board.get_move_pgn(&san), Ok(m));
+                (san, crate::eval::evaluate(board))
+            })).collect();
+            handles.into_iter().map(|handle| handle.join().expect("no panics")).collect()
+        });
+        assert_eq!(sans.len(), 20);
+        assert!(sans.iter().any(|(san, _)| san == "Nf3"));
+    }
+
+    #[test]
+    fn test_chessboard_clone_shares_history() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        for uci in ["e2e4", "e7e5", "g1f3"] {
+            board.make_move_uci(uci).expect("legal move");
+        }
+
+        let mut clone = board.clone();
+        assert_eq!(clone.get_move_history().as_ptr(), board.get_move_history().as_ptr());
+
+        // Changing the clone copies the history, the original keeps its own
+        clone.make_move(Move::from_uci("b8c6"), true);
+        assert_ne!(clone.get_move_history().as_ptr(), board.get_move_history().as_ptr());
+        assert_eq!(board.get_move_history().len(), 3);
+        let _ = clone.unmake_n(4);
+        assert!(clone.get_move_history().is_empty());
+        assert_eq!(board.last_move(), Some(Move::from_uci("g1f3")));
+    }
+
+    #[test]
+    fn test_chessboard_apply_uci_moves() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k3/1P6/8/8/8/8/1p6/2B1K2R w K - 0 1").expect("valid fen");
+        board.apply_uci_moves("b7a8N b2c1n E1G1").expect("legal moves");
+        assert_eq!(board.to_fen(), "N3k3/8/8/8/8/8/8/2n2RK1 b - - 1 2");
+
+        board.parse_fen("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1").expect("valid fen");
+        assert_eq!(board.apply_uci_moves("b7a8"), Err(MoveError::IllegalMove));
+        assert_eq!(board.apply_uci_moves("b7a8k"), Err(MoveError::ParseError));
+        assert_eq!(board.apply_uci_moves(""), Ok(()));
+        assert!(board.get_move_history().is_empty());
+    }
+
+    #[test]
+    fn test_chessboard_promotion_policy() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("3r3k/4P3/8/8/8/8/4P3/4K3 w - - 0 1").expect("valid fen");
+        // Only moves to the last rank are affected
+        assert!(board.make_move_uci_with("e2e4", PromotionPolicy::Prompt).is_ok());
+        board.unmake_move().expect("move was made");
+
+        assert_eq!(board.make_move_uci_with("e7d8", PromotionPolicy::Prompt), Err(MoveError::MissingPromotion));
+        let m = board.make_move_uci_with("e7d8", PromotionPolicy::Queen).expect("legal capture");
+        assert_eq!(m.to_uci(), "e7d8q");
+        board.unmake_move().expect("move was made");
+
+        // An explicit piece is always respected
+        let m = board.make_move_uci_with("e7e8n", PromotionPolicy::Queen).expect("legal move");
+        assert_eq!(m.get_flag(), MoveFlag::PromoteKnight);
+    }
+
+    #[test]
+    fn test_chessboard_legal_moves_cache() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("4k3/8/8/8/8/8/4P3/R3K3 w Q - 0 1").expect("valid fen");
+        assert_eq!(board.legal_moves_cached().len(), board.get_legal_moves().len());
+        assert_eq!(board.get_legal_moves_for_square(Square::E2 as i32).len(), 2);
+
+        // Every change to the position clears it
+        let _ = board.set_piece(Square::E3 as i32, Piece::from_char('p'));
+        assert!(board.get_legal_moves_for_square(Square::E2 as i32).is_empty());
+        board.make_move_uci("e1c1").expect("legal move");
+        assert_eq!(board.legal_moves_cached().len(), board.get_legal_moves().len());
+        board.unmake_move().expect("move was made");
+        assert!(board.legal_moves_cached().contains(Move::castle_queenside(PieceColor::White)));
+        board.edit(|edit| edit.set_castling_rights([false; 4]));
+        assert!(!board.legal_moves_cached().contains(Move::castle_queenside(PieceColor::White)));
+        board.set_turn(PieceColor::Black);
+        assert!(board.get_legal_moves_for_square(Square::E1 as i32).is_empty());
+
+        // Single squares don't fill it and clones don't copy it
+        let mut board = ChessBoard::from_fen(STARTPOS_FEN).expect("valid fen");
+        assert_eq!(board.get_legal_moves_for_square(Square::G1 as i32).len(), 2);
+        assert!(board.legal_moves_cache.0.get().is_none());
+        let _ = board.legal_moves_cached();
+        assert!(board.clone().legal_moves_cache.0.get().is_none());
+        board.make_move_uci("g1f3").expect("legal move");
+        assert!(board.legal_moves_cache.0.get().is_none());
+    }
 }
@@ -0,0 +1,164 @@
+use super::ChessBoard;
+use super::magics::{get_bishop_magic, get_rook_magic};
+
+use crate::bitschess::bitboard::{KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS};
+use crate::board_helper::BoardHelper;
+use crate::piece::{Piece, PieceColor, PieceType};
+
+impl ChessBoard {
+    /// Squares the `piece` on the `square` attacks with the `occupancy` as blockers, 0 for an empty square.
+    #[must_use]
+    #[inline(always)]
+    pub const fn attacks_from(piece: Piece, square: i32, occupancy: u64) -> u64 {
+        let square_idx = square as usize;
+        match piece.get_piece_type() {
+            PieceType::None => { 0 }
+            PieceType::Pawn => { PAWN_ATTACKS[piece.get_color() as usize][square_idx] }
+            PieceType::Knight => { KNIGHT_ATTACKS[square_idx] }
+            PieceType::Bishop => { get_bishop_magic(square, occupancy) }
+            PieceType::Rook => { get_rook_magic(square, occupancy) }
+            PieceType::Queen => { get_bishop_magic(square, occupancy) | get_rook_magic(square, occupancy) }
+            PieceType::King => { KING_ATTACKS[square_idx] }
+        }
+    }
+
+    /// Squares attacked by the `color`, sliders seeing through the king of the other side,
+    /// so the king can't step back along a checking ray.
+    /// Recomputed from the pieces, or with the `incremental-attacks` feature combined from the attack maps kept by [ChessBoard::set_piece].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let board = ChessBoard::from_fen("8/8/8/8/4k3/8/8/4R1K1 b - - 0 1").expect("valid fen");
+    /// let attacks = board.attacked_squares(PieceColor::White);
+    /// assert_ne!(attacks & (1u64 << Square::E5 as u32), 0); // through the black king
+    /// assert_eq!(attacks & (1u64 << Square::D5 as u32), 0);
+    /// ```
+    #[must_use]
+    pub fn attacked_squares(&self, color: PieceColor) -> u64 {
+        #[cfg(feature = "incremental-attacks")]
+        {
+            self.incremental_attacked_squares(color)
+        }
+        #[cfg(not(feature = "incremental-attacks"))]
+        {
+            self.computed_attacked_squares(color)
+        }
+    }
+
+    /// [ChessBoard::attacked_squares] computed from scratch, one piece type at a time.
+    #[cfg_attr(all(feature = "incremental-attacks", not(test)), allow(dead_code))]
+    pub(super) fn computed_attacked_squares(&self, color: PieceColor) -> u64 {
+        let king_mask = self.bitboards[PieceType::King.get_side_index(color.flipped())].0;
+        // Sliders see through the king
+        let occupancy = (self.side_bitboards[0].0 | self.side_bitboards[1].0) ^ king_mask;
+        let pieces = |piece_type: PieceType| self.bitboards[piece_type.get_side_index(color)].0;
+
+        let mut attacks = 0u64;
+        let mut pawns = pieces(PieceType::Pawn);
+        while pawns != 0 {
+            attacks |= PAWN_ATTACKS[color as usize][BoardHelper::pop_lsb(&mut pawns) as usize];
+        }
+
+        let mut knights = pieces(PieceType::Knight);
+        while knights != 0 {
+            attacks |= KNIGHT_ATTACKS[BoardHelper::pop_lsb(&mut knights) as usize];
+        }
+
+        let mut diagonal_sliders = pieces(PieceType::Bishop) | pieces(PieceType::Queen);
+        while diagonal_sliders != 0 {
+            attacks |= get_bishop_magic(BoardHelper::pop_lsb(&mut diagonal_sliders), occupancy);
+        }
+
+        let mut orthogonal_sliders = pieces(PieceType::Rook) | pieces(PieceType::Queen);
+        while orthogonal_sliders != 0 {
+            attacks |= get_rook_magic(BoardHelper::pop_lsb(&mut orthogonal_sliders), occupancy);
+        }
+
+        let mut kings = pieces(PieceType::King);
+        while kings != 0 {
+            attacks |= KING_ATTACKS[BoardHelper::pop_lsb(&mut kings) as usize];
+        }
+        attacks
+    }
+}
+
+#[cfg(feature = "incremental-attacks")]
+impl ChessBoard {
+    /// Squares attacked by the piece on the `square`, with every piece as a blocker.
+    #[must_use]
+    #[inline(always)]
+    pub const fn piece_attacks(&self, square: i32) -> u64 {
+        self.attack_maps[square as usize]
+    }
+
+    fn incremental_attacked_squares(&self, color: PieceColor) -> u64 {
+        let mut attacks = 0u64;
+        let mut pieces = self.side_bitboards[color as usize].0;
+        while pieces != 0 {
+            attacks |= self.attack_maps[BoardHelper::pop_lsb(&mut pieces) as usize];
+        }
+
+        // Rays of the sliders checking the king go on behind it
+        let king_mask = self.bitboards[PieceType::King.get_side_index(color.flipped())].0;
+        if king_mask & attacks != 0 {
+            let occupancy = (self.side_bitboards[0].0 | self.side_bitboards[1].0) ^ king_mask;
+            let mut checkers = self.sliders_seeing(BoardHelper::bitscan_forward(king_mask), occupancy) & self.side_bitboards[color as usize].0;
+            while checkers != 0 {
+                let square = BoardHelper::pop_lsb(&mut checkers);
+                attacks |= Self::attacks_from(self.board[square as usize], square, occupancy);
+            }
+        }
+        attacks
+    }
+
+    /// Updates the attack maps after the piece on the `square` changed, the sliders seeing the square get their rays lengthened or shortened.
+    pub(super) fn update_attack_maps(&mut self, square: i32) {
+        let occupancy = self.side_bitboards[0].0 | self.side_bitboards[1].0;
+        self.attack_maps[square as usize] = Self::attacks_from(self.board[square as usize], square, occupancy);
+
+        let mut sliders = self.sliders_seeing(square, occupancy);
+        while sliders != 0 {
+            let slider = BoardHelper::pop_lsb(&mut sliders);
+            self.attack_maps[slider as usize] = Self::attacks_from(self.board[slider as usize], slider, occupancy);
+        }
+    }
+
+    /// Bishops, rooks and queens of both sides which have the `square` on their rays.
+    const fn sliders_seeing(&self, square: i32, occupancy: u64) -> u64 {
+        let queens = self.bitboards[PieceType::Queen.get_side_index(PieceColor::White)].0 | self.bitboards[PieceType::Queen.get_side_index(PieceColor::Black)].0;
+        let bishops = self.bitboards[PieceType::Bishop.get_side_index(PieceColor::White)].0 | self.bitboards[PieceType::Bishop.get_side_index(PieceColor::Black)].0;
+        let rooks = self.bitboards[PieceType::Rook.get_side_index(PieceColor::White)].0 | self.bitboards[PieceType::Rook.get_side_index(PieceColor::Black)].0;
+        (get_bishop_magic(square, occupancy) & (bishops | queens)) | (get_rook_magic(square, occupancy) & (rooks | queens))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::PERFT_SUITE;
+
+    /// Every position reached within `depth` plies has the same attacks computed both ways.
+    fn check_attacks(board: &mut ChessBoard, depth: u32) {
+        for color in [PieceColor::White, PieceColor::Black] {
+            assert_eq!(board.attacked_squares(color), board.computed_attacked_squares(color), "{}", board.to_fen());
+        }
+        if depth == 0 {
+            return;
+        }
+
+        for m in board.get_legal_moves() {
+            board.make_move(m, true);
+            check_attacks(board, depth - 1);
+            let _ = board.unmake_move();
+        }
+    }
+
+    #[test]
+    fn test_attacked_squares() {
+        for (fen, _, _) in PERFT_SUITE {
+            let mut board = ChessBoard::from_fen(fen).expect("valid fen");
+            check_attacks(&mut board, 2);
+        }
+    }
+}
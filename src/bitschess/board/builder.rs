@@ -0,0 +1,227 @@
+use super::ChessBoard;
+
+use crate::board_helper::{BoardHelper, Square};
+use crate::piece::{Piece, PieceColor, PieceType};
+
+/// The 1st and the 8th rank, pawns can never be on these.
+const BACK_RANKS: u64 = 0xFF00_0000_0000_00FF;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BoardBuildError {
+    NoWhiteKing,
+    NoBlackKing,
+    TooManyKings,
+    TooManyPawns,
+    PawnOnBackRank,
+    OpponentInCheck,
+}
+
+/// # Builder for custom positions
+/// Places pieces programmatically instead of formatting a FEN string by hand.
+/// The position is validated when calling [ChessBoardBuilder::build].
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// let board = ChessBoardBuilder::new()
+///     .piece(Square::E1, Piece::from_char('K'))
+///     .piece(Square::E8, Piece::from_char('k'))
+///     .piece(Square::A7, Piece::from_char('P'))
+///     .turn(PieceColor::Black)
+///     .build()
+///     .expect("valid position");
+/// assert_eq!(board.to_fen(), "4k3/P7/8/8/8/8/8/4K3 b - - 0 1");
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChessBoardBuilder {
+    pieces: [Piece; 64],
+    turn: PieceColor,
+    castling_rights: [bool; 4],
+    en_passant: i32,
+    half_move: u8,
+    full_move: u16,
+}
+
+impl Default for ChessBoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChessBoardBuilder {
+    /// An empty board with white to move and no castling rights.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            pieces: [Piece::new(0); 64],
+            turn: PieceColor::White,
+            castling_rights: [false; 4],
+            en_passant: -1,
+            half_move: 0,
+            full_move: 1,
+        }
+    }
+
+    /// Places a piece on a square, replacing the existing one.
+    #[must_use]
+    pub const fn piece(mut self, square: Square, piece: Piece) -> Self {
+        self.pieces[square as usize] = piece;
+        self
+    }
+
+    /// Removes the piece from a square.
+    #[must_use]
+    pub const fn clear_square(mut self, square: Square) -> Self {
+        self.pieces[square as usize] = Piece::new(0);
+        self
+    }
+
+    #[must_use]
+    pub const fn turn(mut self, turn: PieceColor) -> Self {
+        self.turn = turn;
+        self
+    }
+
+    /// Lines up with fen's "KQkq" -> [white_king_side, white_queen_side, black_king_side, black_queen_side]
+    #[must_use]
+    pub const fn castling(mut self, castling_rights: [bool; 4]) -> Self {
+        self.castling_rights = castling_rights;
+        self
+    }
+
+    #[must_use]
+    pub const fn en_passant(mut self, square: Square) -> Self {
+        self.en_passant = square as i32;
+        self
+    }
+
+    #[must_use]
+    pub const fn half_move(mut self, half_move: u8) -> Self {
+        self.half_move = half_move;
+        self
+    }
+
+    #[must_use]
+    pub const fn full_move(mut self, full_move: u16) -> Self {
+        self.full_move = full_move;
+        self
+    }
+
+    /// Validates the position and constructs a [ChessBoard] out of it.
+    pub fn build(&self) -> Result<ChessBoard, BoardBuildError> {
+        let mut board = ChessBoard::new();
+        for (square, piece) in self.pieces.iter().enumerate() {
+            if !piece.is_none() {
+                let _ = board.set_piece(square as i32, *piece);
+            }
+        }
+
+        board.turn = self.turn;
+        board.castling_rights = self.castling_rights;
+        board.en_passant = self.en_passant;
+        board.half_move = self.half_move;
+        board.full_move = self.full_move;
+
+        // Error checking
+        for color in [PieceColor::White, PieceColor::Black] {
+            let kings = BoardHelper::count_bits(board.bitboards[PieceType::King.get_side_index(color)].0);
+            if kings == 0 {
+                return Err(if color == PieceColor::White { BoardBuildError::NoWhiteKing } else { BoardBuildError::NoBlackKing });
+            }
+            if kings > 1 {
+                return Err(BoardBuildError::TooManyKings);
+            }
+
+            let pawns = board.bitboards[PieceType::Pawn.get_side_index(color)].0;
+            if BoardHelper::count_bits(pawns) > 8 {
+                return Err(BoardBuildError::TooManyPawns);
+            }
+            if pawns & BACK_RANKS != 0 {
+                return Err(BoardBuildError::PawnOnBackRank);
+            }
+        }
+
+        if board.is_king_in_check(board.get_turn().flipped()) {
+            return Err(BoardBuildError::OpponentInCheck);
+        }
+
+        let hash = board.create_zobrist_hash();
+        board.zobrist_hash = hash;
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitschess::board::fen::STARTPOS_FEN;
+
+    fn _kings() -> ChessBoardBuilder {
+        ChessBoardBuilder::new()
+            .piece(Square::E1, Piece::from_char('K'))
+            .piece(Square::E8, Piece::from_char('k'))
+    }
+
+    #[test]
+    fn test_builder_matches_fen() {
+        let board = _kings()
+            .piece(Square::A1, Piece::from_char('R'))
+            .piece(Square::H8, Piece::from_char('r'))
+            .castling([false, true, true, false])
+            .turn(PieceColor::Black)
+            .half_move(3)
+            .full_move(20)
+            .build()
+            .expect("valid position");
+
+        let mut expected = ChessBoard::new();
+        expected.parse_fen("4k2r/8/8/8/8/8/8/R3K3 b Qk - 3 20").expect("valid fen");
+        assert_eq!(board, expected);
+        assert_eq!(board.zobrist_hash, expected.zobrist_hash);
+    }
+
+    #[test]
+    fn test_builder_start_position() {
+        let mut builder = ChessBoardBuilder::new().castling([true; 4]);
+        let back_rank = "RNBQKBNR";
+        for (file, ch) in back_rank.chars().enumerate() {
+            builder = builder
+                .piece(Square::from_u32(file as u32), Piece::from_char(ch))
+                .piece(Square::from_u32(8 + file as u32), Piece::from_char('P'))
+                .piece(Square::from_u32(48 + file as u32), Piece::from_char('p'))
+                .piece(Square::from_u32(56 + file as u32), Piece::from_char(ch.to_ascii_lowercase()));
+        }
+        assert_eq!(builder.build().expect("valid position").to_fen(), STARTPOS_FEN);
+    }
+
+    #[test]
+    fn test_builder_missing_kings() {
+        let no_white = ChessBoardBuilder::new().piece(Square::E8, Piece::from_char('k'));
+        assert_eq!(no_white.build().err(), Some(BoardBuildError::NoWhiteKing));
+
+        let no_black = ChessBoardBuilder::new().piece(Square::E1, Piece::from_char('K'));
+        assert_eq!(no_black.build().err(), Some(BoardBuildError::NoBlackKing));
+
+        let two_kings = _kings().piece(Square::A1, Piece::from_char('K'));
+        assert_eq!(two_kings.build().err(), Some(BoardBuildError::TooManyKings));
+    }
+
+    #[test]
+    fn test_builder_pawns() {
+        let back_rank = _kings().piece(Square::A8, Piece::from_char('P'));
+        assert_eq!(back_rank.build().err(), Some(BoardBuildError::PawnOnBackRank));
+
+        let mut too_many = _kings();
+        for square in 8..17 {
+            too_many = too_many.piece(Square::from_u32(square), Piece::from_char('p'));
+        }
+        assert_eq!(too_many.build().err(), Some(BoardBuildError::TooManyPawns));
+    }
+
+    #[test]
+    fn test_builder_opponent_in_check() {
+        let builder = _kings().piece(Square::E4, Piece::from_char('R'));
+        assert_eq!(builder.build().err(), Some(BoardBuildError::OpponentInCheck));
+        assert!(builder.turn(PieceColor::Black).build().is_ok());
+    }
+}
@@ -0,0 +1,265 @@
+use super::ChessBoard;
+use super::fen::FenParsingError;
+use super::move_generation::MoveGenInfo;
+use super::zobrist::{POCKET_HASH_LIMIT, ZOBRIST_KEYS, ZOBRIST_POCKET};
+
+use crate::chess_move::{Move, MoveContainer};
+use crate::board_helper::{BoardHelper, Square};
+use crate::piece::{Piece, PieceColor, PieceType};
+use alloc::string::String;
+
+/// 1st and 8th rank, pawns can't be dropped on them.
+const BACK_RANKS: u64 = 0xFF00_0000_0000_00FF;
+
+/// Piece types which can be held in hand, in the order of [PieceType::get_index].
+const POCKET_PIECES: [PieceType; 5] = [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen];
+
+/// Pieces in hand in crazyhouse, the pieces a side has captured and can drop back on the board as its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pockets([[u8; 5]; 2]);
+
+impl Pockets {
+    /// How many pieces of the `piece_type` the `color` holds, kings are never held.
+    #[must_use]
+    #[inline(always)]
+    pub const fn count(&self, color: PieceColor, piece_type: PieceType) -> u8 {
+        match piece_type {
+            PieceType::None | PieceType::King => { 0 }
+            _ => { self.0[color as usize][piece_type.get_index()] }
+        }
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        let mut idx = 0;
+        while idx < 5 {
+            if self.0[0][idx] != 0 || self.0[1][idx] != 0 {
+                return false;
+            }
+            idx += 1;
+        }
+        true
+    }
+
+    /// Parses the pieces in hand of a crazyhouse FEN, e.g. "QNp" from "[QNp]", uppercase pieces being white's.
+    ///
+    /// # Errors
+    /// [FenParsingError::InvalidPiece] for kings and characters which aren't pieces.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let pockets = Pockets::from_fen("QNpp").expect("valid pockets");
+    /// assert_eq!(pockets.count(PieceColor::Black, PieceType::Pawn), 2);
+    /// assert_eq!(pockets.to_fen(), "QNpp");
+    /// assert_eq!(Pockets::from_fen("K"), Err(FenParsingError::InvalidPiece('K')));
+    /// ```
+    pub fn from_fen(pieces: &str) -> Result<Self, FenParsingError> {
+        let mut pockets = Self::default();
+        for ch in pieces.chars() {
+            let piece = Piece::from_char(ch);
+            match piece.get_piece_type() {
+                PieceType::None | PieceType::King => { return Err(FenParsingError::InvalidPiece(ch)); }
+                piece_type => { pockets.0[piece.get_color() as usize][piece_type.get_index()] += 1; }
+            }
+        }
+        Ok(pockets)
+    }
+
+    /// The pieces in hand as in a crazyhouse FEN, white's first from queens to pawns, e.g. "QNpp".
+    #[must_use]
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for color in [PieceColor::White, PieceColor::Black] {
+            for piece_type in POCKET_PIECES.into_iter().rev() {
+                let piece = Piece::from_type(piece_type, color);
+                for _ in 0..self.count(color, piece_type) {
+                    fen.push(piece.to_char());
+                }
+            }
+        }
+        fen
+    }
+
+    /// Zobrist key of the pieces in hand, empty pockets hash to 0.
+    #[must_use]
+    pub const fn hash(&self) -> u64 {
+        let mut hash = 0u64;
+        let mut color = 0;
+        while color < 2 {
+            let mut idx = 0;
+            while idx < 5 {
+                let mut count = 0;
+                while count < self.0[color][idx] as usize {
+                    hash ^= Self::key(color, idx, count);
+                    count += 1;
+                }
+                idx += 1;
+            }
+            color += 1;
+        }
+        hash
+    }
+
+    /// Key of the `count`th (from 0) piece in hand, pieces over [POCKET_HASH_LIMIT] aren't hashed.
+    const fn key(color: usize, idx: usize, count: usize) -> u64 {
+        if count >= POCKET_HASH_LIMIT {
+            return 0;
+        }
+        ZOBRIST_KEYS[ZOBRIST_POCKET + (color * 5 + idx) * POCKET_HASH_LIMIT + count]
+    }
+}
+
+impl ChessBoard {
+    /// The pieces in hand, only used in [crazyhouse](crate::prelude::VariantKind::Crazyhouse).
+    #[must_use]
+    #[inline(always)]
+    pub const fn pockets(&self) -> &Pockets {
+        &self.pockets
+    }
+
+    /// Pieces which were promoted from pawns, they go back to the hand as pawns when captured in crazyhouse.
+    #[must_use]
+    #[inline(always)]
+    pub const fn promoted_pieces(&self) -> u64 {
+        self.promoted
+    }
+
+    /// Legal drops of the pieces in hand of the side to move.
+    /// In check a dropped piece has to block the check, pawns can't be dropped on the 1st or the 8th rank.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.set_variant(VariantKind::Crazyhouse);
+    /// board.parse_fen("4k3/8/8/8/8/8/8/r3K3[N] w - - 0 1").expect("valid fen");
+    /// let drops: Vec<String> = board.drop_moves().iter().map(|m| m.to_uci()).collect();
+    /// assert_eq!(drops, ["N@b1", "N@c1", "N@d1"]);
+    /// ```
+    #[must_use]
+    pub fn drop_moves(&self) -> MoveContainer {
+        let mut moves = MoveContainer::new();
+        let info = MoveGenInfo::new(self);
+        if info.double_check {
+            return moves;
+        }
+
+        let empty = !(self.side_bitboards[0].0 | self.side_bitboards[1].0) & info.check_mask;
+        for piece_type in POCKET_PIECES {
+            if self.pockets.count(self.turn, piece_type) == 0 {
+                continue;
+            }
+
+            let mut squares = if piece_type == PieceType::Pawn { empty & !BACK_RANKS } else { empty };
+            while squares != 0 {
+                let square = BoardHelper::pop_lsb(&mut squares);
+                if let Some(m) = Move::drop(piece_type, Square::from_u32(square as u32)) {
+                    moves.push(m);
+                }
+            }
+        }
+        moves
+    }
+
+    /// Sets the pieces in hand, used when parsing a FEN.
+    pub(super) fn set_pockets(&mut self, pockets: Pockets) {
+        self.zobrist_hash ^= self.pockets.hash() ^ pockets.hash();
+        self.pockets = pockets;
+    }
+
+    pub(super) fn add_to_pocket(&mut self, color: PieceColor, piece_type: PieceType) {
+        let idx = piece_type.get_index();
+        let count = &mut self.pockets.0[color as usize][idx];
+        self.zobrist_hash ^= Pockets::key(color as usize, idx, *count as usize);
+        *count += 1;
+    }
+
+    pub(super) fn remove_from_pocket(&mut self, color: PieceColor, piece_type: PieceType) {
+        let idx = piece_type.get_index();
+        let count = &mut self.pockets.0[color as usize][idx];
+        *count -= 1;
+        self.zobrist_hash ^= Pockets::key(color as usize, idx, *count as usize);
+    }
+
+    /// After a move was made, the captured piece goes to the hand of the side which captured it and promoted pieces are tracked.
+    /// The previous promoted pieces are saved for [ChessBoard::untrack_hand], keyed by the index the move gets in the move history.
+    pub(super) fn track_hand(&mut self, chess_move: Move, captured: Piece) {
+        let to_mask = 1u64 << chess_move.get_to_idx();
+        if !captured.is_none() {
+            let piece_type = if self.promoted & to_mask != 0 { PieceType::Pawn } else { captured.get_piece_type() };
+            self.add_to_pocket(self.turn.flipped(), piece_type);
+        }
+        if chess_move.is_drop() {
+            return;
+        }
+
+        let from_mask = 1u64 << chess_move.get_from_idx();
+        let mut promoted = self.promoted & !to_mask;
+        if self.promoted & from_mask != 0 || chess_move.is_promotion() {
+            promoted = (promoted & !from_mask) | to_mask;
+        }
+        if promoted != self.promoted {
+            self.promoted_history.push((self.move_history.len(), self.promoted));
+            self.promoted = promoted;
+        }
+    }
+
+    /// Takes back [ChessBoard::track_hand] of the move at `ply` in the move history, before the move itself is taken back.
+    pub(super) fn untrack_hand(&mut self, ply: usize, chess_move: Move, captured: Piece) {
+        if let Some(&(promoted_ply, promoted)) = self.promoted_history.last() {
+            if promoted_ply == ply {
+                self.promoted_history.pop();
+                self.promoted = promoted;
+            }
+        }
+        if !captured.is_none() {
+            let piece_type = if self.promoted & (1u64 << chess_move.get_to_idx()) != 0 { PieceType::Pawn } else { captured.get_piece_type() };
+            self.remove_from_pocket(self.turn.flipped(), piece_type);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitschess::board::variant::VariantKind;
+
+    #[test]
+    fn test_crazyhouse_capture_and_drop() {
+        let mut board = ChessBoard::new();
+        board.set_variant(VariantKind::Crazyhouse);
+        board.parse_fen("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR[] w KQkq - 0 2").expect("valid fen");
+        let before = board.clone();
+
+        board.apply_uci_moves("e4d5 d8d5 P@e4").expect("legal moves");
+        assert_eq!(board.to_fen(), "rnb1kbnr/ppp1pppp/8/3q4/4P3/8/PPPP1PPP/RNBQKBNR[p] b KQkq - 0 3");
+        assert_eq!(board.zobrist_hash, board.create_zobrist_hash());
+        board.apply_uci_moves("P@f3 g1f3").expect("legal moves");
+        assert_eq!(board.pockets().to_fen(), "P");
+
+        let _ = board.unmake_n(5);
+        assert_eq!(board, before);
+        assert_eq!(board.zobrist_hash, before.zobrist_hash);
+    }
+
+    #[test]
+    fn test_crazyhouse_promoted_piece_returns_as_pawn() {
+        let mut board = ChessBoard::new();
+        board.set_variant(VariantKind::Crazyhouse);
+        board.parse_fen("1r2k3/P7/8/8/8/8/8/4K3[] w - - 0 1").expect("valid fen");
+        board.apply_uci_moves("a7a8q b8a8").expect("legal moves");
+        assert_eq!(board.pockets().to_fen(), "p");
+        assert_eq!(board.promoted_pieces(), 0);
+
+        let _ = board.unmake_move();
+        assert_eq!(board.promoted_pieces(), 1u64 << Square::A8 as u32);
+        assert!(board.to_fen().starts_with("Q~r2k3/8/8/8/8/8/8/4K3[] b"));
+
+        // Pawns can't be dropped on the back ranks, and only blocking drops are legal in check
+        board.parse_fen("4k3/8/8/8/8/8/8/r3K3[P] w - - 0 1").expect("valid fen");
+        assert!(board.drop_moves().is_empty());
+        assert!(!board.is_legal(Move::from_uci("P@e4")));
+    }
+}
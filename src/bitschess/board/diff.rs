@@ -0,0 +1,98 @@
+use super::ChessBoard;
+
+use crate::board_helper::Square;
+use crate::chess_move::Move;
+use crate::piece::Piece;
+use alloc::vec::Vec;
+
+/// A square with a different piece in two positions, see [ChessBoard::diff].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SquareChange {
+    pub square: Square,
+    /// Piece on the square in the first position, empty if there was none.
+    pub before: Piece,
+    /// Piece on the square in the second position, empty if there is none.
+    pub after: Piece,
+}
+
+impl ChessBoard {
+    /// Squares whose pieces differ between this position and the `other`, from a1 to h8.
+    /// Only the pieces are compared, not the side to move, the castling rights or the move counters.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let before = ChessBoard::from_fen(STARTPOS_FEN).expect("valid fen");
+    /// let after = ChessBoard::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").expect("valid fen");
+    /// let changes = before.diff(&after);
+    /// assert_eq!(changes.len(), 2);
+    /// assert_eq!((changes[0].square, changes[0].before.to_char()), (Square::E2, 'P'));
+    /// assert_eq!((changes[1].square, changes[1].after.to_char()), (Square::E4, 'P'));
+    /// ```
+    #[must_use]
+    pub fn diff(&self, other: &ChessBoard) -> Vec<SquareChange> {
+        (0..64)
+            .filter(|&square| self.board[square] != other.board[square])
+            .map(|square| SquareChange { square: Square::from_u32(square as u32), before: self.board[square], after: other.board[square] })
+            .collect()
+    }
+
+    /// The legal move which turns this position into the `other`, [None] if no single move does.
+    /// Only the pieces are compared, so positions read from an electronic board or streamed without the moves can be followed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").expect("valid fen");
+    /// let mut after = board.clone();
+    /// after.make_move_uci("e1g1").expect("legal move");
+    ///
+    /// assert_eq!(board.infer_move(&after), Some(Move::castle_kingside(PieceColor::White)));
+    /// assert_eq!(board.infer_move(&board), None);
+    /// ```
+    #[must_use]
+    pub fn infer_move(&self, other: &ChessBoard) -> Option<Move> {
+        let changed = self.diff(other).iter().fold(0u64, |mask, change| mask | (1u64 << change.square as u32));
+        if changed == 0 {
+            return None;
+        }
+
+        let mut board = self.clone();
+        self.get_legal_moves().iter().copied().find(|m| {
+            // The moving piece leaves its square, a dropped piece lands on an empty one
+            let square = if m.is_drop() { m.get_to_idx() } else { m.get_from_idx() };
+            if changed & (1u64 << square) == 0 {
+                return false;
+            }
+
+            board.make_move(*m, true);
+            let same = board.board == other.board;
+            let _ = board.unmake_move();
+            same
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitschess::board::fen::STARTPOS_FEN;
+
+    #[test]
+    fn test_infer_move() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k3/1P6/8/3pP3/8/8/8/4K3 w q d6 0 1").expect("valid fen");
+        for uci in ["e5d6", "b7b8n", "b7a8q", "e1d2"] {
+            let mut after = board.clone();
+            let m = after.make_move_uci(uci).expect("legal move");
+            assert_eq!(board.infer_move(&after), Some(m), "{uci}");
+        }
+
+        // Two moves apart
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        let mut after = board.clone();
+        after.apply_uci_moves("e2e4 e7e5").expect("legal moves");
+        assert_eq!(board.infer_move(&after), None);
+        assert_eq!(board.diff(&after).len(), 4);
+    }
+}
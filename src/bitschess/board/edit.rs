@@ -0,0 +1,153 @@
+use super::ChessBoard;
+
+use crate::board_helper::Square;
+use crate::piece::{Piece, PieceColor};
+use alloc::sync::Arc;
+
+/// Changes the pieces of a board inside [ChessBoard::edit], the rest of the board is fixed up once the editing is done.
+pub struct BoardEditor<'a> {
+    board: &'a mut ChessBoard,
+    changed: bool,
+}
+
+impl BoardEditor<'_> {
+    /// Places the `piece` on the `square`, returns the piece which was there before.
+    ///
+    /// # Panics
+    /// If the `square` is [Square::INVALID].
+    pub fn put(&mut self, square: Square, piece: Piece) -> Piece {
+        self.changed = true;
+        self.board.set_piece_on(square, piece)
+    }
+
+    /// Empties the `square`, returns the piece which was there.
+    ///
+    /// # Panics
+    /// If the `square` is [Square::INVALID].
+    pub fn remove(&mut self, square: Square) -> Piece {
+        self.put(square, Piece::new(0))
+    }
+
+    #[must_use]
+    pub const fn piece_on(&self, square: Square) -> Piece {
+        self.board.piece_on(square)
+    }
+
+    pub fn set_turn(&mut self, turn: PieceColor) {
+        self.changed = true;
+        self.board.set_turn(turn);
+    }
+
+    /// Lines up with fen's "KQkq" -> [white_king_side, white_queen_side, black_king_side, black_queen_side].
+    /// Rights without the king and the rook on their starting squares are removed when the editing is done.
+    pub fn set_castling_rights(&mut self, castling_rights: [bool; 4]) {
+        self.changed = true;
+        self.board.castling_rights = castling_rights;
+    }
+
+    /// The en passant square is removed when the editing is done, if no pawn could have just moved past it.
+    pub fn set_en_passant(&mut self, square: Square) {
+        self.changed = true;
+        self.board.en_passant = square.index();
+    }
+
+    /// Castling rights and the en passant square which aren't possible anymore are removed and the zobrist hash is recalculated.
+    /// The move history no longer leads to the position, so it's cleared, which also resets the repetitions.
+    fn commit(self) {
+        if !self.changed {
+            return;
+        }
+
+        let board = self.board;
+        let _ = board.check_castling_and_en_passant(true);
+        board.zobrist_hash = board.create_zobrist_hash();
+        board.move_history = Arc::default();
+        board.redo_moves.clear();
+        board.exploded.clear();
+        board.promoted_history.clear();
+        board.clear_legal_moves_cache();
+        board.notify_observers(|observer, board| observer.on_position_set(board));
+    }
+}
+
+impl ChessBoard {
+    /// Edits the position in a batch, unlike [ChessBoard::set_piece] the castling rights, the en passant square,
+    /// the zobrist hash and the move history are kept consistent with the pieces. Returns what the `edit` returns.
+    /// The position isn't validated otherwise, e.g. a side may be left without a king.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// board.make_move_uci("e2e4").expect("legal move");
+    ///
+    /// let rook = board.edit(|edit| {
+    ///     edit.put(Square::E4, Piece::new(0));
+    ///     edit.remove(Square::H1)
+    /// });
+    /// assert_eq!(rook.to_char(), 'R');
+    /// assert_eq!(board.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPP1PPP/RNBQKBN1 b Qkq - 0 1");
+    /// assert_eq!(board.zobrist_hash, board.create_zobrist_hash());
+    /// assert!(board.get_move_history().is_empty());
+    /// ```
+    pub fn edit<R>(&mut self, edit: impl FnOnce(&mut BoardEditor<'_>) -> R) -> R {
+        let mut editor = BoardEditor { board: self, changed: false };
+        let result = edit(&mut editor);
+        editor.commit();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitschess::board::fen::STARTPOS_FEN;
+
+    #[test]
+    fn test_board_edit() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").expect("valid fen");
+
+        // Reading doesn't change anything
+        let hash = board.zobrist_hash;
+        assert_eq!(board.edit(|edit| edit.piece_on(Square::A8).to_char()), 'r');
+        assert_eq!(board.en_passant, Square::D6 as i32);
+        assert_eq!(board.zobrist_hash, hash);
+
+        // Moving the black pawn away removes the en passant square, moving the rook its castling right
+        board.edit(|edit| {
+            let pawn = edit.remove(Square::D5);
+            edit.put(Square::D4, pawn);
+            let rook = edit.remove(Square::H8);
+            edit.put(Square::G8, rook);
+        });
+        assert_eq!(board.to_fen(), "r3k1r1/8/8/4P3/3p4/8/8/R3K2R w KQq - 0 1");
+        let mut expected = ChessBoard::new();
+        expected.parse_fen(&board.to_fen()).expect("valid fen");
+        assert_eq!(board.zobrist_hash, expected.zobrist_hash);
+
+        // Rights are only kept where they're possible
+        board.edit(|edit| {
+            edit.set_castling_rights([true; 4]);
+            edit.set_turn(PieceColor::Black);
+        });
+        assert_eq!(board.castling_rights, [true, true, false, true]);
+        assert_eq!(board.zobrist_hash, board.create_zobrist_hash());
+    }
+
+    #[test]
+    fn test_board_edit_resets_repetitions() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            board.make_move_uci(uci).expect("legal move");
+        }
+        assert_eq!(board.repetition_count(), 2);
+
+        board.edit(|edit| edit.set_turn(PieceColor::White));
+        assert_eq!(board.repetition_count(), 1);
+        assert!(board.get_move_history().is_empty());
+        assert!(board.unmake_move().is_none());
+    }
+}
@@ -1,48 +1,223 @@
 use super::{ChessBoard, CHESSBOARD_WIDTH};
+use super::crazyhouse::Pockets;
 
-use crate::board_helper::BoardHelper;
+use crate::board_helper::{BoardHelper, Square};
 use crate::piece::{Piece, PieceColor, PieceType};
+use alloc::{string::{String, ToString}, vec, vec::Vec};
 
 #[allow(dead_code)]
 pub const STARTPOS_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 #[allow(dead_code)]
 pub const STARTPOS_FEN_BLACK: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1";
 
-#[derive(Debug, PartialEq, Eq)]
+/// Reasons a FEN is rejected, the board is left cleared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FenParsingError {
     NoWhiteKing,
     NoBlackKing,
-    OpponentInCheck
+    OpponentInCheck,
+    /// The piece placement doesn't have 8 ranks, the value is the amount found.
+    WrongRankCount(usize),
+    /// The rank (1-8) doesn't have exactly 8 squares.
+    WrongFileCount(u8),
+    /// A character in the piece placement which is neither a piece nor a digit from 1 to 8.
+    InvalidPiece(char),
+    MultipleKings(PieceColor),
+    /// A pawn on the first or the last rank, the value is the square.
+    PawnOnBackRank(i32),
+    /// The side has more pieces than it could have even if every missing pawn had promoted.
+    TooManyPieces(PieceColor),
+    /// The castling right ('K', 'Q', 'k' or 'q') without the king and the rook on their starting squares.
+    InvalidCastlingRight(char),
+    /// No pawn could have just moved 2 squares past the en passant square.
+    InvalidEnPassant(i32),
+}
+
+/// Inconsistencies found while parsing, these don't prevent the position from being played.
+/// Values are the ones found in the FEN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenWarning {
+    /// Full move counter starts from 1.
+    FullMoveZero,
+    /// Over 100 half moves, the game could've been claimed a draw already.
+    /// Normalizing keeps the clock and only clamps it to 150, where the 75-move rule ends the game.
+    HalfMoveOverLimit(u8),
+    /// More half moves than plies played according to the full move counter.
+    HalfMoveExceedsPlies(u8),
+    /// An en passant square means the last move was a pawn move, which resets the half move clock.
+    HalfMoveWithEnPassant(u8),
+    /// With [FenParseOptions::lenient], see [FenParsingError::InvalidCastlingRight].
+    CastlingRightRemoved(char),
+    /// With [FenParseOptions::lenient], see [FenParsingError::InvalidEnPassant].
+    EnPassantRemoved(i32),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FenParseOptions {
+    /// Fixes the counters reported in the [FenWarning]s, otherwise they're kept as is.
+    pub normalize_counters: bool,
+    /// Removes impossible castling rights and en passant squares and reports them as [FenWarning]s, instead of failing.
+    pub lenient: bool,
+}
+
+/// Castling rights in the order of [ChessBoard::castling_rights] with the king's and the rook's starting squares.
+pub(super) const CASTLING_SQUARES: [(char, Square, Square); 4] = [
+    ('K', Square::E1, Square::H1),
+    ('Q', Square::E1, Square::A1),
+    ('k', Square::E8, Square::H8),
+    ('q', Square::E8, Square::A8),
+];
+
+/// 1st and 8th rank.
+const BACK_RANKS: u64 = 0xFF00_0000_0000_00FF;
+
+impl TryFrom<&str> for ChessBoard {
+    type Error = FenParsingError;
+
+    /// See [ChessBoard::from_fen].
+    fn try_from(fen: &str) -> Result<Self, Self::Error> {
+        Self::from_fen(fen)
+    }
 }
 
 impl ChessBoard {
 
+    /// Creates a board with the position of the `fen`, see [ChessBoard::parse_fen].
+    ///
+    /// # Errors
+    /// If the FEN is malformed or the position is impossible.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let board = ChessBoard::from_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.get_legal_moves().len(), 20);
+    ///
+    /// let board = ChessBoard::try_from("4k3/8/8/8/8/8/8/4K3 b - - 0 1").expect("valid fen");
+    /// assert_eq!(board.get_turn(), PieceColor::Black);
+    /// assert_eq!(ChessBoard::from_fen("4k3/8/8/8/8/8/8/8 w - - 0 1"), Err(FenParsingError::NoWhiteKing));
+    /// ```
+    pub fn from_fen(fen: &str) -> Result<Self, FenParsingError> {
+        let mut board = Self::new();
+        board.parse_fen(fen)?;
+        Ok(board)
+    }
+
+    /// Sets up the position, see [FenParsingError] for the checks done.
+    ///
+    /// # Errors
+    /// If the FEN is malformed or the position is impossible, the board is left cleared.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// assert_eq!(board.parse_fen("4k3/8/8/8/8/8/8/4K3/8 w - - 0 1"), Err(FenParsingError::WrongRankCount(9)));
+    /// assert_eq!(board.parse_fen("4k3/8/8/8/8/8/8/4K3 w K - 0 1"), Err(FenParsingError::InvalidCastlingRight('K')));
+    /// ```
     pub fn parse_fen(&mut self, fen_whole: &str) -> Result<(), FenParsingError> {
-        let mut args: Vec<&str> = fen_whole.split(' ').rev().collect();
-        
+        self.parse_fen_with_options(fen_whole, FenParseOptions::default()).map(|_| ())
+    }
+
+    /// Same as [ChessBoard::parse_fen], but fixes the counters, castling rights and en passant square where it's safe
+    /// and reports the fixes as [FenWarning]s. Malformed piece placements are still errors.
+    ///
+    /// # Errors
+    /// If the FEN is malformed or the position is impossible, the board is left cleared.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// let warnings = board.parse_fen_lenient("4k2r/8/8/8/8/8/8/4K3 w Kk e6 0 1").expect("fixable fen");
+    /// assert_eq!(warnings, vec![FenWarning::CastlingRightRemoved('K'), FenWarning::EnPassantRemoved(Square::E6 as i32)]);
+    /// assert_eq!(board.to_fen(), "4k2r/8/8/8/8/8/8/4K3 w k - 0 1");
+    /// ```
+    pub fn parse_fen_lenient(&mut self, fen_whole: &str) -> Result<Vec<FenWarning>, FenParsingError> {
+        self.parse_fen_with_options(fen_whole, FenParseOptions { normalize_counters: true, lenient: true })
+    }
+
+    /// Same as [ChessBoard::parse_fen], but also reports inconsistent half and full move counters.
+    ///
+    /// # Errors
+    /// If the FEN is malformed or the position is impossible, the board is left cleared.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// let options = FenParseOptions { normalize_counters: true, ..Default::default() };
+    /// let warnings = board.parse_fen_with_options("4k3/8/8/8/8/8/8/4K3 w - - 7 0", options).expect("valid fen");
+    /// assert_eq!(warnings, vec![FenWarning::FullMoveZero, FenWarning::HalfMoveExceedsPlies(7)]);
+    /// assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    /// ```
+    pub fn parse_fen_with_options(&mut self, fen_whole: &str, options: FenParseOptions) -> Result<Vec<FenWarning>, FenParsingError> {
+        let result = self.set_fen(fen_whole, options);
+        self.notify_observers(|observer, board| observer.on_position_set(board));
+        result
+    }
+
+    fn set_fen(&mut self, fen_whole: &str, options: FenParseOptions) -> Result<Vec<FenWarning>, FenParsingError> {
         // Clear Board
-        self.new_game();
+        self.reset();
+
+        let warnings = match self.read_fen(fen_whole, options) {
+            Ok(warnings) => { warnings }
+            Err(e) => {
+                self.reset();
+                return Err(e);
+            }
+        };
+
+        let hash = self.create_zobrist_hash();
+        self.zobrist_hash = hash;
+        Ok(warnings)
+    }
+
+    fn read_fen(&mut self, fen_whole: &str, options: FenParseOptions) -> Result<Vec<FenWarning>, FenParsingError> {
+        let mut args: Vec<&str> = fen_whole.split(' ').rev().collect();
+        self.castling_rights = [false; 4];
 
         // Parse Position
-        if let Some(fen_position) = args.pop() {
-            let mut x = 0i32; 
-            let mut y = 7i32;
-
-            for ch in fen_position.chars() {
-                // No piece
-                if ch.is_ascii_digit() {
-                    let num = ch.to_digit(10).unwrap();
-                    x += num as i32;
+        if let Some(mut fen_position) = args.pop() {
+            // Pieces in hand after the piece placement, e.g. "[Qp]"
+            if self.rules().drops() {
+                if let Some((position, pockets)) = fen_position.split_once('[') {
+                    let pockets = pockets.strip_suffix(']').ok_or(FenParsingError::InvalidPiece('['))?;
+                    self.set_pockets(Pockets::from_fen(pockets)?);
+                    fen_position = position;
                 }
-                else if ch != '/' {
-                    let _ =  self.set_piece(y * CHESSBOARD_WIDTH + x, Piece::from_char(ch));
+            }
+
+            let ranks: Vec<&str> = fen_position.split('/').collect();
+            if ranks.len() != 8 {
+                return Err(FenParsingError::WrongRankCount(ranks.len()));
+            }
+
+            for (y, rank) in (0..8i32).rev().zip(ranks) {
+                let mut x = 0i32;
+                for ch in rank.chars() {
+                    // No piece
+                    if let Some(num @ 1..=8) = ch.to_digit(10) {
+                        x += num as i32;
+                        continue;
+                    }
+                    // The piece before was promoted
+                    if ch == '~' && self.rules().drops() && x > 0 && !self.get_piece(y * CHESSBOARD_WIDTH + x - 1).is_none() {
+                        self.promoted |= 1u64 << (y * CHESSBOARD_WIDTH + x - 1);
+                        continue;
+                    }
+                    if PieceType::from_char(ch) == PieceType::None {
+                        return Err(FenParsingError::InvalidPiece(ch));
+                    }
+                    if x >= 8 {
+                        return Err(FenParsingError::WrongFileCount(y as u8 + 1));
+                    }
+                    let _ = self.set_piece(y * CHESSBOARD_WIDTH + x, Piece::from_char(ch));
                     x += 1;
                 }
-
-                // overflow
-                if x >= 8 {
-                    x = 0;
-                    y -= 1;
+                if x != 8 {
+                    return Err(FenParsingError::WrongFileCount(y as u8 + 1));
                 }
             }
         }
@@ -68,7 +243,7 @@ impl ChessBoard {
         // Parse en passant square
         if let Some(en_passant) = args.pop() {
             if en_passant != "-" {
-                self.en_passant = BoardHelper::text_to_square(en_passant);     
+                self.en_passant = BoardHelper::text_to_square(en_passant).map_or(-1, |square| square as i32);
             }
         }
 
@@ -85,28 +260,123 @@ impl ChessBoard {
                 self.full_move = parsed;     
             }
         }
-        
+
         // Error checking
-        if self.bitboards[PieceType::King.get_side_index(PieceColor::White)] == 0u64 {
-            self.clear();
-            return Err(FenParsingError::NoWhiteKing);
+        self.check_pieces()?;
+        let mut warnings = self.check_castling_and_en_passant(options.lenient)?;
+        warnings.extend(self.check_move_counters(options.normalize_counters));
+
+        if self.rules().in_check(self, self.get_turn().flipped()) {
+            return Err(FenParsingError::OpponentInCheck);
         }
-        if self.bitboards[PieceType::King.get_side_index(PieceColor::Black)] == 0u64 {
-            self.clear();
-            return Err(FenParsingError::NoBlackKing);
+        Ok(warnings)
+    }
+
+    /// Amount of kings, pawns on the back ranks and the amount of pieces.
+    fn check_pieces(&self) -> Result<(), FenParsingError> {
+        // Kings can be captured in variants where the king isn't special
+        for (color, missing) in [(PieceColor::White, FenParsingError::NoWhiteKing), (PieceColor::Black, FenParsingError::NoBlackKing)] {
+            match self.bitboards[PieceType::King.get_side_index(color)].0.count_ones() {
+                0 if !self.rules().royal_king() => {}
+                0 => { return Err(missing); }
+                1 => {}
+                _ => { return Err(FenParsingError::MultipleKings(color)); }
+            }
         }
 
-        if self.is_king_in_check(self.get_turn().flipped()) {
-            self.clear();
-            return Err(FenParsingError::OpponentInCheck);
+        let pawns = self.bitboards[PieceType::Pawn.get_side_index(PieceColor::White)].0 | self.bitboards[PieceType::Pawn.get_side_index(PieceColor::Black)].0;
+        if pawns & BACK_RANKS != 0 {
+            return Err(FenParsingError::PawnOnBackRank(BoardHelper::bitscan_forward(pawns & BACK_RANKS)));
         }
 
-        let hash = self.create_zobrist_hash();
-        self.repetitions.increment_repetition(hash);
-        self.zobrist_hash = hash;
+        // Dropped pieces can be any of the captured ones
+        if self.rules().drops() {
+            return Ok(());
+        }
+        for color in [PieceColor::White, PieceColor::Black] {
+            let count = |piece_type: PieceType| self.bitboards[piece_type.get_side_index(color)].0.count_ones();
+            // Every piece over the starting amount has to be a promoted pawn
+            let promoted = count(PieceType::Queen).saturating_sub(1)
+                + count(PieceType::Rook).saturating_sub(2)
+                + count(PieceType::Bishop).saturating_sub(2)
+                + count(PieceType::Knight).saturating_sub(2);
+            if count(PieceType::Pawn) + promoted > 8 {
+                return Err(FenParsingError::TooManyPieces(color));
+            }
+        }
         Ok(())
     }
 
+    /// Castling rights need the king and the rook on their starting squares, an en passant square needs the pawn which just moved past it.
+    /// If `lenient` the invalid ones are removed instead.
+    pub(super) fn check_castling_and_en_passant(&mut self, lenient: bool) -> Result<Vec<FenWarning>, FenParsingError> {
+        let mut warnings = vec![];
+
+        for (idx, (right, king_square, rook_square)) in CASTLING_SQUARES.into_iter().enumerate() {
+            let color = if idx < 2 { PieceColor::White } else { PieceColor::Black };
+            let in_place = self.bitboards[PieceType::King.get_side_index(color)].0 & (1u64 << king_square as u32) != 0
+                && self.bitboards[PieceType::Rook.get_side_index(color)].0 & (1u64 << rook_square as u32) != 0;
+            if !self.castling_rights[idx] || in_place {
+                continue;
+            }
+            if !lenient {
+                return Err(FenParsingError::InvalidCastlingRight(right));
+            }
+            self.castling_rights[idx] = false;
+            warnings.push(FenWarning::CastlingRightRemoved(right));
+        }
+
+        if self.en_passant != -1 {
+            // The pawn of the side which just moved went from behind the square to in front of it.
+            let (rank, direction) = if self.turn == PieceColor::White { (5, -8) } else { (2, 8) };
+            let pawns = self.bitboards[PieceType::Pawn.get_side_index(self.turn.flipped())].0;
+            let possible = BoardHelper::get_rank(self.en_passant) == rank
+                && pawns & (1u64 << (self.en_passant + direction)) != 0
+                && self.get_piece(self.en_passant).is_none()
+                && self.get_piece(self.en_passant - direction).is_none();
+            if !possible {
+                if !lenient {
+                    return Err(FenParsingError::InvalidEnPassant(self.en_passant));
+                }
+                warnings.push(FenWarning::EnPassantRemoved(self.en_passant));
+                self.en_passant = -1;
+            }
+        }
+        Ok(warnings)
+    }
+
+    fn check_move_counters(&mut self, normalize: bool) -> Vec<FenWarning> {
+        let mut warnings = vec![];
+
+        if self.full_move == 0 {
+            warnings.push(FenWarning::FullMoveZero);
+            if normalize {
+                self.full_move = 1;
+            }
+        }
+
+        let half_move = self.half_move;
+        let plies = 2 * (u32::from(self.full_move.max(1)) - 1) + self.turn as u32;
+        let mut plausible = half_move;
+        if self.en_passant != -1 && half_move != 0 {
+            warnings.push(FenWarning::HalfMoveWithEnPassant(half_move));
+            plausible = 0;
+        }
+        if half_move > 100 {
+            warnings.push(FenWarning::HalfMoveOverLimit(half_move));
+            plausible = plausible.min(150);
+        }
+        if u32::from(half_move) > plies {
+            warnings.push(FenWarning::HalfMoveExceedsPlies(half_move));
+            plausible = plausible.min(plies as u8);
+        }
+
+        if normalize {
+            self.half_move = plausible;
+        }
+        warnings
+    }
+
     pub fn to_fen(&self) -> String {
         let mut fen = String::new();
         
@@ -128,6 +398,9 @@ impl ChessBoard {
                         empty_counter = 0;
                     }
                     fen.push(piece_char);
+                    if self.promoted & (1u64 << idx) != 0 {
+                        fen.push('~');
+                    }
                 }
             }
             if empty_counter != 0 {
@@ -138,6 +411,12 @@ impl ChessBoard {
         }
         fen.pop().unwrap(); // pop last '/'
 
+        if self.rules().drops() {
+            fen.push('[');
+            fen.push_str(&self.pockets.to_fen());
+            fen.push(']');
+        }
+
         // Turn
         fen.push(' ');
         fen.push(if self.get_turn() == PieceColor::White {'w'} else {'b'});
@@ -180,7 +459,7 @@ impl ChessBoard {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::board_helper::BoardHelper;
+    use crate::bitschess::bitboard::BitBoard;
     use crate::piece::PieceType;
 
     #[test]
@@ -190,7 +469,7 @@ mod tests {
 
         assert_eq!(board.get_turn(), PieceColor::Black);
 
-        let piece = board.get_piece(BoardHelper::text_to_square("f2"));
+        let piece = board.piece_on(Square::F2);
         assert_eq!(piece.get_piece_type(), PieceType::Pawn);
         assert_eq!(piece.get_color(), PieceColor::Black);
     }
@@ -214,6 +493,17 @@ mod tests {
         assert_eq!(board.parse_fen("rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQha - 0 1"), Err(FenParsingError::NoBlackKing));
     }
 
+    #[test]
+    fn test_from_fen() {
+        let board = ChessBoard::from_fen("4k3/2P5/4K3/8/8/8/5p2/8 b - - 0 1").expect("valid fen");
+        let mut parsed = ChessBoard::new();
+        parsed.parse_fen("4k3/2P5/4K3/8/8/8/5p2/8 b - - 0 1").expect("valid fen");
+        assert_eq!(board, parsed);
+        assert_eq!(board.zobrist_hash, parsed.zobrist_hash);
+
+        assert_eq!(ChessBoard::try_from("k7/4n3/8/3K4/8/1N6/8/8 b - - 0 1"), Err(FenParsingError::OpponentInCheck));
+    }
+
     #[test]
     fn test_to_fen_start_pos() {
         let mut board = ChessBoard::new();
@@ -226,7 +516,7 @@ mod tests {
     fn test_parse_fen_en_passant() {
         let mut board = ChessBoard::new();
         board.parse_fen("4k3/8/8/5Pp1/8/8/8/4K3 w - g6 0 1").expect("valid fen");
-        assert_eq!(board.en_passant, BoardHelper::text_to_square("g6"));
+        assert_eq!(board.en_passant, Square::G6 as i32);
     }
 
     #[test]
@@ -236,4 +526,79 @@ mod tests {
         assert_eq!(board.half_move, 69);
         assert_eq!(board.full_move, 420);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_fen_counter_warnings() {
+        let mut board = ChessBoard::new();
+        let options = FenParseOptions { normalize_counters: true, ..Default::default() };
+
+        let warnings = board.parse_fen_with_options(STARTPOS_FEN, options).expect("valid fen");
+        assert!(warnings.is_empty());
+
+        let warnings = board.parse_fen_with_options("4k3/8/8/3pP3/8/8/8/4K3 w - d6 3 40", options).expect("valid fen");
+        assert_eq!(warnings, vec![FenWarning::HalfMoveWithEnPassant(3)]);
+        assert_eq!(board.half_move, 0);
+
+        let warnings = board.parse_fen_with_options("4k3/8/8/8/8/8/8/4K3 b - - 120 90", options).expect("valid fen");
+        assert_eq!(warnings, vec![FenWarning::HalfMoveOverLimit(120)]);
+        assert_eq!(board.half_move, 120);
+
+        let warnings = board.parse_fen_with_options("4k3/8/8/8/8/8/8/4K3 b - - 200 110", options).expect("valid fen");
+        assert_eq!(warnings, vec![FenWarning::HalfMoveOverLimit(200)]);
+        assert_eq!(board.half_move, 150);
+
+        // 2 full moves and black to move, only 3 plies could've been played.
+        let warnings = board.parse_fen_with_options("4k3/8/8/8/8/8/8/4K3 b - - 5 2", options).expect("valid fen");
+        assert_eq!(warnings, vec![FenWarning::HalfMoveExceedsPlies(5)]);
+        assert_eq!(board.half_move, 3);
+    }
+
+    #[test]
+    fn test_parse_fen_counter_warnings_without_normalizing() {
+        let mut board = ChessBoard::new();
+        let warnings = board.parse_fen_with_options("4k3/8/8/8/8/8/8/4K3 w - - 7 0", FenParseOptions::default()).expect("valid fen");
+        assert_eq!(warnings, vec![FenWarning::FullMoveZero, FenWarning::HalfMoveExceedsPlies(7)]);
+        assert_eq!(board.half_move, 7);
+        assert_eq!(board.full_move, 0);
+    }
+
+    #[test]
+    fn test_parse_fen_malformed_placement() {
+        let mut board = ChessBoard::new();
+        assert_eq!(board.parse_fen("4k3/8/8/8/8/8/4K3 w - - 0 1"), Err(FenParsingError::WrongRankCount(7)));
+        assert_eq!(board.parse_fen("4k3/8/8/8/8/8/8/4K4 w - - 0 1"), Err(FenParsingError::WrongFileCount(1)));
+        assert_eq!(board.parse_fen("4k3/7/8/8/8/8/8/4K3 w - - 0 1"), Err(FenParsingError::WrongFileCount(7)));
+        assert_eq!(board.parse_fen("4k3/8/8/8/8/8/8/4K2X w - - 0 1"), Err(FenParsingError::InvalidPiece('X')));
+        assert_eq!(board.parse_fen("4k3/8/8/8/8/8/8/K3K3 w - - 0 1"), Err(FenParsingError::MultipleKings(PieceColor::White)));
+        assert_eq!(board.parse_fen("p3k3/8/8/8/8/8/8/4K3 w - - 0 1"), Err(FenParsingError::PawnOnBackRank(56)));
+        assert_eq!(board.parse_fen("4k3/pppppppp/8/8/8/8/8/nnn1K3 w - - 0 1"), Err(FenParsingError::TooManyPieces(PieceColor::Black)));
+        // Promoted pieces are fine as long as pawns are missing
+        assert!(board.parse_fen("4k3/8/8/8/8/8/PPPPPPP1/QQ2K3 w - - 0 1").is_ok());
+        // A rejected FEN leaves the board cleared
+        assert!(board.parse_fen("4k3/8/8/8/8/8/8/4K2X w - - 0 1").is_err());
+        assert_eq!(board.bitboards, [BitBoard::EMPTY; 12]);
+
+        // Missing fields default to no castling rights and no en passant
+        board.parse_fen("4k3/8/8/8/8/8/8/4K3").expect("valid fen");
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn test_parse_fen_castling_and_en_passant() {
+        let mut board = ChessBoard::new();
+        assert_eq!(board.parse_fen("r3k2r/8/8/8/8/8/8/R3K1R1 w KQkq - 0 1"), Err(FenParsingError::InvalidCastlingRight('K')));
+        assert_eq!(board.parse_fen("r3k2r/8/8/8/8/8/8/R2K3R w Qkq - 0 1"), Err(FenParsingError::InvalidCastlingRight('Q')));
+        assert_eq!(board.parse_fen("4k3/8/8/3p4/8/8/8/4K3 b - d6 0 1"), Err(FenParsingError::InvalidEnPassant(43)));
+        assert_eq!(board.parse_fen("4k3/8/8/4P3/8/8/8/4K3 w - d6 0 1"), Err(FenParsingError::InvalidEnPassant(43)));
+        assert_eq!(board.parse_fen("4k3/3n4/8/3p4/8/8/8/4K3 w - d6 0 1"), Err(FenParsingError::InvalidEnPassant(43)));
+
+        let warnings = board.parse_fen_lenient("r3k2r/8/8/8/8/8/8/R2K3R w KQkq d3 9 1").expect("fixable fen");
+        assert_eq!(warnings, vec![
+            FenWarning::CastlingRightRemoved('K'),
+            FenWarning::CastlingRightRemoved('Q'),
+            FenWarning::EnPassantRemoved(19),
+            FenWarning::HalfMoveExceedsPlies(9),
+        ]);
+        assert_eq!(board.to_fen(), "r3k2r/8/8/8/8/8/8/R2K3R w kq - 0 1");
+    }
+}
@@ -0,0 +1,250 @@
+use super::ChessBoard;
+
+use crate::piece::{PieceColor, PieceType};
+
+const LIGHT_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+
+/// Final outcome of a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl core::fmt::Display for GameResult {
+    /// Formats the result as in PGN, e.g. "1-0".
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(match self {
+            Self::WhiteWins => { "1-0" }
+            Self::BlackWins => { "0-1" }
+            Self::Draw => { "1/2-1/2" }
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+    Ongoing,
+    /// Contains the side which delivered the checkmate.
+    Checkmate(PieceColor),
+    Stalemate,
+    /// The 75-move rule, the 50-move rule only allows claiming a draw, see [ChessBoard::get_draw_claim].
+    DrawByFifty,
+    /// A fivefold repetition, a threefold one only allows claiming a draw.
+    DrawByRepetition,
+    DrawByInsufficientMaterial,
+    /// Contains the side which won by the rules of the board's [variant](ChessBoard::set_variant),
+    /// e.g. by exploding the king in atomic chess or by losing every piece in antichess.
+    VariantWin(PieceColor),
+}
+
+impl GameState {
+    /// `None` if the game is still ongoing.
+    #[must_use]
+    pub const fn result(self) -> Option<GameResult> {
+        match self {
+            Self::Ongoing => { None }
+            Self::Checkmate(PieceColor::White) | Self::VariantWin(PieceColor::White) => { Some(GameResult::WhiteWins) }
+            Self::Checkmate(PieceColor::Black) | Self::VariantWin(PieceColor::Black) => { Some(GameResult::BlackWins) }
+            Self::Stalemate | Self::DrawByFifty | Self::DrawByRepetition | Self::DrawByInsufficientMaterial => { Some(GameResult::Draw) }
+        }
+    }
+
+    #[must_use]
+    pub const fn is_game_over(self) -> bool {
+        !matches!(self, Self::Ongoing)
+    }
+}
+
+impl ChessBoard {
+    /// Returns whether the game has ended and how.
+    /// Checkmate takes precedence over the draw rules, e.g. a mate on the 150th half move still wins.
+    /// Only the draws which need no claim end the game, see [ChessBoard::is_draw_automatic].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").expect("valid fen");
+    /// assert_eq!(board.get_game_state(), GameState::Stalemate);
+    /// assert_eq!(board.get_game_state().result(), Some(GameResult::Draw));
+    ///
+    /// board.parse_fen("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2").expect("valid fen");
+    /// board.make_move_uci("d8h4").expect("legal move");
+    /// assert_eq!(board.get_game_state(), GameState::Checkmate(PieceColor::Black));
+    /// assert_eq!(board.get_game_state().result().map(|r| r.to_string()), Some(String::from("0-1")));
+    /// ```
+    /// In a [variant](ChessBoard::set_variant) the game ends by its rules.
+    #[must_use]
+    pub fn get_game_state(&self) -> GameState {
+        self.rules().game_state(self)
+    }
+
+    pub(super) fn standard_game_state(&self) -> GameState {
+        if self.legal_moves_iter().next().is_none() {
+            return if self.is_king_in_check(self.turn) { GameState::Checkmate(self.turn.flipped()) } else { GameState::Stalemate };
+        }
+
+        if self.is_insufficient_material() {
+            GameState::DrawByInsufficientMaterial
+        }
+        else {
+            self.draw_state()
+        }
+    }
+
+    /// The 75-move rule and fivefold repetition, which apply in every variant.
+    pub(super) fn draw_state(&self) -> GameState {
+        if !self.is_draw_automatic() {
+            GameState::Ongoing
+        }
+        else if self.half_move >= 150 {
+            GameState::DrawByFifty
+        }
+        else {
+            GameState::DrawByRepetition
+        }
+    }
+
+    /// Neither side can checkmate with any sequence of moves:
+    /// kings only, a single minor piece or any amount of bishops all on the same colored squares.
+    #[must_use]
+    pub const fn is_insufficient_material(&self) -> bool {
+        let mut heavy_or_pawns = 0u64;
+        let mut knights = 0u64;
+        let mut bishops = 0u64;
+        let mut side = 0;
+        while side < 2 {
+            let color = PieceColor::from_u8(side as u8);
+            heavy_or_pawns |= self.bitboards[PieceType::Pawn.get_side_index(color)].0
+                | self.bitboards[PieceType::Rook.get_side_index(color)].0
+                | self.bitboards[PieceType::Queen.get_side_index(color)].0;
+            knights |= self.bitboards[PieceType::Knight.get_side_index(color)].0;
+            bishops |= self.bitboards[PieceType::Bishop.get_side_index(color)].0;
+            side += 1;
+        }
+
+        if heavy_or_pawns != 0 {
+            return false;
+        }
+        let minors = (knights | bishops).count_ones();
+        minors <= 1 || (knights == 0 && (bishops & LIGHT_SQUARES == 0 || bishops & !LIGHT_SQUARES == 0))
+    }
+
+    /// If the `color` could checkmate with some sequence of legal moves, however unlikely, i.e. a helpmate.
+    /// Decides the result of a game lost on time: if the opponent of the side which ran out of time can't mate, the game is drawn.
+    /// A lone king, a knight against at most a queen, or bishops all on the same colored squares without any knights or pawns on the board can't mate.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// // Black can't win on time with just a knight, but could mate a king with a pawn left
+    /// board.parse_fen("8/8/4k3/8/8/3KN3/8/4n3 w - - 0 1").expect("valid fen");
+    /// assert!(board.has_mating_material(PieceColor::White));
+    /// assert!(board.has_mating_material(PieceColor::Black));
+    ///
+    /// board.parse_fen("8/8/4k3/8/8/3K4/8/4n3 w - - 0 1").expect("valid fen");
+    /// assert!(!board.has_mating_material(PieceColor::Black));
+    /// assert!(board.is_insufficient_material());
+    /// ```
+    #[must_use]
+    pub const fn has_mating_material(&self, color: PieceColor) -> bool {
+        let opponent = color.flipped();
+        let heavy_or_pawns = self.bitboards[PieceType::Pawn.get_side_index(color)].0
+            | self.bitboards[PieceType::Rook.get_side_index(color)].0
+            | self.bitboards[PieceType::Queen.get_side_index(color)].0;
+        if heavy_or_pawns != 0 {
+            return true;
+        }
+
+        let knights = self.bitboards[PieceType::Knight.get_side_index(color)].0;
+        if knights != 0 {
+            // The opponent needs something to block its own king in with, a queen can't be forced to do that
+            let blockers = self.side_bitboards[opponent as usize].0
+                & !self.bitboards[PieceType::King.get_side_index(opponent)].0
+                & !self.bitboards[PieceType::Queen.get_side_index(opponent)].0;
+            return self.side_bitboards[color as usize].0.count_ones() > 2 || blockers != 0;
+        }
+
+        let bishops = self.bitboards[PieceType::Bishop.get_side_index(color)].0;
+        if bishops != 0 {
+            let all_bishops = self.bitboards[PieceType::Bishop.get_side_index(PieceColor::White)].0 | self.bitboards[PieceType::Bishop.get_side_index(PieceColor::Black)].0;
+            let same_colored = all_bishops & LIGHT_SQUARES == 0 || all_bishops & !LIGHT_SQUARES == 0;
+            let pawns = self.bitboards[PieceType::Pawn.get_side_index(opponent)].0;
+            let knights = self.bitboards[PieceType::Knight.get_side_index(opponent)].0;
+            return !same_colored || pawns != 0 || knights != 0;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn _game_state(fen: &str) -> GameState {
+        let mut board = ChessBoard::new();
+        board.parse_fen(fen).expect("valid fen");
+        board.get_game_state()
+    }
+
+    #[test]
+    fn test_game_state_insufficient_material() {
+        assert_eq!(_game_state("8/8/4k3/8/8/3K4/8/8 w - - 0 1"), GameState::DrawByInsufficientMaterial);
+        assert_eq!(_game_state("8/8/4k3/8/8/3KN3/8/8 w - - 0 1"), GameState::DrawByInsufficientMaterial);
+        // Bishops on the same colored squares
+        assert_eq!(_game_state("8/8/3bk3/8/8/3KB3/8/8 w - - 0 1"), GameState::DrawByInsufficientMaterial);
+        assert_eq!(_game_state("8/8/2b1k3/8/8/3KB3/8/8 w - - 0 1"), GameState::Ongoing);
+        assert_eq!(_game_state("8/8/4k3/8/8/3KNN2/8/8 w - - 0 1"), GameState::Ongoing);
+        assert_eq!(_game_state("8/8/4k3/8/8/3KP3/8/8 w - - 0 1"), GameState::Ongoing);
+    }
+
+    #[test]
+    fn test_has_mating_material() {
+        let mut board = ChessBoard::new();
+        for (fen, white, black) in [
+            ("8/8/4k3/8/8/3K4/8/8 w - - 0 1", false, false),
+            ("8/8/4k3/8/8/3K4/7R/8 w - - 0 1", true, false),
+            ("8/8/4k3/8/8/3KN3/8/8 w - - 0 1", false, false),
+            ("8/8/4k3/8/8/3KNN2/8/8 w - - 0 1", true, false),
+            ("8/8/4kq2/8/8/3KN3/8/8 w - - 0 1", false, true),
+            ("8/8/4kr2/8/8/3KN3/8/8 w - - 0 1", true, true),
+            // Bishops on the same colored squares, and on both colors
+            ("8/8/3bk3/8/8/3KB3/8/8 w - - 0 1", false, false),
+            ("8/8/2b1k3/8/8/3KB3/8/8 w - - 0 1", true, true),
+            ("8/8/4k3/4p3/8/3KB3/8/8 w - - 0 1", true, true),
+        ] {
+            board.parse_fen(fen).expect("valid fen");
+            assert_eq!(board.has_mating_material(PieceColor::White), white, "{fen}");
+            assert_eq!(board.has_mating_material(PieceColor::Black), black, "{fen}");
+            assert_eq!(board.is_insufficient_material(), !white && !black, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_game_state_draw_rules() {
+        assert_eq!(_game_state("8/8/4k3/8/8/3K4/7R/8 w - - 150 100"), GameState::DrawByFifty);
+        assert_eq!(_game_state("8/8/4k3/8/8/3K4/7R/8 w - - 149 100"), GameState::Ongoing);
+        // Mate on the last move before the 75-move rule
+        assert_eq!(_game_state("R5k1/5ppp/8/8/8/8/8/6K1 b - - 150 100"), GameState::Checkmate(PieceColor::White));
+
+        // The 50-move rule only allows a claim
+        let mut board = ChessBoard::new();
+        board.parse_fen("8/8/4k3/8/8/3K4/7R/8 w - - 100 80").expect("valid fen");
+        assert_eq!(board.get_game_state(), GameState::Ongoing);
+        assert!(!board.get_game_state().is_game_over());
+        assert!(board.can_claim_draw());
+
+        board.parse_fen("8/8/4k3/8/8/3K4/7R/8 w - - 0 1").expect("valid fen");
+        for (ply, m) in ["h2h3", "e6f6", "h3h2", "f6e6"].iter().cycle().take(16).enumerate() {
+            assert_eq!(board.get_game_state(), GameState::Ongoing);
+            assert_eq!(board.can_claim_draw(), ply >= 8);
+            board.make_move_uci(m).expect("legal move");
+        }
+        assert_eq!(board.get_game_state(), GameState::DrawByRepetition);
+        assert_eq!(board.get_game_state().result(), Some(GameResult::Draw));
+    }
+}
@@ -0,0 +1,127 @@
+use super::ChessBoard;
+use super::game_state::GameState;
+
+use crate::board_helper::BoardHelper;
+use crate::piece::PieceColor;
+use alloc::{collections::BTreeMap, format, string::{String, ToString}, vec::Vec};
+
+/// Snapshot of a position for web frontends, so they don't need FEN parsing and move generation of their own.
+/// Serializes to JSON with [ChessBoard::to_json], see [ChessBoard::to_json_state].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct JsonState {
+    pub fen: String,
+    /// Pieces by square, e.g. `"e1": "K"`, uppercase pieces being white's.
+    pub pieces: BTreeMap<String, char>,
+    /// "white" or "black".
+    pub turn: String,
+    /// As in a FEN, e.g. "KQkq", or "-" for none.
+    pub castling: String,
+    /// Square behind a pawn which just moved two squares, e.g. "e3".
+    pub en_passant: Option<String>,
+    pub half_move: u8,
+    pub full_move: u16,
+    pub check: bool,
+    pub checkmate: bool,
+    pub stalemate: bool,
+    pub game_over: bool,
+    /// As in PGN, e.g. "1-0", when the game is over.
+    pub result: Option<String>,
+    /// Legal moves of the side to move in UCI, e.g. "e2e4" or "e7e8q".
+    pub legal_moves: Vec<String>,
+}
+
+impl ChessBoard {
+    /// Structured snapshot of the position, the side to move's legal moves and whether the game is over.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let board = ChessBoard::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").expect("valid fen");
+    /// let state = board.to_json_state();
+    /// assert_eq!(state.pieces.get("f7"), Some(&'Q'));
+    /// assert_eq!(state.turn, "black");
+    /// assert!(state.stalemate && state.game_over);
+    /// assert_eq!(state.result.as_deref(), Some("1/2-1/2"));
+    /// assert!(state.legal_moves.is_empty());
+    /// ```
+    #[must_use]
+    pub fn to_json_state(&self) -> JsonState {
+        let pieces = (0..64)
+            .filter(|&square| !self.get_piece(square).is_none())
+            .map(|square| {
+                let (file, rank) = BoardHelper::square_to_chars(square);
+                (format!("{file}{rank}"), self.get_piece(square).to_char())
+            })
+            .collect();
+
+        let en_passant = (self.en_passant != -1).then(|| {
+            let (file, rank) = BoardHelper::square_to_chars(self.en_passant);
+            format!("{file}{rank}")
+        });
+
+        // The castling rights are the third field of the FEN
+        let fen = self.to_fen();
+        let castling = fen.split(' ').nth(2).unwrap_or("-").to_string();
+
+        let state = self.get_game_state();
+        JsonState {
+            fen,
+            pieces,
+            turn: String::from(if self.turn == PieceColor::White { "white" } else { "black" }),
+            castling,
+            en_passant,
+            half_move: self.half_move,
+            full_move: self.full_move,
+            check: self.rules().in_check(self, self.turn),
+            checkmate: matches!(state, GameState::Checkmate(_)),
+            stalemate: state == GameState::Stalemate,
+            game_over: state.is_game_over(),
+            result: state.result().map(|result| result.to_string()),
+            legal_moves: self.get_legal_moves().iter().map(|m| m.to_uci()).collect(),
+        }
+    }
+
+    /// [ChessBoard::to_json_state] as a JSON string.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let board = ChessBoard::from_fen(STARTPOS_FEN).expect("valid fen");
+    /// let json = board.to_json();
+    /// assert!(json.contains(r#""turn":"white""#));
+    /// assert!(json.contains(r#""e1":"K""#));
+    /// assert!(json.contains(r#""g1f3""#));
+    /// ```
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.to_json_state()).expect("the state only has string keys")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json_state() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq g3 0 2").expect("valid fen");
+        let state = board.to_json_state();
+        assert_eq!(state.castling, "KQkq");
+        assert_eq!(state.en_passant.as_deref(), Some("g3"));
+        assert_eq!(state.pieces.len(), 32);
+        assert!(!state.check && !state.game_over);
+        assert_eq!(state.legal_moves.len(), board.get_legal_moves().len());
+
+        board.make_move_uci("d8h4").expect("legal move");
+        let state = board.to_json_state();
+        assert!(state.check && state.checkmate && state.game_over);
+        assert_eq!(state.result.as_deref(), Some("0-1"));
+        assert_eq!(state.castling, "KQkq");
+        assert_eq!(state.en_passant, None);
+
+        let json: serde_json::Value = serde_json::from_str(&board.to_json()).expect("valid json");
+        assert_eq!(json["pieces"]["h4"], "q");
+        assert_eq!(json["legal_moves"].as_array().map(Vec::len), Some(0));
+    }
+}
@@ -23,8 +23,12 @@ const ROOK_MASK: [u64; 64] = {
     mask
 };
 
-const ROOK_ATTACK_MAP: [[u64; 4096]; 64] = {
-    let mut map = [[0u64; 4096]; 64];
+const ROOK_OFFSETS: [usize; 64] = table_offsets(&ROOK_SHIFTS);
+const ROOK_TABLE_SIZE: usize = ROOK_OFFSETS[63] + (1 << ROOK_SHIFTS[63]);
+
+/// Attacks of every square one after another ("fancy" magics), the square's attacks start from [ROOK_OFFSETS].
+static ROOK_ATTACK_MAP: [u64; ROOK_TABLE_SIZE] = {
+    let mut map = [0u64; ROOK_TABLE_SIZE];
     
     const_for!(square in 0..64 => {
         let mask = rook_mask(square);
@@ -33,7 +37,7 @@ const ROOK_ATTACK_MAP: [[u64; 4096]; 64] = {
         const_for!(b_idx in 0..len => {
             let b = blockers[b_idx];
             let key = magic_index(ROOK_MAGICS[square as usize], b, ROOK_SHIFTS[square as usize]);
-            map[square as usize][key] = BitBoard::get_rook_attack_mask(square, b);
+            map[ROOK_OFFSETS[square as usize] + key] = BitBoard::get_rook_attack_mask(square, b);
         });
     });
     
@@ -60,8 +64,12 @@ const BISHOP_MASK: [u64; 64] = {
     mask
 };
 
-const BISHOP_ATTACK_MAP: [[u64; 512]; 64] = {
-    let mut map = [[0u64; 512]; 64];
+const BISHOP_OFFSETS: [usize; 64] = table_offsets(&BISHOP_SHIFTS);
+const BISHOP_TABLE_SIZE: usize = BISHOP_OFFSETS[63] + (1 << BISHOP_SHIFTS[63]);
+
+/// Attacks of every square one after another, see [ROOK_ATTACK_MAP].
+static BISHOP_ATTACK_MAP: [u64; BISHOP_TABLE_SIZE] = {
+    let mut map = [0u64; BISHOP_TABLE_SIZE];
 
     const_for!(square in 0..64 => {
         let mask = bishop_mask(square);
@@ -70,13 +78,22 @@ const BISHOP_ATTACK_MAP: [[u64; 512]; 64] = {
         const_for!(b_idx in 0..len => {
             let b = blockers[b_idx];
             let key = magic_index(BISHOP_MAGICS[square as usize], b, BISHOP_SHIFTS[square as usize]);
-            map[square as usize][key] = BitBoard::get_bishop_attack_mask(square, b);
+            map[BISHOP_OFFSETS[square as usize] + key] = BitBoard::get_bishop_attack_mask(square, b);
         });
     });
 
     map
 };
 
+/// Where the attacks of each square start in the flat attack table, a square with `shift` index bits takes 2^shift entries.
+const fn table_offsets(shifts: &[u64; 64]) -> [usize; 64] {
+    let mut offsets = [0usize; 64];
+    const_for!(square in 1..64 => {
+        offsets[square] = offsets[square - 1] + (1 << shifts[square - 1]);
+    });
+    offsets
+}
+
 #[inline(always)]
 pub const fn magic_index(magic: u64, blockers: u64, shift: u64) -> usize {
     ((magic.wrapping_mul(blockers)) >> (64 - shift)) as usize
@@ -87,7 +104,7 @@ pub const fn get_bishop_magic(square: i32, blockers: u64) -> u64 {
     let magic = BISHOP_MAGICS[square as usize];
     let shift = BISHOP_SHIFTS[square as usize];
     let mask: u64 = BISHOP_MASK[square as usize];
-    BISHOP_ATTACK_MAP[square as usize][magic_index(magic, blockers & mask, shift)]
+    BISHOP_ATTACK_MAP[BISHOP_OFFSETS[square as usize] + magic_index(magic, blockers & mask, shift)]
 }
 
 #[inline(always)]
@@ -95,7 +112,7 @@ pub const fn get_rook_magic(square: i32, blockers: u64) -> u64 {
     let magic = ROOK_MAGICS[square as usize];
     let shift = ROOK_SHIFTS[square as usize];
     let mask = ROOK_MASK[square as usize];
-    ROOK_ATTACK_MAP[square as usize][magic_index(magic, blockers & mask, shift)]
+    ROOK_ATTACK_MAP[ROOK_OFFSETS[square as usize] + magic_index(magic, blockers & mask, shift)]
 }
 
 const fn bishop_mask(square: i32) -> u64 {
@@ -207,7 +224,7 @@ mod tests {
                 let b = blockers[b_idx];
                 let idx = magic_index(ROOK_MAGICS[square as usize], b, ROOK_SHIFTS[square as usize]);
 
-                let magic_mask = ROOK_ATTACK_MAP[square as usize][idx];
+                let magic_mask = ROOK_ATTACK_MAP[ROOK_OFFSETS[square as usize] + idx];
                 let expected_mask = BitBoard::get_rook_attack_mask(square, b);
                 
                 if magic_mask != expected_mask {
@@ -220,6 +237,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_magic_bitboard_table_sizes() {
+        assert_eq!(ROOK_TABLE_SIZE, 102_400);
+        assert_eq!(BISHOP_TABLE_SIZE, 5_248);
+        assert_eq!(ROOK_OFFSETS[1], 4096);
+        assert_eq!(BISHOP_OFFSETS[63], BISHOP_TABLE_SIZE - 64);
+    }
+
     #[test]
     fn test_magic_bitboard_rook_index_collision() {
         for square in 0..64 {
@@ -252,7 +277,7 @@ mod tests {
                 let b = blockers[b_idx];
                 let idx = magic_index(BISHOP_MAGICS[square as usize], b, BISHOP_SHIFTS[square as usize]);
 
-                let magic_mask = BISHOP_ATTACK_MAP[square as usize][idx];
+                let magic_mask = BISHOP_ATTACK_MAP[BISHOP_OFFSETS[square as usize] + idx];
                 let expected_mask = BitBoard::get_bishop_attack_mask(square, b);
                 
                 if magic_mask != expected_mask {
@@ -0,0 +1,105 @@
+use super::ChessBoard;
+
+use crate::board_helper::Square;
+use crate::piece::{Piece, PieceColor, PieceType};
+
+/// Centipawn values of the pieces used by [ChessBoard::material], indexed by `piece_type as usize`.
+/// The king has no material value.
+pub const PIECE_VALUES: [i32; 7] = [0, 100, 300, 320, 500, 900, 0];
+
+impl ChessBoard {
+    /// How many pieces of the `piece_type` the `color` has.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.piece_count(PieceType::Pawn, PieceColor::White), 8);
+    /// assert_eq!(board.piece_count(PieceType::Queen, PieceColor::Black), 1);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn piece_count(&self, piece_type: PieceType, color: PieceColor) -> u32 {
+        self.bitboards[piece_type.get_side_index(color)].count()
+    }
+
+    /// Material of the `color` in centipawns, see [PIECE_VALUES].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/8/8/8/8/8/PPP5/R3K3 w - - 0 1").expect("valid fen");
+    /// assert_eq!(board.material(PieceColor::White), 800);
+    /// assert_eq!(board.material(PieceColor::Black), 0);
+    /// ```
+    #[must_use]
+    pub const fn material(&self, color: PieceColor) -> i32 {
+        self.piece_count(PieceType::Pawn, color) as i32 * PIECE_VALUES[PieceType::Pawn as usize] + self.non_pawn_material_of(color)
+    }
+
+    /// Material of both sides without pawns in centipawns, e.g. for telling how far the game has progressed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.non_pawn_material(), 2 * (2 * 300 + 2 * 320 + 2 * 500 + 900));
+    /// ```
+    #[must_use]
+    pub const fn non_pawn_material(&self) -> i32 {
+        self.non_pawn_material_of(PieceColor::White) + self.non_pawn_material_of(PieceColor::Black)
+    }
+
+    const fn non_pawn_material_of(&self, color: PieceColor) -> i32 {
+        let mut material = 0;
+        let piece_types = [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen];
+        let mut i = 0;
+        while i < piece_types.len() {
+            material += self.piece_count(piece_types[i], color) as i32 * PIECE_VALUES[piece_types[i] as usize];
+            i += 1;
+        }
+        material
+    }
+
+    /// Every piece of the `color` with its square, from a1 to h8.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").expect("valid fen");
+    /// let pieces: Vec<(Square, char)> = board.pieces(PieceColor::White).map(|(square, piece)| (square, piece.to_char())).collect();
+    /// assert_eq!(pieces, [(Square::A1, 'R'), (Square::E1, 'K')]);
+    /// ```
+    pub fn pieces(&self, color: PieceColor) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        self.side_bitboards[color as usize].iter_squares().map(|square| (Square::from_u32(square as u32), self.board[square as usize]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_material_accessors() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").expect("valid fen");
+        for color in [PieceColor::White, PieceColor::Black] {
+            let pieces: alloc::vec::Vec<(Square, Piece)> = board.pieces(color).collect();
+            assert_eq!(pieces.len() as u32, board.side_bitboards[color as usize].count());
+            assert!(pieces.iter().all(|(square, piece)| piece.get_color() == color && board.piece_on(*square) == *piece));
+
+            let material: i32 = pieces.iter().map(|(_, piece)| PIECE_VALUES[piece.get_piece_type() as usize]).sum();
+            assert_eq!(board.material(color), material);
+        }
+        assert_eq!(board.piece_count(PieceType::Pawn, PieceColor::White), 8);
+        assert_eq!(board.piece_count(PieceType::Knight, PieceColor::Black), 2);
+        assert_eq!(board.material(PieceColor::White), board.material(PieceColor::Black));
+
+        let pawns = board.piece_count(PieceType::Pawn, PieceColor::White) + board.piece_count(PieceType::Pawn, PieceColor::Black);
+        assert_eq!(board.non_pawn_material(), board.material(PieceColor::White) + board.material(PieceColor::Black) - 100 * pawns as i32);
+    }
+}
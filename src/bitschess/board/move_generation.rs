@@ -1,11 +1,11 @@
 use super::ChessBoard;
 
-use crate::bitschess::bitboard::{PAWN_ATTACKS, KING_ATTACKS, KNIGHT_ATTACKS};
+use crate::bitschess::bitboard::{BETWEENS, PAWN_ATTACKS, KING_ATTACKS, KNIGHT_ATTACKS};
 use crate::bitschess::board::magics::{get_bishop_magic, get_rook_magic};
 
 use crate::board_helper::{BoardHelper, Square};
 use crate::chess_move::{Move, MoveFlag, MoveContainer};
-use crate::piece::{PieceColor, PieceType};
+use crate::piece::{Piece, PieceColor, PieceType};
 
 impl ChessBoard {
     #[inline(always)]
@@ -16,21 +16,567 @@ impl ChessBoard {
 
     // https://www.chessprogramming.org/Checks_and_Pinned_Pieces_(Bitboards)
     pub const fn is_square_in_check(&self, king_color: PieceColor, square: i32) -> bool {
-        const ENEMY_BITBOARD: [usize; 2] = [6, 0];
-        let enemy_bitboard_idx = ENEMY_BITBOARD[king_color as usize];
-        let all_pieces = self.side_bitboards[0] | self.side_bitboards[1];
-        
-        let pawn_checks   = PAWN_ATTACKS[king_color as usize][square as usize] & self.bitboards[enemy_bitboard_idx];
-        let knight_checks = KNIGHT_ATTACKS[square as usize] & self.bitboards[enemy_bitboard_idx+1];
-        let king_checks = KING_ATTACKS[square as usize] & self.bitboards[enemy_bitboard_idx+5];
+        self.attackers_to(square, king_color.flipped()) != 0
+    }
+
+    /// Returns a bitboard of all the pieces of `color` attacking the `square`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/8/8/3p4/4n3/8/5B2/R3K3 w - - 0 1").expect("valid fen");
+    /// let attackers = board.attackers_to(Square::D5 as i32, PieceColor::White);
+    /// assert_eq!(attackers, 0);
+    /// let attackers = board.attackers_to(Square::E4 as i32, PieceColor::Black);
+    /// assert_eq!(attackers, 1u64 << Square::D5 as u32);
+    /// let attackers = board.attackers_to(Square::A7 as i32, PieceColor::White);
+    /// assert_eq!(attackers, (1u64 << Square::A1 as u32) | (1u64 << Square::F2 as u32));
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn attackers_to(&self, square: i32, color: PieceColor) -> u64 {
+        let idx = PieceType::Pawn.get_side_index(color);
+        let all_pieces = self.side_bitboards[0].0 | self.side_bitboards[1].0;
+        let diagonal_sliders = self.bitboards[idx+2].0 | self.bitboards[idx+4].0;
+        let orthogonal_sliders = self.bitboards[idx+3].0 | self.bitboards[idx+4].0;
+
+        // A pawn of 'color' attacks the square, if a pawn of the other color on the square would attack it.
+        (PAWN_ATTACKS[color.flipped() as usize][square as usize] & self.bitboards[idx].0) |
+        (KNIGHT_ATTACKS[square as usize] & self.bitboards[idx+1].0) |
+        (get_bishop_magic(square, all_pieces) & diagonal_sliders) |
+        (get_rook_magic(square, all_pieces) & orthogonal_sliders) |
+        (KING_ATTACKS[square as usize] & self.bitboards[idx+5].0)
+    }
+
+    /// Returns the square of the least valuable piece of `color` attacking the `square`, useful for static exchange evaluation.
+    /// The order of value is pawn, knight, bishop, rook, queen and king.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/8/8/3p4/4n3/8/5B2/R3K3 w - - 0 1").expect("valid fen");
+    /// assert_eq!(board.least_valuable_attacker(Square::A7 as i32, PieceColor::White), Some(Square::F2 as i32));
+    /// assert_eq!(board.least_valuable_attacker(Square::H8 as i32, PieceColor::White), None);
+    /// ```
+    #[must_use]
+    pub const fn least_valuable_attacker(&self, square: i32, color: PieceColor) -> Option<i32> {
+        let attackers = self.attackers_to(square, color);
+        if attackers == 0 {
+            return None;
+        }
+
+        let idx = PieceType::Pawn.get_side_index(color);
+        let mut i = 0;
+        while i < 6 {
+            let pieces = attackers & self.bitboards[idx + i].0;
+            if pieces != 0 {
+                return Some(BoardHelper::bitscan_forward(pieces));
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Returns a bitboard of the squares a `piece` could move to, if it was placed on the `square`. The board is not modified.
+    /// The piece replaces the one on the `square`, checks and pins are not considered.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// let moves = board.moves_if_piece_at(Square::D4 as i32, Piece::from_char('B'));
+    /// assert_eq!(BoardHelper::count_bits(moves), 8);
+    /// let moves = board.moves_if_piece_at(Square::E3 as i32, Piece::from_char('p'));
+    /// assert_eq!(moves, (1u64 << Square::D2 as u32) | (1u64 << Square::F2 as u32));
+    /// ```
+    #[must_use]
+    pub const fn moves_if_piece_at(&self, square: i32, piece: Piece) -> u64 {
+        if piece.is_none() {
+            return 0;
+        }
+
+        let square_mask = 1u64 << square;
+        let color = piece.get_color();
+        let friendly_pieces = self.side_bitboards[color as usize].0 & !square_mask;
+        let enemy_pieces = self.side_bitboards[color.flipped() as usize].0 & !square_mask;
+        let all_pieces = friendly_pieces | enemy_pieces;
+
+        match piece.get_piece_type() {
+            PieceType::Pawn => {
+                let en_passant = if self.en_passant == -1 { 0 } else { 1u64 << self.en_passant };
+                let mut moves = PAWN_ATTACKS[color as usize][square as usize] & (enemy_pieces | en_passant);
+
+                let (move_dir, start_rank) = if color as u32 == PieceColor::White as u32 { (8, 1) } else { (-8, 6) };
+                let advance = square + move_dir;
+                if advance >= 0 && advance < 64 && all_pieces & (1u64 << advance) == 0 {
+                    moves |= 1u64 << advance;
+
+                    let advance_two = advance + move_dir;
+                    if BoardHelper::get_rank(square) == start_rank && all_pieces & (1u64 << advance_two) == 0 {
+                        moves |= 1u64 << advance_two;
+                    }
+                }
+                moves
+            }
+            PieceType::Knight => { KNIGHT_ATTACKS[square as usize] & !friendly_pieces }
+            PieceType::Bishop => { get_bishop_magic(square, all_pieces) & !friendly_pieces }
+            PieceType::Rook => { get_rook_magic(square, all_pieces) & !friendly_pieces }
+            PieceType::Queen => { (get_bishop_magic(square, all_pieces) | get_rook_magic(square, all_pieces)) & !friendly_pieces }
+            PieceType::King => { KING_ATTACKS[square as usize] & !friendly_pieces }
+            _ => { 0 }
+        }
+    }
+}
+
+/// Checks, pins and attacks of the position from the side to move's point of view, computed once per position
+/// with [ChessBoard::movegen_info] and shared by the legal move generation.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// let mut board = ChessBoard::new();
+/// // The knight on d2 is pinned by the bishop and the king is checked by the rook
+/// board.parse_fen("4k3/8/8/b7/8/8/3N4/r3K3 w - - 0 1").expect("valid fen");
+/// let info = board.movegen_info();
+/// assert_eq!(info.checkers, 1u64 << Square::A1 as u32);
+/// assert_eq!(info.pin_d12 & (1u64 << Square::D2 as u32), 1u64 << Square::D2 as u32);
+/// assert_eq!(info.pin_hv, 0);
+/// assert!(!info.double_check);
+/// assert!(info.in_check());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveGenInfo {
+    /// Enemy pieces giving check to the king of the side to move.
+    pub checkers: u64,
+    /// Squares a piece other than the king may move to, the checker and the squares between it and the king.
+    /// Every square when not in check.
+    pub check_mask: u64,
+    /// Horizontally and vertically pinned pieces with the pinning rays, including the pinners.
+    pub pin_hv: u64,
+    /// Diagonally pinned pieces with the pinning rays, including the pinners.
+    pub pin_d12: u64,
+    /// Squares attacked by the enemy, sliders seeing through the own king.
+    pub attack_mask: u64,
+    pub double_check: bool,
+}
+
+impl MoveGenInfo {
+    #[must_use]
+    pub fn new(board: &ChessBoard) -> Self {
+        let attack_mask = MoveGenerator::get_attack_mask(board);
+        let (pin_hv, pin_d12) = MoveGenerator::get_pinned_mask(board);
+        let king_square = board.get_king_square(board.turn);
+
+        let (checkers, double_check, check_mask) = if attack_mask & (1u64 << king_square) != 0 {
+            let (double_check, check_mask) = MoveGenerator::get_check_mask(board);
+            (board.attackers_to(king_square, board.turn.flipped()), double_check, check_mask)
+        } else {
+            (0, false, !0u64)
+        };
+
+        Self { checkers, check_mask, pin_hv, pin_d12, attack_mask, double_check }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn in_check(&self) -> bool {
+        self.checkers != 0
+    }
+}
+
+/// Everything about the position the legal move generation needs, computed once per position.
+struct LegalMoveContext {
+    generate_quiet: bool,
+    /// Checks and pins are ignored, see [MoveGenerator::get_pseudo_legal_moves].
+    pseudo_legal: bool,
+    color_idx: usize,
+    king_square: i32,
+    attack_mask: u64,
+    friendly_pieces: u64,
+    enemy_pieces: u64,
+    all_pieces: u64,
+    enemy_or_empty: u64,
+    move_filter_mask: u64,
+    pin_hv: u64,
+    pin_d12: u64,
+    pin_mask: u64,
+    check_mask: u64,
+    double_check: bool,
+}
+
+impl LegalMoveContext {
+    #[inline(always)]
+    fn new(board: &ChessBoard, generate_quiet: bool) -> Self {
+        Self::from_info(board, &MoveGenInfo::new(board), generate_quiet)
+    }
+
+    fn from_info(board: &ChessBoard, info: &MoveGenInfo, generate_quiet: bool) -> Self {
+        let color_idx = board.turn as usize;
+        let friendly_pieces = board.side_bitboards[color_idx].0;
+        let enemy_pieces = board.side_bitboards[board.turn.flipped() as usize].0;
+
+        Self {
+            generate_quiet,
+            pseudo_legal: false,
+            color_idx,
+            king_square: board.get_king_square(board.turn),
+            attack_mask: info.attack_mask,
+            friendly_pieces,
+            enemy_pieces,
+            all_pieces: friendly_pieces | enemy_pieces,
+            enemy_or_empty: (!0u64) ^ friendly_pieces,
+            move_filter_mask: if generate_quiet { !0u64 } else { enemy_pieces },
+            pin_hv: info.pin_hv,
+            pin_d12: info.pin_d12,
+            pin_mask: info.pin_hv | info.pin_d12,
+            check_mask: info.check_mask,
+            double_check: info.double_check,
+        }
+    }
+
+    /// Context without any pins or checks, the attack mask is only used for castling.
+    fn pseudo_legal(board: &ChessBoard) -> Self {
+        let color_idx = board.turn as usize;
+        let friendly_pieces = board.side_bitboards[color_idx].0;
+        let enemy_pieces = board.side_bitboards[board.turn.flipped() as usize].0;
+
+        Self {
+            generate_quiet: true,
+            pseudo_legal: true,
+            color_idx,
+            king_square: board.get_king_square(board.turn),
+            attack_mask: MoveGenerator::get_attack_mask(board),
+            friendly_pieces,
+            enemy_pieces,
+            all_pieces: friendly_pieces | enemy_pieces,
+            enemy_or_empty: (!0u64) ^ friendly_pieces,
+            move_filter_mask: !0u64,
+            pin_hv: 0,
+            pin_d12: 0,
+            pin_mask: 0,
+            check_mask: !0u64,
+            double_check: false,
+        }
+    }
+
+    /// If the `m` is generated when quiet moves aren't, i.e. a capture that isn't an under promotion.
+    #[inline(always)]
+    fn is_capture_stage(&self, m: Move) -> bool {
+        match m.get_flag() {
+            MoveFlag::EnPassant => { true }
+            MoveFlag::PromoteKnight | MoveFlag::PromoteBishop | MoveFlag::PromoteRook => { false }
+            _ => { self.enemy_pieces & (1u64 << m.get_to_idx()) != 0 }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GenerationStage {
+    King,
+    Knights,
+    Bishops,
+    Rooks,
+    Pawns,
+    Done,
+}
+
+/// Legal moves generated one piece at a time, see [MoveGenerator::legal_moves_iter].
+/// Yields the captures and queen promoting captures first, then the rest of the moves.
+pub struct LegalMoveIter<'a> {
+    board: &'a ChessBoard,
+    context: LegalMoveContext,
+    /// If the captures are being generated, the quiet moves are generated on a second pass.
+    captures: bool,
+    stage: GenerationStage,
+    /// Squares of the pieces left in the current stage.
+    pieces: u64,
+    /// Moves of the last generated piece.
+    buffer: MoveContainer,
+    index: usize,
+}
+
+impl<'a> LegalMoveIter<'a> {
+    pub(crate) fn new(board: &'a ChessBoard) -> Self {
+        Self {
+            board,
+            context: LegalMoveContext::new(board, false),
+            captures: true,
+            stage: GenerationStage::King,
+            pieces: 0,
+            buffer: MoveContainer::new(),
+            index: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn side_pieces(&self, piece_types: &[PieceType]) -> u64 {
+        piece_types.iter().fold(0, |mask, piece_type| mask | self.board.bitboards[piece_type.get_side_index(self.board.turn)].0)
+    }
+}
+
+impl Iterator for LegalMoveIter<'_> {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        loop {
+            if let Some(m) = self.buffer.get(self.index) {
+                self.index += 1;
+                // Captures were already yielded on the first pass
+                if !self.captures && self.context.is_capture_stage(m) {
+                    continue;
+                }
+                return Some(m);
+            }
+            self.buffer.clear();
+            self.index = 0;
+
+            // Go to the next stage once the pieces of the current one have been generated
+            if self.pieces == 0 && self.stage != GenerationStage::King {
+                let (stage, pieces) = match self.stage {
+                    GenerationStage::Knights => { (GenerationStage::Bishops, self.side_pieces(&[PieceType::Bishop, PieceType::Queen])) }
+                    GenerationStage::Bishops => { (GenerationStage::Rooks, self.side_pieces(&[PieceType::Rook, PieceType::Queen])) }
+                    GenerationStage::Rooks => { (GenerationStage::Pawns, self.side_pieces(&[PieceType::Pawn])) }
+                    _ if self.captures => {
+                        self.captures = false;
+                        self.context.generate_quiet = true;
+                        self.context.move_filter_mask = !0u64;
+                        (GenerationStage::King, 0)
+                    }
+                    _ => { return None; }
+                };
+                self.stage = stage;
+                self.pieces = pieces;
+                continue;
+            }
+
+            match self.stage {
+                GenerationStage::King => {
+                    MoveGenerator::generate_king_moves(self.board, &self.context, &mut self.buffer);
+                    // In double check, only king is allowed to move.
+                    self.stage = if self.context.double_check { GenerationStage::Done } else { GenerationStage::Knights };
+                    self.pieces = if self.context.double_check { 0 } else { self.side_pieces(&[PieceType::Knight]) };
+                }
+                GenerationStage::Knights => {
+                    let square = BoardHelper::pop_lsb(&mut self.pieces);
+                    MoveGenerator::generate_knight_moves(&self.context, square, &mut self.buffer);
+                }
+                GenerationStage::Bishops => {
+                    let square = BoardHelper::pop_lsb(&mut self.pieces);
+                    MoveGenerator::generate_bishop_moves(&self.context, square, &mut self.buffer);
+                }
+                GenerationStage::Rooks => {
+                    let square = BoardHelper::pop_lsb(&mut self.pieces);
+                    MoveGenerator::generate_rook_moves(&self.context, square, &mut self.buffer);
+                }
+                GenerationStage::Pawns => {
+                    let square = BoardHelper::pop_lsb(&mut self.pieces);
+                    MoveGenerator::generate_pawn_moves(self.board, &self.context, square, &mut self.buffer);
+                }
+                GenerationStage::Done => { return None; }
+            }
+        }
+    }
+}
+
+/// The stage a [StagedMoveGen] is yielding moves from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveStage {
+    /// The move from the transposition table.
+    TtMove,
+    /// Captures and queen promotions, most valuable victim & least valuable attacker first.
+    Captures,
+    /// Quiet moves which caused a cutoff in sibling nodes.
+    Killers,
+    /// Rest of the quiet moves, in the generation order.
+    Quiets,
+    Done,
+}
+
+/// Legal moves in the order a search usually wants to try them, see [MoveStage].
+/// Every legal move is yielded exactly once, the TT move and killers are only yielded if they are legal.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// let mut board = ChessBoard::new();
+/// board.parse_fen("4k3/8/8/3q4/4P3/8/8/4K2N w - - 0 1").expect("valid fen");
+///
+/// let killers = [Some(Move::from_uci("h1g3")), None];
+/// let mut moves = StagedMoveGen::new(&board, Some(Move::from_uci("e1f2")), killers);
+/// assert_eq!(moves.next(), Some(Move::from_uci("e1f2")));
+/// assert_eq!(moves.stage(), MoveStage::TtMove);
+/// assert_eq!(moves.next(), Some(Move::from_uci("e4d5")));
+/// assert_eq!(moves.stage(), MoveStage::Captures);
+/// assert_eq!(moves.next(), Some(Move::from_uci("h1g3")));
+/// assert_eq!(moves.stage(), MoveStage::Killers);
+/// assert_eq!(moves.count(), board.get_legal_moves().len() - 3);
+/// ```
+pub struct StagedMoveGen {
+    stage: MoveStage,
+    tt_move: Option<Move>,
+    killers: [Option<Move>; 2],
+    /// Legal moves, captures first and then the quiet moves.
+    moves: MoveContainer,
+    /// MVV-LVA scores for the captures.
+    scores: [i32; 218],
+    capture_count: usize,
+    /// Index of the next move to look at, or the next killer slot.
+    index: usize,
+}
+
+impl StagedMoveGen {
+    /// Generates the legal moves for the `board`, the TT move and killers may be illegal or missing.
+    #[must_use]
+    pub fn new(board: &ChessBoard, tt_move: Option<Move>, killers: [Option<Move>; 2]) -> Self {
+        let mut moves = MoveGenerator::get_legal_moves(board, true);
+        let mut scores = [0; 218];
+        let mut capture_count = 0;
+
+        // Partition the captures to the front.
+        for i in 0..moves.len() {
+            let m = unsafe { moves.get_unchecked(i) };
+            if let Some(score) = Self::mvv_lva(board, m) {
+                moves.swap(i, capture_count);
+                scores[capture_count] = score;
+                capture_count += 1;
+            }
+        }
+
+        let is_legal = |m: &Move| moves.contains(*m);
+        let tt_move = tt_move.filter(is_legal);
+        let mut killers = killers.map(|killer| killer.filter(|k| is_legal(k) && Some(*k) != tt_move));
+        if killers[0] == killers[1] {
+            killers[1] = None;
+        }
+
+        Self {
+            stage: if tt_move.is_some() { MoveStage::TtMove } else { MoveStage::Captures },
+            tt_move,
+            killers,
+            moves,
+            scores,
+            capture_count,
+            index: 0,
+        }
+    }
+
+    /// The stage the last yielded move came from.
+    #[must_use]
+    #[inline(always)]
+    pub const fn stage(&self) -> MoveStage {
+        self.stage
+    }
+
+    /// Score for captures and queen promotions, [None] for the quiet moves.
+    fn mvv_lva(board: &ChessBoard, m: Move) -> Option<i32> {
+        let attacker = board.moved_piece(m).get_piece_type() as i32;
+        let victim = board.captured_piece(m).map_or(PieceType::None, Piece::get_piece_type);
+        let promotion = if m.get_flag() == MoveFlag::PromoteQueen { PieceType::Queen as i32 } else { 0 };
+
+        if victim == PieceType::None && promotion == 0 {
+            return None;
+        }
+        Some((victim as i32 + promotion) * 8 - attacker)
+    }
+
+    #[inline(always)]
+    fn is_killer(&self, m: Move) -> bool {
+        self.killers.contains(&Some(m))
+    }
+}
+
+impl Iterator for StagedMoveGen {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        loop {
+            match self.stage {
+                MoveStage::TtMove => {
+                    // Yielded already, continue to captures
+                    if self.index != 0 {
+                        self.stage = MoveStage::Captures;
+                        self.index = 0;
+                        continue;
+                    }
+                    self.index = 1;
+                    return self.tt_move;
+                }
+                MoveStage::Captures => {
+                    if self.index >= self.capture_count {
+                        self.stage = MoveStage::Killers;
+                        self.index = 0;
+                        continue;
+                    }
 
-        let bishop_checks = get_bishop_magic(square, all_pieces) & (self.bitboards[enemy_bitboard_idx+2] | self.bitboards[enemy_bitboard_idx+4]);
-        let rook_checks   = get_rook_magic(square, all_pieces) & (self.bitboards[enemy_bitboard_idx+3] | self.bitboards[enemy_bitboard_idx+4]);
+                    // Selection sort, only the moves actually looked at get sorted.
+                    let mut best = self.index;
+                    for i in self.index + 1..self.capture_count {
+                        if self.scores[i] > self.scores[best] {
+                            best = i;
+                        }
+                    }
+                    self.moves.swap(self.index, best);
+                    self.scores.swap(self.index, best);
+
+                    let m = unsafe { self.moves.get_unchecked(self.index) };
+                    self.index += 1;
+                    if Some(m) != self.tt_move {
+                        return Some(m);
+                    }
+                }
+                MoveStage::Killers => {
+                    if self.index >= self.killers.len() {
+                        self.stage = MoveStage::Quiets;
+                        self.index = self.capture_count;
+                        continue;
+                    }
 
-        (pawn_checks | knight_checks | bishop_checks | rook_checks | king_checks) != 0
+                    let killer = self.killers[self.index];
+                    self.index += 1;
+                    // Captures were already yielded
+                    if let Some(m) = killer {
+                        if self.moves.as_slice()[self.capture_count..].contains(&m) {
+                            return Some(m);
+                        }
+                    }
+                }
+                MoveStage::Quiets => {
+                    let Some(m) = self.moves.get(self.index) else {
+                        self.stage = MoveStage::Done;
+                        continue;
+                    };
+                    self.index += 1;
+                    if Some(m) != self.tt_move && !self.is_killer(m) {
+                        return Some(m);
+                    }
+                }
+                MoveStage::Done => { return None; }
+            }
+        }
     }
 }
 
+/// Which moves [MoveGenerator::generate_into] generates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GenType {
+    /// Every legal move.
+    #[default]
+    All,
+    /// Legal captures and queen promotions, under promotions are left out.
+    Captures,
+    /// [GenType::Captures] and every promotion, including the under promotions.
+    CapturesAndPromotions,
+    /// [GenType::Captures] and the quiet moves which give check, see [MoveGenerator::gives_check].
+    CapturesAndChecks,
+    /// Every legal move when in check, nothing otherwise.
+    Evasions,
+    /// Moves which may leave the own king in check, see [MoveGenerator::get_pseudo_legal_moves].
+    PseudoLegal,
+}
+
 pub struct MoveGenerator;
 
 impl MoveGenerator {
@@ -56,201 +602,618 @@ impl MoveGenerator {
         }
     }
 
+    /// Legal moves generated lazily, captures first. Cheaper than [MoveGenerator::get_legal_moves]
+    /// when only some of the moves are needed, e.g. checking if there are any legal moves.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").expect("valid fen");
+    /// let first = MoveGenerator::legal_moves_iter(&board).next();
+    /// assert_eq!(first.map(|m| m.to_uci()), Some(String::from("e4d5")));
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub fn legal_moves_iter(board: &ChessBoard) -> LegalMoveIter<'_> {
+        LegalMoveIter::new(board)
+    }
+
     /// if generate_quiet == false then moves which doesn't either capture or promote to a queen won't be generated.
     pub fn get_legal_moves(board: &ChessBoard, generate_quiet: bool) -> MoveContainer {
-        use crate::bitschess::bitboard;
-        let color_idx = board.turn as usize;
-        let enemy_bitboard_idx = board.turn.flipped() as usize;
+        Self::get_legal_moves_with_info(board, &MoveGenInfo::new(board), generate_quiet)
+    }
 
-        // 
-        let attack_mask = Self::get_attack_mask(board);
+    /// Same as [MoveGenerator::get_legal_moves], but with the checks and pins already computed,
+    /// e.g. when the [MoveGenInfo] was needed for something else in the same position.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/8/8/8/8/8/3N4/r3K3 w - - 0 1").expect("valid fen");
+    /// let info = board.movegen_info();
+    /// let moves = MoveGenerator::get_legal_moves_with_info(&board, &info, true);
+    /// assert!(info.in_check());
+    /// assert_eq!(moves.len(), board.get_legal_moves().len());
+    /// ```
+    #[must_use]
+    pub fn get_legal_moves_with_info(board: &ChessBoard, info: &MoveGenInfo, generate_quiet: bool) -> MoveContainer {
+        let mut moves = MoveContainer::new();
+        let context = LegalMoveContext::from_info(board, info, generate_quiet);
+        Self::generate_all_moves(board, &context, &mut moves);
+        moves
+    }
 
-        let friendly_pieces = board.side_bitboards[color_idx];
-        let enemy_pieces = board.side_bitboards[enemy_bitboard_idx];
-        let all_pieces = friendly_pieces | enemy_pieces;
-        let enemy_or_empty = (!0u64) ^ friendly_pieces;
-        let move_filter_mask = if generate_quiet { !0u64 } else { enemy_pieces };
+    /// Replaces the contents of the `moves` with the moves of the `gen_type`.
+    /// Lets a search reuse the same container on every visit of a ply instead of creating a new one every time.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// let mut moves = MoveContainer::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// MoveGenerator::generate_into(&board, &mut moves, GenType::All);
+    /// assert_eq!(moves.len(), 20);
+    ///
+    /// board.parse_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").expect("valid fen");
+    /// MoveGenerator::generate_into(&board, &mut moves, GenType::Captures);
+    /// assert_eq!(moves.iter().map(|m| m.to_uci()).collect::<Vec<_>>(), ["e4d5"]);
+    ///
+    /// board.parse_fen("4k3/1P6/8/3p4/4P3/8/8/R3K3 w - - 0 1").expect("valid fen");
+    /// MoveGenerator::generate_into(&board, &mut moves, GenType::CapturesAndPromotions);
+    /// assert_eq!(moves.len(), 5);
+    /// MoveGenerator::generate_into(&board, &mut moves, GenType::CapturesAndChecks);
+    /// assert_eq!(moves.iter().map(|m| m.to_uci()).collect::<Vec<_>>(), ["a1a8", "e4d5", "b7b8r", "b7b8q"]);
+    /// MoveGenerator::generate_into(&board, &mut moves, GenType::Evasions);
+    /// assert!(moves.is_empty());
+    /// ```
+    pub fn generate_into(board: &ChessBoard, moves: &mut MoveContainer, gen_type: GenType) {
+        let context = match gen_type {
+            GenType::All | GenType::CapturesAndPromotions | GenType::CapturesAndChecks | GenType::Evasions => { LegalMoveContext::new(board, true) }
+            GenType::Captures => { LegalMoveContext::new(board, false) }
+            GenType::PseudoLegal => { LegalMoveContext::pseudo_legal(board) }
+        };
+        moves.clear();
+        if gen_type == GenType::Evasions && context.attack_mask & (1u64 << context.king_square) == 0 {
+            return;
+        }
 
-        let (pin_hv, pin_d12) = Self::get_pinned_mask(board);
-        let pin_mask = pin_hv | pin_d12;
-        let mut moves = MoveContainer::new();
-        let mut check_mask = !0u64;
+        Self::generate_all_moves(board, &context, moves);
+        match gen_type {
+            GenType::CapturesAndPromotions => {
+                moves.retain(|m| context.is_capture_stage(m) || m.is_promotion());
+            }
+            GenType::CapturesAndChecks => {
+                moves.retain(|m| context.is_capture_stage(m) || Self::gives_check(board, m));
+            }
+            _ => {}
+        }
+    }
 
-        // King 
-        let king_square = board.get_king_square(board.turn);
-        let king_moves = KING_ATTACKS[king_square as usize] & !attack_mask & !friendly_pieces & move_filter_mask;
-        Self::generate_moves(king_square, king_moves, &mut moves);
+    /// Checks if the `m` puts the opponent's king in check, directly or by uncovering a slider, without making the move.
+    /// The `m` is expected to be pseudo legal.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// // The knight on e4 uncovers the rook on e1
+    /// board.parse_fen("4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1").expect("valid fen");
+    /// assert!(MoveGenerator::gives_check(&board, Move::from_uci("e4c3")));
+    /// assert!(MoveGenerator::gives_check(&board, Move::from_uci("e4f6")));
+    /// assert!(!MoveGenerator::gives_check(&board, Move::from_uci("e1d1")));
+    /// ```
+    #[must_use]
+    pub fn gives_check(board: &ChessBoard, m: Move) -> bool {
+        let us = board.turn;
+        let them = us.flipped();
+        let king_mask = board.bitboards[PieceType::King.get_side_index(them)].0;
+        let king_square = board.get_king_square(them);
+        let (from, to) = (m.get_from_idx(), m.get_to_idx());
+        // Drops come from the hand, not from a square
+        let from_mask = if m.is_drop() { 0 } else { 1u64 << from };
 
-        let king_attacked_mask = attack_mask & (1u64 << king_square);
-        if king_attacked_mask != 0 {            
-            let double_check;
-            (double_check, check_mask) = Self::get_check_mask(board);
+        let own_index = PieceType::Pawn.get_side_index(us);
+        let mut diagonal_sliders = board.bitboards[own_index + 2].0 | board.bitboards[own_index + 4].0;
+        let mut orthogonal_sliders = board.bitboards[own_index + 3].0 | board.bitboards[own_index + 4].0;
+        let mut occupied = (board.side_bitboards[0].0 | board.side_bitboards[1].0) & !from_mask | (1u64 << to);
+        diagonal_sliders &= !from_mask;
+        orthogonal_sliders &= !from_mask;
 
-            // In double check, only king is allowed to move.
-            if double_check {
-                return moves;
-            }
-        }
-        else if generate_quiet {
-            // Castling
-            let rights_idx = (color_idx) * 2;
-            let rooks = board.bitboards[PieceType::Rook.get_side_index(board.turn)];
-            let square_for_black = (color_idx as i32) * 56;
-
-            // King Side
-            if board.castling_rights[rights_idx] {
-                const ROOK_LOCATION_MASK: [u64; 2] = [1u64 << (Square::H1 as u64), 1u64 << (Square::H8 as u64)];
-                const EMPTY_SQUARES: [u64; 2] = [0b1100000, 0b1100000 << (7*8)];
-
-                let are_empty = all_pieces & EMPTY_SQUARES[color_idx] == 0;
-                let are_attacked = attack_mask & EMPTY_SQUARES[color_idx] != 0;
-                let rook_in_place = rooks & ROOK_LOCATION_MASK[color_idx] != 0;
-                if are_empty && !are_attacked && rook_in_place {
-                    moves.push(Move::new((Square::E1 as i32) + square_for_black, (Square::G1 as i32) + square_for_black, MoveFlag::Castle));
-                }
+        let piece_type = match m.get_flag() {
+            MoveFlag::PromoteQueen => { PieceType::Queen }
+            MoveFlag::PromoteRook => { PieceType::Rook }
+            MoveFlag::PromoteBishop => { PieceType::Bishop }
+            MoveFlag::PromoteKnight => { PieceType::Knight }
+            MoveFlag::EnPassant => {
+                let captured_square = if us == PieceColor::White { to - 8 } else { to + 8 };
+                occupied &= !(1u64 << captured_square);
+                PieceType::Pawn
+            }
+            MoveFlag::Castle => {
+                let (rook_from, rook_to) = if to > from { (from + 3, from + 1) } else { (from - 4, from - 1) };
+                occupied = occupied & !(1u64 << rook_from) | (1u64 << rook_to);
+                orthogonal_sliders = orthogonal_sliders & !(1u64 << rook_from) | (1u64 << rook_to);
+                PieceType::King
             }
+            MoveFlag::Drop => { m.dropped_piece().unwrap_or(PieceType::None) }
+            _ => { board.get_piece(from).get_piece_type() }
+        };
 
-            // Queen Side
-            if board.castling_rights[rights_idx+1] {
-                const ROOK_LOCATION_MASK: [u64; 2] = [1u64 << (Square::A1 as u64), 1u64 << (Square::A8 as u64)];
-                const EMPTY_SQUARES: [u64; 2] = [0b1110, 0b1110 << (7*8)];
-                const NON_ATTACKED_MASK: [u64; 2] = [0b1100, 0b1100 << (7*8)];
+        // Direct check by the moved piece
+        let attacks = match piece_type {
+            PieceType::Pawn => { PAWN_ATTACKS[us as usize][to as usize] }
+            PieceType::Knight => { KNIGHT_ATTACKS[to as usize] }
+            PieceType::Bishop => { get_bishop_magic(to, occupied) }
+            PieceType::Rook => { get_rook_magic(to, occupied) }
+            PieceType::Queen => { get_bishop_magic(to, occupied) | get_rook_magic(to, occupied) }
+            PieceType::King | PieceType::None => { 0 }
+        };
+        if attacks & king_mask != 0 {
+            return true;
+        }
+
+        // Sliders seeing the king after the move, includes the rook of a castling move
+        (get_bishop_magic(king_square, occupied) & diagonal_sliders) | (get_rook_magic(king_square, occupied) & orthogonal_sliders) != 0
+    }
+
+    /// Checks if the `m` checkmates the opponent without making the move: the king can't step out of the check,
+    /// and a single checker can't be captured or blocked by an unpinned piece, or by a drop when the variant has them.
+    /// The `m` is expected to be legal. Follows the standard rules of check, which atomic chess and antichess change.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").expect("valid fen");
+    /// assert!(MoveGenerator::gives_checkmate(&board, Move::from_uci("a1a8")));
+    ///
+    /// // The rook can be taken
+    /// board.parse_fen("1r4k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").expect("valid fen");
+    /// assert!(!MoveGenerator::gives_checkmate(&board, Move::from_uci("a1a8")));
+    /// ```
+    #[must_use]
+    pub fn gives_checkmate(board: &ChessBoard, m: Move) -> bool {
+        if !Self::gives_check(board, m) {
+            return false;
+        }
+
+        let us = board.turn;
+        let them = us.flipped();
+        let (from, to) = (m.get_from_idx(), m.get_to_idx());
+        let from_mask = if m.is_drop() { 0 } else { 1u64 << from };
+        let to_mask = 1u64 << to;
+
+        // Our pieces and the occupancy after the move, theirs without the captured piece
+        let own = |piece_type: PieceType| board.bitboards[piece_type.get_side_index(us)].0 & !from_mask;
+        let (mut pawns, mut knights, mut bishops, mut rooks, mut queens, mut king) =
+            (own(PieceType::Pawn), own(PieceType::Knight), own(PieceType::Bishop), own(PieceType::Rook), own(PieceType::Queen), own(PieceType::King));
+        let mut occupied = (board.side_bitboards[0].0 | board.side_bitboards[1].0) & !from_mask | to_mask;
+        let mut theirs = board.side_bitboards[them as usize].0 & !to_mask;
+
+        let moved_type = match m.dropped_piece() {
+            Some(piece_type) => { piece_type }
+            None => { m.promotion_piece().unwrap_or(board.get_piece(from).get_piece_type()) }
+        };
+        match moved_type {
+            PieceType::Pawn => { pawns |= to_mask; }
+            PieceType::Knight => { knights |= to_mask; }
+            PieceType::Bishop => { bishops |= to_mask; }
+            PieceType::Rook => { rooks |= to_mask; }
+            PieceType::Queen => { queens |= to_mask; }
+            PieceType::King => { king |= to_mask; }
+            PieceType::None => {}
+        }
+        match m.get_flag() {
+            MoveFlag::EnPassant => {
+                let captured_mask = 1u64 << if us == PieceColor::White { to - 8 } else { to + 8 };
+                theirs &= !captured_mask;
+                occupied &= !captured_mask;
+            }
+            MoveFlag::Castle => {
+                let (rook_from, rook_to) = if to > from { (from + 3, from + 1) } else { (from - 4, from - 1) };
+                rooks = rooks & !(1u64 << rook_from) | (1u64 << rook_to);
+                occupied = occupied & !(1u64 << rook_from) | (1u64 << rook_to);
+            }
+            _ => {}
+        }
+        let diagonal_sliders = bishops | queens;
+        let orthogonal_sliders = rooks | queens;
 
-                let are_empty = all_pieces & EMPTY_SQUARES[color_idx] == 0;
-                let are_attacked = attack_mask & NON_ATTACKED_MASK[color_idx] != 0;
-                let rook_in_place = rooks & ROOK_LOCATION_MASK[color_idx] != 0;
-                if are_empty && !are_attacked && rook_in_place {
-                    moves.push(Move::new((Square::E1 as i32) + square_for_black, (Square::C1 as i32) + square_for_black, MoveFlag::Castle));
+        // The king steps out of the check, our sliders seeing through it
+        let their_king = board.bitboards[PieceType::King.get_side_index(them)].0;
+        let king_square = BoardHelper::bitscan_forward(their_king);
+        let mut attacked = 0u64;
+        let xray_occupied = occupied & !their_king;
+        for (mut pieces, piece_type) in [(pawns, PieceType::Pawn), (knights, PieceType::Knight), (diagonal_sliders, PieceType::Bishop), (orthogonal_sliders, PieceType::Rook), (king, PieceType::King)] {
+            while pieces != 0 {
+                attacked |= ChessBoard::attacks_from(Piece::from_type(piece_type, us), BoardHelper::pop_lsb(&mut pieces), xray_occupied);
+            }
+        }
+        if KING_ATTACKS[king_square as usize] & !theirs & !attacked != 0 {
+            return false;
+        }
+
+        let checkers = (PAWN_ATTACKS[them as usize][king_square as usize] & pawns)
+            | (KNIGHT_ATTACKS[king_square as usize] & knights)
+            | (get_bishop_magic(king_square, occupied) & diagonal_sliders)
+            | (get_rook_magic(king_square, occupied) & orthogonal_sliders);
+        if checkers.count_ones() > 1 {
+            return true;
+        }
+        let blocks = BETWEENS[king_square as usize][BoardHelper::bitscan_forward(checkers) as usize];
+
+        // Whether moving their piece from `from` to `to` leaves the king out of the sliders' reach, `removed` also being emptied
+        let resolves = |from: i32, to: i32, removed: u64| {
+            let occupancy = occupied & !(1u64 << from) & !removed | (1u64 << to);
+            let remaining = !(1u64 << to);
+            (get_bishop_magic(king_square, occupancy) & diagonal_sliders & remaining) | (get_rook_magic(king_square, occupancy) & orthogonal_sliders & remaining) == 0
+        };
+
+        // Capturing the checker or blocking its ray
+        let forward = if them == PieceColor::White { 8 } else { -8 };
+        let start_rank = if them == PieceColor::White { 1 } else { 6 };
+        let mut defenders = theirs & !their_king;
+        while defenders != 0 {
+            let square = BoardHelper::pop_lsb(&mut defenders);
+            let mut targets = match board.get_piece(square).get_piece_type() {
+                PieceType::Pawn => {
+                    let mut pawn_moves = PAWN_ATTACKS[them as usize][square as usize] & checkers;
+                    let one = square + forward;
+                    if occupied & (1u64 << one) == 0 {
+                        pawn_moves |= 1u64 << one;
+                        if BoardHelper::get_rank(square) == start_rank && occupied & (1u64 << (one + forward)) == 0 {
+                            pawn_moves |= 1u64 << (one + forward);
+                        }
+                    }
+                    pawn_moves
+                }
+                PieceType::Knight => { KNIGHT_ATTACKS[square as usize] }
+                PieceType::Bishop => { get_bishop_magic(square, occupied) }
+                PieceType::Rook => { get_rook_magic(square, occupied) }
+                PieceType::Queen => { get_bishop_magic(square, occupied) | get_rook_magic(square, occupied) }
+                PieceType::King | PieceType::None => { 0 }
+            } & (checkers | blocks);
+            while targets != 0 {
+                if resolves(square, BoardHelper::pop_lsb(&mut targets), 0) {
+                    return false;
+                }
+            }
+        }
+
+        // Taking the pawn which just moved two squares en passant
+        if m.get_flag() == MoveFlag::PawnTwoUp {
+            let en_passant = (from + to) / 2;
+            if (checkers | blocks) & ((1u64 << en_passant) | to_mask) != 0 {
+                let mut capturers = PAWN_ATTACKS[us as usize][en_passant as usize] & board.bitboards[PieceType::Pawn.get_side_index(them)].0;
+                while capturers != 0 {
+                    if resolves(BoardHelper::pop_lsb(&mut capturers), en_passant, to_mask) {
+                        return false;
+                    }
                 }
             }
         }
 
+        // Dropping a piece in between, pawns can't be dropped on the first and last ranks
+        if blocks != 0 && board.rules().drops() {
+            let pockets = board.pockets();
+            let droppable = [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen].iter().any(|piece_type| pockets.count(them, *piece_type) > 0);
+            let pawn_blocks = blocks & !0xFF00_0000_0000_00FF;
+            if droppable || (pockets.count(them, PieceType::Pawn) > 0 && pawn_blocks != 0) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Moves which follow the movement rules of the pieces, but may leave the own king in check.
+    /// Castling is still only generated when the king doesn't castle out of or through a check.
+    /// The legal ones can be filtered with [MoveGenerator::is_legal].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// // The knight on d2 is pinned
+    /// board.parse_fen("4k3/8/8/b7/8/8/3N4/4K3 w - - 0 1").expect("valid fen");
+    /// let moves = MoveGenerator::get_pseudo_legal_moves(&board);
+    /// assert!(moves.iter().any(|m| m.to_uci() == "d2f3"));
+    /// assert!(!board.is_legal(Move::from_uci("d2f3")));
+    ///
+    /// let legal: Vec<Move> = moves.into_iter().filter(|m| board.is_legal(*m)).collect();
+    /// assert_eq!(legal.len(), board.get_legal_moves().len());
+    /// ```
+    #[must_use]
+    pub fn get_pseudo_legal_moves(board: &ChessBoard) -> MoveContainer {
+        let mut moves = MoveContainer::new();
+        Self::generate_into(board, &mut moves, GenType::PseudoLegal);
+        moves
+    }
+
+    /// Checks if the `m` is one of the [pseudo legal moves](MoveGenerator::get_pseudo_legal_moves),
+    /// only the moves of the moving piece are generated.
+    #[must_use]
+    pub fn is_pseudo_legal(board: &ChessBoard, m: Move) -> bool {
+        let context = LegalMoveContext::pseudo_legal(board);
+        let mut moves = MoveContainer::new();
+        Self::generate_piece_moves(board, &context, m.get_from_idx(), &mut moves);
+        moves.contains(m)
+    }
+
+    fn generate_all_moves(board: &ChessBoard, context: &LegalMoveContext, moves: &mut MoveContainer) {
+        Self::generate_king_moves(board, context, moves);
+        // In double check, only king is allowed to move.
+        if context.double_check {
+            return;
+        }
+
         // Knights
-        let mut knights = board.bitboards[PieceType::Knight.get_side_index(board.turn)];
+        let mut knights = board.bitboards[PieceType::Knight.get_side_index(board.turn)].0;
         while knights != 0 {
             let knight_square = BoardHelper::pop_lsb(&mut knights);
-            // Pinned knight cannot move
-            if pin_mask & (1 << knight_square) != 0 { continue; } 
-
-            let knight_attacks = bitboard::KNIGHT_ATTACKS[knight_square as usize] & enemy_or_empty & check_mask & move_filter_mask;
-            Self::generate_moves(knight_square, knight_attacks, &mut moves);
+            Self::generate_knight_moves(context, knight_square, moves);
         } 
         
         // Bishop
-        let mut bishops = board.bitboards[PieceType::Bishop.get_side_index(board.turn)] | board.bitboards[PieceType::Queen.get_side_index(board.turn)];
+        let mut bishops = board.bitboards[PieceType::Bishop.get_side_index(board.turn)].0 | board.bitboards[PieceType::Queen.get_side_index(board.turn)].0;
         while bishops != 0 {
             let bishop_square = BoardHelper::pop_lsb(&mut bishops);
-            let bishop_attacks = get_bishop_magic(bishop_square, all_pieces) & enemy_or_empty & check_mask & move_filter_mask;
-            if pin_mask & (1 << bishop_square) != 0 {
-                // For Bishops the pin cannot be by horizontal/vertical moving piece for it be able to move  
-                if pin_hv & (1 << bishop_square) == 0 {
-                    Self::generate_moves(bishop_square, bishop_attacks & pin_d12, &mut moves);
-                }
-                continue;
-            }
-            Self::generate_moves(bishop_square, bishop_attacks, &mut moves);
+            Self::generate_bishop_moves(context, bishop_square, moves);
         } 
 
         // Rook
-        let mut rooks = board.bitboards[PieceType::Rook.get_side_index(board.turn)] | board.bitboards[PieceType::Queen.get_side_index(board.turn)];
+        let mut rooks = board.bitboards[PieceType::Rook.get_side_index(board.turn)].0 | board.bitboards[PieceType::Queen.get_side_index(board.turn)].0;
         while rooks != 0 {
             let rook_square = BoardHelper::pop_lsb(&mut rooks);
-            let rook_attacks = get_rook_magic(rook_square, all_pieces) & enemy_or_empty & check_mask & move_filter_mask;
-            if pin_mask & (1 << rook_square) != 0 {
-                // For rooks the pin cannot be by diagonal moving piece for it be able to move  
-                if pin_d12 & (1 << rook_square) == 0 {
-                    Self::generate_moves(rook_square, rook_attacks & pin_hv, &mut moves);
-                }
-                continue;
-            }
-            Self::generate_moves(rook_square, rook_attacks, &mut moves);
+            Self::generate_rook_moves(context, rook_square, moves);
         }
 
         // Pawns
-        let mut pawns = board.bitboards[PieceType::Pawn.get_side_index(board.turn)];
+        let mut pawns = board.bitboards[PieceType::Pawn.get_side_index(board.turn)].0;
         while pawns != 0 {
             let pawn_square = BoardHelper::pop_lsb(&mut pawns);
+            Self::generate_pawn_moves(board, context, pawn_square, moves);
+        }
+    }
 
-            let mut promotable_moves = 0u64;
-            let current_rank = BoardHelper::get_rank(pawn_square);
-            
-            // Attack
-            if pin_mask & (1 << pawn_square) == 0 {
-                promotable_moves |= PAWN_ATTACKS[color_idx][pawn_square as usize] & enemy_pieces & check_mask;
-            }
-            else if pin_d12 & (1 << pawn_square) != 0 && pin_hv & (1 << pawn_square) == 0 {
-                promotable_moves |= PAWN_ATTACKS[color_idx][pawn_square as usize] & enemy_pieces & check_mask & pin_d12;
-            }
-
-            // Advance by 1
-            let move_dir = if board.turn == PieceColor::White{ 8 } else { -8 };
-            let move_mask = 1u64 << (pawn_square + move_dir);
-            let pin_allowed_to_move = ((pin_hv & (1 << pawn_square) == 0) || (move_mask & pin_hv) != 0) && ((pin_d12 & (1 << pawn_square) == 0) || (move_mask & pin_d12) != 0); // don't allow pawn jumping pin masks
-            if generate_quiet && (all_pieces & move_mask) == 0 && pin_allowed_to_move {
-                promotable_moves |= (1u64 << (pawn_square + move_dir)) & check_mask;
-
-                // Advance by 2
-                // FIXME: only on a different if, because '1u64 << (pawn_square + move_dir*2)' would overflow
-                let on_start_rank = if board.turn == PieceColor::White { 1 } else { 6 } == current_rank;
-                if on_start_rank {
-                    let advance_mask = 1u64 << (pawn_square + move_dir*2);
-                    let not_blocked = all_pieces & advance_mask == 0;
-                    if on_start_rank && not_blocked && (advance_mask & check_mask) != 0 {
-                        moves.push(Move::new(pawn_square, pawn_square + move_dir * 2, MoveFlag::PawnTwoUp));
-                    }
-                }
+    /// King moves and castling.
+    #[inline(always)]
+    fn generate_king_moves(board: &ChessBoard, context: &LegalMoveContext, moves: &mut MoveContainer) {
+        // The king may have been captured in a variant
+        if board.bitboards[PieceType::King.get_side_index(board.turn)].0 == 0 {
+            return;
+        }
+        let color_idx = context.color_idx;
+        let king_square = context.king_square;
+        let safe_squares = if context.pseudo_legal { !0u64 } else { !context.attack_mask };
+        let king_moves = KING_ATTACKS[king_square as usize] & safe_squares & !context.friendly_pieces & context.move_filter_mask;
+        Self::generate_moves(king_square, king_moves, moves);
+
+        let in_check = context.attack_mask & (1u64 << king_square) != 0;
+        if in_check || !context.generate_quiet {
+            return;
+        }
+
+        // Castling
+        let rights_idx = (color_idx) * 2;
+        let rooks = board.bitboards[PieceType::Rook.get_side_index(board.turn)].0;
+        let square_for_black = (color_idx as i32) * 56;
+
+        // King Side
+        if board.castling_rights[rights_idx] {
+            const ROOK_LOCATION_MASK: [u64; 2] = [1u64 << (Square::H1 as u64), 1u64 << (Square::H8 as u64)];
+            const EMPTY_SQUARES: [u64; 2] = [0b1100000, 0b1100000 << (7*8)];
+
+            let are_empty = context.all_pieces & EMPTY_SQUARES[color_idx] == 0;
+            let are_attacked = context.attack_mask & EMPTY_SQUARES[color_idx] != 0;
+            let rook_in_place = rooks & ROOK_LOCATION_MASK[color_idx] != 0;
+            if are_empty && !are_attacked && rook_in_place {
+                moves.push(Move::new((Square::E1 as i32) + square_for_black, (Square::G1 as i32) + square_for_black, MoveFlag::Castle));
             }
-            
-            // Push promotable_moves
-            let promotion_rank = if board.turn == PieceColor::White{ 6 } else { 1 };
-            if promotion_rank == current_rank {
-                Self::generate_moves_promotion(pawn_square, promotable_moves, &mut moves, generate_quiet);
+        }
+
+        // Queen Side
+        if board.castling_rights[rights_idx+1] {
+            const ROOK_LOCATION_MASK: [u64; 2] = [1u64 << (Square::A1 as u64), 1u64 << (Square::A8 as u64)];
+            const EMPTY_SQUARES: [u64; 2] = [0b1110, 0b1110 << (7*8)];
+            const NON_ATTACKED_MASK: [u64; 2] = [0b1100, 0b1100 << (7*8)];
+
+            let are_empty = context.all_pieces & EMPTY_SQUARES[color_idx] == 0;
+            let are_attacked = context.attack_mask & NON_ATTACKED_MASK[color_idx] != 0;
+            let rook_in_place = rooks & ROOK_LOCATION_MASK[color_idx] != 0;
+            if are_empty && !are_attacked && rook_in_place {
+                moves.push(Move::new((Square::E1 as i32) + square_for_black, (Square::C1 as i32) + square_for_black, MoveFlag::Castle));
             }
-            else {
-                Self::generate_moves(pawn_square, promotable_moves, &mut moves);
+        }
+    }
+
+    #[inline(always)]
+    fn generate_knight_moves(context: &LegalMoveContext, knight_square: i32, moves: &mut MoveContainer) {
+        // Pinned knight cannot move
+        if context.pin_mask & (1 << knight_square) != 0 { return; } 
+
+        let knight_attacks = KNIGHT_ATTACKS[knight_square as usize] & context.enemy_or_empty & context.check_mask & context.move_filter_mask;
+        Self::generate_moves(knight_square, knight_attacks, moves);
+    }
+
+    /// Diagonal moves of bishops and queens.
+    #[inline(always)]
+    fn generate_bishop_moves(context: &LegalMoveContext, bishop_square: i32, moves: &mut MoveContainer) {
+        let bishop_attacks = get_bishop_magic(bishop_square, context.all_pieces) & context.enemy_or_empty & context.check_mask & context.move_filter_mask;
+        if context.pin_mask & (1 << bishop_square) != 0 {
+            // For Bishops the pin cannot be by horizontal/vertical moving piece for it be able to move  
+            if context.pin_hv & (1 << bishop_square) == 0 {
+                Self::generate_moves(bishop_square, bishop_attacks & context.pin_d12, moves);
             }
+            return;
+        }
+        Self::generate_moves(bishop_square, bishop_attacks, moves);
+    }
 
-            // En Passant
-            if board.en_passant != -1 && (pin_mask & (1 << pawn_square) == 0) {
-                // check if the attack pattern overlaps the en passant square
-                let en_passant_square_mask = 0b1u64 << board.en_passant;
+    /// Horizontal and vertical moves of rooks and queens.
+    #[inline(always)]
+    fn generate_rook_moves(context: &LegalMoveContext, rook_square: i32, moves: &mut MoveContainer) {
+        let rook_attacks = get_rook_magic(rook_square, context.all_pieces) & context.enemy_or_empty & context.check_mask & context.move_filter_mask;
+        if context.pin_mask & (1 << rook_square) != 0 {
+            // For rooks the pin cannot be by diagonal moving piece for it be able to move  
+            if context.pin_d12 & (1 << rook_square) == 0 {
+                Self::generate_moves(rook_square, rook_attacks & context.pin_hv, moves);
+            }
+            return;
+        }
+        Self::generate_moves(rook_square, rook_attacks, moves);
+    }
 
-                // If the pawn which moved 2 up is part of the pinned mask
-                let pawn_moved_mask = if color_idx == 0 {en_passant_square_mask >> 8} else {en_passant_square_mask << 8};
-                let pawn_moved_diag_pinned = pawn_moved_mask & pin_d12 != 0; // only checking diagonal pins allows capturing vertically pinned pieces.
-                let en_passant_on_attack = PAWN_ATTACKS[color_idx][pawn_square as usize] & en_passant_square_mask != 0;
+    #[inline(always)]
+    fn generate_pawn_moves(board: &ChessBoard, context: &LegalMoveContext, pawn_square: i32, moves: &mut MoveContainer) {
+        let color_idx = context.color_idx;
+        let (pin_mask, pin_hv, pin_d12, check_mask) = (context.pin_mask, context.pin_hv, context.pin_d12, context.check_mask);
+        let all_pieces = context.all_pieces;
+        let king_square = context.king_square;
 
-                if en_passant_on_attack && !pawn_moved_diag_pinned {
+        let mut promotable_moves = 0u64;
+        let current_rank = BoardHelper::get_rank(pawn_square);
+        
+        // Attack
+        if pin_mask & (1 << pawn_square) == 0 {
+            promotable_moves |= PAWN_ATTACKS[color_idx][pawn_square as usize] & context.enemy_pieces & check_mask;
+        }
+        else if pin_d12 & (1 << pawn_square) != 0 && pin_hv & (1 << pawn_square) == 0 {
+            promotable_moves |= PAWN_ATTACKS[color_idx][pawn_square as usize] & context.enemy_pieces & check_mask & pin_d12;
+        }
+
+        // Advance by 1
+        let move_dir = if board.turn == PieceColor::White{ 8 } else { -8 };
+        let move_mask = 1u64 << (pawn_square + move_dir);
+        let pin_allowed_to_move = ((pin_hv & (1 << pawn_square) == 0) || (move_mask & pin_hv) != 0) && ((pin_d12 & (1 << pawn_square) == 0) || (move_mask & pin_d12) != 0); // don't allow pawn jumping pin masks
+        if context.generate_quiet && (all_pieces & move_mask) == 0 && pin_allowed_to_move {
+            promotable_moves |= (1u64 << (pawn_square + move_dir)) & check_mask;
+
+            // Advance by 2
+            // FIXME: only on a different if, because '1u64 << (pawn_square + move_dir*2)' would overflow
+            let on_start_rank = if board.turn == PieceColor::White { 1 } else { 6 } == current_rank;
+            if on_start_rank {
+                let advance_mask = 1u64 << (pawn_square + move_dir*2);
+                let not_blocked = all_pieces & advance_mask == 0;
+                if on_start_rank && not_blocked && (advance_mask & check_mask) != 0 {
+                    moves.push(Move::new(pawn_square, pawn_square + move_dir * 2, MoveFlag::PawnTwoUp));
+                }
+            }
+        }
+        
+        // Push promotable_moves
+        let promotion_rank = if board.turn == PieceColor::White{ 6 } else { 1 };
+        if promotion_rank == current_rank {
+            Self::generate_moves_promotion(pawn_square, promotable_moves, moves, context.generate_quiet);
+        }
+        else {
+            Self::generate_moves(pawn_square, promotable_moves, moves);
+        }
+
+        // En Passant
+        if board.en_passant != -1 && (pin_mask & (1 << pawn_square) == 0) {
+            // check if the attack pattern overlaps the en passant square
+            let en_passant_square_mask = 0b1u64 << board.en_passant;
+
+            // If the pawn which moved 2 up is part of the pinned mask
+            let pawn_moved_mask = if color_idx == 0 {en_passant_square_mask >> 8} else {en_passant_square_mask << 8};
+            let pawn_moved_diag_pinned = pawn_moved_mask & pin_d12 != 0; // only checking diagonal pins allows capturing vertically pinned pieces.
+            let en_passant_on_attack = PAWN_ATTACKS[color_idx][pawn_square as usize] & en_passant_square_mask != 0;
+
+            if en_passant_on_attack && !pawn_moved_diag_pinned {
+                
+                // handles this 8/2p5/3p4/KP5r/1R2Pp1k/8/6P1/8 b - e3 0 1
+                if !context.pseudo_legal && BoardHelper::get_rank(pawn_square) == BoardHelper::get_rank(king_square) {
+                    let opp_rq = board.bitboards[PieceType::Rook.get_side_index(board.turn.flipped())].0 | board.bitboards[PieceType::Queen.get_side_index(board.turn.flipped())].0;
                     
-                    // handles this 8/2p5/3p4/KP5r/1R2Pp1k/8/6P1/8 b - e3 0 1
-                    if BoardHelper::get_rank(pawn_square) == BoardHelper::get_rank(king_square) {
-                        let opp_rq = board.bitboards[PieceType::Rook.get_side_index(board.turn.flipped())] | board.bitboards[PieceType::Queen.get_side_index(board.turn.flipped())];
-                        
-                        let two_pawn_mask = pawn_moved_mask | (1 << pawn_square);
-                        let blockers = all_pieces ^ two_pawn_mask;
-                        let rook_attacks = get_rook_magic(king_square, blockers);
-
-                        if rook_attacks & opp_rq == 0 {
-                            moves.push(Move::new(pawn_square, board.en_passant, MoveFlag::EnPassant));
-                        }
-                    }
+                    let two_pawn_mask = pawn_moved_mask | (1 << pawn_square);
+                    let blockers = all_pieces ^ two_pawn_mask;
+                    let rook_attacks = get_rook_magic(king_square, blockers);
 
-                    // Allows to en passant a checking pawn
-                    else if check_mask & pawn_moved_mask == pawn_moved_mask {
+                    if rook_attacks & opp_rq == 0 {
                         moves.push(Move::new(pawn_square, board.en_passant, MoveFlag::EnPassant));
                     }
                 }
+
+                // Allows to en passant a checking pawn
+                else if check_mask & pawn_moved_mask == pawn_moved_mask {
+                    moves.push(Move::new(pawn_square, board.en_passant, MoveFlag::EnPassant));
+                }
             }
-        }   
+        }
+    }
 
+    /// Legal moves of the piece on the `square`, empty if it isn't a piece of the side to move.
+    /// Only the moves of that piece are generated.
+    pub fn get_legal_moves_for_square(board: &ChessBoard, square: i32) -> MoveContainer {
+        let mut moves = MoveContainer::new();
+        if (0..64).contains(&square) {
+            let context = LegalMoveContext::new(board, true);
+            Self::generate_piece_moves(board, &context, square, &mut moves);
+        }
         moves
     }
 
-    #[inline(always)]
-    pub fn get_legal_moves_for_square(board: &ChessBoard, square: i32) -> MoveContainer {
-        Self::get_legal_moves(board, true).into_iter().filter(|m| {
-            m.get_from_idx() == square
-        }).collect()
+    /// Checks if the `m` is legal without generating every legal move, only the moves of the moving piece are generated.
+    /// Cheap enough for validating moves from the transposition table or user input.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// // The knight on d2 is pinned
+    /// board.parse_fen("4k3/8/8/b7/8/8/3N4/4K3 w - - 0 1").expect("valid fen");
+    /// assert!(!MoveGenerator::is_legal(&board, Move::from_uci("d2f3")));
+    /// assert!(MoveGenerator::is_legal(&board, Move::from_uci("e1f2")));
+    /// assert!(!MoveGenerator::is_legal(&board, Move::from_uci("e8e7"))); // not black's turn
+    /// ```
+    #[must_use]
+    pub fn is_legal(board: &ChessBoard, m: Move) -> bool {
+        let from = m.get_from_idx();
+        let piece = board.get_piece(from);
+        if piece.is_none() || piece.get_color() as u8 != board.turn as u8 {
+            return false;
+        }
+
+        let context = LegalMoveContext::new(board, true);
+        let mut moves = MoveContainer::new();
+        Self::generate_piece_moves(board, &context, from, &mut moves);
+        moves.contains(m)
+    }
+
+    /// Generates the legal moves of the piece of the side to move on the `square`.
+    fn generate_piece_moves(board: &ChessBoard, context: &LegalMoveContext, square: i32, moves: &mut MoveContainer) {
+        let piece = board.get_piece(square);
+        if piece.is_none() || piece.get_color() as u8 != board.turn as u8 {
+            return;
+        }
+
+        match piece.get_piece_type() {
+            PieceType::King => {
+                Self::generate_king_moves(board, context, moves);
+            }
+            // In double check, only king is allowed to move.
+            _ if context.double_check => {}
+            PieceType::Knight => {
+                Self::generate_knight_moves(context, square, moves);
+            }
+            PieceType::Bishop => {
+                Self::generate_bishop_moves(context, square, moves);
+            }
+            PieceType::Rook => {
+                Self::generate_rook_moves(context, square, moves);
+            }
+            PieceType::Queen => {
+                Self::generate_bishop_moves(context, square, moves);
+                Self::generate_rook_moves(context, square, moves);
+            }
+            PieceType::Pawn => {
+                Self::generate_pawn_moves(board, context, square, moves);
+            }
+            PieceType::None => {}
+        }
     }
 
     /// (HorizontalVertical, Diagonal)
@@ -258,12 +1221,12 @@ impl MoveGenerator {
         let opponent = board.get_turn().flipped();
         let current_turn = board.get_turn();
 
-        let opp_bq = board.bitboards[PieceType::Bishop.get_side_index(opponent)] | board.bitboards[PieceType::Queen.get_side_index(opponent)];
-        let opp_rq = board.bitboards[PieceType::Rook  .get_side_index(opponent)] | board.bitboards[PieceType::Queen.get_side_index(opponent)];
+        let opp_bq = board.bitboards[PieceType::Bishop.get_side_index(opponent)].0 | board.bitboards[PieceType::Queen.get_side_index(opponent)].0;
+        let opp_rq = board.bitboards[PieceType::Rook  .get_side_index(opponent)].0 | board.bitboards[PieceType::Queen.get_side_index(opponent)].0;
         let king_square = board.get_king_square(current_turn);
 
-        let occupied = board.side_bitboards[0] | board.side_bitboards[1];
-        let own_pieces = board.side_bitboards[current_turn as usize];
+        let occupied = board.side_bitboards[0].0 | board.side_bitboards[1].0;
+        let own_pieces = board.side_bitboards[current_turn as usize].0;
         
         let (mut rook_pins, mut bishop_pins) = (0u64, 0u64);
 
@@ -295,9 +1258,9 @@ impl MoveGenerator {
         let opponent = board.get_turn().flipped();
         let side_king = board.get_turn() as usize;
         
-        let king_mask = board.bitboards[side_king * 6 + 5];
+        let king_mask = board.bitboards[side_king * 6 + 5].0;
         let king_square = board.get_king_square(board.get_turn());
-        let blockers = board.side_bitboards[0] | board.side_bitboards[1];
+        let blockers = board.side_bitboards[0].0 | board.side_bitboards[1].0;
 
         let mut check_mask = 0u64;
 
@@ -306,7 +1269,7 @@ impl MoveGenerator {
 
         // Pawns
         {
-            let mut pawns = board.bitboards[PieceType::Pawn.get_side_index(opponent)];
+            let mut pawns = board.bitboards[PieceType::Pawn.get_side_index(opponent)].0;
             while pawns != 0 {
                 let pawn_square = BoardHelper::pop_lsb(&mut pawns);
                 
@@ -321,7 +1284,7 @@ impl MoveGenerator {
  
         // Knights
         {
-            let mut knights = board.bitboards[PieceType::Knight.get_side_index(opponent)];
+            let mut knights = board.bitboards[PieceType::Knight.get_side_index(opponent)].0;
             while knights != 0 {
                 let knight_square = BoardHelper::pop_lsb(&mut knights);
                 
@@ -336,7 +1299,7 @@ impl MoveGenerator {
 
         // Bishop
         {
-            let mut bishops = board.bitboards[PieceType::Bishop.get_side_index(opponent)] | board.bitboards[PieceType::Queen.get_side_index(opponent)];
+            let mut bishops = board.bitboards[PieceType::Bishop.get_side_index(opponent)].0 | board.bitboards[PieceType::Queen.get_side_index(opponent)].0;
             while bishops != 0 {
                 let bishop_square = BoardHelper::pop_lsb(&mut bishops);
                 
@@ -352,7 +1315,7 @@ impl MoveGenerator {
             
         // Rooks
         {
-            let mut rooks = board.bitboards[PieceType::Rook.get_side_index(opponent)] | board.bitboards[PieceType::Queen.get_side_index(opponent)];
+            let mut rooks = board.bitboards[PieceType::Rook.get_side_index(opponent)].0 | board.bitboards[PieceType::Queen.get_side_index(opponent)].0;
             while rooks != 0 {
                 let rook_square = BoardHelper::pop_lsb(&mut rooks);
                 
@@ -369,50 +1332,10 @@ impl MoveGenerator {
         (is_double_check, check_mask)
     }
     
+    /// Squares attacked by the side not to move, see [ChessBoard::attacked_squares].
+    #[inline(always)]
     pub fn get_attack_mask(board: &ChessBoard) -> u64 {
-        use crate::bitschess::bitboard;
-        let king_mask = board.bitboards[board.get_turn() as usize * 6 + 5];
-        let enemy_color = board.get_turn().flipped();
-        
-        // erase king from blockers as well
-        let all_pieces = (board.side_bitboards[0] | board.side_bitboards[1]) ^ king_mask;
-    
-        let mut attacks = 0u64;
-            
-        {
-            let mut pawns = board.bitboards[PieceType::Pawn.get_side_index(enemy_color)];
-            while pawns != 0 {
-                let pawn_square = BoardHelper::pop_lsb(&mut pawns);
-                attacks |= bitboard::PAWN_ATTACKS[enemy_color as usize][pawn_square as usize];
-            }
-        }
-
-        {
-            let mut knights = board.bitboards[PieceType::Knight.get_side_index(enemy_color)];
-            while knights != 0 {
-                let knight_square = BoardHelper::pop_lsb(&mut knights);
-                attacks |= bitboard::KNIGHT_ATTACKS[knight_square as usize];
-            }
-        }
-
-        {
-            let mut bishops = board.bitboards[PieceType::Bishop.get_side_index(enemy_color)] | board.bitboards[PieceType::Queen.get_side_index(enemy_color)];
-            while bishops != 0 {
-                let bishop_square = BoardHelper::pop_lsb(&mut bishops);
-                attacks |= get_bishop_magic(bishop_square, all_pieces);
-            }
-        }
-
-        {
-            let mut rooks = board.bitboards[PieceType::Rook.get_side_index(enemy_color)] | board.bitboards[PieceType::Queen.get_side_index(enemy_color)];
-            while rooks != 0 {
-                let rook_square = BoardHelper::pop_lsb(&mut rooks);
-                attacks |= get_rook_magic(rook_square, all_pieces);
-            }
-        }
-        
-        attacks |= KING_ATTACKS[board.get_king_square(enemy_color) as usize];
-        attacks
+        board.attacked_squares(board.turn.flipped())
     }
 
     // https://www.chessprogramming.org/X-ray_Attacks_(Bitboards)#ModifyingOccupancy
@@ -436,6 +1359,76 @@ mod tests {
     use super::*;
 
 
+    #[test]
+    fn test_legal_moves_iter_matches_get_legal_moves() {
+        let mut board = ChessBoard::new();
+        for fen in crate::bench::BENCH_POSITIONS.iter().chain([
+            "8/2p5/3p4/KP5r/1R2Pp1k/8/6P1/8 b - e3 0 1", // en passant revealing a check
+            "4k3/8/8/8/1b6/8/3N4/4K2R w K - 0 1",      // pinned knight
+            "4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1",         // double check
+            "3k4/1P6/8/8/8/8/8/4K3 w - - 0 1",          // promotions
+        ].iter()) {
+            board.parse_fen(fen).expect("valid fen");
+            let mut expected: Vec<Move> = board.get_legal_moves().into_iter().collect();
+            let mut lazy: Vec<Move> = board.legal_moves_iter().collect();
+
+            // Captures first, exactly the ones generated without quiet moves
+            let captures: Vec<Move> = MoveGenerator::get_legal_moves(&board, false).into_iter().collect();
+            assert_eq!(lazy[..captures.len()], captures, "{fen}");
+
+            expected.sort_by_key(|m| m.0);
+            lazy.sort_by_key(|m| m.0);
+            assert_eq!(lazy, expected, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_is_legal_matches_get_legal_moves() {
+        let mut board = ChessBoard::new();
+        for fen in crate::bench::BENCH_POSITIONS.iter().chain([
+            "8/2p5/3p4/KP5r/1R2Pp1k/8/6P1/8 b - e3 0 1", // en passant revealing a check
+            "4k3/8/8/8/1b6/8/3N4/4K2R w K - 0 1",      // pinned knight
+            "4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1",         // double check
+        ].iter()) {
+            board.parse_fen(fen).expect("valid fen");
+            let legal = board.get_legal_moves();
+            for m in (0..=u16::MAX).map(Move) {
+                assert_eq!(board.is_legal(m), legal.iter().any(|legal| *legal == m), "{fen} {m}");
+            }
+            for square in 0..64 {
+                let expected: Vec<Move> = legal.iter().copied().filter(|m| m.get_from_idx() == square).collect();
+                assert_eq!(board.get_legal_moves_for_square(square).into_iter().collect::<Vec<Move>>(), expected, "{fen}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_pseudo_legal_moves() {
+        let mut board = ChessBoard::new();
+        for fen in crate::bench::BENCH_POSITIONS.iter().chain([
+            "8/2p5/3p4/KP5r/1R2Pp1k/8/6P1/8 b - e3 0 1", // en passant revealing a check
+            "4k3/8/8/8/1b6/8/3N4/4K2R w K - 0 1",      // pinned knight
+            "4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1",         // double check
+            "4k3/8/8/8/8/8/8/R3K1r1 w Q - 0 1",        // castling through a check
+        ].iter()) {
+            board.parse_fen(fen).expect("valid fen");
+            let pseudo_legal = MoveGenerator::get_pseudo_legal_moves(&board);
+            let legal: Vec<Move> = pseudo_legal.iter().copied().filter(|m| board.is_legal(*m)).collect();
+            assert_eq!(legal, board.get_legal_moves().into_iter().collect::<Vec<Move>>(), "{fen}");
+
+            for m in (0..=u16::MAX).map(Move) {
+                assert_eq!(board.is_pseudo_legal(m), pseudo_legal.iter().any(|pseudo| *pseudo == m), "{fen} {m}");
+            }
+        }
+
+        // Castling doesn't become pseudo legal through a check
+        board.parse_fen("4k3/8/8/8/8/8/8/R3K1r1 w Q - 0 1").expect("valid fen");
+        assert!(!board.is_pseudo_legal(Move::new(Square::E1 as i32, Square::C1 as i32, MoveFlag::Castle)));
+        board.parse_fen("4k3/8/8/8/8/8/8/R3K2r w Q - 0 1").expect("valid fen");
+        assert!(board.is_pseudo_legal(Move::from_uci("e1d1")));
+        assert!(!board.is_pseudo_legal(Move::new(Square::E1 as i32, Square::C1 as i32, MoveFlag::Castle)));
+    }
+
     #[test]
     #[should_panic]
     fn test_chess_board_move_generation_en_passant_pin() {
@@ -464,14 +1457,14 @@ mod tests {
     fn test_chess_board_move_generation_en_passant_in_check() {
         let mut board = ChessBoard::new();
         board.parse_fen("8/8/3p4/1Pp4r/1K3p2/6k1/4P1P1/1R6 w - c6 0 3").expect("valid fen");
-        board.make_move_uci("b5c6").expect("en passant resolves the check and as such, should be allowed")
+        board.make_move_uci("b5c6").expect("en passant resolves the check and as such, should be allowed");
     }
 
     #[test]
     fn test_chess_board_move_generation_en_passant_vertical_pin() {
         let mut board = ChessBoard::new();
         board.parse_fen("r1bqkbnr/ppp1pppp/8/2Pp4/8/8/PPPKPPPP/RNBQ1BNR w kq d6 0 4").expect("valid fen");
-        board.make_move_uci("c5d6").expect("en passant captures pinned piece and also resolves the pin so should be allowed")
+        board.make_move_uci("c5d6").expect("en passant captures pinned piece and also resolves the pin so should be allowed");
     }
 
     #[test]
@@ -497,5 +1490,164 @@ mod tests {
         board.parse_fen("8/R4p1k/5rP1/8/1P2Q3/P7/5P2/5K2 b - - 0 52").expect("valid fen");
         board.make_move_uci("f7g6").unwrap(); 
     }
-}
 
+    #[test]
+    fn test_attackers_to() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").expect("valid fen");
+        let bb = |squares: &[i32]| squares.iter().fold(0u64, |acc, sq| acc | (1u64 << sq));
+
+        // The e6 pawn and the knights on b6 and f6
+        assert_eq!(board.attackers_to(Square::D5 as i32, PieceColor::Black), bb(&[Square::E6 as i32, Square::B6 as i32, Square::F6 as i32]));
+        assert_eq!(board.attackers_to(Square::F7 as i32, PieceColor::White), bb(&[Square::E5 as i32]));
+        assert_eq!(board.attackers_to(Square::F1 as i32, PieceColor::White), bb(&[Square::E1 as i32, Square::E2 as i32, Square::H1 as i32]));
+        assert_eq!(board.least_valuable_attacker(Square::F1 as i32, PieceColor::White), Some(Square::E2 as i32));
+        assert_eq!(board.least_valuable_attacker(Square::D5 as i32, PieceColor::Black), Some(Square::E6 as i32));
+
+        for square in 0..64 {
+            assert_eq!(board.attackers_to(square, PieceColor::Black) != 0, board.is_square_in_check(PieceColor::White, square));
+            assert_eq!(board.attackers_to(square, PieceColor::White) != 0, board.is_square_in_check(PieceColor::Black, square));
+        }
+    }
+
+    #[test]
+    fn test_moves_if_piece_at() {
+        use crate::bitschess::bitboard::BitBoard;
+        let mut board = ChessBoard::new();
+        board.parse_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").expect("valid fen");
+        let before = board.clone();
+
+        // En passant and double advances for hypothetical pawns
+        let moves = board.moves_if_piece_at(Square::C5 as i32, Piece::from_char('P'));
+        assert_eq!(moves, (1u64 << Square::C6 as u32) | (1u64 << Square::D6 as u32));
+        let moves = board.moves_if_piece_at(Square::D2 as i32, Piece::from_char('P'));
+        assert_eq!(moves, (1u64 << Square::D3 as u32) | (1u64 << Square::D4 as u32));
+        let moves = board.moves_if_piece_at(Square::D7 as i32, Piece::from_char('p'));
+        assert_eq!(moves, 1u64 << Square::D6 as u32);
+
+        // Replaces the king on e1, so the rook isn't blocked by it.
+        let moves = board.moves_if_piece_at(Square::E1 as i32, Piece::from_char('R'));
+        assert_eq!(moves, BitBoard::get_rook_attack_mask(Square::E1 as i32, 1u64 << Square::E5 as u32) & !(1u64 << Square::E5 as u32));
+
+        assert_eq!(board.moves_if_piece_at(Square::E1 as i32, Piece::new(0)), 0);
+        assert_eq!(board, before);
+    }
+
+    #[test]
+    fn test_staged_move_gen_order() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").expect("valid fen");
+        let tt_move = Move::new(Square::E1 as i32, Square::G1 as i32, MoveFlag::Castle);
+        let killer = Move::from_uci("a2a3");
+
+        let mut staged = StagedMoveGen::new(&board, Some(tt_move), [Some(killer), Some(Move::from_uci("e2a6"))]);
+        let mut yielded = vec![];
+        let mut last_capture_score = i32::MAX;
+        while let Some(m) = staged.next() {
+            match staged.stage() {
+                MoveStage::TtMove => { assert_eq!(m, tt_move); }
+                MoveStage::Captures => {
+                    let score = StagedMoveGen::mvv_lva(&board, m).expect("capture");
+                    assert!(score <= last_capture_score);
+                    last_capture_score = score;
+                }
+                // e2a6 is a capture, so only the quiet killer is yielded here
+                MoveStage::Killers => { assert_eq!(m, killer); }
+                MoveStage::Quiets => { assert_eq!(StagedMoveGen::mvv_lva(&board, m), None); }
+                MoveStage::Done => { unreachable!(); }
+            }
+            yielded.push(m);
+        }
+        assert_eq!(staged.stage(), MoveStage::Done);
+        assert_eq!(yielded[0], tt_move);
+        // Bishop takes bishop is the most valuable capture with the least valuable attacker
+        assert_eq!(yielded[1], Move::from_uci("e2a6"));
+
+        let legal = board.get_legal_moves();
+        assert_eq!(yielded.len(), legal.len());
+        assert!(legal.iter().all(|m| yielded.contains(m)));
+    }
+
+    #[test]
+    fn test_staged_move_gen_illegal_hints() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(crate::bitschess::board::fen::STARTPOS_FEN).expect("valid fen");
+        let illegal = Move::from_uci("e2e5");
+
+        let staged = StagedMoveGen::new(&board, Some(illegal), [Some(illegal), Some(illegal)]);
+        let moves: Vec<Move> = staged.collect();
+        assert_eq!(moves.len(), 20);
+        assert!(!moves.contains(&illegal));
+
+        // Same killer in both slots is yielded once
+        let knight = Move::from_uci("g1f3");
+        let mut staged = StagedMoveGen::new(&board, None, [Some(knight), Some(knight)]);
+        assert_eq!(staged.next(), Some(knight));
+        assert_eq!(staged.stage(), MoveStage::Killers);
+        assert_eq!(staged.filter(|m| *m == knight).count(), 0);
+    }
+
+    #[test]
+    fn test_movegen_info() {
+        let mut board = ChessBoard::new();
+        // Double check from the knight and the rook
+        board.parse_fen("4k3/8/8/8/8/3n4/8/r3K3 w - - 0 1").expect("valid fen");
+        let info = board.movegen_info();
+        assert_eq!(info.checkers, (1u64 << Square::D3 as u32) | (1u64 << Square::A1 as u32));
+        assert!(info.double_check);
+        assert_eq!(MoveGenerator::get_legal_moves_with_info(&board, &info, true).len(), board.get_legal_moves().len());
+
+        for fen in [
+            crate::bitschess::board::fen::STARTPOS_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "4k3/8/8/b7/8/8/3N4/r3K3 w - - 0 1",
+        ] {
+            board.parse_fen(fen).expect("valid fen");
+            let info = board.movegen_info();
+            let (pin_hv, pin_d12) = MoveGenerator::get_pinned_mask(&board);
+            assert_eq!((info.pin_hv, info.pin_d12), (pin_hv, pin_d12));
+            assert_eq!(info.attack_mask, MoveGenerator::get_attack_mask(&board));
+            assert_eq!(info.in_check(), board.is_king_in_check(board.get_turn()));
+            assert_eq!(info.checkers.count_ones() > 1, info.double_check);
+        }
+    }
+
+    #[test]
+    fn test_gives_check_and_gen_types() {
+        let mut board = ChessBoard::new();
+        for fen in crate::bench::BENCH_POSITIONS.iter().chain([
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",                   // castling rook checks
+            "5k2/8/8/8/8/8/8/4K2R w K - 0 1",                          // castling rook gives check
+            "8/8/8/1k6/2pP4/8/8/4K1B1 b - d3 0 1",                     // en passant uncovering a bishop
+            "2r1k3/1P6/8/8/8/8/8/4K3 w - - 0 1",                       // capturing under promotions
+            "8/2p5/3p4/KP5r/1R2Pp1k/8/6P1/8 b - e3 0 1",
+        ].iter()) {
+            board.parse_fen(fen).expect("valid fen");
+            let all = board.get_legal_moves();
+            let in_check = board.is_king_in_check(board.get_turn());
+            let mut checks = MoveContainer::new();
+            for m in all.iter() {
+                let mut after = board.clone();
+                after.make_move(*m, true);
+                let expected = after.is_king_in_check(after.get_turn());
+                assert_eq!(board.gives_check(*m), expected, "{fen} {}", m.to_uci());
+                if expected {
+                    checks.push(*m);
+                }
+            }
+
+            let mut moves = MoveContainer::new();
+            let captures = MoveGenerator::get_legal_moves(&board, false);
+            MoveGenerator::generate_into(&board, &mut moves, GenType::CapturesAndChecks);
+            assert!(captures.iter().chain(checks.iter()).all(|m| moves.contains(*m)), "{fen}");
+            assert!(moves.iter().all(|m| captures.contains(*m) || checks.contains(*m)), "{fen}");
+
+            MoveGenerator::generate_into(&board, &mut moves, GenType::CapturesAndPromotions);
+            assert_eq!(moves.len(), all.iter().filter(|m| captures.contains(**m) || m.is_promotion()).count(), "{fen}");
+
+            MoveGenerator::generate_into(&board, &mut moves, GenType::Evasions);
+            assert_eq!(moves.len(), if in_check { all.len() } else { 0 }, "{fen}");
+        }
+    }
+}
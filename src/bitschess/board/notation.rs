@@ -0,0 +1,230 @@
+use super::{ChessBoard, MoveError};
+
+use crate::board_helper::BoardHelper;
+use crate::chess_move::{Move, MoveFlag};
+use crate::piece::PieceType;
+
+/// Notations moves can be written in, see [ChessBoard::format_move] and [ChessBoard::parse_move].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    /// As in the UCI protocol, e.g. "g1f3" or "e7e8q".
+    Uci,
+    /// Standard algebraic notation, e.g. "Nf3" or "exd8=Q+".
+    San,
+    /// Long algebraic notation with both squares, e.g. "Ng1-f3" or "e5xd6 e.p.".
+    Lan,
+    /// ICCF numeric notation used in correspondence chess, files and ranks as digits, e.g. "7163" for g1-f3.
+    /// Promotions end with 1 for a queen, 2 for a rook, 3 for a bishop and 4 for a knight, e.g. "57581".
+    Iccf,
+}
+
+impl ChessBoard {
+    /// Writes a legal move of the current position in the `notation`, with the check ('+') and checkmate ('#')
+    /// suffixes in SAN and LAN. Drops have no ICCF form, they're written as in UCI.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let board = ChessBoard::from_fen("4k3/8/8/3pP3/8/8/8/R3K2R w KQ d6 0 1").expect("valid fen");
+    /// let en_passant = board.parse_move("e5d6", Notation::Uci).expect("legal move");
+    /// assert_eq!(board.format_move(en_passant, Notation::San), "exd6");
+    /// assert_eq!(board.format_move(en_passant, Notation::Lan), "e5xd6 e.p.");
+    /// assert_eq!(board.format_move(en_passant, Notation::Iccf), "5546");
+    ///
+    /// let rook_move = Move::from_uci("a1a8");
+    /// assert_eq!(board.format_move(rook_move, Notation::Lan), "Ra1-a8+");
+    /// assert_eq!(board.format_move(Move::castle_kingside(PieceColor::White), Notation::Lan), "O-O");
+    /// ```
+    #[must_use]
+    pub fn format_move(&self, m: Move, notation: Notation) -> String {
+        match notation {
+            Notation::Uci => { m.to_uci() }
+            Notation::San => { self.move_to_san(m) }
+            Notation::Lan => { self.move_to_lan(m) }
+            Notation::Iccf => { Self::move_to_iccf(m) }
+        }
+    }
+
+    /// Parses a legal move of the current position written in the `notation`.
+    /// Check and checkmate suffixes are optional.
+    ///
+    /// # Errors
+    /// [MoveError::ParseError] if the text isn't a move in the `notation`,
+    /// otherwise as in [ChessBoard::make_move_uci] and [ChessBoard::make_move_pgn].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let board = ChessBoard::from_fen(STARTPOS_FEN).expect("valid fen");
+    /// let knight_move = Move::from_uci("g1f3");
+    /// assert_eq!(board.parse_move("Nf3", Notation::San), Ok(knight_move));
+    /// assert_eq!(board.parse_move("Ng1-f3", Notation::Lan), Ok(knight_move));
+    /// assert_eq!(board.parse_move("7163", Notation::Iccf), Ok(knight_move));
+    ///
+    /// assert_eq!(board.parse_move("Bg1-f3", Notation::Lan), Err(MoveError::IllegalMove));
+    /// assert_eq!(board.parse_move("7193", Notation::Iccf), Err(MoveError::ParseError));
+    /// ```
+    pub fn parse_move(&self, text: &str, notation: Notation) -> Result<Move, MoveError> {
+        match notation {
+            Notation::Uci => { self.parse_uci(text) }
+            Notation::San => { self.get_move_pgn(text) }
+            Notation::Lan => { self.parse_lan(text) }
+            Notation::Iccf => { self.parse_iccf(text) }
+        }
+    }
+
+    fn move_to_lan(&self, m: Move) -> String {
+        let suffix = self.check_suffix(m);
+        if m.get_flag() == MoveFlag::Castle {
+            let castle = if BoardHelper::get_file(m.get_to_idx()) == 6 { "O-O" } else { "O-O-O" };
+            return format!("{castle}{suffix}");
+        }
+        if m.is_drop() {
+            return format!("{}{suffix}", m.to_uci());
+        }
+
+        let mut lan = String::new();
+        let piece_type = self.moved_piece(m).get_piece_type();
+        if piece_type != PieceType::Pawn {
+            lan.push(piece_type.to_char().to_ascii_uppercase());
+        }
+        lan.push_str(&BoardHelper::square_to_string(m.get_from_idx()));
+        lan.push(if self.is_capture(m) { 'x' } else { '-' });
+        lan.push_str(&BoardHelper::square_to_string(m.get_to_idx()));
+        if let Some(promotion) = m.promotion_piece() {
+            lan.push('=');
+            lan.push(promotion.to_char().to_ascii_uppercase());
+        }
+        lan.push_str(suffix);
+        if m.is_en_passant() {
+            lan.push_str(" e.p.");
+        }
+        lan
+    }
+
+    fn move_to_iccf(m: Move) -> String {
+        if m.is_drop() {
+            return m.to_uci();
+        }
+
+        let (from_file, from_rank) = BoardHelper::file_and_rank(m.get_from_idx());
+        let (to_file, to_rank) = BoardHelper::file_and_rank(m.get_to_idx());
+        let mut iccf = format!("{}{}{}{}", from_file + 1, from_rank + 1, to_file + 1, to_rank + 1);
+        match m.promotion_piece() {
+            Some(PieceType::Queen) => { iccf.push('1'); }
+            Some(PieceType::Rook) => { iccf.push('2'); }
+            Some(PieceType::Bishop) => { iccf.push('3'); }
+            Some(PieceType::Knight) => { iccf.push('4'); }
+            _ => {}
+        }
+        iccf
+    }
+
+    fn parse_uci(&self, uci: &str) -> Result<Move, MoveError> {
+        let m: Move = uci.trim().parse().map_err(|_| MoveError::ParseError)?;
+        if m.is_drop() {
+            return if self.get_legal_moves().contains(m) { Ok(m) } else { Err(MoveError::IllegalMove) };
+        }
+        self.find_legal_move(m.get_from_idx(), m.get_to_idx(), m.promotion_piece())
+    }
+
+    fn parse_lan(&self, lan: &str) -> Result<Move, MoveError> {
+        let lan = lan.trim();
+        let lan = lan.strip_suffix("e.p.").map_or(lan, str::trim_end).trim_end_matches(['+', '#']);
+        if lan.starts_with(['O', '0']) {
+            return self.get_move_pgn(lan);
+        }
+        if lan.as_bytes().get(1) == Some(&b'@') {
+            return self.parse_uci(lan);
+        }
+
+        let (piece_type, rest) = match lan.as_bytes() {
+            [piece, rest @ ..] if piece.is_ascii_uppercase() => { (PieceType::from_char(*piece as char), rest) }
+            bytes => { (PieceType::Pawn, bytes) }
+        };
+        let [from_file, from_rank, separator @ (b'-' | b'x'), to_file, to_rank, promotion @ ..] = rest else {
+            return Err(MoveError::ParseError);
+        };
+        let promotion = match promotion {
+            [] => { None }
+            [b'=', piece] | [piece] => { Some(Self::promotion_from_char(*piece as char)?) }
+            _ => { return Err(MoveError::ParseError); }
+        };
+
+        let from = BoardHelper::chars_to_square(*from_file as char, *from_rank as char).map_err(|_| MoveError::ParseError)?;
+        let to = BoardHelper::chars_to_square(*to_file as char, *to_rank as char).map_err(|_| MoveError::ParseError)?;
+        let m = self.find_legal_move(from as i32, to as i32, promotion)?;
+
+        // The piece and the capture marker have to match the move too
+        if self.moved_piece(m).get_piece_type() != piece_type || self.is_capture(m) != (*separator == b'x') {
+            return Err(MoveError::IllegalMove);
+        }
+        Ok(m)
+    }
+
+    fn parse_iccf(&self, iccf: &str) -> Result<Move, MoveError> {
+        let digits: Vec<i32> = iccf.trim().bytes().map(|digit| i32::from(digit) - i32::from(b'1')).collect();
+        let promotion = match digits[..] {
+            [.., digit] if digits.len() == 5 => {
+                match digit {
+                    0 => { Some(PieceType::Queen) }
+                    1 => { Some(PieceType::Rook) }
+                    2 => { Some(PieceType::Bishop) }
+                    3 => { Some(PieceType::Knight) }
+                    _ => { return Err(MoveError::ParseError); }
+                }
+            }
+            _ if digits.len() == 4 => { None }
+            _ => { return Err(MoveError::ParseError); }
+        };
+        if digits[..4].iter().any(|digit| !(0..8).contains(digit)) {
+            return Err(MoveError::ParseError);
+        }
+
+        let from = BoardHelper::file_rank_to_idx(digits[0], digits[1]);
+        let to = BoardHelper::file_rank_to_idx(digits[2], digits[3]);
+        self.find_legal_move(from, to, promotion)
+    }
+
+    /// The legal move between the squares, castling being the king's move to its destination.
+    fn find_legal_move(&self, from: i32, to: i32, promotion: Option<PieceType>) -> Result<Move, MoveError> {
+        self.check_side_to_move(from)?;
+        self.get_legal_moves_for_square(from)
+            .into_iter()
+            .find(|m| m.get_to_idx() == to && m.promotion_piece() == promotion)
+            .ok_or(MoveError::IllegalMove)
+    }
+
+    fn promotion_from_char(piece: char) -> Result<PieceType, MoveError> {
+        match PieceType::from_char(piece) {
+            piece_type @ (PieceType::Knight | PieceType::Bishop | PieceType::Rook | PieceType::Queen) => { Ok(piece_type) }
+            _ => { Err(MoveError::ParseError) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::piece::PieceColor;
+
+    #[test]
+    fn test_notation_round_trip() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").expect("valid fen");
+        for m in board.get_legal_moves().iter() {
+            for notation in [Notation::Uci, Notation::San, Notation::Lan, Notation::Iccf] {
+                let text = board.format_move(*m, notation);
+                assert_eq!(board.parse_move(&text, notation), Ok(*m), "{text}");
+            }
+        }
+
+        let promotion = Move::from_uci("b7a8n");
+        assert_eq!(board.format_move(promotion, Notation::Lan), "b7xa8=N");
+        assert_eq!(board.format_move(promotion, Notation::Iccf), "27184");
+        assert_eq!(board.format_move(Move::castle_queenside(PieceColor::White), Notation::Iccf), "5131");
+        assert_eq!(board.parse_move("e5-d6", Notation::Lan), Err(MoveError::IllegalMove));
+        assert_eq!(board.parse_move("e5d6", Notation::Lan), Err(MoveError::ParseError));
+        assert_eq!(board.parse_move("27185", Notation::Iccf), Err(MoveError::ParseError));
+    }
+}
@@ -0,0 +1,140 @@
+use super::ChessBoard;
+
+use crate::chess_move::Move;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+/// Gets notified about the changes made to a [ChessBoard], see [ChessBoard::add_observer].
+/// All the methods do nothing by default, implement only the ones needed.
+///
+/// Moves made by a search are reported too, search on a clone of the board to avoid that.
+/// Observers are not carried over to clones.
+pub trait BoardObserver: Send + Sync {
+    /// Called after `m` has been made on the `board`.
+    fn on_move_made(&mut self, _board: &ChessBoard, _m: Move) {}
+    /// Called after `m` has been taken back from the `board`.
+    fn on_move_unmade(&mut self, _board: &ChessBoard, _m: Move) {}
+    /// Called after the whole position was replaced, e.g. by [ChessBoard::parse_fen] or [ChessBoard::clear].
+    fn on_position_set(&mut self, _board: &ChessBoard) {}
+}
+
+/// Observers registered to a board, cloning gives an empty list.
+#[derive(Default)]
+pub(crate) struct Observers(Vec<Box<dyn BoardObserver>>);
+
+impl Clone for Observers {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl core::fmt::Debug for Observers {
+    fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(formatter, "{} observers", self.0.len())
+    }
+}
+
+impl ChessBoard {
+    /// Registers an observer, which gets notified about moves made and unmade, and positions set on this board.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// struct MoveLogger(Arc<Mutex<Vec<String>>>);
+    /// impl BoardObserver for MoveLogger {
+    ///     fn on_move_made(&mut self, _board: &ChessBoard, m: Move) {
+    ///         self.0.lock().unwrap().push(m.to_uci());
+    ///     }
+    /// }
+    ///
+    /// let log = Arc::new(Mutex::new(vec![]));
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// board.add_observer(Box::new(MoveLogger(log.clone())));
+    /// board.make_move_uci("e2e4").expect("legal move");
+    /// board.make_move_uci("e7e5").expect("legal move");
+    /// assert_eq!(*log.lock().unwrap(), ["e2e4", "e7e5"]);
+    /// ```
+    pub fn add_observer(&mut self, observer: Box<dyn BoardObserver>) {
+        self.observers.0.push(observer);
+    }
+
+    /// Unregisters and returns all the observers.
+    #[must_use]
+    pub fn take_observers(&mut self) -> Vec<Box<dyn BoardObserver>> {
+        core::mem::take(&mut self.observers.0)
+    }
+
+    #[inline(always)]
+    pub(crate) fn notify_observers(&mut self, mut event: impl FnMut(&mut dyn BoardObserver, &ChessBoard)) {
+        if self.observers.0.is_empty() {
+            return;
+        }
+
+        // Taken out for the duration, so the observers can look at the board.
+        let mut observers = core::mem::take(&mut self.observers);
+        for observer in &mut observers.0 {
+            event(observer.as_mut(), self);
+        }
+        self.observers = observers;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitschess::board::fen::STARTPOS_FEN;
+    use alloc::string::String;
+    use std::sync::{Arc, Mutex};
+
+    struct EventLog(Arc<Mutex<Vec<String>>>);
+
+    impl BoardObserver for EventLog {
+        fn on_move_made(&mut self, board: &ChessBoard, m: Move) {
+            self.0.lock().unwrap().push(format!("made {m} {:?}", board.get_turn()));
+        }
+        fn on_move_unmade(&mut self, board: &ChessBoard, m: Move) {
+            self.0.lock().unwrap().push(format!("unmade {m} {:?}", board.get_turn()));
+        }
+        fn on_position_set(&mut self, board: &ChessBoard) {
+            self.0.lock().unwrap().push(format!("set {}", board.to_fen()));
+        }
+    }
+
+    #[test]
+    fn test_observer_events() {
+        let log = Arc::new(Mutex::new(vec![]));
+        let mut board = ChessBoard::new();
+        board.add_observer(Box::new(EventLog(log.clone())));
+
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        board.make_move_uci("g1f3").expect("legal move");
+        let _ = board.unmake_move();
+        assert!(board.parse_fen("8/8/8/8/8/8/8/8 w - - 0 1").is_err());
+
+        assert_eq!(*log.lock().unwrap(), [
+            format!("set {STARTPOS_FEN}"),
+            String::from("made g1f3 Black"),
+            String::from("unmade g1f3 White"),
+            String::from("set 8/8/8/8/8/8/8/8 w - - 0 1"),
+        ]);
+    }
+
+    #[test]
+    fn test_observers_not_cloned() {
+        let log = Arc::new(Mutex::new(vec![]));
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        board.add_observer(Box::new(EventLog(log.clone())));
+
+        let mut copy = board.clone();
+        copy.make_move_uci("e2e4").expect("legal move");
+        assert!(log.lock().unwrap().is_empty());
+
+        assert_eq!(board.take_observers().len(), 1);
+        board.make_move_uci("e2e4").expect("legal move");
+        assert!(log.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use super::ChessBoard;
+
+use crate::bitschess::bitboard::{PAWN_ATTACKS, PAWN_ATTACK_SPANS};
+use crate::board_helper::BoardHelper;
+use crate::piece::{PieceColor, PieceType};
+
+/// Ranks where a piece can make use of an outpost, 4th to 6th from the side's own point of view.
+/// Indexed by the color of the side occupying the outpost.
+const OUTPOST_RANKS: [u64; 2] = [0x0000_FFFF_FF00_0000, 0x0000_00FF_FFFF_0000];
+
+impl ChessBoard {
+    /// Squares the pawns of `color` attack now or could attack by advancing.
+    #[must_use]
+    pub const fn pawn_attack_span(&self, color: PieceColor) -> u64 {
+        let mut pawns = self.bitboards[PieceType::Pawn.get_side_index(color)].0;
+        let mut span = 0u64;
+        while pawns != 0 {
+            let square = BoardHelper::bitscan_forward(pawns);
+            span |= PAWN_ATTACK_SPANS[color as usize][square as usize];
+            pawns &= pawns - 1;
+        }
+        span
+    }
+
+    /// Squares the pawns of `color` attack right now.
+    #[must_use]
+    pub const fn pawn_attacks(&self, color: PieceColor) -> u64 {
+        let mut pawns = self.bitboards[PieceType::Pawn.get_side_index(color)].0;
+        let mut attacks = 0u64;
+        while pawns != 0 {
+            let square = BoardHelper::bitscan_forward(pawns);
+            attacks |= PAWN_ATTACKS[color as usize][square as usize];
+            pawns &= pawns - 1;
+        }
+        attacks
+    }
+
+    /// Holes in the camp of `color`: squares on the opponent's outpost ranks (see [ChessBoard::outposts])
+    /// which the pawns of `color` can never attack anymore.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.weak_squares(PieceColor::Black), 0);
+    ///
+    /// // Without the c- and e-pawns nothing can ever chase a piece away from the d-file
+    /// board.parse_fen("4k3/pp1p1ppp/8/8/8/8/PPPPPPPP/4K3 w - - 0 1").expect("valid fen");
+    /// let d_file = 0x0808_0808_0808_0808u64;
+    /// assert_eq!(board.weak_squares(PieceColor::Black), d_file & 0x0000_FFFF_FF00_0000);
+    /// ```
+    #[must_use]
+    pub const fn weak_squares(&self, color: PieceColor) -> u64 {
+        OUTPOST_RANKS[color.flipped() as usize] & !self.pawn_attack_span(color)
+    }
+
+    /// Squares on the 4th to 6th rank (from the point of view of `color`) which are defended by a pawn of `color`
+    /// and can't be attacked by the opponent's pawns.
+    #[must_use]
+    pub const fn outposts(&self, color: PieceColor) -> u64 {
+        self.weak_squares(color.flipped()) & self.pawn_attacks(color)
+    }
+
+    /// Knights of `color` standing on an outpost, see [ChessBoard::outposts].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/pp3ppp/8/3N4/4P3/8/8/4K3 w - - 0 1").expect("valid fen");
+    /// assert_eq!(board.knight_outposts(PieceColor::White), 1u64 << Square::D5 as u32);
+    ///
+    /// // A pawn on c7 could kick the knight away
+    /// board.parse_fen("4k3/ppp2ppp/8/3N4/4P3/8/8/4K3 w - - 0 1").expect("valid fen");
+    /// assert_eq!(board.knight_outposts(PieceColor::White), 0);
+    /// ```
+    #[must_use]
+    pub const fn knight_outposts(&self, color: PieceColor) -> u64 {
+        self.outposts(color) & self.bitboards[PieceType::Knight.get_side_index(color)].0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pawn_attack_spans() {
+        use crate::board_helper::Square;
+
+        let mut board = ChessBoard::new();
+        board.parse_fen("4k3/7p/8/8/8/8/P7/4K3 w - - 0 1").expect("valid fen");
+        // a2 covers b3-b8, h7 covers g6-g1
+        let b_file = 0x0202_0202_0202_0202u64;
+        let g_file = 0x4040_4040_4040_4040u64;
+        assert_eq!(board.pawn_attack_span(PieceColor::White), b_file & !0x0000_0000_0000_FFFF);
+        assert_eq!(board.pawn_attack_span(PieceColor::Black), g_file & 0x0000_FFFF_FFFF_FFFF);
+        assert_eq!(board.pawn_attacks(PieceColor::White), 1u64 << Square::B3 as u32);
+
+        // The black outpost ranks are the 5th to 3rd
+        board.parse_fen("4k3/8/8/4n3/3p4/8/2P5/4K3 b - - 0 1").expect("valid fen");
+        assert_eq!(board.knight_outposts(PieceColor::Black), 0);
+        board.parse_fen("4k3/8/8/8/3p4/4n3/8/4K3 b - - 0 1").expect("valid fen");
+        assert_eq!(board.knight_outposts(PieceColor::Black), 1u64 << Square::E3 as u32);
+    }
+}
@@ -1,10 +1,27 @@
 use super::ChessBoard;
+use super::transposition_table::{ReplacementScheme, TranspositionTable};
+
+use crate::chess_move::Move;
+use alloc::vec::Vec;
 
 impl ChessBoard {
 
-    /// https://www.chessprogramming.org/Perft
+    /// https://www.chessprogramming.org/Perft  
+    /// Printing the node counts per move requires the `std` feature.
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
     pub fn perft(&mut self, depth: u32, print: bool) -> u64 {
         debug_assert!(depth >= 1);
+
+        #[cfg(feature = "std")]
+        if print {
+            let divide = self.perft_divide(depth);
+            for (m, move_perft) in &divide {
+                println!("{}: {}", m.to_uci(), move_perft);
+            }
+            let positions = divide.iter().map(|(_, move_perft)| move_perft).sum();
+            println!("Positions Searched: {}", positions);
+            return positions;
+        }
         
         // Uses bulk-counting explained at the website mention earlier.
         let moves = self.get_legal_moves();
@@ -15,22 +32,139 @@ impl ChessBoard {
 
         let mut positions = 0u64;
         for m in moves {
+            self.make_move(m, true);
+            positions += self.perft(depth - 1, false);
+            let _ = self.unmake_move();
+        }
+
+        positions
+    }
+
+    /// Node counts of [ChessBoard::perft] split by the legal moves of the position, in the move generation order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// let divide = board.perft_divide(3);
+    /// assert_eq!(divide.len(), 20);
+    /// assert!(divide.contains(&(Move::from_uci("g1f3"), 440)));
+    /// assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 8902);
+    /// ```
+    #[must_use]
+    pub fn perft_divide(&mut self, depth: u32) -> Vec<(Move, u64)> {
+        debug_assert!(depth >= 1);
+
+        self.get_legal_moves().into_iter().map(|m| {
+            if depth == 1 {
+                return (m, 1);
+            }
             self.make_move(m, true);
             let move_perft = self.perft(depth - 1, false);
-            if print {
+            let _ = self.unmake_move();
+            (m, move_perft)
+        }).collect()
+    }
+
+    /// Same as [ChessBoard::perft], but node counts of already visited positions are looked up from a transposition table
+    /// of `table_size_kb` kilobytes instead of searched again.
+    /// A mismatch with [ChessBoard::perft] means that two different positions share a zobrist hash.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.perft_hashed(4, 1024, false), 197281);
+    /// ```
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    pub fn perft_hashed(&mut self, depth: u32, table_size_kb: usize, print: bool) -> u64 {
+        debug_assert!(depth >= 1);
+
+        let mut table = TranspositionTable::new(table_size_kb, ReplacementScheme::DepthPreferred);
+
+        #[cfg(feature = "std")]
+        if print && depth > 1 {
+            let mut positions = 0u64;
+            for m in self.get_legal_moves() {
+                self.make_move(m, true);
+                let move_perft = self.perft_cached(depth - 1, &mut table);
                 println!("{}: {}", m.to_uci(), move_perft);
+                positions += move_perft;
+                let _ = self.unmake_move();
             }
-            positions += move_perft;
-            let _ = self.unmake_move();
+            println!("Positions Searched: {}", positions);
+            return positions;
         }
 
-        if print {
-            println!("Positions Searched: {}", positions);
+        self.perft_cached(depth, &mut table)
+    }
+
+    fn perft_cached(&mut self, depth: u32, table: &mut TranspositionTable<u64>) -> u64 {
+        let moves = self.get_legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        // The en passant square changes the move count
+        let hash = self.zobrist_with_ep();
+        if let Some(entry) = table.probe(hash) {
+            if u32::from(entry.depth) == depth {
+                return entry.data;
+            }
         }
 
+        let mut positions = 0u64;
+        for m in moves {
+            self.make_move(m, true);
+            positions += self.perft_cached(depth - 1, table);
+            let _ = self.unmake_move();
+        }
+
+        table.store(hash, depth as u8, positions);
         positions
     }
 
+    /// Same as [ChessBoard::perft], but the root moves are split across `threads` threads, each searching on its own copy of the board.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.perft_parallel(3, 4), 8902);
+    /// ```
+    #[must_use]
+    #[cfg(feature = "std")]
+    pub fn perft_parallel(&self, depth: u32, threads: usize) -> u64 {
+        debug_assert!(depth >= 1);
+
+        let moves = self.get_legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        let threads = threads.clamp(1, moves.len().max(1));
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads).map(|thread| {
+                let mut board = self.clone();
+                let moves = &moves;
+                scope.spawn(move || {
+                    let mut positions = 0u64;
+                    for m in moves.iter().skip(thread).step_by(threads) {
+                        board.make_move(*m, true);
+                        positions += board.perft(depth - 1, false);
+                        let _ = board.unmake_move();
+                    }
+                    positions
+                })
+            }).collect();
+
+            workers.into_iter().map(|worker| worker.join().expect("perft thread panicked")).sum()
+        })
+    }
+
 }
 
 
@@ -47,6 +181,39 @@ mod tests {
         board.perft(depth, true)
     }
 
+    #[test]
+    fn test_chess_board_perft_hashed() {
+        for (fen, depth, nodes) in [(POSITION_2, 4, 4085603), (POSITION_3, 5, 674624), (POSITION_5, 4, 2103487)] {
+            let mut board = ChessBoard::new();
+            board.parse_fen(fen).expect("valid fen");
+            assert_eq!(board.perft_hashed(depth, 1024, false), nodes);
+        }
+    }
+
+    #[test]
+    fn test_chess_board_perft_parallel() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(POSITION_2).expect("valid fen");
+        assert_eq!(board.perft_parallel(1, 3), 48);
+        assert_eq!(board.perft_parallel(3, 3), 97862);
+        assert_eq!(board.perft_parallel(3, 64), 97862);
+        assert_eq!(board.perft_parallel(3, 0), 97862);
+    }
+
+    #[test]
+    fn test_chess_board_perft_divide() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(POSITION_2).expect("valid fen");
+        let before = board.clone();
+
+        let divide = board.perft_divide(3);
+        assert_eq!(divide.len(), 48);
+        assert_eq!(divide.iter().map(|(_, nodes)| nodes).sum::<u64>(), 97862);
+        assert!(divide.iter().all(|(_, nodes)| *nodes > 0));
+        assert!(board.perft_divide(1).iter().all(|(_, nodes)| *nodes == 1));
+        assert_eq!(board, before);
+    }
+
     #[test]
     fn test_chess_board_perft_startpos_1() {
         assert_eq!(_test_do_perft(STARTPOS_FEN, 1), 20);
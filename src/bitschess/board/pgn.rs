@@ -1,19 +1,88 @@
 
-use super::{ BoardHelper, ChessBoard, PieceType, Move, MoveFlag, ReversibleMove, Square };
-use super::fen::STARTPOS_FEN;
+use super::{ BoardHelper, ChessBoard, DrawClaim, MoveError, PieceType, Move, MoveFlag, Square };
+use super::fen::{FenParsingError, STARTPOS_FEN};
+use super::game_state::GameResult;
+use super::move_generation::MoveGenerator;
+use super::variant::VariantKind;
+use crate::clock::{GameClock, TimeControl};
 use std::collections::HashMap;
+use std::time::Duration;
 
-#[allow(dead_code)]
-#[derive(Debug, PartialEq, Eq)]
+/// Where the parser failed as (line, column), both starting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PGNParserError {
-    SyntaxError,
+    SyntaxError(usize, usize),
+    /// A tag pair without the closing ']'.
+    UnterminatedTag(usize, usize),
+    /// A string without the closing '"'.
+    UnterminatedString(usize, usize),
+    /// A comment without the closing '}'.
+    UnterminatedComment(usize, usize),
+    /// A ')' without a '(' or the other way around.
+    UnbalancedVariation(usize, usize),
+    /// A '$' not followed by a number in the range 0-255.
+    InvalidNag(usize, usize),
+}
+
+/// Why a game couldn't be set up on a board, see [ChessBoard::load_pgn].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgnImportError {
+    Parse(PGNParserError),
+    /// The "FEN" tag isn't a valid position.
+    InvalidFen(FenParsingError),
+    /// The move at the index of its line couldn't be made, the index is counted from the start of the variation for a variation.
+    IllegalMove(usize, MoveError),
+}
+
+impl From<PGNParserError> for PgnImportError {
+    fn from(error: PGNParserError) -> Self {
+        Self::Parse(error)
+    }
 }
 
 // https://en.wikipedia.org/wiki/Portable_Game_Notation
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pgn {
     tags: HashMap<String, String>, 
-    moves: Vec<String>
+    moves: Vec<String>,
+    /// Comment before the first move.
+    initial_comment: Option<String>,
+    /// Comments after a move, key is the index of the move.
+    comments: HashMap<usize, String>,
+    /// Numeric annotation glyphs ($1 = "!", $2 = "?" ...) after a move, key is the index of the move.
+    nags: HashMap<usize, Vec<u8>>,
+    /// Alternatives for the move at the index, the tags of a variation are always empty.
+    variations: HashMap<usize, Vec<Pgn>>,
+}
+
+/// Options for [ChessBoard::to_pgn_with_options].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PgnExportOptions {
+    /// If the game can be claimed a draw after the last move (threefold repetition or the 50-move rule),
+    /// the last move gets a comment describing the claim.
+    pub annotate_draw_claim: bool,
+    /// If the game can be claimed a draw after the last move, sets the "Result" tag to "1/2-1/2" and the "Termination" tag.
+    pub draw_claim_result: bool,
+    /// Result of a game which didn't end on the board, e.g. by resignation or on time.
+    /// If [None], the result is derived from the final position with [ChessBoard::get_game_state].
+    pub result: Option<GameResult>,
+}
+
+/// Metadata of a move added with [Pgn::annotate].
+/// Clocks and evaluations are written as commands in the move's comment, as lichess does, e.g. "{[%eval 0.34] [%clk 0:04:58]}".
+#[derive(Debug, Clone, PartialEq)]
+pub enum Annotation {
+    /// Text appended to the comment of the move.
+    Comment(String),
+    /// Numeric annotation glyph, e.g. 1 for "!" or 14 for "+=".
+    Nag(u8),
+    /// Time left for the side which moved, written as "[%clk h:mm:ss]".
+    Clock(Duration),
+    /// Evaluation in pawns from White's point of view, written with 2 decimals as "[%eval 0.34]".
+    Eval(f64),
+    /// Forced mate in the number of moves, negative if Black mates, written as "[%eval #-3]".
+    Mate(i32),
 }
 
 impl ToString for Pgn {
@@ -22,29 +91,20 @@ impl ToString for Pgn {
 
         // Tags
         for (key, value) in &self.tags {
-            pgn.push_str(format!("[{} \"{}\"]\n", key, value).as_str());
+            let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+            pgn.push_str(format!("[{} \"{}\"]\n", key, escaped).as_str());
         }
         pgn.push('\n');
         
-        // Moves
-        let mut moves_iter = self.moves.iter().peekable();
-        let mut is_white = true;
-        let mut full_turns = 1;
-        while moves_iter.peek().is_some() {
-            let pgn_move = moves_iter.next().unwrap();
-            
-            if is_white {
-                if full_turns % 4 == 0 {
-                    pgn.push('\n');
-                }
-                
-                pgn.push_str(format!("{}. {} ", full_turns, pgn_move).as_str());
-                full_turns += 1;
-            } else {
-                pgn.push_str(format!("{} ", pgn_move).as_str());
-            }
+        // Moves, numbered from the starting position
+        let mut board = ChessBoard::new();
+        let first_ply = board.parse_fen(self.starting_fen()).map_or(0, |()| board.ply_count() as usize);
+        self.write_movetext(&mut pgn, first_ply, false);
 
-            is_white = !is_white;
+        // Game termination marker, "*" for a game in progress or an unknown result
+        match self.tags.get("Result").map(String::as_str) {
+            None | Some("?") => { pgn.push('*'); }
+            Some(result) => { pgn.push_str(result); }
         }
 
         pgn
@@ -61,10 +121,129 @@ impl Pgn {
     pub fn new() -> Self {
         Self {
             tags: HashMap::new(),
-            moves: vec![]
+            moves: vec![],
+            initial_comment: None,
+            comments: HashMap::new(),
+            nags: HashMap::new(),
+            variations: HashMap::new(),
         }
     }
 
+    /// Moves of the main line in SAN.
+    #[must_use]
+    #[inline(always)]
+    pub fn get_moves(&self) -> &[String] {
+        &self.moves
+    }
+
+    /// Sets a comment after the move at `move_idx`, replaces the comment if already set.
+    #[inline(always)]
+    pub fn set_comment(&mut self, move_idx: usize, comment: impl Into<String>) {
+        self.comments.insert(move_idx, comment.into());
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn get_comment(&self, move_idx: usize) -> Option<&String> {
+        self.comments.get(&move_idx)
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn get_initial_comment(&self) -> Option<&String> {
+        self.initial_comment.as_ref()
+    }
+
+    /// Numeric annotation glyphs of the move at `move_idx`, suffixes such as "!?" are converted into NAGs.
+    #[must_use]
+    #[inline(always)]
+    pub fn get_nags(&self, move_idx: usize) -> &[u8] {
+        self.nags.get(&move_idx).map_or(&[], Vec::as_slice)
+    }
+
+    /// Adds the `annotation` to the move at `move_idx`.
+    /// A clock or an evaluation replaces the earlier one of the move, comments are appended and NAGs are added once.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// use std::time::Duration;
+    /// let mut pgn = Pgn::parse_string("1. e4 e5 2. Qh5 *").expect("valid pgn");
+    /// pgn.annotate(0, Annotation::Clock(Duration::from_secs(298)));
+    /// pgn.annotate(0, Annotation::Eval(0.34));
+    /// pgn.annotate(2, Annotation::Nag(6));
+    /// pgn.annotate(2, Annotation::Comment(String::from("Too early")));
+    /// pgn.annotate(2, Annotation::Mate(-12));
+    /// assert!(pgn.to_string().ends_with("1. e4 {[%eval 0.34] [%clk 0:04:58]} 1... e5 2. Qh5 $6 {[%eval #-12] Too early} *"));
+    ///
+    /// assert_eq!(pgn.get_annotations(0), [Annotation::Eval(0.34), Annotation::Clock(Duration::from_secs(298))]);
+    /// assert_eq!(pgn.get_annotations(2), [Annotation::Mate(-12), Annotation::Nag(6), Annotation::Comment(String::from("Too early"))]);
+    /// ```
+    pub fn annotate(&mut self, move_idx: usize, annotation: Annotation) {
+        match annotation {
+            Annotation::Comment(comment) => {
+                let (commands, text) = split_commands(self.comments.get(&move_idx).map_or("", String::as_str));
+                let text = if text.is_empty() { comment } else { format!("{text} {comment}") };
+                self.comments.insert(move_idx, join_commands(&commands, &text));
+            }
+            Annotation::Nag(nag) => {
+                let nags = self.nags.entry(move_idx).or_default();
+                if !nags.contains(&nag) {
+                    nags.push(nag);
+                }
+            }
+            Annotation::Clock(time) => { self.set_command(move_idx, "clk", format_clock(time)); }
+            Annotation::Eval(pawns) => { self.set_command(move_idx, "eval", format!("{pawns:.2}")); }
+            Annotation::Mate(moves) => { self.set_command(move_idx, "eval", format!("#{moves}")); }
+        }
+    }
+
+    /// Annotations of the move at `move_idx`: the evaluation and the clock of its comment, its NAGs and the rest of the comment.
+    #[must_use]
+    pub fn get_annotations(&self, move_idx: usize) -> Vec<Annotation> {
+        let (commands, text) = split_commands(self.comments.get(&move_idx).map_or("", String::as_str));
+        let mut annotations = Vec::new();
+        let mut other_commands = Vec::new();
+        for (name, value) in commands {
+            let annotation = match name.as_str() {
+                "eval" => { parse_eval(&value) }
+                "clk" => { parse_clock(&value).map(Annotation::Clock) }
+                _ => { None }
+            };
+            match annotation {
+                Some(annotation) => { annotations.push(annotation); }
+                None => { other_commands.push((name, value)); }
+            }
+        }
+
+        annotations.extend(self.get_nags(move_idx).iter().map(|nag| Annotation::Nag(*nag)));
+        let comment = join_commands(&other_commands, &text);
+        if !comment.is_empty() {
+            annotations.push(Annotation::Comment(comment));
+        }
+        annotations
+    }
+
+    /// Sets the "[%`name` `value`]" command in the comment of the move at `move_idx`, the evaluation before the clock as lichess writes them.
+    fn set_command(&mut self, move_idx: usize, name: &str, value: String) {
+        let (mut commands, text) = split_commands(self.comments.get(&move_idx).map_or("", String::as_str));
+        commands.retain(|(other, _)| other != name);
+        commands.push((name.to_string(), value));
+        commands.sort_by_key(|(name, _)| match name.as_str() {
+            "eval" => { 0 }
+            "clk" => { 1 }
+            _ => { 2 }
+        });
+        self.comments.insert(move_idx, join_commands(&commands, &text));
+    }
+
+    /// Alternative lines for the move at `move_idx`.
+    #[must_use]
+    #[inline(always)]
+    pub fn get_variations(&self, move_idx: usize) -> &[Pgn] {
+        self.variations.get(&move_idx).map_or(&[], Vec::as_slice)
+    }
+
     /// Replaces the tag if already set
     #[allow(dead_code)]
     #[inline(always)]
@@ -74,204 +253,841 @@ impl Pgn {
 
     #[allow(dead_code)]
     #[inline(always)]
-    pub fn get_tag(&mut self, tag: impl AsRef<String>) -> Option<&String> {
+    pub fn get_tag(&self, tag: impl AsRef<str>) -> Option<&String> {
         self.tags.get(tag.as_ref())
     }
 
     #[allow(dead_code)]
     #[inline(always)]
-    pub fn del_tag(&mut self, tag: impl AsRef<String>) -> bool {
+    pub fn del_tag(&mut self, tag: impl AsRef<str>) -> bool {
         self.tags.remove_entry(tag.as_ref()).is_some()
     }
 
-    #[allow(dead_code)]
-    pub fn parse_string(&mut self, contents: &str) {
-        self.tags = Self::parse_tags(contents).expect("parse error");
-        self.moves = Self::parse_moves(contents).expect("parse error");
+    /// Position the game starts from, the "FEN" tag unless the "SetUp" tag is "0".
+    /// The standard starting position if there's no "FEN" tag.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let pgn = Pgn::parse_string("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]\n1. e4 *").expect("valid pgn");
+    /// assert_eq!(pgn.starting_fen(), "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+    /// assert_eq!(Pgn::new().starting_fen(), STARTPOS_FEN);
+    /// ```
+    #[must_use]
+    pub fn starting_fen(&self) -> &str {
+        match self.tags.get("FEN") {
+            Some(fen) if self.get_tag("SetUp").is_none_or(|setup| setup != "0") => { fen }
+            _ => { STARTPOS_FEN }
+        }
+    }
+
+    /// Parses the first game of the `contents`, see [PgnParser] for reading multiple games.
+    /// The game termination marker is saved to the "Result" tag, if the tag is missing.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let pgn = Pgn::parse_string("[White \"Me\"]\n1. e4 {best by test} e5 (1... c5 $1) 2. Nf3!? 1-0").expect("valid pgn");
+    /// assert_eq!(pgn.get_moves(), &["e4", "e5", "Nf3"]);
+    /// assert_eq!(pgn.get_comment(0), Some(&String::from("best by test")));
+    /// assert_eq!(pgn.get_variations(1)[0].get_moves(), &["c5"]);
+    /// assert_eq!(pgn.get_nags(2), &[5]);
+    ///
+    /// assert_eq!(Pgn::parse_string("1. e4 {unfinished").err(), Some(PGNParserError::UnterminatedComment(1, 7)));
+    /// ```
+    pub fn parse_string(contents: &str) -> Result<Pgn, PGNParserError> {
+        PgnParser::new(contents).next().unwrap_or_else(|| Ok(Pgn::new()))
     }
 
-    #[allow(dead_code, clippy::unnecessary_wraps)] // TODO: proper error handling
     pub fn parse_tags(contents: &str) -> Result<HashMap<String, String>, PGNParserError> {
-        /*
-        What we're trying to parse:
-        [Event "F/S Return Match"]
-        [Site "Belgrade, Serbia JUG"]
-        [Date "1992.11.04"]
-        [Round "29"]
-        [White "Fischer, Robert J."]
-        [Black "Spassky, Boris V."]
-        [Result "1/2-1/2"]
-
-        (stop here ->) 1. e4 e5 ...
-        */
-        let mut tags = HashMap::new();
-
-        let mut is_literal = false;
-        let mut is_in_tag = false;
-        
-        let mut key = String::from("");
-        let mut working_word = String::from("");
+        Ok(Self::parse_string(contents)?.tags)
+    }
+
+    pub fn parse_moves(contents: &str) -> Result<Vec<String>, PGNParserError> {
+        Ok(Self::parse_string(contents)?.moves)
+    }
+
+    /// `first_ply` is the ply of the first move, counted from the first move of the game.
+    fn write_movetext(&self, pgn: &mut String, first_ply: usize, is_variation: bool) {
+        let mut needs_number = true;
+        if let Some(comment) = &self.initial_comment {
+            pgn.push_str(format!("{{{}}} ", comment).as_str());
+        }
+
+        for (idx, pgn_move) in self.moves.iter().enumerate() {
+            let ply = first_ply + idx;
+            let full_turns = ply / 2 + 1;
+            
+            if ply.is_multiple_of(2) {
+                if full_turns.is_multiple_of(4) && !is_variation {
+                    pgn.push('\n');
+                }
+                
+                pgn.push_str(format!("{}. {} ", full_turns, pgn_move).as_str());
+            } else if needs_number {
+                // Move number has to be repeated after a comment or a variation.
+                pgn.push_str(format!("{}... {} ", full_turns, pgn_move).as_str());
+            } else {
+                pgn.push_str(format!("{} ", pgn_move).as_str());
+            }
+            needs_number = false;
 
-        for c in contents.chars() {
-            if c == '[' {
-                is_in_tag = true;
-                continue;
+            for nag in self.get_nags(idx) {
+                pgn.push_str(format!("${} ", nag).as_str());
             }
-            else if c == ']' {
-                is_in_tag = false;
-                continue;
+
+            if let Some(comment) = self.comments.get(&idx) {
+                pgn.push_str(format!("{{{}}} ", comment).as_str());
+                needs_number = true;
             }
 
-            if !is_in_tag && !c.is_whitespace() {
+            for variation in self.get_variations(idx) {
+                pgn.push('(');
+                variation.write_movetext(pgn, ply, true);
+                if pgn.ends_with(' ') {
+                    pgn.pop();
+                }
+                pgn.push_str(") ");
+                needs_number = true;
+            }
+        }
+    }
+
+    fn add_comment(&mut self, comment: &str) {
+        let comment = comment.trim();
+        let target = match self.moves.len() {
+            0 => { self.initial_comment.get_or_insert_with(String::new) }
+            len => { self.comments.entry(len - 1).or_default() }
+        };
+        if !target.is_empty() {
+            target.push(' ');
+        }
+        target.push_str(comment);
+    }
+}
+
+/// Reads games one by one from a string containing any number of PGN games.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// let games = "[Event \"1\"]\n1. e4 e5 1-0\n\n[Event \"2\"]\n1. d4 d5 0-1";
+/// let parsed: Vec<_> = PgnParser::new(games).collect::<Result<_, _>>().expect("valid pgn");
+/// assert_eq!(parsed.len(), 2);
+/// assert_eq!(parsed[1].get_moves(), &["d4", "d5"]);
+/// ```
+pub struct PgnParser<'a> {
+    contents: &'a [u8],
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> PgnParser<'a> {
+    #[must_use]
+    pub const fn new(contents: &'a str) -> Self {
+        Self {
+            contents: contents.as_bytes(),
+            pos: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    #[inline(always)]
+    fn peek(&self) -> Option<u8> {
+        self.contents.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        }
+        // Don't count UTF-8 continuation bytes
+        else if c & 0xC0 != 0x80 {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    #[inline(always)]
+    const fn location(&self) -> (usize, usize) {
+        (self.line, self.column)
+    }
+
+    /// Skips whitespace and '%' escaped lines.
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == b'%' && self.column == 1 {
+                self.skip_line();
+            }
+            else if c.is_ascii_whitespace() {
+                self.bump();
+            }
+            else {
                 break;
             }
+        }
+    }
 
-            // parsing key
-            if key.is_empty() {
-                if !c.is_whitespace() {
-                    working_word.push(c);
-                    continue;
+    fn skip_line(&mut self) -> &'a str {
+        let begin = self.pos;
+        while let Some(c) = self.peek() {
+            if c == b'\n' {
+                break;
+            }
+            self.bump();
+        }
+        self.slice(begin, self.pos)
+    }
+
+    #[inline(always)]
+    fn slice(&self, begin: usize, end: usize) -> &'a str {
+        // Only split at ascii characters, so it's still valid UTF-8
+        std::str::from_utf8(&self.contents[begin..end]).unwrap_or_default()
+    }
+
+    fn parse_game(&mut self) -> Result<Pgn, PGNParserError> {
+        let mut pgn = Pgn::new();
+
+        self.skip_whitespace();
+        while self.peek() == Some(b'[') {
+            let (key, value) = self.parse_tag()?;
+            pgn.tags.insert(key, value);
+            self.skip_whitespace();
+        }
+
+        if let Some(result) = self.parse_line(&mut pgn, 0)? {
+            pgn.tags.entry(String::from("Result")).or_insert(result);
+        }
+        Ok(pgn)
+    }
+
+    fn parse_tag(&mut self) -> Result<(String, String), PGNParserError> {
+        let (line, column) = self.location();
+        self.bump(); // '['
+        self.skip_whitespace();
+
+        let begin = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_') {
+            self.bump();
+        }
+        let key = self.slice(begin, self.pos).to_string();
+        if key.is_empty() {
+            let (line, column) = self.location();
+            return Err(PGNParserError::SyntaxError(line, column));
+        }
+
+        self.skip_whitespace();
+        let value = self.parse_string_literal()?;
+        self.skip_whitespace();
+
+        match self.bump() {
+            Some(b']') => { Ok((key, value)) }
+            Some(_) => { Err(PGNParserError::SyntaxError(self.line, self.column - 1)) }
+            None => { Err(PGNParserError::UnterminatedTag(line, column)) }
+        }
+    }
+
+    fn parse_string_literal(&mut self) -> Result<String, PGNParserError> {
+        let (line, column) = self.location();
+        if self.peek() != Some(b'"') {
+            return Err(PGNParserError::SyntaxError(line, column));
+        }
+        self.bump();
+
+        let mut value = String::new();
+        let mut begin = self.pos;
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    value.push_str(self.slice(begin, self.pos));
+                    self.bump();
+                    return Ok(value);
                 }
-                
-                if !working_word.is_empty() {
-                    key = working_word.clone();
-                    working_word.clear();
+                Some(b'\\') => {
+                    value.push_str(self.slice(begin, self.pos));
+                    self.bump();
+                    begin = self.pos;
+                    self.bump(); // escaped character
+                }
+                Some(b'\n') | None => {
+                    return Err(PGNParserError::UnterminatedString(line, column));
+                }
+                Some(_) => {
+                    self.bump();
                 }
-                continue;
             }
-            
-            // parsing value
+        }
+    }
+
+    /// Parses moves until the end of the (sub)variation or the game, returns the game termination marker if there was one.
+    fn parse_line(&mut self, pgn: &mut Pgn, depth: usize) -> Result<Option<String>, PGNParserError> {
+        loop {
+            self.skip_whitespace();
+            let (line, column) = self.location();
+            let Some(c) = self.peek() else {
+                if depth > 0 {
+                    return Err(PGNParserError::UnbalancedVariation(line, column));
+                }
+                return Ok(None);
+            };
+
             match c {
-                '"' => {
-                    if is_literal {
-                        tags.insert(key.clone(), working_word.clone());
-                        working_word.clear();
-                        key.clear();
+                b'{' => {
+                    self.bump();
+                    let begin = self.pos;
+                    while self.peek().is_some_and(|c| c != b'}') {
+                        self.bump();
                     }
-                    
-                    is_literal = !is_literal;
-                    continue;
+                    if self.peek().is_none() {
+                        return Err(PGNParserError::UnterminatedComment(line, column));
+                    }
+                    let comment = self.slice(begin, self.pos);
+                    self.bump();
+                    pgn.add_comment(comment);
+                }
+                b';' => {
+                    self.bump();
+                    let comment = self.skip_line();
+                    pgn.add_comment(comment);
+                }
+                b'(' => {
+                    self.bump();
+                    if pgn.moves.is_empty() {
+                        return Err(PGNParserError::SyntaxError(line, column));
+                    }
+                    let mut variation = Pgn::new();
+                    self.parse_line(&mut variation, depth + 1)?;
+                    pgn.variations.entry(pgn.moves.len() - 1).or_default().push(variation);
+                }
+                b')' => {
+                    if depth == 0 {
+                        return Err(PGNParserError::UnbalancedVariation(line, column));
+                    }
+                    self.bump();
+                    return Ok(None);
+                }
+                b'$' => {
+                    self.bump();
+                    let begin = self.pos;
+                    while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        self.bump();
+                    }
+                    let nag = self.slice(begin, self.pos).parse::<u8>().map_err(|_| PGNParserError::InvalidNag(line, column))?;
+                    if pgn.moves.is_empty() {
+                        return Err(PGNParserError::SyntaxError(line, column));
+                    }
+                    pgn.nags.entry(pgn.moves.len() - 1).or_default().push(nag);
+                }
+                // Tags of the next game
+                b'[' if depth == 0 => {
+                    return Ok(None);
+                }
+                b'*' => {
+                    self.bump();
+                    if depth > 0 {
+                        return Err(PGNParserError::SyntaxError(line, column));
+                    }
+                    return Ok(Some(String::from("*")));
                 }
+                c if c.is_ascii_alphanumeric() => {
+                    let begin = self.pos;
+                    while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || b"_+#=:-/@".contains(&c)) {
+                        self.bump();
+                    }
+                    let symbol = self.slice(begin, self.pos);
 
-                _ => {
-                    if c.is_whitespace() && !is_literal {
+                    // Move number, "1." or "1..."
+                    if symbol.bytes().all(|c| c.is_ascii_digit()) {
+                        while self.peek() == Some(b'.') {
+                            self.bump();
+                        }
                         continue;
                     }
-                    working_word.push(c);
+
+                    if matches!(symbol, "1-0" | "0-1" | "1/2-1/2") {
+                        if depth > 0 {
+                            return Err(PGNParserError::SyntaxError(line, column));
+                        }
+                        return Ok(Some(symbol.to_string()));
+                    }
+                    pgn.moves.push(symbol.to_string());
+
+                    // Suffix annotations "!", "?", "!!", "??", "!?" and "?!"
+                    let (line, column) = self.location();
+                    let begin = self.pos;
+                    while self.peek().is_some_and(|c| c == b'!' || c == b'?') {
+                        self.bump();
+                    }
+                    let nag = match self.slice(begin, self.pos) {
+                        "" => { continue; }
+                        "!" => { 1 }
+                        "?" => { 2 }
+                        "!!" => { 3 }
+                        "??" => { 4 }
+                        "!?" => { 5 }
+                        "?!" => { 6 }
+                        _ => { return Err(PGNParserError::SyntaxError(line, column)); }
+                    };
+                    pgn.nags.entry(pgn.moves.len() - 1).or_default().push(nag);
+                }
+                _ => {
+                    return Err(PGNParserError::SyntaxError(line, column));
                 }
             }
-
         }
-
-        Ok(tags)
     }
+}
 
-    // TODO: proper error handling
-    #[allow(clippy::unnecessary_wraps)]
-    pub fn parse_moves(contents: &str) -> Result<Vec<String>, PGNParserError> {
-        /*
-        What we're trying to parse:
-        1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
-        11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15. Nb1 h6 16. Bh4 c5 17. dxe5
-        Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21. Nc4 Nxc4 22. Bxc4 Nb6
-        23. Ne5 Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7 27. Qe3 Qg5 28. Qxg5
-        hxg5 29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33. f3 Bc8 34. Kf2 Bf5
-        35. Ra7 g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5 40. Rd6 Kc5 41. Ra6
-        Nf2 42. g4 Bd3 43. Re6 1/2-1/2
-        */
-
-        let pos = contents.rfind(']').unwrap_or(0usize);
-        let unescape = &contents[(pos+1)..contents.len()]
-            .replace('\"', "")
-            .replace('\n', " ");
-
-        Ok(
-            unescape.split(' ')
-            .collect::<Vec<&str>>()
-            .into_iter()
-            .filter(|x| { !x.is_empty() && !x.contains('.') })
-            .map(|x| { String::from(x)} )
-            .collect::<Vec<String>>()
-        )
+impl Iterator for PgnParser<'_> {
+    type Item = Result<Pgn, PGNParserError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.skip_whitespace();
+        self.peek()?;
+
+        let game = self.parse_game();
+        if game.is_err() {
+            // Can't know where the next game starts
+            self.pos = self.contents.len();
+        }
+        Some(game)
     }
 }
 
-impl ChessBoard {
-
-    /// (add_file, add_rank)
-    /// https://en.wikipedia.org/wiki/Algebraic_notation_(chess)#Disambiguating_moves
-    fn pgn_needs_disambiguating(&self, m: ReversibleMove) -> (bool, bool) {
-        use super::magics::{get_bishop_magic, get_rook_magic};
-        use super::super::bitboard::KNIGHT_ATTACKS;
+/// A game with its moves made on the board, unlike [Pgn] which keeps the moves as SAN strings.
+/// Variations are games of their own, starting from the position before the move they are an alternative to.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// let game = Game::from_pgn("[White \"Me\"]\n1. e4 {best by test} e5 2. Nf3?! Nc6 1-0").expect("valid game");
+/// assert_eq!(game.moves().len(), 4);
+/// assert_eq!(game.moves()[2].to_uci(), "g1f3");
+/// assert_eq!(game.get_comment(0), Some(&String::from("best by test")));
+/// assert_eq!(game.get_nags(2), &[6]);
+/// assert_eq!(game.get_tag("White"), Some(&String::from("Me")));
+/// assert_eq!(game.result(), Some(GameResult::WhiteWins));
+///
+/// let board = game.board_at(2).expect("2 moves were made");
+/// assert_eq!(board.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+/// assert!(game.to_pgn().to_string().ends_with("1. e4 {best by test} 1... e5 2. Nf3 $6 Nc6 1-0"));
+///
+/// let game = Game::from_pgn("1. e4 e5 (1... c5 2. Nf3 (2. c3) d6) 2. Nf3 *").expect("valid game");
+/// let sicilian = &game.get_variations(1)[0];
+/// assert_eq!(sicilian.moves().iter().map(|m| m.to_uci()).collect::<Vec<_>>(), ["c7c5", "g1f3", "d7d6"]);
+/// assert_eq!(sicilian.get_variations(1)[0].moves()[0].to_uci(), "c2c3");
+/// assert!(game.to_pgn().to_string().ends_with("1. e4 e5 (1... c5 2. Nf3 (2. c3) 2... d6) 2. Nf3 *"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    tags: HashMap<String, String>,
+    starting_fen: String,
+    moves: Vec<Move>,
+    /// Comment before the first move.
+    initial_comment: Option<String>,
+    /// Comments after a move, key is the index of the move.
+    comments: HashMap<usize, String>,
+    /// Numeric annotation glyphs after a move, key is the index of the move.
+    nags: HashMap<usize, Vec<u8>>,
+    /// Time left for the side which moved after a move, the "[%clk]" commands of the comments, key is the index of the move.
+    clocks: HashMap<usize, Duration>,
+    /// Alternatives for the move at the index, the tags of a variation are always empty.
+    variations: HashMap<usize, Vec<Game>>,
+}
 
-        let mut attackers;
+impl Game {
+    /// Parses the first game of the `contents` and makes its moves, see [Game::from_parsed].
+    ///
+    /// # Errors
+    /// If the game can't be parsed, the "FEN" tag is invalid or a move of the main line can't be made.
+    pub fn from_pgn(contents: &str) -> Result<Self, PgnImportError> {
+        Self::from_parsed(&Pgn::parse_string(contents)?)
+    }
 
-        let turn = self.get_turn();
-        let own_pieces = self.get_side_mask(turn);
-        let blockers = self.get_side_mask(turn.flipped()) | own_pieces;
-        let to = m.board_move.get_to_idx();
+    /// Makes the moves of the main line of an already parsed game, starting from its [starting position](Pgn::starting_fen).
+    ///
+    /// # Errors
+    /// If the "FEN" tag is invalid or a move can't be made.
+    pub fn from_parsed(pgn: &Pgn) -> Result<Self, PgnImportError> {
+        let mut board = ChessBoard::new();
+        board.parse_fen(pgn.starting_fen()).map_err(PgnImportError::InvalidFen)?;
 
-        let piece = self.get_piece(m.board_move.get_from_idx());
-        match piece.get_piece_type() {
+        let mut game = Self::from_line(pgn, &board)?;
+        game.tags = pgn.tags.clone();
+        game.starting_fen = String::from(pgn.starting_fen());
+        Ok(game)
+    }
 
-            PieceType::Knight => {
-                attackers = KNIGHT_ATTACKS[to as usize] & self.bitboards[PieceType::Knight.get_side_index(turn)];
+    /// Makes the moves of the `pgn` and its variations starting from the `start`.
+    fn from_line(pgn: &Pgn, start: &ChessBoard) -> Result<Self, PgnImportError> {
+        let mut board = start.clone();
+        let mut moves = Vec::with_capacity(pgn.moves.len());
+        let mut variations: HashMap<usize, Vec<Game>> = HashMap::new();
+        for (idx, san) in pgn.moves.iter().enumerate() {
+            for variation in pgn.get_variations(idx) {
+                variations.entry(idx).or_default().push(Self::from_line(variation, &board)?);
             }
+            moves.push(board.make_move_pgn(san).map_err(|error| PgnImportError::IllegalMove(idx, error))?);
+        }
 
-            PieceType::Bishop => {
-                attackers = get_bishop_magic(to, blockers) & self.bitboards[PieceType::Bishop.get_side_index(turn)];
+        // Clock commands are kept apart from the rest of the comment
+        let mut comments = HashMap::new();
+        let mut clocks = HashMap::new();
+        for (idx, comment) in &pgn.comments {
+            let (clock, rest) = take_clock_command(comment);
+            if let Some(clock) = clock {
+                clocks.insert(*idx, clock);
             }
-
-            PieceType::Rook => {
-                attackers = get_rook_magic(to, blockers) & self.bitboards[PieceType::Rook.get_side_index(turn)];
+            if let Some(rest) = rest {
+                comments.insert(*idx, rest);
             }
+        }
 
-            PieceType::Queen => {
-                attackers = (get_rook_magic(to, blockers) | get_bishop_magic(to, blockers)) & self.bitboards[PieceType::Queen.get_side_index(turn)];
-            }
+        Ok(Self {
+            tags: HashMap::new(),
+            starting_fen: start.to_fen(),
+            moves,
+            initial_comment: pgn.initial_comment.clone(),
+            comments,
+            nags: pgn.nags.clone(),
+            clocks,
+            variations,
+        })
+    }
 
-            // If a pawn made the capture then file is always needed
-            PieceType::Pawn => {
-                return (!m.captured.is_none(), false);
-            }
+    /// Converts the game back into a [Pgn], the moves are written in SAN.
+    pub fn to_pgn(&self) -> Pgn {
+        let mut board = self.starting_board();
+        let mut pgn = Pgn::new();
+        for m in &self.moves {
+            pgn.moves.push(board.move_to_san(*m));
+            board.make_move(*m, false);
+        }
 
-            _ => { return (false, false); }
+        pgn.tags = self.tags.clone();
+        pgn.initial_comment = self.initial_comment.clone();
+        pgn.comments = self.comments.clone();
+        for (idx, clock) in &self.clocks {
+            pgn.annotate(*idx, Annotation::Clock(*clock));
         }
+        pgn.nags = self.nags.clone();
+        pgn.variations = self.variations.iter()
+            .map(|(idx, variations)| (*idx, variations.iter().map(Game::to_pgn).collect()))
+            .collect();
+        pgn
+    }
 
-        // Pieces which can also move to the same position
-        let mut overlapping_pieces = vec![];
-        while attackers != 0 {
-            let square = BoardHelper::pop_lsb(&mut attackers);
-            overlapping_pieces.push(square);
+    /// The position after the first `ply` moves, [None] if the game has fewer moves than that.
+    /// For a variation the moves are counted from the start of the variation.
+    #[must_use]
+    pub fn board_at(&self, ply: usize) -> Option<ChessBoard> {
+        if ply > self.moves.len() {
+            return None;
         }
-        if overlapping_pieces.len() < 2 { return (false, false); }
 
+        let mut board = self.starting_board();
+        for m in &self.moves[..ply] {
+            board.make_move(*m, false);
+        }
+        Some(board)
+    }
 
-        let mut add_file = false;
-        let mut add_rank = false;
-        for x in &overlapping_pieces {
-            for y in &overlapping_pieces {
-                if x == y { continue; }
-                if BoardHelper::get_file(*x) == BoardHelper::get_file(*y) {
-                    add_rank = true;
-                }
-                else if BoardHelper::get_rank(*x) == BoardHelper::get_rank(*y) {
-                    add_file = true;
-                }
-            }
+    fn starting_board(&self) -> ChessBoard {
+        let mut board = ChessBoard::new();
+        board.parse_fen(&self.starting_fen).expect("validated when the game was created");
+        board
+    }
+
+    /// Moves of the main line, oldest first.
+    #[must_use]
+    #[inline(always)]
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn starting_fen(&self) -> &str {
+        &self.starting_fen
+    }
+
+    /// The "Result" tag, [None] for an unfinished game ("*").
+    #[must_use]
+    pub fn result(&self) -> Option<GameResult> {
+        match self.get_tag("Result").map(String::as_str) {
+            Some("1-0") => { Some(GameResult::WhiteWins) }
+            Some("0-1") => { Some(GameResult::BlackWins) }
+            Some("1/2-1/2") => { Some(GameResult::Draw) }
+            _ => { None }
+        }
+    }
+
+    /// Replaces the tag if already set
+    #[inline(always)]
+    pub fn set_tag(&mut self, tag: impl Into<String>, value: impl Into<String>) {
+        self.tags.insert(tag.into(), value.into());
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn get_tag(&self, tag: impl AsRef<str>) -> Option<&String> {
+        self.tags.get(tag.as_ref())
+    }
+
+    /// Sets a comment after the move at `move_idx`, replaces the comment if already set.
+    #[inline(always)]
+    pub fn set_comment(&mut self, move_idx: usize, comment: impl Into<String>) {
+        self.comments.insert(move_idx, comment.into());
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn get_comment(&self, move_idx: usize) -> Option<&String> {
+        self.comments.get(&move_idx)
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn get_initial_comment(&self) -> Option<&String> {
+        self.initial_comment.as_ref()
+    }
+
+    /// Numeric annotation glyphs of the move at `move_idx`.
+    #[must_use]
+    #[inline(always)]
+    pub fn get_nags(&self, move_idx: usize) -> &[u8] {
+        self.nags.get(&move_idx).map_or(&[], Vec::as_slice)
+    }
+
+    /// Time left for the side which moved after the move at `move_idx`, written as a "[%clk]" command in the move's comment.
+    #[must_use]
+    #[inline(always)]
+    pub fn get_clock(&self, move_idx: usize) -> Option<Duration> {
+        self.clocks.get(&move_idx).copied()
+    }
+
+    /// Sets the time left after the move at `move_idx`, replaces the time if already set.
+    #[inline(always)]
+    pub fn set_clock(&mut self, move_idx: usize, time: Duration) {
+        self.clocks.insert(move_idx, time);
+    }
+
+    /// The "TimeControl" tag, [None] if it's missing, unknown ("?"), none ("-") or invalid.
+    #[must_use]
+    pub fn time_control(&self) -> Option<TimeControl> {
+        self.get_tag("TimeControl")?.parse().ok()
+    }
+
+    /// Sets the "TimeControl" tag and the time left after every move from the `clock` which timed the main line.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// use std::time::Duration;
+    /// let mut game = Game::from_pgn("1. e4 e5 2. Nf3 {developing} *").expect("valid game");
+    /// let mut clock = GameClock::new(TimeControl::Increment { base: Duration::from_secs(180), increment: Duration::from_secs(2) }, PieceColor::White);
+    /// for seconds in [1, 3, 62] {
+    ///     clock.tick(Duration::from_secs(seconds));
+    ///     clock.press();
+    /// }
+    /// game.record_clock(&clock);
+    /// assert_eq!(game.get_clock(2), Some(Duration::from_secs(121)));
+    /// assert!(game.to_pgn().to_string().contains("[TimeControl \"180+2\"]"));
+    /// assert!(game.to_pgn().to_string().ends_with("1. e4 {[%clk 0:03:01]} 1... e5 {[%clk 0:02:59]} 2. Nf3 {[%clk 0:02:01] developing} *"));
+    ///
+    /// let parsed = Game::from_pgn(&game.to_pgn().to_string()).expect("valid game");
+    /// assert_eq!(parsed.get_comment(2), Some(&String::from("developing")));
+    /// assert_eq!(parsed, game);
+    /// ```
+    pub fn record_clock(&mut self, clock: &GameClock) {
+        self.set_tag("TimeControl", clock.time_control().to_string());
+        for (idx, time) in clock.clock_times().iter().take(self.moves.len()).enumerate() {
+            self.set_clock(idx, *time);
+        }
+    }
+
+    /// Alternative lines for the move at `move_idx`.
+    #[must_use]
+    #[inline(always)]
+    pub fn get_variations(&self, move_idx: usize) -> &[Game] {
+        self.variations.get(&move_idx).map_or(&[], Vec::as_slice)
+    }
+
+    /// Adds the `moves` as an alternative line for the move at `move_idx`.
+    ///
+    /// # Errors
+    /// [MoveError::IllegalMove] if the game has no move at `move_idx`, otherwise as in [ChessBoard::make_move_checked].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut game = Game::from_pgn("1. e4 e5 2. Nf3 *").expect("valid game");
+    /// game.add_variation(1, &[Move::from_uci("g8f6"), Move::from_uci("b1c3")]).expect("legal moves");
+    /// assert!(game.to_pgn().to_string().ends_with("1. e4 e5 (1... Nf6 2. Nc3) 2. Nf3 *"));
+    ///
+    /// assert_eq!(game.add_variation(3, &[Move::from_uci("b8c6")]), Err(MoveError::IllegalMove));
+    /// assert_eq!(game.add_variation(0, &[Move::from_uci("e7e5")]), Err(MoveError::WrongSideToMove));
+    /// ```
+    pub fn add_variation(&mut self, move_idx: usize, moves: &[Move]) -> Result<(), MoveError> {
+        if move_idx >= self.moves.len() {
+            return Err(MoveError::IllegalMove);
         }
 
-        // by default add file if not in the same file nor rank
-        // but can move into same location (happens with knights)
-        add_file = add_file || !add_rank;
+        let mut board = self.board_at(move_idx).expect("checked above");
+        let mut variation = Self {
+            tags: HashMap::new(),
+            starting_fen: board.to_fen(),
+            moves: Vec::with_capacity(moves.len()),
+            initial_comment: None,
+            comments: HashMap::new(),
+            nags: HashMap::new(),
+            clocks: HashMap::new(),
+            variations: HashMap::new(),
+        };
+        for m in moves {
+            variation.moves.push(board.make_move_checked(*m)?);
+        }
+        self.variations.entry(move_idx).or_default().push(variation);
+        Ok(())
+    }
+}
+
+/// Formats the time of a "[%clk]" command as "h:mm:ss", with tenths of a second if there are any.
+fn format_clock(time: Duration) -> String {
+    let seconds = time.as_secs();
+    let clock = format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60);
+    match time.subsec_millis() / 100 {
+        0 => { clock }
+        tenths => { format!("{clock}.{tenths}") }
+    }
+}
 
-        (add_file, add_rank)
+fn parse_clock(clock: &str) -> Option<Duration> {
+    let mut fields = clock.trim().split(':');
+    let (hours, minutes, seconds) = (fields.next()?, fields.next()?, fields.next()?);
+    if fields.next().is_some() {
+        return None;
     }
 
-    fn get_move_san(&self, m: ReversibleMove) -> String {
+    let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, "0"));
+    let millis: u64 = format!("{fraction:0<3}").get(..3)?.parse().ok()?;
+    let minutes: u64 = minutes.parse().ok()?;
+    let seconds: u64 = seconds.parse().ok()?;
+    if minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+    Some(Duration::from_secs(hours.parse::<u64>().ok()? * 3600 + minutes * 60 + seconds) + Duration::from_millis(millis))
+}
+
+/// Splits the first valid "[%clk h:mm:ss]" command off the `comment`, the rest of the comment is [None] if nothing else is left.
+fn take_clock_command(comment: &str) -> (Option<Duration>, Option<String>) {
+    let (mut commands, text) = split_commands(comment);
+    let Some(idx) = commands.iter().position(|(name, value)| name == "clk" && parse_clock(value).is_some()) else {
+        return (None, Some(comment.to_string()));
+    };
+
+    let clock = parse_clock(&commands.remove(idx).1);
+    let rest = join_commands(&commands, &text);
+    (clock, (!rest.is_empty()).then_some(rest))
+}
+
+/// "[%eval 0.34]" in pawns or "[%eval #-3]" for a mate, anything after a ',' (e.g. the depth) is ignored.
+fn parse_eval(eval: &str) -> Option<Annotation> {
+    let eval = eval.split(',').next()?.trim();
+    match eval.strip_prefix('#') {
+        Some(moves) => { moves.parse().ok().map(Annotation::Mate) }
+        None => { eval.parse().ok().map(Annotation::Eval) }
+    }
+}
+
+/// Splits the "[%name value]" commands off the `comment`, returns them and the text around them.
+fn split_commands(comment: &str) -> (Vec<(String, String)>, String) {
+    let mut commands = Vec::new();
+    let mut text = Vec::new();
+    let mut rest = comment;
+    while let Some(start) = rest.find("[%") {
+        let Some(len) = rest[start..].find(']') else {
+            break;
+        };
+        let command = &rest[start + 2..start + len];
+        let (name, value) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+        commands.push((name.to_string(), value.trim().to_string()));
+        text.push(rest[..start].trim());
+        rest = &rest[start + len + 1..];
+    }
+    text.push(rest.trim());
+
+    let text = text.into_iter().filter(|part| !part.is_empty()).collect::<Vec<_>>().join(" ");
+    (commands, text)
+}
+
+/// The commands first, as lichess writes them, then the text.
+fn join_commands(commands: &[(String, String)], text: &str) -> String {
+    commands.iter()
+        .map(|(name, value)| format!("[%{name} {value}]"))
+        .chain((!text.is_empty()).then(|| text.to_string()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl ChessBoard {
+
+    /// (add_file, add_rank)
+    /// https://en.wikipedia.org/wiki/Algebraic_notation_(chess)#Disambiguating_moves
+    /// Returns the (file, rank) of the moving piece needed to tell it apart from the other pieces which can legally move to the same square.
+    fn san_disambiguation(&self, m: Move) -> (bool, bool) {
+        // Drops name the piece from the hand, there's nothing to tell apart
+        if m.is_drop() {
+            return (false, false);
+        }
+        let from = m.get_from_idx();
+        let piece = self.get_piece(from);
+
+        // If a pawn made the capture then file is always needed
+        if piece.get_piece_type() == PieceType::Pawn {
+            return (self.is_capture(m), false);
+        }
+
+        // Pieces which can also move to the same position
+        let mut overlapping_pieces = 0u64;
+        for other in self.get_legal_moves().iter() {
+            let square = other.get_from_idx();
+            if !other.is_drop() && other.get_to_idx() == m.get_to_idx() && square != from && self.get_piece(square) == piece {
+                overlapping_pieces |= 1u64 << square;
+            }
+        }
+        if overlapping_pieces == 0 {
+            return (false, false);
+        }
+
+        let (mut same_file, mut same_rank) = (false, false);
+        while overlapping_pieces != 0 {
+            let square = BoardHelper::pop_lsb(&mut overlapping_pieces);
+            same_file |= BoardHelper::get_file(square) == BoardHelper::get_file(from);
+            same_rank |= BoardHelper::get_rank(square) == BoardHelper::get_rank(from);
+        }
+        match (same_file, same_rank) {
+            (false, _) => { (true, false) }
+            (true, false) => { (false, true) }
+            (true, true) => { (true, true) }
+        }
+    }
+
+    /// SAN without the check or checkmate suffix.
+    fn get_move_san(&self, m: Move) -> String {
+        // Piece from the hand, "P@e4"
+        if let Some(piece_type) = m.dropped_piece() {
+            return format!("{}@{}", piece_type.to_char().to_ascii_uppercase(), BoardHelper::square_to_string(m.get_to_idx()));
+        }
+
         // Castling
-        if m.board_move.get_flag() == MoveFlag::Castle {
-            let to = m.board_move.get_to_idx();
+        if m.get_flag() == MoveFlag::Castle {
+            let to = m.get_to_idx();
             match to {
                 // King side
                 6 | 62 => {
@@ -288,8 +1104,8 @@ impl ChessBoard {
         // if more than 1 pieces of the same type can move to the same location then a starting location is added as prefix for disambiguation.
         // https://en.wikipedia.org/wiki/Algebraic_notation_(chess)#Disambiguating_moves
         let mut dis_amb = "".to_string();
-        let (dis_file, dis_rank) = self.pgn_needs_disambiguating(m);
-        let (file, rank) = BoardHelper::square_to_chars(m.board_move.get_from_idx());
+        let (dis_file, dis_rank) = self.san_disambiguation(m);
+        let (file, rank) = BoardHelper::square_to_chars(m.get_from_idx());
         if dis_file {
             dis_amb.push(file);
         }
@@ -297,22 +1113,84 @@ impl ChessBoard {
             dis_amb.push(rank);
         }
 
-        let piece = self.get_piece(m.board_move.get_from_idx());
-        let captured = if m.captured.is_none() { "" } else { "x" };
+        let piece = self.get_piece(m.get_from_idx());
+        let captured = if self.is_capture(m) { "x" } else { "" };
         let moving = if piece.get_piece_type() == PieceType::Pawn { "".to_string() } else { piece.get_piece_type().to_char().to_uppercase().to_string() };
-        let promotion = match m.board_move.get_flag() {
+        let promotion = match m.get_flag() {
             MoveFlag::PromoteKnight => { "=N" }
             MoveFlag::PromoteBishop => { "=B" }
             MoveFlag::PromoteRook => { "=R" }
             MoveFlag::PromoteQueen => { "=Q" }
             _ => { "" }
         };
-        let to_square = BoardHelper::square_to_string(m.board_move.get_to_idx());
+        let to_square = BoardHelper::square_to_string(m.get_to_idx());
 
         format!("{}{}{}{}{}", moving, dis_amb, captured, to_square, promotion)
     }
 
+    /// Renders a legal move of the current position in SAN, including the check ('+') and checkmate ('#') suffixes.
+    /// The output for illegal moves is unspecified.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("8/8/6k1/8/8/8/4K3/R6R w - - 0 1").expect("valid fen");
+    /// assert_eq!(board.move_to_san(Move::from_uci("a1d1")), "Rad1");
+    /// assert_eq!(board.move_to_san(Move::from_uci("h1g1")), "Rhg1+");
+    ///
+    /// board.parse_fen("rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2").expect("valid fen");
+    /// assert_eq!(board.move_to_san(Move::from_uci("d8h4")), "Qh4#");
+    /// ```
+    #[must_use]
+    pub fn move_to_san(&self, m: Move) -> String {
+        self.get_move_san(m) + self.check_suffix(m)
+    }
+
+    /// "+" if the `m` gives check, "#" if it gives checkmate and nothing otherwise.
+    pub(super) fn check_suffix(&self, m: Move) -> &'static str {
+        match self.variant {
+            VariantKind::Standard | VariantKind::Crazyhouse => {
+                if !MoveGenerator::gives_check(self, m) { "" } else if MoveGenerator::gives_checkmate(self, m) { "#" } else { "+" }
+            }
+            // Explosions and forced captures change what a check is, so the move is made on a copy
+            VariantKind::Atomic | VariantKind::Antichess => {
+                let mut board = self.clone();
+                board.make_move(m, true);
+                if !board.is_king_in_check(board.turn) { "" } else if board.get_legal_moves().is_empty() { "#" } else { "+" }
+            }
+        }
+    }
+
     pub fn to_pgn(&self) -> Pgn {
+        self.to_pgn_with_options(PgnExportOptions::default())
+    }
+
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// for m in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"] {
+    ///     board.make_move_uci(m).expect("valid move");
+    /// }
+    /// let options = PgnExportOptions { annotate_draw_claim: true, draw_claim_result: true, ..Default::default() };
+    /// let pgn = board.to_pgn_with_options(options).to_string();
+    /// assert!(pgn.contains("[Result \"1/2-1/2\"]"));
+    /// assert!(pgn.ends_with("4. Ng1 Ng8 {Draw claimed by threefold repetition} 1/2-1/2"));
+    ///
+    /// // The result of a checkmate is derived from the position, a resignation has to be given
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// for m in ["f2f3", "e7e5", "g2g4", "d8h4"] {
+    ///     board.make_move_uci(m).expect("valid move");
+    /// }
+    /// assert!(board.to_pgn().to_string().ends_with("2. g4 Qh4# 0-1"));
+    /// board.unmake_move();
+    /// let options = PgnExportOptions { result: Some(GameResult::WhiteWins), ..Default::default() };
+    /// assert!(board.to_pgn_with_options(options).to_string().ends_with("2. g4 1-0"));
+    /// assert!(board.to_pgn().to_string().ends_with("2. g4 *"));
+    /// ```
+    pub fn to_pgn_with_options(&self, options: PgnExportOptions) -> Pgn {
         use chrono::prelude::*;
 
         let now = Local::now();
@@ -327,7 +1205,7 @@ impl ChessBoard {
         pgn.set_tag("Round", "?");
         pgn.set_tag("White", "?");
         pgn.set_tag("Black", "?");
-        pgn.set_tag("Result", "?");
+        pgn.set_tag("Result", "*");
 
         // get moves as pgn
         let mut board = self.clone();
@@ -335,159 +1213,194 @@ impl ChessBoard {
             let check_or_mate = if board.is_check_mate() { "#" } else if board.is_king_in_check(board.turn) { "+" } else { "" };
             board.unmake_move().unwrap();
 
-            let move_pgn = format!("{}{}", board.get_move_san(reversible_move), check_or_mate);
+            let move_pgn = format!("{}{}", board.get_move_san(reversible_move.board_move), check_or_mate);
             pgn.moves.push(move_pgn);
         }
         pgn.moves.reverse();
 
+        if let (Some(claim), false) = (self.get_draw_claim(), pgn.moves.is_empty()) {
+            if options.annotate_draw_claim {
+                let comment = match claim {
+                    DrawClaim::ThreefoldRepetition => { "Draw claimed by threefold repetition" }
+                    DrawClaim::FiftyMoveRule => { "Draw claimed by the 50-move rule" }
+                };
+                pgn.set_comment(pgn.moves.len() - 1, comment);
+            }
+            if options.draw_claim_result {
+                pgn.set_tag("Result", "1/2-1/2");
+                pgn.set_tag("Termination", "normal");
+            }
+        }
+
+        if let Some(result) = options.result.or_else(|| self.get_game_state().result()) {
+            pgn.set_tag("Result", result.to_string());
+        }
+
         // Add fen if the position differs from starting position
         let board_fen = board.to_fen();
         if board_fen != STARTPOS_FEN {
+            pgn.set_tag("SetUp", "1");
             pgn.set_tag("FEN", board_fen);
         }
 
         pgn
     }
 
-    /// Tags are not saved!
-    #[allow(dead_code)]
-    pub fn parse_pgn(&mut self, pgn_str: &str) {
-        let mut pgn = Pgn::new();
-        pgn.parse_string(pgn_str);
-
-        println!("parsed moves {:?}", pgn.moves);
+    /// Parses the first game of the `pgn_str` and sets it up with [ChessBoard::load_pgn]. Tags are not saved!
+    ///
+    /// # Errors
+    /// If the game can't be parsed or set up, the board is left at the last move which could be made.
+    pub fn parse_pgn(&mut self, pgn_str: &str) -> Result<(), PgnImportError> {
+        self.load_pgn(&Pgn::parse_string(pgn_str)?)
+    }
 
-        for pgn_m in &pgn.moves {
-            if let Some(m) = self.make_move_pgn(pgn_m) {
-                println!("executed '{}'->'{}'", pgn_m, m.to_uci());
-            }
-            else {
-                println!("couldn't execute '{}'", pgn_m);
-                break;
-            }
+    /// Sets up the [starting position](Pgn::starting_fen) of the game and makes the moves of the main line.
+    ///
+    /// # Errors
+    /// If the "FEN" tag is invalid or a move can't be made, the board is left at the last move which could be made.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let pgn = Pgn::parse_string("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 12\"]\n12... Kd7 13. e4 *").expect("valid pgn");
+    /// let mut board = ChessBoard::new();
+    /// board.load_pgn(&pgn).expect("legal moves");
+    /// assert_eq!(board.to_fen(), "8/3k4/8/8/4P3/8/8/4K3 b - e3 0 13");
+    /// assert_eq!(board.start_ply(), 23);
+    ///
+    /// let pgn = Pgn::parse_string("1. e4 e5 2. Ke3 *").expect("valid pgn");
+    /// assert_eq!(board.load_pgn(&pgn), Err(PgnImportError::IllegalMove(2, MoveError::IllegalMove)));
+    /// ```
+    pub fn load_pgn(&mut self, pgn: &Pgn) -> Result<(), PgnImportError> {
+        self.parse_fen(pgn.starting_fen()).map_err(PgnImportError::InvalidFen)?;
+        for (idx, san) in pgn.moves.iter().enumerate() {
+            self.make_move_pgn(san).map_err(|error| PgnImportError::IllegalMove(idx, error))?;
         }
+        Ok(())
     }
 
     /// Gets a LEGAL move from a PGN string
-    pub fn get_move_pgn(&mut self, pgn: &str) -> Option<Move> {
+    pub fn get_move_pgn(&self, pgn: &str) -> Result<Move, MoveError> {
         // PGN move examples: 
         // e4      (A pawn moved to 'e4')
         // Ng1     (A Knight moved to 'g1')
         // Qe2xe4+ (Queen moved from e2, captured a piece on e4 whilst putting the opponent's king in check)
         // exd8=Q# (A pawn moved from e file to d8, captured a piece, promoted to a Queen and check mated the opponent).
+        // N@f3    (A Knight from the hand was dropped on 'f3', crazyhouse only)
 
         if pgn.len() < 2 {
-            return None;
+            return Err(MoveError::ParseError);
         }
-        
-        // The objective here is to get the destination square, which at the end of the pgn (before the flags).
-        // So removing the flags such "#", "=Q", "=N", "+" we guarantee that the destination square is at the end.
-        // "Qe2xe4+" -> "Qe2e4"
-        let flagless = pgn
-        .replace(['#', '+', 'x'], "") // [check make, check, capture]
-        .replace("=Q", "") // promote queen
-        .replace("=R", "") // promote rook
-        .replace("=B", "") // promote bishop
-        .replace("=N", ""); // promote knight
-
-        let mut moves = self.get_legal_moves().into_iter();
+
+        // Check and mate markers don't change which move it is
+        let san = pgn.trim_end_matches(['+', '#']);
 
         // first if check if it's castle
-        if flagless == "O-O" || flagless == "0-0" {
-            return moves.find(|m| {
-                m.get_to_idx() == Square::G1 as i32 || m.get_to_idx() == Square::G8 as i32
-            });
-        }
-        else if flagless == "O-O-O" || flagless == "0-0-0" {
-            return moves.find(|m| { 
-                m.get_to_idx() == Square::C1 as i32 || m.get_to_idx() == Square::C8 as i32
-            });
-        }
-
-        // extracting the destination square "Qe2e4" -> "4e2eQ" -> ('4', 'e') -> 28
-        let mut flagless_iter = flagless.chars().rev();
-        let to_square: i32 = {
-            let rank_to_char = flagless_iter.next().unwrap();
-            let file_to_char = flagless_iter.next().unwrap();
-            BoardHelper::chars_to_square(file_to_char, rank_to_char)
+        let castle_targets = match san {
+            "O-O" | "0-0" => { Some([Square::G1 as i32, Square::G8 as i32]) }
+            "O-O-O" | "0-0-0" => { Some([Square::C1 as i32, Square::C8 as i32]) }
+            _ => { None }
         };
-        
+        if let Some(targets) = castle_targets {
+            return self.legal_moves_iter().find(|m| {
+                m.get_flag() == MoveFlag::Castle && targets.contains(&m.get_to_idx())
+            }).ok_or(MoveError::IllegalMove);
+        }
+
+        // Drops, legal only if the side to move has the piece in hand
+        if let Some((piece, square)) = san.split_once('@') {
+            let piece_type = match piece.as_bytes() {
+                [] => { PieceType::Pawn }
+                [c] if c.is_ascii_uppercase() => { PieceType::from_char(*c as char) }
+                _ => { return Err(MoveError::ParseError); }
+            };
+            let square = BoardHelper::text_to_square(square).map_err(|_| MoveError::ParseError)?;
+            let m = Move::drop(piece_type, square).ok_or(MoveError::ParseError)?;
+            return if self.get_legal_moves().contains(m) { Ok(m) } else { Err(MoveError::IllegalMove) };
+        }
+
         // if the first char is upper like in "Qe2" that means that a queen moved to e2. If there's no uppercase letter it means that a pawn moved.
-        let moving_piece = {
-            let piece_char = pgn.chars().next().unwrap();
-            if piece_char.is_uppercase() { PieceType::from_char(piece_char) } else { PieceType::Pawn }
+        let (moving_piece, rest) = match san.as_bytes() {
+            [piece, rest @ ..] if piece.is_ascii_uppercase() => { (PieceType::from_char(*piece as char), rest) }
+            bytes => { (PieceType::Pawn, bytes) }
         };
 
-        // We want get additional information about the file and rank which the piece is moving from if provided.
-        // "Qe2xe8" -> "e2", "axb7" -> "a"
-        let from_info = {
-            let skip_first = if moving_piece != PieceType::Pawn { 1 } else { 0 }; // remove first if moving piece is not a pawn
-            &flagless[skip_first..flagless.len()-2]// remove last 2 (destination square)
-        };
-        
-        let mut file_from = -1;
-        let mut rank_from = -1;
-        for c in from_info.chars() {
-            if ('a'..='h').contains(&c) {
-                file_from = BoardHelper::file_to_idx(c);
-            }
-            else if ('1'..='8').contains(&c) {
-                rank_from = BoardHelper::rank_to_idx(c);
+        // Files and ranks in the order they appear in a single pass, the last 2 are the destination square
+        // and the ones before it tell where the piece moved from. "Qe2xe4" -> [e, 2, e, 4], "exd8=Q" -> [e, d, 8]
+        let mut coordinates = [(false, 0i32); 4]; // (is_rank, index)
+        let mut count = 0;
+        let mut promotion = MoveFlag::None;
+        let mut bytes = rest.iter();
+        while let Some(&c) = bytes.next() {
+            let coordinate = match c {
+                b'a'..=b'h' => { (false, i32::from(c - b'a')) }
+                b'1'..=b'8' => { (true, i32::from(c - b'1')) }
+                b'x' => { continue; }
+                b'=' => {
+                    promotion = match bytes.next() {
+                        Some(b'Q') => { MoveFlag::PromoteQueen }
+                        Some(b'R') => { MoveFlag::PromoteRook }
+                        Some(b'B') => { MoveFlag::PromoteBishop }
+                        Some(b'N') => { MoveFlag::PromoteKnight }
+                        _ => { return Err(MoveError::ParseError); }
+                    };
+                    continue;
+                }
+                _ => { return Err(MoveError::ParseError); }
+            };
+            if count == coordinates.len() {
+                return Err(MoveError::ParseError);
             }
+            coordinates[count] = coordinate;
+            count += 1;
         }
-        let promotion = {
-            if pgn.contains("=Q") {
-                MoveFlag::PromoteQueen
-            }
-            else if pgn.contains("=R") {
-                MoveFlag::PromoteRook
-            }
-            else if pgn.contains("=B") {
-                MoveFlag::PromoteBishop
-            }
-            else if pgn.contains("=N") {
-                MoveFlag::PromoteKnight
-            }
-            else {
-                MoveFlag::None
-            }
+
+        let to_square = match coordinates[..count] {
+            [.., (false, file), (true, rank)] => { BoardHelper::file_rank_to_idx(file, rank) }
+            _ => { return Err(MoveError::ParseError); }
         };
 
-        let mut result: Vec<Move> = moves.filter(|m| {
-            if m.get_to_idx() != to_square {
-                return false;
-            }
-            
-            // Now let's check if the move meets our conditions
-            let mut filter = true;
-            filter = filter && self.get_piece(m.get_from_idx()).get_piece_type() == moving_piece;
-            if file_from != -1 {
-                filter = filter && BoardHelper::get_file(m.get_from_idx()) == file_from;
-            }
-            if rank_from != -1 {
-                filter = filter && BoardHelper::get_rank(m.get_from_idx()) == rank_from;
-            }
-            if promotion != MoveFlag::None {
-                filter = filter && m.get_flag() == promotion;
-            }
-        
-            filter
-        }).collect();
+        // We want get additional information about the file and rank which the piece is moving from if provided.
+        let mut file_from = -1;
+        let mut rank_from = -1;
+        for (is_rank, idx) in &coordinates[..count - 2] {
+            if *is_rank { rank_from = *idx; } else { file_from = *idx; }
+        }
 
         // There SHOULD only be 1 move which matches the given conditions.
-        if result.len() == 1 {
-            return result.pop();
+        let mut found = None;
+        let candidates = self.get_legal_moves().into_iter().filter(|m| {
+            !m.is_drop()
+                && m.get_to_idx() == to_square
+                && self.get_piece(m.get_from_idx()).get_piece_type() == moving_piece
+                && (file_from == -1 || BoardHelper::get_file(m.get_from_idx()) == file_from)
+                && (rank_from == -1 || BoardHelper::get_rank(m.get_from_idx()) == rank_from)
+                && (promotion == MoveFlag::None || m.get_flag() == promotion)
+        });
+        for m in candidates {
+            if found.is_some() {
+                return Err(MoveError::AmbiguousSan);
+            }
+            found = Some(m);
         }
-        None
+        found.ok_or(MoveError::IllegalMove)
     }
 
     /// Returns the made move, only does legal moves
-    pub fn make_move_pgn(&mut self, pgn: &str) -> Option<Move> {
-        let m = self.get_move_pgn(pgn);
-        if let Some(chess_move) = m {
-            self.make_move(chess_move, false);
-        }
-        m
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1").expect("valid fen");
+    /// assert_eq!(board.make_move_pgn("Nd2"), Err(MoveError::AmbiguousSan));
+    /// assert_eq!(board.make_move_pgn("Ne4"), Err(MoveError::IllegalMove));
+    /// assert!(board.make_move_pgn("Nbd2").is_ok());
+    /// ```
+    pub fn make_move_pgn(&mut self, pgn: &str) -> Result<Move, MoveError> {
+        let m = self.get_move_pgn(pgn)?;
+        self.make_move(m, false);
+        Ok(m)
     }
 }
 
@@ -569,7 +1482,309 @@ mod tests {
 
         let mut board = ChessBoard::new();
         board.parse_fen(STARTPOS_FEN).unwrap();
-        board.parse_pgn(FISCHER_V_SPASSKY.into());
+        board.parse_pgn(FISCHER_V_SPASSKY).expect("valid pgn");
         assert_eq!(board.to_fen(), "8/8/4R1p1/2k3p1/1p4P1/1P1b1P2/3K1n2/8 b - - 2 43");
     }
+
+    #[test]
+    fn test_pgn_draw_claim_fifty_move_rule() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("4k3/8/8/8/8/8/8/R3K3 w - - 99 80").expect("valid fen");
+        board.make_move_uci("a1a7").unwrap();
+
+        let options = PgnExportOptions { annotate_draw_claim: true, draw_claim_result: true, ..Default::default() };
+        let pgn = board.to_pgn_with_options(options);
+        assert_eq!(pgn.get_comment(0), Some(&String::from("Draw claimed by the 50-move rule")));
+        assert_eq!(pgn.tags.get("Result"), Some(&String::from("1/2-1/2")));
+        assert_eq!(pgn.tags.get("Termination"), Some(&String::from("normal")));
+
+        // Not claimed, the game goes on until the 75-move rule
+        let pgn = board.to_pgn();
+        assert_eq!(pgn.get_comment(0), None);
+        assert_eq!(pgn.tags.get("Result"), Some(&String::from("*")));
+        assert_eq!(pgn.tags.get("Termination"), None);
+    }
+
+    #[test]
+    fn test_pgn_comment_repeats_move_number() {
+        let mut pgn = Pgn::new();
+        pgn.moves = vec![String::from("e4"), String::from("e5"), String::from("Nf3")];
+        pgn.set_comment(0, "best by test");
+        assert_eq!(pgn.to_string(), "\n1. e4 {best by test} 1... e5 2. Nf3 *");
+    }
+
+    #[test]
+    fn test_game_round_trip() {
+        let contents = "[SetUp \"1\"]\n[FEN \"4k3/1P6/8/8/8/8/8/4K3 w - - 0 40\"]\n[Result \"1-0\"]\n\n{Endgame} 40. b8=Q+ $1 Kd7 (40... Ke7 41. Qc7+) 41. Qb7+ {check} Kd6 1-0";
+        let game = Game::from_pgn(contents).expect("valid game");
+        assert_eq!(game.moves().iter().map(|m| m.to_uci()).collect::<Vec<_>>(), ["b7b8q", "e8d7", "b8b7", "d7d6"]);
+        assert_eq!(game.get_initial_comment(), Some(&String::from("Endgame")));
+        assert_eq!(game.get_comment(2), Some(&String::from("check")));
+        assert_eq!(game.get_nags(0), &[1]);
+        assert_eq!(game.board_at(0).map(|board| board.to_fen()), Some(String::from("4k3/1P6/8/8/8/8/8/4K3 w - - 0 40")));
+        let mut board = ChessBoard::new();
+        board.parse_pgn(contents).expect("valid game");
+        assert_eq!(game.board_at(4), Some(board));
+        assert_eq!(game.board_at(5), None);
+
+        let pgn = game.to_pgn();
+        assert_eq!(pgn.get_moves(), &["b8=Q+", "Kd7", "Qb7+", "Kd6"]);
+        assert_eq!(pgn.get_variations(1)[0].get_moves(), &["Ke7", "Qc7+"]);
+        assert_eq!(Game::from_parsed(&pgn), Ok(game));
+
+        assert_eq!(Game::from_pgn("1. e4 e5 2. Ke3 *"), Err(PgnImportError::IllegalMove(2, MoveError::IllegalMove)));
+        assert_eq!(Game::from_pgn("1. e4 e5 (1... Ke3) *"), Err(PgnImportError::IllegalMove(0, MoveError::IllegalMove)));
+    }
+
+    #[test]
+    fn test_game_nested_variations() {
+        let contents = "1. d4 {main} d5 (1... Nf6 2. c4 (2. Nf3 g6 $5) 2... e6 {Nimzo?}) (1... f5) 2. c4 *";
+        let game = Game::from_pgn(contents).expect("valid game");
+        assert_eq!(game.moves().len(), 3);
+        assert_eq!(game.get_variations(1).len(), 2);
+
+        let indian = &game.get_variations(1)[0];
+        assert_eq!(indian.starting_fen(), "rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 1");
+        assert_eq!(indian.get_comment(2), Some(&String::from("Nimzo?")));
+        let knight = &indian.get_variations(1)[0];
+        assert_eq!(knight.moves().iter().map(|m| m.to_uci()).collect::<Vec<_>>(), ["g1f3", "g7g6"]);
+        assert_eq!(knight.get_nags(1), &[5]);
+        assert_eq!(knight.board_at(2).map(|board| board.get_turn()), Some(crate::piece::PieceColor::White));
+
+        // Survives a round trip through the text
+        let written = game.to_pgn().to_string();
+        assert_eq!(Game::from_pgn(&written), Ok(game));
+    }
+
+    #[test]
+    fn test_game_clock_commands() {
+        let contents = "[TimeControl \"40/5400+30:1800+30\"]\n1. e4 { [%eval 0.2] [%clk 1:29:58.5] } e5 {[%clk 1:30:20]} 2. Nf3 {[%clk 0:61:00]} *";
+        let game = Game::from_pgn(contents).expect("valid game");
+        assert_eq!(game.get_clock(0), Some(Duration::from_millis(5_398_500)));
+        assert_eq!(game.get_comment(0), Some(&String::from("[%eval 0.2]")));
+        assert_eq!(game.get_clock(1), Some(Duration::from_secs(5420)));
+        assert_eq!(game.get_comment(1), None);
+        // Minutes past 59 aren't a valid clock, the comment is kept as it is
+        assert_eq!(game.get_clock(2), None);
+        assert_eq!(game.get_comment(2), Some(&String::from("[%clk 0:61:00]")));
+        assert_eq!(game.time_control().map(|control| control.stage(1).time), Some(Duration::from_secs(1800)));
+
+        let written = game.to_pgn().to_string();
+        assert!(written.contains("1. e4 {[%eval 0.2] [%clk 1:29:58.5]} 1... e5 {[%clk 1:30:20]}"));
+        assert_eq!(Game::from_pgn(&written), Ok(game));
+    }
+
+    #[test]
+    fn test_pgn_annotations_replace_commands() {
+        let mut pgn = Pgn::parse_string("1. e4 { [%cal Ge2e4] [%eval 0.1,22] best by test } *").expect("valid pgn");
+        assert_eq!(pgn.get_annotations(0), [Annotation::Eval(0.1), Annotation::Comment(String::from("[%cal Ge2e4] best by test"))]);
+
+        pgn.annotate(0, Annotation::Eval(-1.5));
+        pgn.annotate(0, Annotation::Nag(2));
+        pgn.annotate(0, Annotation::Nag(2));
+        assert_eq!(pgn.get_comment(0), Some(&String::from("[%eval -1.50] [%cal Ge2e4] best by test")));
+        assert_eq!(pgn.get_nags(0), &[2]);
+
+        // Survives a round trip through the text
+        let written = pgn.to_string();
+        assert_eq!(Pgn::parse_string(&written).expect("valid pgn").get_annotations(0), pgn.get_annotations(0));
+    }
+
+    #[test]
+    fn test_pgn_parse_full_game() {
+        let pgn = Pgn::parse_string(r#"
+[Event "F/S Return Match"]
+[Site "Belgrade, Serbia JUG"]
+[White "Fischer, Robert J."]
+[Annotator "A \"quoted\" name"]
+
+% escaped line
+{Opening comment} 1. e4 e5 2. Nf3 Nc6 3. Bb5 {This opening is called the Ruy Lopez.} 3... a6
+4. Ba4 Nf6 ; rest of the line
+5. O-O Be7 (5... b5 6. Bb3 (6. Bxb5?? axb5) 6... Be7) 6. Re1 $2 $18 b5 1/2-1/2
+"#).expect("valid pgn");
+
+        assert_eq!(pgn.tags.get("Site"), Some(&String::from("Belgrade, Serbia JUG")));
+        assert_eq!(pgn.tags.get("Annotator"), Some(&String::from("A \"quoted\" name")));
+        assert_eq!(pgn.tags.get("Result"), Some(&String::from("1/2-1/2")));
+        assert_eq!(pgn.moves, vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O", "Be7", "Re1", "b5"]);
+        assert_eq!(pgn.get_initial_comment(), Some(&String::from("Opening comment")));
+        assert_eq!(pgn.get_comment(4), Some(&String::from("This opening is called the Ruy Lopez.")));
+        assert_eq!(pgn.get_comment(7), Some(&String::from("rest of the line")));
+        assert_eq!(pgn.get_nags(10), &[2, 18]);
+
+        let variation = &pgn.get_variations(9)[0];
+        assert_eq!(variation.moves, vec!["b5", "Bb3", "Be7"]);
+        assert_eq!(variation.get_variations(1)[0].moves, vec!["Bxb5", "axb5"]);
+        assert_eq!(variation.get_variations(1)[0].get_nags(0), &[4]);
+    }
+
+    #[test]
+    fn test_pgn_parse_errors() {
+        assert_eq!(Pgn::parse_string("[Event \"x\"").err(), Some(PGNParserError::UnterminatedTag(1, 1)));
+        assert_eq!(Pgn::parse_string("[Event \"x]").err(), Some(PGNParserError::UnterminatedString(1, 8)));
+        assert_eq!(Pgn::parse_string("1. e4 (e3").err(), Some(PGNParserError::UnbalancedVariation(1, 10)));
+        assert_eq!(Pgn::parse_string("1. e4 e5)").err(), Some(PGNParserError::UnbalancedVariation(1, 9)));
+        assert_eq!(Pgn::parse_string("1. e4 $x").err(), Some(PGNParserError::InvalidNag(1, 7)));
+        assert_eq!(Pgn::parse_string("1. e4\n  e5!!! ").err(), Some(PGNParserError::SyntaxError(2, 5)));
+        assert_eq!(Pgn::parse_string("1. e4 & e5").err(), Some(PGNParserError::SyntaxError(1, 7)));
+    }
+
+    #[test]
+    fn test_pgn_round_trip_variations() {
+        let movetext = "1. e4 e5 (1... c5 2. Nf3 $1 {Sicilian}) 2. Nf3 Nc6 3. Bb5 *";
+        let pgn = Pgn::parse_string(movetext).expect("valid pgn");
+        assert_eq!(pgn.to_string(), format!("[Result \"*\"]\n\n{movetext}"));
+        assert_eq!(Pgn::parse_string(&pgn.to_string()).expect("valid pgn"), pgn);
+    }
+
+    #[test]
+    fn test_pgn_board_round_trip() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        for m in ["e2e4", "c7c5", "g1f3", "d7d6", "d2d4", "c5d4", "f3d4", "g8f6", "b1c3", "a7a6"] {
+            board.make_move_uci(m).expect("valid move");
+        }
+
+        let pgn = Pgn::parse_string(&board.to_pgn().to_string()).expect("valid pgn");
+        let mut replayed = ChessBoard::new();
+        replayed.parse_fen(STARTPOS_FEN).expect("valid fen");
+        for m in pgn.get_moves() {
+            replayed.make_move_pgn(m).expect("valid move");
+        }
+        assert_eq!(replayed.to_fen(), board.to_fen());
+    }
+
+    #[test]
+    fn test_pgn_crazyhouse_drops_round_trip() {
+        let mut board = ChessBoard::new();
+        board.set_variant(VariantKind::Crazyhouse);
+        board.parse_fen("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR[] w KQkq - 0 2").expect("valid fen");
+        let start = board.to_fen();
+        board.make_move_uci("e4d5").expect("legal move");
+        board.make_move_uci("d8d5").expect("legal move");
+        assert_eq!(board.move_to_san(Move::from_uci("P@e4")), "P@e4");
+        board.make_move_uci("b1c3").expect("legal move");
+        assert_eq!(board.move_to_san(Move::from_uci("P@e4")), "P@e4");
+        for san in ["P@e4", "Nxe4", "Qxe4+"] {
+            board.make_move_pgn(san).expect("legal move");
+        }
+        assert_eq!(board.move_to_san(Move::from_uci("P@e2")), "P@e2");
+        assert_eq!(board.get_move_pgn("N@e2"), Err(MoveError::IllegalMove)); // the knight is in black's hand
+        assert_eq!(board.get_move_pgn("K@e2"), Err(MoveError::ParseError));
+        board.make_move_pgn("P@e2").expect("legal move");
+
+        let pgn = Pgn::parse_string(&board.to_pgn().to_string()).expect("valid pgn");
+        assert_eq!(pgn.get_moves()[3..], ["P@e4", "Nxe4", "Qxe4+", "P@e2"]);
+        let mut replayed = ChessBoard::new();
+        replayed.set_variant(VariantKind::Crazyhouse);
+        replayed.parse_fen(&start).expect("valid fen");
+        for m in pgn.get_moves() {
+            replayed.make_move_pgn(m).expect("valid move");
+        }
+        assert_eq!(replayed.to_fen(), board.to_fen());
+    }
+
+    #[test]
+    fn test_pgn_numbering_from_fen() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 12").expect("valid fen");
+        for m in ["e8d7", "e2e4", "d7e6"] {
+            board.make_move_uci(m).expect("valid move");
+        }
+        assert_eq!(board.start_ply(), 23);
+
+        let pgn = board.to_pgn().to_string();
+        assert!(pgn.contains("12... Kd7 13. e4 Ke6"), "{pgn}");
+    }
+
+    #[test]
+    fn test_pgn_round_trip_from_fen() {
+        let movetext = "12... Kd7 (12... Kf7 13. e4 (13. e3 Ke6) 13... Kf6) 13. e4 {Pawn race} 13... Ke6 14. e5 *";
+        let contents = format!("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 12\"]\n\n{movetext}");
+        let pgn = Pgn::parse_string(&contents).expect("valid pgn");
+        assert!(pgn.to_string().ends_with(&format!("\n\n{movetext}")), "{}", pgn.to_string());
+        assert_eq!(Pgn::parse_string(&pgn.to_string()).expect("valid pgn"), pgn);
+
+        let mut board = ChessBoard::new();
+        board.parse_pgn(&contents).expect("legal moves");
+        assert_eq!(board.to_fen(), "8/8/4k3/4P3/8/8/8/4K3 b - - 0 14");
+        let exported = board.to_pgn();
+        assert_eq!(exported.get_tag("SetUp"), Some(&String::from("1")));
+        assert_eq!(exported.starting_fen(), pgn.starting_fen());
+        assert_eq!(exported.get_moves(), pgn.get_moves());
+
+        // SetUp "0" means the game starts from the standard position
+        let mut pgn = pgn;
+        pgn.set_tag("SetUp", "0");
+        assert_eq!(pgn.starting_fen(), STARTPOS_FEN);
+        assert_eq!(board.load_pgn(&pgn), Err(PgnImportError::IllegalMove(0, MoveError::IllegalMove)));
+    }
+
+    #[test]
+    fn test_move_to_san_disambiguation() {
+        let mut board = ChessBoard::new();
+
+        // Pinned knight can't move to e4, so no disambiguation is needed
+        board.parse_fen("7k/8/8/4b3/8/2N3N1/8/K7 w - - 0 1").expect("valid fen");
+        assert_eq!(board.move_to_san(Move::from_uci("g3e4")), "Ne4");
+
+        board.parse_fen("6k1/8/8/8/8/Q7/8/Q1Q4K w - - 0 1").expect("valid fen");
+        assert_eq!(board.move_to_san(Move::from_uci("a1b2")), "Qa1b2");
+        assert_eq!(board.move_to_san(Move::from_uci("a3b2")), "Q3b2");
+        assert_eq!(board.move_to_san(Move::from_uci("c1b2")), "Qcb2");
+
+        board.parse_fen("3r3k/4P3/8/8/8/8/8/7K w - - 0 1").expect("valid fen");
+        assert_eq!(board.move_to_san(Move::from_uci("e7d8q")), "exd8=Q+");
+        assert_eq!(board.move_to_san(Move::from_uci("e7e8n")), "e8=N");
+        assert_eq!(board.to_fen(), "3r3k/4P3/8/8/8/8/8/7K w - - 0 1");
+    }
+
+    #[test]
+    fn test_check_suffix_matches_made_move() {
+        fn made_suffix(board: &ChessBoard, m: Move) -> &'static str {
+            let mut board = board.clone();
+            board.make_move(m, true);
+            if !board.is_king_in_check(board.turn) { "" } else if board.get_legal_moves().is_empty() { "#" } else { "+" }
+        }
+        fn assert_suffixes(board: &mut ChessBoard, depth: u32, fen: &str) {
+            for m in board.get_legal_moves() {
+                assert_eq!(board.check_suffix(m), made_suffix(board, m), "{fen} {m}");
+                if depth > 1 {
+                    board.make_move(m, true);
+                    assert_suffixes(board, depth - 1, fen);
+                    board.unmake_move().expect("a move was made");
+                }
+            }
+        }
+
+        let mut board = ChessBoard::new();
+        for fen in crate::bench::BENCH_POSITIONS.iter().chain([
+            "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",          // back rank mate
+            "1r4k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",        // the checker can be taken
+            "6k1/5p1p/6p1/8/8/8/1B6/R5K1 w - - 0 1",      // discovered and double checks
+            "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4", // scholar's mate
+            "8/8/8/2k5/3Pp3/8/8/4K2Q b - d3 0 1",         // en passant evades the check
+            "4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1",           // castling gives check
+            "8/1P6/8/8/8/8/k7/2K5 w - - 0 1",             // promotions
+        ].iter()) {
+            board.parse_fen(fen).expect("valid fen");
+            assert_suffixes(&mut board, 2, fen);
+        }
+
+        board.set_variant(VariantKind::Crazyhouse);
+        for fen in [
+            "6k1/5ppp/8/8/8/8/8/R5K1[n] w - - 0 1",       // a drop blocks the back rank mate
+            "6k1/5ppp/8/8/8/8/8/R5K1[p] w - - 0 1",       // pawns can't be dropped on the last rank
+            "6k1/5ppp/8/8/8/8/8/R5K1[Q] w - - 0 1",
+            "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR[Nb] w KQkq - 0 2",
+        ] {
+            board.parse_fen(fen).expect("valid fen");
+            assert_suffixes(&mut board, 2, fen);
+        }
+        board.parse_fen("6k1/5ppp/8/8/8/8/8/R5K1[n] w - - 0 1").expect("valid fen");
+        assert_eq!(board.move_to_san(Move::from_uci("a1a8")), "Ra8+");
+        board.parse_fen("6k1/5ppp/8/8/8/8/8/R5K1[p] w - - 0 1").expect("valid fen");
+        assert_eq!(board.move_to_san(Move::from_uci("a1a8")), "Ra8#");
+    }
 }
@@ -0,0 +1,71 @@
+use super::ChessBoard;
+use super::zobrist::{ZOBRIST_EN_PASSANT, ZOBRIST_KEYS};
+
+use crate::board_helper::BoardHelper;
+use alloc::{string::String, vec::Vec};
+
+/// Identifies a position regardless of how it was reached or the move counters, see [ChessBoard::position_key].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PositionKey {
+    /// The first 4 fields of the FEN, e.g. "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq -".
+    /// The en passant square is only written when an en passant capture is legal, as in X-FEN.
+    pub epd: String,
+    /// Zobrist hash of the same information, the en passant file included only when the `epd` has the square.
+    pub hash: u64,
+}
+
+impl ChessBoard {
+    /// Canonical key of the position for opening books and statistics, where transpositions have to be counted as the same position.
+    /// Positions differing only in the move counters or in an en passant square no pawn can capture on get the same key.
+    ///
+    /// The hash is computed like a [Polyglot](http://hgm.nubati.net/book_format.html) key,
+    /// but with the crate's own Zobrist keys, so it can't be used to look up positions in Polyglot books.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::from_fen(STARTPOS_FEN).expect("valid fen");
+    /// board.apply_uci_moves("e2e4 e7e6").expect("legal moves");
+    /// let transposed = ChessBoard::from_fen("rnbqkbnr/pppp1ppp/4p3/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 7 30").expect("valid fen");
+    /// assert_eq!(board.position_key(), transposed.position_key());
+    ///
+    /// // No black pawn can take on e3
+    /// let board = ChessBoard::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").expect("valid fen");
+    /// assert_eq!(board.position_key().epd, "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq -");
+    /// ```
+    #[must_use]
+    pub fn position_key(&self) -> PositionKey {
+        // Pseudo-legal captures first, so the moves are generated only when a pawn is next to the pawn which just moved
+        let en_passant = self.zobrist_with_ep() != self.zobrist_hash && self.get_legal_moves().iter().any(|m| m.is_en_passant());
+
+        let fen = self.to_fen();
+        let mut fields: Vec<&str> = fen.split(' ').take(3).collect();
+        let square = BoardHelper::square_to_string(self.en_passant);
+        fields.push(if en_passant { &square } else { "-" });
+
+        let mut hash = self.zobrist_hash;
+        if en_passant {
+            hash ^= ZOBRIST_KEYS[ZOBRIST_EN_PASSANT + BoardHelper::get_file(self.en_passant) as usize];
+        }
+        PositionKey { epd: fields.join(" "), hash }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_key_en_passant() {
+        // Taking en passant would leave the king in check from the rook
+        let pinned = ChessBoard::from_fen("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1").expect("valid fen");
+        let without = ChessBoard::from_fen("8/8/8/KPp4r/8/8/8/4k3 w - - 0 1").expect("valid fen");
+        assert_eq!(pinned.position_key(), without.position_key());
+        assert_ne!(pinned.zobrist_with_ep(), without.zobrist_with_ep());
+
+        let capturable = ChessBoard::from_fen("8/8/8/1Pp4r/8/8/8/K3k3 w - c6 0 1").expect("valid fen");
+        let key = capturable.position_key();
+        assert!(key.epd.ends_with("w - c6"));
+        assert_eq!(key.hash, capturable.zobrist_with_ep());
+    }
+}
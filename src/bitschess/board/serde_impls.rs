@@ -0,0 +1,83 @@
+//! Serde doesn't implement the traits for arrays longer than 32 elements.
+
+pub mod piece_array {
+    use alloc::vec::Vec;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::piece::Piece;
+
+    pub fn serialize<S: Serializer>(board: &[Piece; 64], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(board)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[Piece; 64], D::Error> {
+        let pieces = Vec::<Piece>::deserialize(deserializer)?;
+        let len = pieces.len();
+        pieces.try_into().map_err(|_| D::Error::invalid_length(len, &"64 squares"))
+    }
+}
+
+#[cfg(feature = "incremental-attacks")]
+pub mod u64_array {
+    use alloc::vec::Vec;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(values: &[u64; 64], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(values)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u64; 64], D::Error> {
+        let values = Vec::<u64>::deserialize(deserializer)?;
+        let len = values.len();
+        values.try_into().map_err(|_| D::Error::invalid_length(len, &"64 squares"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitschess::board::ChessBoard;
+    use crate::bitschess::board::game_state::GameResult;
+    use crate::bitschess::board::pgn::Pgn;
+    use crate::chess_move::Move;
+
+    #[test]
+    fn test_serde_chessboard_roundtrip() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("4k3/8/8/8/8/8/4P3/4K2R w - - 0 1").expect("valid fen");
+        for uci in ["e2e4", "e8d8", "e1f1", "d8e8", "f1e1"] {
+            board.make_move_uci(uci).expect("legal move");
+        }
+
+        let json = serde_json::to_string(&board).expect("serializable");
+        let mut restored: ChessBoard = serde_json::from_str(&json).expect("deserializable");
+        assert_eq!(restored, board);
+        assert_eq!(restored.to_fen(), board.to_fen());
+        assert_eq!(restored.repetition_count(), 2);
+
+        // The move history is kept, so the moves can still be taken back
+        assert!(restored.unmake_move().is_some());
+        assert!(board.unmake_move().is_some());
+        assert_eq!(restored, board);
+    }
+
+    #[test]
+    fn test_serde_chessboard_invalid_board_length() {
+        let board = ChessBoard::new();
+        let mut value = serde_json::to_value(&board).expect("serializable");
+        value["board"].as_array_mut().expect("board is a sequence").pop();
+        assert!(serde_json::from_value::<ChessBoard>(value).is_err());
+    }
+
+    #[test]
+    fn test_serde_pgn_and_small_types() {
+        let pgn = Pgn::parse_string("[Event \"Test\"]\n1. e4 {Best by test} e5 (1... c5) 2. Nf3 $1 *").expect("valid pgn");
+        let json = serde_json::to_string(&pgn).expect("serializable");
+        assert_eq!(serde_json::from_str::<Pgn>(&json).expect("deserializable"), pgn);
+
+        let m = Move::from_uci("e7e8q");
+        assert_eq!(serde_json::from_str::<Move>(&serde_json::to_string(&m).unwrap()).unwrap(), m);
+        assert_eq!(serde_json::to_string(&GameResult::Draw).unwrap(), "\"Draw\"");
+    }
+}
@@ -0,0 +1,113 @@
+use super::ChessBoard;
+
+use crate::bitschess::board::magics::{get_bishop_magic, get_rook_magic};
+use crate::board_helper::BoardHelper;
+use crate::piece::{Piece, PieceColor, PieceType};
+use alloc::{vec, vec::Vec};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SquareAttacker {
+    pub square: i32,
+    pub piece: Piece,
+    /// Attacks only through friendly pieces which are also attacking the square, e.g. a rook behind a queen.
+    pub xray: bool,
+}
+
+/// Who controls a square, see [ChessBoard::control_of].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SquareControl {
+    /// Pieces of the opponent of the piece on the square.
+    pub attackers: Vec<SquareAttacker>,
+    /// Pieces of the same color as the piece on the square.
+    pub defenders: Vec<SquareAttacker>,
+}
+
+impl SquareControl {
+    /// Attackers minus defenders, x-ray attackers included.
+    #[must_use]
+    pub fn balance(&self) -> i32 {
+        self.attackers.len() as i32 - self.defenders.len() as i32
+    }
+}
+
+impl ChessBoard {
+    /// Lists all the pieces attacking and defending the `square`.
+    /// If the square is empty, the side to move is the attacker.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("3rk3/3r4/8/3p4/5N2/8/3Q4/3RK3 w - - 0 1").expect("valid fen");
+    /// let control = board.control_of(Square::D5 as i32);
+    /// // Knight, queen & rook behind the queen
+    /// assert_eq!(control.attackers.len(), 3);
+    /// assert_eq!(control.attackers.iter().filter(|a| a.xray).count(), 1);
+    /// // Rook & rook behind the rook
+    /// assert_eq!(control.defenders.len(), 2);
+    /// assert_eq!(control.balance(), 1);
+    /// ```
+    #[must_use]
+    pub fn control_of(&self, square: i32) -> SquareControl {
+        let piece = self.get_piece(square);
+        let defending = if piece.is_none() { self.get_turn().flipped() } else { piece.get_color() };
+
+        SquareControl {
+            attackers: self.square_attackers(square, defending.flipped()),
+            defenders: self.square_attackers(square, defending),
+        }
+    }
+
+    fn square_attackers(&self, square: i32, color: PieceColor) -> Vec<SquareAttacker> {
+        let diagonal_sliders = self.bitboards[PieceType::Bishop.get_side_index(color)].0 | self.bitboards[PieceType::Queen.get_side_index(color)].0;
+        let orthogonal_sliders = self.bitboards[PieceType::Rook.get_side_index(color)].0 | self.bitboards[PieceType::Queen.get_side_index(color)].0;
+
+        let mut attackers = vec![];
+        let mut direct = self.attackers_to(square, color);
+        let mut found = direct;
+        let mut occupied = self.side_bitboards[0].0 | self.side_bitboards[1].0;
+        let mut xray = false;
+        while direct != 0 {
+            // Remove the attackers from the occupancy to find the pieces behind them
+            occupied &= !direct;
+
+            while direct != 0 {
+                let attacker_square = BoardHelper::pop_lsb(&mut direct);
+                attackers.push(SquareAttacker { square: attacker_square, piece: self.get_piece(attacker_square), xray });
+            }
+
+            let sliders = (get_bishop_magic(square, occupied) & diagonal_sliders) | (get_rook_magic(square, occupied) & orthogonal_sliders);
+            direct = sliders & !found;
+            found |= direct;
+            xray = true;
+        }
+        attackers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_helper::Square;
+
+    #[test]
+    fn test_control_of_empty_square() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 1").expect("valid fen");
+        let control = board.control_of(Square::E2 as i32);
+        assert_eq!(control.defenders, vec![SquareAttacker { square: Square::E1 as i32, piece: Piece::from_char('K'), xray: false }]);
+        assert!(control.attackers.is_empty());
+    }
+
+    #[test]
+    fn test_control_of_bishop_behind_pawn() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("4k3/8/8/2n5/1P6/B7/8/4K3 w - - 0 1").expect("valid fen");
+        let control = board.control_of(Square::C5 as i32);
+        assert_eq!(control.attackers, vec![
+            SquareAttacker { square: Square::B4 as i32, piece: Piece::from_char('P'), xray: false },
+            SquareAttacker { square: Square::A3 as i32, piece: Piece::from_char('B'), xray: true },
+        ]);
+        assert!(control.defenders.is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+use super::ChessBoard;
+
+use crate::chess_move::Move;
+use crate::piece::Piece;
+
+/// What a move can't restore by itself when it's taken back, saved by [ChessBoard::make_move_with].
+/// Small and `Copy`, so a search can keep one for every ply in a fixed size array instead of the board's move history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardState {
+    pub captured: Piece,
+    pub en_passant_square: i32,
+    pub castling: [bool; 4],
+    pub half_move: u8,
+    pub zobrist_hash: u64,
+}
+
+impl Default for BoardState {
+    fn default() -> Self {
+        Self { captured: Piece::new(0), en_passant_square: -1, castling: [false; 4], half_move: 0, zobrist_hash: 0 }
+    }
+}
+
+impl ChessBoard {
+    /// Same as [ChessBoard::make_move], but the move isn't pushed into the move history, the `state` needed for taking it back is written
+    /// into the caller's struct instead. Take it back with [ChessBoard::unmake_move_with], moves made this way don't show up in
+    /// [ChessBoard::get_move_history] and can't be taken back with [ChessBoard::unmake_move].
+    /// The pieces exploded in [atomic chess](crate::prelude::VariantKind::Atomic) and the pieces going to the hand in
+    /// [crazyhouse](crate::prelude::VariantKind::Crazyhouse) aren't saved in the `state`, so captures only work as in standard chess.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    ///
+    /// let mut states = [BoardState::default(); 2];
+    /// let moves = [Move::from_uci("e2e4"), Move::from_uci("d7d5")];
+    /// for (m, state) in moves.iter().zip(states.iter_mut()) {
+    ///     board.make_move_with(*m, state);
+    /// }
+    /// assert!(board.get_move_history().is_empty());
+    ///
+    /// for (m, state) in moves.iter().zip(states.iter()).rev() {
+    ///     board.unmake_move_with(*m, state);
+    /// }
+    /// assert_eq!(board.to_fen(), STARTPOS_FEN);
+    /// ```
+    pub fn make_move_with(&mut self, chess_move: Move, state: &mut BoardState) {
+        if !chess_move.is_drop() && self.get_piece(chess_move.get_from_idx()).is_none() { return; }
+
+        *state = self.make_move_inner(chess_move);
+        self.notify_observers(|observer, board| observer.on_move_made(board, chess_move));
+    }
+
+    /// Takes back `chess_move` made with [ChessBoard::make_move_with], `state` being the one it wrote.
+    /// Moves have to be taken back in the reverse order they were made.
+    pub fn unmake_move_with(&mut self, chess_move: Move, state: &BoardState) {
+        self.unmake_move_inner(chess_move, state);
+        self.notify_observers(|observer, board| observer.on_move_unmade(board, chess_move));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_make_move_with_matches_make_move() {
+        // Castling, en passant, promotions and captures
+        let mut board = ChessBoard::new();
+        board.parse_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1").expect("valid fen");
+        for m in board.get_legal_moves() {
+            let mut with_history = board.clone();
+            with_history.make_move(m, true);
+            let reversible = *with_history.get_move_history().last().expect("move was made");
+
+            let mut with_state = board.clone();
+            let mut state = BoardState::default();
+            with_state.make_move_with(m, &mut state);
+            assert_eq!(state, reversible.state());
+            assert_eq!(with_state.zobrist_hash, with_history.zobrist_hash);
+            assert_eq!(with_state.to_fen(), with_history.to_fen());
+            assert!(with_state.get_move_history().is_empty());
+
+            with_state.unmake_move_with(m, &state);
+            assert_eq!(with_state, board);
+            assert_eq!(with_state.zobrist_hash, board.zobrist_hash);
+        }
+
+        board.parse_fen("8/1P6/8/K1Pp3k/8/8/8/8 w - d6 0 1").expect("valid fen");
+        let before = board.clone();
+        let mut state = BoardState::default();
+        for uci in ["c5d6", "b7b8q"] {
+            let m = board.get_legal_moves().into_iter().find(|m| m.to_uci() == uci).expect("legal move");
+            board.make_move_with(m, &mut state);
+            board.unmake_move_with(m, &state);
+            assert_eq!(board, before);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use super::ChessBoard;
+
+use crate::piece::Piece;
+
+impl ChessBoard {
+    /// The board flipped vertically, the 1st rank becomes the 8th, the pieces keep their colors.
+    /// The castling rights and the en passant square are dropped, as they don't apply anymore.
+    /// The move history isn't carried over.
+    #[must_use]
+    pub fn mirrored_vertical(&self) -> Self {
+        self.transformed(|square| square ^ 56, false, [false; 4], -1)
+    }
+
+    /// The board mirrored horizontally, the a-file becomes the h-file.
+    /// The castling rights are dropped, as the kings and the rooks aren't on their starting squares anymore.
+    /// The move history isn't carried over.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/8/8/3pP3/8/8/8/R3K3 w Q d6 0 1").expect("valid fen");
+    /// assert_eq!(board.mirrored_horizontal().to_fen(), "3k4/8/8/3Pp3/8/8/8/3K3R w - e6 0 1");
+    /// ```
+    #[must_use]
+    pub fn mirrored_horizontal(&self) -> Self {
+        let en_passant = if self.en_passant == -1 { -1 } else { self.en_passant ^ 7 };
+        self.transformed(|square| square ^ 7, false, [false; 4], en_passant)
+    }
+
+    /// The same position from the opponent's point of view: the colors are swapped and the board is flipped vertically,
+    /// along with the side to move, the castling rights and the en passant square.
+    /// The move history isn't carried over.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 1").expect("valid fen");
+    /// assert_eq!(board.color_flipped().to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/8/8/PPPPPPPP/RNBQKBNR w Qk e6 0 1");
+    /// ```
+    #[must_use]
+    pub fn color_flipped(&self) -> Self {
+        let [white_king_side, white_queen_side, black_king_side, black_queen_side] = self.castling_rights;
+        let castling_rights = [black_king_side, black_queen_side, white_king_side, white_queen_side];
+        let en_passant = if self.en_passant == -1 { -1 } else { self.en_passant ^ 56 };
+        self.transformed(|square| square ^ 56, true, castling_rights, en_passant)
+    }
+
+    /// With `flip_colors` the side to move is flipped as well.
+    fn transformed(&self, map_square: impl Fn(i32) -> i32, flip_colors: bool, castling_rights: [bool; 4], en_passant: i32) -> Self {
+        let mut board = Self::new();
+        for (square, piece) in self.board.iter().enumerate() {
+            if piece.is_none() {
+                continue;
+            }
+            let piece: Piece = if flip_colors { piece.color_flipped() } else { *piece };
+            let _ = board.set_piece(map_square(square as i32), piece);
+        }
+
+        board.turn = if flip_colors { self.turn.flipped() } else { self.turn };
+        board.castling_rights = castling_rights;
+        board.en_passant = en_passant;
+        board.half_move = self.half_move;
+        board.full_move = self.full_move;
+        board.zobrist_hash = board.create_zobrist_hash();
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitschess::bitboard::BitBoard;
+    use crate::bench::BENCH_POSITIONS;
+    use crate::testing::{color_flipped_fen, assert_incremental_state};
+
+    #[test]
+    fn test_color_flipped_matches_fen() {
+        let mut board = ChessBoard::new();
+        for fen in BENCH_POSITIONS.iter().chain(["rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"].iter()) {
+            board.parse_fen(fen).expect("valid fen");
+            let flipped = board.color_flipped();
+            assert_eq!(flipped.to_fen(), color_flipped_fen(fen));
+            assert_incremental_state(&flipped);
+            assert_eq!(flipped.get_legal_moves().len(), board.get_legal_moves().len());
+            assert_eq!(flipped.color_flipped().to_fen(), *fen);
+        }
+    }
+
+    #[test]
+    fn test_mirrored_boards() {
+        let mut board = ChessBoard::new();
+        for fen in BENCH_POSITIONS {
+            board.parse_fen(fen).expect("valid fen");
+            for mirrored in [board.mirrored_vertical(), board.mirrored_horizontal()] {
+                assert_incremental_state(&mirrored);
+                assert_eq!(mirrored.castling_rights, [false; 4]);
+            }
+
+            let vertical = board.mirrored_vertical();
+            let horizontal = board.mirrored_horizontal();
+            for (idx, bitboard) in board.bitboards.iter().enumerate() {
+                assert_eq!(vertical.bitboards[idx].0, BitBoard::flip_vertical(bitboard.0));
+                assert_eq!(horizontal.bitboards[idx].0, BitBoard::mirror_horizontal(bitboard.0));
+            }
+            assert_eq!(vertical.mirrored_vertical().board, board.board);
+            assert_eq!(horizontal.mirrored_horizontal().board, board.board);
+
+            // Without castling the mirrored position has as many moves
+            board.castling_rights = [false; 4];
+            assert_eq!(horizontal.get_legal_moves().len(), board.get_legal_moves().len());
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use alloc::{vec, vec::Vec};
+
+/// Decides what happens when a [TranspositionTable::store] maps to an occupied slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplacementScheme {
+    /// The new entry always replaces the old one.
+    Always,
+    /// The old entry is kept if it was searched deeper than the new one, unless it's for the same position.
+    #[default]
+    DepthPreferred,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TranspositionEntry<T> {
+    pub zobrist_hash: u64,
+    pub depth: u8,
+    pub data: T,
+}
+
+/// A fixed size hash table keyed by [ChessBoard::zobrist_with_ep](super::ChessBoard::zobrist_with_ep).
+/// `T` is whatever the search wants to remember about a position, e.g. the score, bound type and best move.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// let board = ChessBoard::new();
+/// let mut table: TranspositionTable<i32> = TranspositionTable::new(64, ReplacementScheme::DepthPreferred);
+/// table.store(board.zobrist_hash, 4, 35);
+/// table.store(board.zobrist_hash ^ 1, 2, -10);
+///
+/// let entry = table.probe(board.zobrist_hash).expect("stored");
+/// assert_eq!((entry.depth, entry.data), (4, 35));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TranspositionTable<T> {
+    entries: Vec<Option<TranspositionEntry<T>>>,
+    scheme: ReplacementScheme,
+}
+
+impl<T: Clone> TranspositionTable<T> {
+    /// Creates a table taking roughly `size_in_kb` kilobytes of memory, always having at least one entry.
+    #[must_use]
+    pub fn new(size_in_kb: usize, scheme: ReplacementScheme) -> Self {
+        let entry_count = (size_in_kb * 1024 / core::mem::size_of::<Option<TranspositionEntry<T>>>()).max(1);
+        Self {
+            entries: vec![None; entry_count],
+            scheme
+        }
+    }
+
+    #[inline(always)]
+    const fn index(&self, hash: u64) -> usize {
+        (hash % self.entries.len() as u64) as usize
+    }
+
+    /// Returns the entry stored for the position, `None` if it was never stored or has been overwritten.
+    #[inline(always)]
+    #[must_use]
+    pub fn probe(&self, hash: u64) -> Option<&TranspositionEntry<T>> {
+        self.entries[self.index(hash)].as_ref().filter(|entry| entry.zobrist_hash == hash)
+    }
+
+    /// Stores the `data` for the position according to the [ReplacementScheme].
+    /// Returns false if the existing entry was kept.
+    #[inline(always)]
+    pub fn store(&mut self, hash: u64, depth: u8, data: T) -> bool {
+        let index = self.index(hash);
+        let replace = match (&self.entries[index], self.scheme) {
+            (None, _) | (Some(_), ReplacementScheme::Always) => { true }
+            (Some(entry), ReplacementScheme::DepthPreferred) => { entry.zobrist_hash == hash || depth >= entry.depth }
+        };
+
+        if replace {
+            self.entries[index] = Some(TranspositionEntry { zobrist_hash: hash, depth, data });
+        }
+        replace
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    /// Resizes the table to roughly `size_in_kb` kilobytes, clearing all of the entries.
+    pub fn resize(&mut self, size_in_kb: usize) {
+        *self = Self::new(size_in_kb, self.scheme);
+    }
+
+    #[inline(always)]
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline(always)]
+    #[must_use]
+    pub const fn get_scheme(&self) -> ReplacementScheme {
+        self.scheme
+    }
+
+    /// How full the table is in permille, the format used by the UCI `hashfull` info.
+    #[must_use]
+    pub fn hashfull(&self) -> u32 {
+        let sample = &self.entries[..self.entries.len().min(1000)];
+        let used = sample.iter().filter(|entry| entry.is_some()).count();
+        (used * 1000 / sample.len()) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_depth_preferred_replacement() {
+        let mut table: TranspositionTable<i32> = TranspositionTable::new(1, ReplacementScheme::DepthPreferred);
+        let colliding = 5 + table.capacity() as u64;
+
+        assert!(table.store(5, 6, 100));
+        assert!(!table.store(colliding, 3, 200));
+        assert_eq!(table.probe(5).map(|e| e.data), Some(100));
+        assert_eq!(table.probe(colliding), None);
+
+        // Same position is always updated
+        assert!(table.store(5, 1, 300));
+        assert_eq!(table.probe(5).map(|e| (e.depth, e.data)), Some((1, 300)));
+
+        assert!(table.store(colliding, 3, 200));
+        assert_eq!(table.probe(5), None);
+    }
+
+    #[test]
+    fn test_always_replacement() {
+        let mut table: TranspositionTable<i32> = TranspositionTable::new(1, ReplacementScheme::Always);
+        let colliding = 5 + table.capacity() as u64;
+
+        assert!(table.store(5, 6, 100));
+        assert!(table.store(colliding, 0, 200));
+        assert_eq!(table.probe(5), None);
+        assert_eq!(table.probe(colliding).map(|e| e.data), Some(200));
+        assert!(table.hashfull() > 0);
+
+        table.clear();
+        assert_eq!(table.probe(colliding), None);
+        assert_eq!(table.hashfull(), 0);
+    }
+}
@@ -0,0 +1,118 @@
+use super::{ChessBoard, MoveError};
+
+use crate::chess_move::{Move, MoveFlag};
+use crate::piece::{Piece, PieceColor, PieceType};
+
+/// Everything a graphical board needs for drawing the position and turning clicks or drags into moves.
+/// Squares are indices from 0 (a1) to 63 (h8).
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// let mut board = ChessBoard::new();
+/// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+/// let e2 = Square::E2 as i32;
+/// assert_eq!(board.legal_targets(e2), (1u64 << Square::E3 as i32) | (1u64 << Square::E4 as i32));
+///
+/// let m = board.apply_move(e2, Square::E4 as i32, None).expect("legal move");
+/// assert_eq!(board.last_move(), Some(m));
+/// assert_eq!(board.piece_at(Square::E4 as i32).to_char(), 'P');
+/// assert_eq!(board.checked_king(), None);
+/// ```
+pub trait BoardUiAdapter {
+    /// The piece on the `square`, [Piece::is_none] for an empty square.
+    fn piece_at(&self, square: i32) -> Piece;
+
+    fn side_to_move(&self) -> PieceColor;
+
+    /// Bitboard of the squares the piece on the `square` can legally move to, empty if it isn't the piece's turn.
+    fn legal_targets(&self, square: i32) -> u64;
+
+    /// If moving from `from` to `to` promotes a pawn, i.e. the GUI should ask what to promote to.
+    fn is_promotion(&self, from: i32, to: i32) -> bool;
+
+    /// Makes the move from `from` to `to`, the `promotion` is only needed when [BoardUiAdapter::is_promotion].
+    ///
+    /// # Errors
+    /// [MoveError::MissingPromotion] if a promotion is missing the piece, otherwise if the move isn't legal.
+    fn apply_move(&mut self, from: i32, to: i32, promotion: Option<PieceType>) -> Result<Move, MoveError>;
+
+    /// The move to highlight as the previous one.
+    fn last_move(&self) -> Option<Move>;
+
+    /// Square of the king of the side to move if it's in check.
+    fn checked_king(&self) -> Option<i32>;
+}
+
+impl BoardUiAdapter for ChessBoard {
+    #[inline(always)]
+    fn piece_at(&self, square: i32) -> Piece {
+        self.get_piece(square)
+    }
+
+    #[inline(always)]
+    fn side_to_move(&self) -> PieceColor {
+        self.get_turn()
+    }
+
+    fn legal_targets(&self, square: i32) -> u64 {
+        self.get_legal_moves_for_square(square).iter().fold(0, |targets, m| targets | (1u64 << m.get_to_idx()))
+    }
+
+    fn is_promotion(&self, from: i32, to: i32) -> bool {
+        self.get_legal_moves_for_square(from).iter().any(|m| m.get_to_idx() == to && m.get_flag() == MoveFlag::PromoteQueen)
+    }
+
+    fn apply_move(&mut self, from: i32, to: i32, promotion: Option<PieceType>) -> Result<Move, MoveError> {
+        self.check_side_to_move(from)?;
+        let flag = match promotion {
+            Some(piece_type) => { Some(MoveFlag::promotion(piece_type).ok_or(MoveError::IllegalMove)?) }
+            None if self.is_promotion(from, to) => { return Err(MoveError::MissingPromotion); }
+            None => { None }
+        };
+
+        let m = self.get_legal_moves_for_square(from).into_iter()
+            .filter(|m| m.get_to_idx() == to)
+            .find(|m| flag.is_none_or(|flag| m.get_flag() == flag))
+            .ok_or(MoveError::IllegalMove)?;
+        self.make_move(m, false);
+        Ok(m)
+    }
+
+    #[inline(always)]
+    fn last_move(&self) -> Option<Move> {
+        ChessBoard::last_move(self)
+    }
+
+    fn checked_king(&self) -> Option<i32> {
+        self.is_king_in_check(self.turn).then(|| self.get_king_square(self.turn))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_helper::Square;
+
+    #[test]
+    fn test_ui_adapter_promotion_and_check() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("3k4/1P6/8/8/8/8/8/4K3 w - - 0 1").expect("valid fen");
+        let (b7, b8) = (Square::B7 as i32, Square::B8 as i32);
+
+        assert!(board.is_promotion(b7, b8));
+        assert!(!board.is_promotion(Square::E1 as i32, Square::E2 as i32));
+        assert_eq!(board.apply_move(b7, b8, None), Err(MoveError::MissingPromotion));
+        assert_eq!(board.apply_move(b7, b8, Some(PieceType::King)), Err(MoveError::IllegalMove));
+        assert_eq!(board.apply_move(Square::D8 as i32, Square::D7 as i32, None), Err(MoveError::WrongSideToMove));
+
+        let m = board.apply_move(b7, b8, Some(PieceType::Rook)).expect("legal move");
+        assert_eq!(m.get_flag(), MoveFlag::PromoteRook);
+        assert_eq!(board.last_move(), Some(m));
+        assert_eq!(board.checked_king(), Some(Square::D8 as i32));
+        assert_eq!(board.side_to_move(), PieceColor::Black);
+        // Only the king can move and not along the 8th rank
+        assert_eq!(board.legal_targets(Square::D8 as i32).count_ones(), 3);
+        assert_eq!(board.legal_targets(Square::E1 as i32), 0);
+    }
+}
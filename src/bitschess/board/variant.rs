@@ -0,0 +1,386 @@
+use super::ChessBoard;
+use super::fen::CASTLING_SQUARES;
+use super::game_state::GameState;
+use super::move_generation::MoveGenerator;
+use super::zobrist;
+
+use crate::bitschess::bitboard::KING_ATTACKS;
+use crate::board_helper::BoardHelper;
+use crate::chess_move::{MoveFlag, MoveContainer};
+use crate::piece::{Piece, PieceColor, PieceType};
+
+/// Rules the board is played by, see [ChessBoard::set_variant].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VariantKind {
+    #[default]
+    Standard,
+    Antichess,
+    Atomic,
+    Crazyhouse,
+}
+
+impl VariantKind {
+    pub const ALL: [Self; 4] = [Self::Standard, Self::Antichess, Self::Atomic, Self::Crazyhouse];
+
+    /// The rules of the variant.
+    #[must_use]
+    pub const fn rules(self) -> &'static dyn Variant {
+        match self {
+            Self::Standard => { &Standard }
+            Self::Antichess => { &Antichess }
+            Self::Atomic => { &Atomic }
+            Self::Crazyhouse => { &Crazyhouse }
+        }
+    }
+}
+
+impl core::fmt::Display for VariantKind {
+    /// Formats the variant as in the UCI_Variant option, e.g. "atomic".
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(self.rules().name())
+    }
+}
+
+/// Where the rules of a chess variant differ from standard chess.
+///
+/// Only the board's own move generation and game state follow the variant, see [ChessBoard::set_variant].
+/// The search, the evaluation and the notation (SAN, PGN) assume standard chess.
+pub trait Variant: Send + Sync {
+    /// Name of the variant as in the UCI_Variant option.
+    fn name(&self) -> &'static str;
+
+    /// If the king has to be kept out of check. If not, the king is captured like any other piece,
+    /// positions without kings are allowed and castling isn't.
+    fn royal_king(&self) -> bool {
+        true
+    }
+
+    /// If the king of `color` is in check, moves which leave the own king in check aren't legal.
+    fn in_check(&self, board: &ChessBoard, color: PieceColor) -> bool {
+        board.is_king_in_check(color)
+    }
+
+    /// If capturing is compulsory, when any capture is available only captures are legal.
+    fn forced_captures(&self) -> bool {
+        false
+    }
+
+    /// Squares emptied after a capture landing on `to`, in the position after the capture.
+    fn explosion(&self, _board: &ChessBoard, _to: i32) -> u64 {
+        0
+    }
+
+    /// If captured pieces go to the hand of the side which captured them, to be dropped back on the board as its own.
+    /// FENs of the variant have the pieces in hand after the piece placement, e.g. "[Qp]", and promoted pieces marked with '~'.
+    fn drops(&self) -> bool {
+        false
+    }
+
+    /// All legal moves for the side to move.
+    fn legal_moves(&self, board: &ChessBoard) -> MoveContainer;
+
+    /// Whether the game has ended and how.
+    fn game_state(&self, board: &ChessBoard) -> GameState;
+}
+
+/// Standard chess, the default rules of a board.
+pub struct Standard;
+
+impl Variant for Standard {
+    fn name(&self) -> &'static str {
+        "chess"
+    }
+
+    fn legal_moves(&self, board: &ChessBoard) -> MoveContainer {
+        MoveGenerator::get_legal_moves(board, true)
+    }
+
+    fn game_state(&self, board: &ChessBoard) -> GameState {
+        board.standard_game_state()
+    }
+}
+
+/// Losing chess: captures are compulsory, there's no check and the king is an ordinary piece.
+/// The side which has lost all of its pieces or can't move wins.
+/// Pawns can't promote to a king.
+pub struct Antichess;
+
+impl Variant for Antichess {
+    fn name(&self) -> &'static str {
+        "antichess"
+    }
+
+    fn royal_king(&self) -> bool {
+        false
+    }
+
+    fn in_check(&self, _board: &ChessBoard, _color: PieceColor) -> bool {
+        false
+    }
+
+    fn forced_captures(&self) -> bool {
+        true
+    }
+
+    fn legal_moves(&self, board: &ChessBoard) -> MoveContainer {
+        let mut moves = MoveGenerator::get_pseudo_legal_moves(board);
+        moves.retain(|m| m.get_flag() != MoveFlag::Castle);
+        if self.forced_captures() && moves.iter().any(|m| board.is_capture(*m)) {
+            moves.retain(|m| board.is_capture(m));
+        }
+        moves
+    }
+
+    fn game_state(&self, board: &ChessBoard) -> GameState {
+        if board.side_bitboards[board.turn as usize].0 == 0 || self.legal_moves(board).is_empty() {
+            GameState::VariantWin(board.turn)
+        }
+        else {
+            board.draw_state()
+        }
+    }
+}
+
+/// Atomic chess: a capture explodes the capturing piece and every piece next to the captured one, except for pawns.
+/// Exploding the enemy king wins, so kings can't capture and the own king mustn't be exploded.
+/// Kings next to each other don't give check.
+pub struct Atomic;
+
+impl Atomic {
+    /// If `color` hasn't lost its king and isn't in check after a move.
+    fn king_safe(&self, board: &ChessBoard, color: PieceColor) -> bool {
+        board.bitboards[PieceType::King.get_side_index(color)].0 != 0 && !self.in_check(board, color)
+    }
+}
+
+impl Variant for Atomic {
+    fn name(&self) -> &'static str {
+        "atomic"
+    }
+
+    fn in_check(&self, board: &ChessBoard, color: PieceColor) -> bool {
+        let king = board.bitboards[PieceType::King.get_side_index(color)].0;
+        let enemy_king = board.bitboards[PieceType::King.get_side_index(color.flipped())].0;
+        if king == 0 || enemy_king == 0 || KING_ATTACKS[BoardHelper::bitscan_forward(king) as usize] & enemy_king != 0 {
+            return false;
+        }
+        board.is_king_in_check(color)
+    }
+
+    fn explosion(&self, board: &ChessBoard, to: i32) -> u64 {
+        let pawns = board.bitboards[PieceType::Pawn.get_side_index(PieceColor::White)].0
+            | board.bitboards[PieceType::Pawn.get_side_index(PieceColor::Black)].0;
+        (KING_ATTACKS[to as usize] & !pawns) | (1u64 << to)
+    }
+
+    fn legal_moves(&self, board: &ChessBoard) -> MoveContainer {
+        let mut moves = MoveGenerator::get_pseudo_legal_moves(board);
+        let mut after = board.clone();
+        let color = board.turn;
+        moves.retain(|m| {
+            if m.get_flag() == MoveFlag::Castle {
+                return MoveGenerator::is_legal(board, m);
+            }
+            if board.is_capture(m) && board.get_piece(m.get_from_idx()).get_piece_type() == PieceType::King {
+                return false;
+            }
+
+            after.make_move(m, true);
+            let legal = self.king_safe(&after, color);
+            let _ = after.unmake_move();
+            legal
+        });
+        moves
+    }
+
+    fn game_state(&self, board: &ChessBoard) -> GameState {
+        if board.bitboards[PieceType::King.get_side_index(board.turn)].0 == 0 {
+            return GameState::VariantWin(board.turn.flipped());
+        }
+        if self.legal_moves(board).is_empty() {
+            return if self.in_check(board, board.turn) { GameState::Checkmate(board.turn.flipped()) } else { GameState::Stalemate };
+        }
+        board.draw_state()
+    }
+}
+
+/// Crazyhouse: captured pieces go to the hand of the side which captured them and can be dropped back on the board instead of moving.
+/// Promoted pieces go back to the hand as pawns.
+pub struct Crazyhouse;
+
+impl Variant for Crazyhouse {
+    fn name(&self) -> &'static str {
+        "crazyhouse"
+    }
+
+    fn drops(&self) -> bool {
+        true
+    }
+
+    fn legal_moves(&self, board: &ChessBoard) -> MoveContainer {
+        let mut moves = MoveGenerator::get_legal_moves(board, true);
+        for m in board.drop_moves().iter() {
+            moves.push(*m);
+        }
+        moves
+    }
+
+    fn game_state(&self, board: &ChessBoard) -> GameState {
+        // Pieces in hand can always mate, so there's no insufficient material
+        if self.legal_moves(board).is_empty() {
+            return if board.is_king_in_check(board.turn) { GameState::Checkmate(board.turn.flipped()) } else { GameState::Stalemate };
+        }
+        board.draw_state()
+    }
+}
+
+impl ChessBoard {
+    /// Changes the rules the board is played by, the position and the move history are kept.
+    /// Set the variant before parsing a FEN which is only valid in the variant, e.g. an antichess position without kings.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.set_variant(VariantKind::Antichess);
+    /// board.parse_fen("8/8/8/8/8/2p5/1P6/8 w - - 0 1").expect("valid antichess fen");
+    /// // Capturing is compulsory
+    /// assert_eq!(board.get_legal_moves().iter().map(|m| m.to_uci()).collect::<Vec<_>>(), ["b2c3"]);
+    /// board.make_move_uci("b2c3").expect("legal move");
+    /// assert_eq!(board.get_game_state(), GameState::VariantWin(PieceColor::Black));
+    /// ```
+    #[inline(always)]
+    pub fn set_variant(&mut self, variant: VariantKind) {
+        self.variant = variant;
+        self.clear_legal_moves_cache();
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn get_variant(&self) -> VariantKind {
+        self.variant
+    }
+
+    /// The rules of the [variant](ChessBoard::get_variant) the board is played by.
+    #[must_use]
+    #[inline(always)]
+    pub const fn rules(&self) -> &'static dyn Variant {
+        self.variant.rules()
+    }
+
+    /// Empties the squares of the variant's [explosion](Variant::explosion) after a capture landing on `to`.
+    /// The pieces are saved for [ChessBoard::restore_explosion], keyed by the index the move gets in the move history.
+    pub(super) fn explode(&mut self, to: i32) {
+        let mut squares = self.rules().explosion(self, to) & (self.side_bitboards[0].0 | self.side_bitboards[1].0);
+        if squares == 0 {
+            return;
+        }
+
+        let ply = self.move_history.len();
+        while squares != 0 {
+            let square = BoardHelper::pop_lsb(&mut squares);
+            let piece = self.put_piece(square, Piece::new(0));
+            self.exploded.push((ply, square, piece));
+        }
+
+        // Exploded kings and rooks can't castle anymore
+        let mask = self.rules().explosion(self, to) | (1u64 << to);
+        for (idx, (_, king_square, rook_square)) in CASTLING_SQUARES.into_iter().enumerate() {
+            let exploded = mask & ((1u64 << king_square as u32) | (1u64 << rook_square as u32)) != 0;
+            if exploded && self.castling_rights[idx] {
+                self.castling_rights[idx] = false;
+                self.zobrist_hash ^= zobrist::ZOBRIST_KEYS[zobrist::ZOBRIST_CASTLING + idx];
+            }
+        }
+    }
+
+    /// Puts back the pieces exploded by the move at `ply` in the move history, before the move itself is taken back.
+    pub(super) fn restore_explosion(&mut self, ply: usize) {
+        while let Some(&(exploded_ply, square, piece)) = self.exploded.last() {
+            if exploded_ply != ply {
+                break;
+            }
+            self.exploded.pop();
+            let _ = self.put_piece(square, piece);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitschess::board::fen::STARTPOS_FEN;
+    use crate::chess_move::Move;
+
+    fn _legal_moves(board: &ChessBoard) -> alloc::vec::Vec<alloc::string::String> {
+        let mut moves: alloc::vec::Vec<_> = board.get_legal_moves().iter().map(|m| m.to_uci()).collect();
+        moves.sort();
+        moves
+    }
+
+    #[test]
+    fn test_variant_standard_unchanged() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        let standard = _legal_moves(&board);
+        board.set_variant(VariantKind::Standard);
+        assert_eq!(_legal_moves(&board), standard);
+        assert_eq!(standard.len(), 20);
+        assert_eq!(board.get_variant().to_string(), "chess");
+    }
+
+    #[test]
+    fn test_variant_antichess() {
+        let mut board = ChessBoard::new();
+        board.set_variant(VariantKind::Antichess);
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        assert_eq!(board.get_legal_moves().len(), 20);
+
+        // The king can be captured and walk into check, no castling
+        board.parse_fen("8/8/8/8/8/8/3q4/R3K3 w Q - 0 1").expect("valid fen");
+        assert_eq!(_legal_moves(&board), ["e1d2"]);
+        board.parse_fen("8/8/8/8/8/8/8/R3K2q w Q - 0 1").expect("valid fen");
+        assert!(board.get_legal_moves().contains(Move::from_uci("e1f1")));
+        assert!(!board.is_legal(Move::from_uci("e1c1")));
+
+        // No pieces left wins
+        board.parse_fen("8/8/8/8/8/8/1k6/K7 b - - 0 1").expect("valid fen");
+        board.make_move_uci("b2a1").expect("legal move");
+        assert_eq!(board.get_game_state(), GameState::VariantWin(PieceColor::White));
+        assert_eq!(board.get_game_state().result(), Some(crate::bitschess::board::game_state::GameResult::WhiteWins));
+    }
+
+    #[test]
+    fn test_variant_atomic() {
+        let mut board = ChessBoard::new();
+        board.set_variant(VariantKind::Atomic);
+        board.parse_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 1").expect("valid fen");
+        let before = board.clone();
+
+        // Knight takes e5, the knight and every piece but the pawns next to e5 explode
+        board.make_move_uci("f3e5").expect("legal move");
+        assert_eq!(board.to_fen(), "rnbqkbnr/pppp1ppp/8/8/4P3/8/PPPP1PPP/RNBQKB1R b KQkq - 0 1");
+        let _ = board.unmake_move();
+        assert_eq!(board, before);
+        assert_eq!(board.zobrist_hash, before.zobrist_hash);
+
+        // Kings can't capture, and the own king can't be exploded
+        board.parse_fen("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1").expect("valid fen");
+        assert!(!board.is_legal(Move::from_uci("e1d2")));
+        board.parse_fen("4k3/8/8/8/8/4B3/3n4/3K4 w - - 0 1").expect("valid fen");
+        assert!(!board.get_legal_moves().iter().any(|m| board.is_capture(*m)));
+
+        // Exploding the king wins, even out of check
+        board.parse_fen("4k3/4p3/8/8/8/8/4R3/r5K1 w - - 0 1").expect("valid fen");
+        assert!(board.is_king_in_check(PieceColor::White));
+        board.make_move_uci("e2e7").expect("legal move");
+        assert_eq!(board.get_game_state(), GameState::VariantWin(PieceColor::White));
+
+        // Kings next to each other don't give check
+        board.parse_fen("8/8/8/3kK3/8/8/8/4r3 w - - 0 1").expect("valid fen");
+        assert!(!board.rules().in_check(&board, PieceColor::White));
+        assert!(board.get_legal_moves().contains(Move::from_uci("e5e4")));
+        assert_eq!(board.get_game_state(), GameState::Ongoing);
+    }
+}
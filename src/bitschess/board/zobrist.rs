@@ -1,23 +1,33 @@
 // https://www.chessprogramming.org/Zobrist_Hashing
 
-use super::{Piece, PieceColor, ChessBoard, BoardHelper};
-
-use lazy_static::lazy_static;
+use super::{Piece, PieceColor, PieceType, ChessBoard, BoardHelper};
+use crate::bitschess::bitboard::PAWN_ATTACKS;
 
 const ZOBRIST_SEED: u64 = 212832809410876;
 pub const ZOBRIST_TURN: usize = 64*12;
 pub const ZOBRIST_CASTLING: usize = ZOBRIST_TURN + 1; // + 4
+pub const ZOBRIST_EN_PASSANT: usize = ZOBRIST_CASTLING + 4; // + 8
+/// Pieces in hand for crazyhouse, one key for every [color][piece type][count] up to [POCKET_HASH_LIMIT].
+pub const ZOBRIST_POCKET: usize = ZOBRIST_EN_PASSANT + 8; // + 2*5*16
+pub const POCKET_HASH_LIMIT: usize = 16;
 
-lazy_static! {
-    pub static ref ZOBRIST_KEYS: [u64; 12*64 + 1 + 4 + 8] = {
-        fastrand::seed(ZOBRIST_SEED);
-        [0; 12*64 + 1 + 4 + 8].map(|_| fastrand::u64(..))
-    };
-}
+/// Generated at compile time with [WyRand](https://github.com/wangyi-fudan/wyhash), the same generator `fastrand` uses.
+pub const ZOBRIST_KEYS: [u64; 12*64 + 1 + 4 + 8 + 2*5*POCKET_HASH_LIMIT] = {
+    let mut keys = [0u64; 12*64 + 1 + 4 + 8 + 2*5*POCKET_HASH_LIMIT];
+    let mut state = ZOBRIST_SEED;
+    let mut i = 0;
+    while i < keys.len() {
+        state = state.wrapping_add(0x2d35_8dcc_aa6c_78a5);
+        let t = (state as u128) * ((state ^ 0x8bb8_4b93_962e_acc9) as u128);
+        keys[i] = (t as u64) ^ (t >> 64) as u64;
+        i += 1;
+    }
+    keys
+};
 
 impl Piece {
     #[inline(always)]
-    pub fn get_hash(self, square: i32) -> u64 {
+    pub const fn get_hash(self, square: i32) -> u64 {
         assert!(!self.is_none());
         ZOBRIST_KEYS[(square as usize) * 12 + self.get_piece_index()]
     }
@@ -46,19 +56,93 @@ impl ChessBoard {
         if self.get_turn() == PieceColor::Black {
             hash ^= ZOBRIST_KEYS[ZOBRIST_TURN];
         }
-        
-        hash
+
+        hash ^ self.pockets.hash()
+    }
+
+    /// [ChessBoard::zobrist_hash] with the en passant file hashed in, when the side to move has a pawn which could capture en passant.
+    /// Positions which differ only by the right to capture en passant get different keys, as a transposition table needs.
+    /// Repetitions are still detected with the [ChessBoard::zobrist_hash], which ignores the en passant square.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1").expect("valid fen");
+    /// board.make_move_uci("e2e4").expect("legal move");
+    /// let with_ep = board.zobrist_with_ep();
+    /// assert_ne!(with_ep, board.zobrist_hash);
+    ///
+    /// // The same position without the en passant right
+    /// let mut other = ChessBoard::new();
+    /// other.parse_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").expect("valid fen");
+    /// assert_eq!(other.zobrist_hash, board.zobrist_hash);
+    /// assert_ne!(other.zobrist_with_ep(), with_ep);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn zobrist_with_ep(&self) -> u64 {
+        if self.en_passant == -1 {
+            return self.zobrist_hash;
+        }
+
+        // A pawn of the side to move attacks the square, if a pawn of the other color on the square would attack it.
+        let pawns = self.bitboards[PieceType::Pawn.get_side_index(self.turn)].0;
+        if PAWN_ATTACKS[self.turn.flipped() as usize][self.en_passant as usize] & pawns == 0 {
+            return self.zobrist_hash;
+        }
+        self.zobrist_hash ^ ZOBRIST_KEYS[ZOBRIST_EN_PASSANT + BoardHelper::get_file(self.en_passant) as usize]
+    }
+
+    /// Digest of the whole game state: the position, en passant square, move counters and the number of moves played.
+    /// Cheap to compare between networked clients, a mismatch means that the boards are out of sync.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut server = ChessBoard::new();
+    /// server.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// let mut client = server.clone();
+    ///
+    /// server.make_move_uci("e2e4").expect("legal move");
+    /// assert!(!client.verify_checksum(server.checksum()));
+    /// client.make_move_uci("e2e4").expect("legal move");
+    /// assert!(client.verify_checksum(server.checksum()));
+    /// ```
+    #[must_use]
+    pub fn checksum(&self) -> u64 {
+        let mut hash = self.zobrist_hash;
+        if self.en_passant != -1 {
+            hash ^= ZOBRIST_KEYS[ZOBRIST_EN_PASSANT + BoardHelper::get_file(self.en_passant) as usize];
+        }
+
+        let counters = u64::from(self.half_move) | (u64::from(self.full_move) << 8) | ((self.move_history.len() as u64) << 24);
+        mix(hash ^ mix(counters))
     }
 
+    /// Whether the `checksum` received from another board matches this board's [ChessBoard::checksum].
+    #[must_use]
+    #[inline(always)]
+    pub fn verify_checksum(&self, checksum: u64) -> bool {
+        self.checksum() == checksum
+    }
+}
 
+/// Finalizer of [SplitMix64](https://prng.di.unimi.it/splitmix64.c), spreads every input bit to the whole output.
+const fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use super::super::fen::STARTPOS_FEN;
+    use crate::board_helper::Square;
 
     #[test]
+    #[allow(clippy::needless_range_loop)]
     fn test_verify_zobrist_keys() {
         // Checks that there isn't any same keys in the array
         for x in 0..ZOBRIST_KEYS.len() {
@@ -69,6 +153,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_zobrist_keys_match_fastrand() {
+        // The keys used to be generated at runtime with fastrand, hashes must stay the same
+        let mut rng = fastrand::Rng::with_seed(ZOBRIST_SEED);
+        let keys = ZOBRIST_KEYS.map(|_| rng.u64(..));
+        assert_eq!(ZOBRIST_KEYS, keys);
+    }
+
+    #[test]
+    fn test_zobrist_with_ep() {
+        let mut board = ChessBoard::new();
+        // No black pawn next to the double pushed pawn, the en passant square doesn't matter
+        board.parse_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").expect("valid fen");
+        board.make_move_uci("e2e4").expect("legal move");
+        assert_eq!(board.en_passant, Square::E3 as i32);
+        assert_eq!(board.zobrist_with_ep(), board.zobrist_hash);
+
+        board.parse_fen("4k3/8/8/8/5p2/8/4P3/4K3 w - - 0 1").expect("valid fen");
+        board.make_move_uci("e2e4").expect("legal move");
+        assert_ne!(board.zobrist_with_ep(), board.zobrist_hash);
+        // Gone after the next move
+        board.make_move_uci("e8d8").expect("legal move");
+        assert_eq!(board.zobrist_with_ep(), board.zobrist_hash);
+    }
+
+    #[test]
+    fn test_checksum() {
+        let mut a = ChessBoard::new();
+        a.parse_fen(STARTPOS_FEN).expect("valid fen");
+        let mut b = a.clone();
+
+        // Same position & counters through a different move order
+        for m in ["g1f3", "g8f6", "b1c3"] { a.make_move_uci(m).expect("valid"); }
+        for m in ["b1c3", "g8f6", "g1f3"] { b.make_move_uci(m).expect("valid"); }
+        assert_eq!(a.checksum(), b.checksum());
+
+        // Only the half move clock differs
+        let mut c = ChessBoard::new();
+        let mut c2 = ChessBoard::new();
+        c.parse_fen(&a.to_fen()).expect("valid fen");
+        c2.parse_fen(&a.to_fen().replace(" 3 2", " 0 2")).expect("valid fen");
+        assert_eq!(c.zobrist_hash, c2.zobrist_hash);
+        assert_ne!(c.checksum(), c2.checksum());
+
+        // Only the amount of moves played differs
+        assert_eq!(c.zobrist_hash, a.zobrist_hash);
+        assert_ne!(c.checksum(), a.checksum());
+
+        // Only the en passant square differs
+        let mut d = ChessBoard::new();
+        let mut e = ChessBoard::new();
+        d.parse_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").expect("valid fen");
+        e.parse_fen("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1").expect("valid fen");
+        assert!(!d.verify_checksum(e.checksum()));
+    }
+
     #[test]
     fn test_make_move_zobrist_updation_basic() {
         let mut board = ChessBoard::new();
@@ -102,4 +242,15 @@ mod tests {
         board.unmake_move().expect("valid");
         assert_eq!(board.zobrist_hash, board.create_zobrist_hash());
     }
+
+    #[test]
+    fn test_make_undo_move_zobrist_en_passant() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("4k3/8/8/8/1p6/8/P7/4K3 w - - 0 1").expect("valid fen");
+        board.make_move_uci("a2a4").expect("valid");
+        board.make_move_uci("b4a3").expect("valid");
+        assert_eq!(board.zobrist_hash, board.create_zobrist_hash());
+        board.unmake_move().expect("valid");
+        assert_eq!(board.zobrist_hash, board.create_zobrist_hash());
+    }
 }
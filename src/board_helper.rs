@@ -1,10 +1,30 @@
 use crate::bitschess::board::CHESSBOARD_WIDTH;
+use const_for::const_for;
+use alloc::{format, string::String};
 
 /// # Has functions to help with board indexes and bit manipulation.
 pub struct BoardHelper;
 
+/// A square of the board, `A1 as i32` is 0 and `H8 as i32` is 63 as with the raw square indices.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// let e4: Square = "e4".parse().expect("valid square");
+/// assert_eq!(e4, Square::E4);
+/// assert_eq!(e4.to_string(), "e4");
+/// assert_eq!(Square::try_from(28), Ok(Square::E4));
+/// assert_eq!(Square::try_from(64), Err(SquareError::OutOfRange(64)));
+/// assert_eq!(e4 + 8, Some(Square::E5));
+/// assert_eq!(Square::H8 + 1, None);
+/// assert_eq!(Square::H4 + 1, Some(Square::A5)); // the raw index wraps around to the a-file
+/// assert_eq!(Square::H4.offset(1, 0), None);    // offset doesn't
+/// assert_eq!(Square::iter().filter(|square| square.rank() == 0).count(), 8);
+/// ```
 #[repr(u32)]
 #[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Square {
     A1, B1, C1, D1, E1, F1, G1, H1,
     A2, B2, C2, D2, E2, F2, G2, H2,
@@ -15,61 +35,269 @@ pub enum Square {
     A7, B7, C7, D7, E7, F7, G7, H7,
     A8, B8, C8, D8, E8, F8, G8, H8,
 
-    INVALID = std::u32::MAX
+    INVALID = u32::MAX
 }
 
+/// Why a value couldn't be converted into a [Square].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SquareError {
+    /// Square indices are from 0 to 63, the value is the index.
+    OutOfRange(i32),
+}
+
+impl core::fmt::Display for SquareError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::OutOfRange(square) => { write!(f, "square index {square} is not in 0..64") }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SquareError {}
+
+/// Why a text couldn't be parsed into a [Square], see [BoardHelper::text_to_square].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SquareParseError {
+    /// Squares are 2 characters long, the value is the length of the input.
+    InvalidLength(usize),
+    /// Not a file from 'a' to 'h'.
+    InvalidFile(char),
+    /// Not a rank from '1' to '8'.
+    InvalidRank(char),
+}
+
+impl core::fmt::Display for SquareParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLength(len) => { write!(f, "expected 2 characters, got {len}") }
+            Self::InvalidFile(c) => { write!(f, "invalid file '{c}'") }
+            Self::InvalidRank(c) => { write!(f, "invalid rank '{c}'") }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SquareParseError {}
+
 impl Square {
+    /// Every square from a1 to h8.
+    pub const ALL: [Self; 64] = {
+        let mut all = [Self::A1; 64];
+        const_for!(square in 0..64 => {
+            all[square] = Self::from_u32(square as u32);
+        });
+        all
+    };
+
     /// Constructs a [Square] from [u32].
     #[inline(always)]
     pub const fn from_u32(f: u32) -> Self {
         unsafe {
-            std::mem::transmute(f & 0b111111)
+            core::mem::transmute(f & 0b111111)
+        }
+    }
+
+    /// `None` if the `square` is not in the range of (0..=63).
+    #[must_use]
+    #[inline(always)]
+    pub const fn from_index(square: i32) -> Option<Self> {
+        if square < 0 || square > 63 {
+            return None;
+        }
+        Some(Self::from_u32(square as u32))
+    }
+
+    /// `None` if the `file` or the `rank` is not in the range of (0..=7).
+    #[must_use]
+    #[inline(always)]
+    pub const fn from_file_rank(file: i32, rank: i32) -> Option<Self> {
+        Self::from_index(BoardHelper::file_rank_to_idx(file, rank))
+    }
+
+    /// Iterates every square from a1 to h8.
+    #[inline(always)]
+    pub fn iter() -> impl DoubleEndedIterator<Item = Self> + ExactSizeIterator {
+        Self::ALL.into_iter()
+    }
+
+    /// The raw square index, -1 for [Square::INVALID].
+    #[must_use]
+    #[inline(always)]
+    pub const fn index(self) -> i32 {
+        self as u32 as i32
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn file(self) -> i32 {
+        BoardHelper::get_file(self.index())
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn rank(self) -> i32 {
+        BoardHelper::get_rank(self.index())
+    }
+
+    /// The square `files` to the right and `ranks` up, `None` if it's off the board.
+    /// Unlike adding to the index, this doesn't wrap around the edge files.
+    #[must_use]
+    #[inline(always)]
+    pub const fn offset(self, files: i32, ranks: i32) -> Option<Self> {
+        if self.index() < 0 {
+            return None;
+        }
+        Self::from_file_rank(self.file() + files, self.rank() + ranks)
+    }
+}
+
+impl TryFrom<i32> for Square {
+    type Error = SquareError;
+
+    fn try_from(square: i32) -> Result<Self, Self::Error> {
+        Self::from_index(square).ok_or(SquareError::OutOfRange(square))
+    }
+}
+
+impl From<Square> for i32 {
+    #[inline(always)]
+    fn from(square: Square) -> Self {
+        square.index()
+    }
+}
+
+impl core::str::FromStr for Square {
+    type Err = SquareParseError;
+
+    /// Same as [BoardHelper::text_to_square].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BoardHelper::text_to_square(s)
+    }
+}
+
+impl core::fmt::Display for Square {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.index() < 0 {
+            return f.pad("-");
+        }
+        let (file, rank) = BoardHelper::square_to_chars(self.index());
+        f.pad(format!("{file}{rank}").as_str())
+    }
+}
+
+/// Raw index arithmetic, `None` only if the index is off the board.
+/// Crosses the edge files like the indices do, `H4 + 1` is `A5`; use [Square::offset] for moving along a rank.
+impl core::ops::Add<i32> for Square {
+    type Output = Option<Self>;
+
+    #[inline(always)]
+    fn add(self, offset: i32) -> Self::Output {
+        if self.index() < 0 {
+            return None;
         }
+        Self::from_index(self.index() + offset)
+    }
+}
+
+/// Raw index arithmetic, `None` only if the index is off the board.
+/// Crosses the edge files like the indices do, `A5 - 1` is `H4`; use [Square::offset] for moving along a rank.
+impl core::ops::Sub<i32> for Square {
+    type Output = Option<Self>;
+
+    #[inline(always)]
+    fn sub(self, offset: i32) -> Self::Output {
+        if self.index() < 0 {
+            return None;
+        }
+        Self::from_index(self.index() - offset)
+    }
+}
+
+/// Difference of the square indices.
+impl core::ops::Sub for Square {
+    type Output = i32;
+
+    #[inline(always)]
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.index() - rhs.index()
     }
 }
 
 impl BoardHelper {
-    /// Constructs a square index from a text.
+    /// Parses a square from a text, such as "e4".
+    ///
     /// # Examples  
     /// ```rust
-    /// use bitschess::BoardHelper;
-    /// assert_eq!(BoardHelper::text_to_square("a1"), 0);
-    /// assert_eq!(BoardHelper::text_to_square("B2"), 9);
-    /// assert_eq!(BoardHelper::text_to_square("1"), -1); // invalid
-    /// assert_eq!(BoardHelper::text_to_square("a"), -1); // invalid
+    /// use bitschess::{BoardHelper, Square, SquareParseError};
+    /// assert_eq!(BoardHelper::text_to_square("a1"), Ok(Square::A1));
+    /// assert_eq!(BoardHelper::text_to_square("B2"), Ok(Square::B2));
+    /// assert_eq!(BoardHelper::text_to_square("1"), Err(SquareParseError::InvalidLength(1)));
+    /// assert_eq!(BoardHelper::text_to_square("a9"), Err(SquareParseError::InvalidRank('9')));
     /// ```
-    #[must_use]
-    pub const fn text_to_square(uci_cmd: &str) -> i32 {
-        if uci_cmd.len() < 2 {
-            return -1;
+    ///
+    /// # Errors
+    /// If the text isn't a file from 'a' to 'h' followed by a rank from '1' to '8'.
+    pub const fn text_to_square(text: &str) -> Result<Square, SquareParseError> {
+        let bytes = text.as_bytes();
+        if bytes.len() != 2 {
+            return Err(SquareParseError::InvalidLength(bytes.len()));
         }
-
-        let file_char = uci_cmd.as_bytes()[0] as char;
-        let rank_char = uci_cmd.as_bytes()[1] as char;
-        let file = Self::file_to_idx(file_char);
-        let rank = Self::rank_to_idx(rank_char);
-        Self::file_rank_to_idx(file, rank)
+        Self::chars_to_square(bytes[0] as char, bytes[1] as char)
     }
 
-    /// Returns index for a rank and index.  
-    /// Invalid input will return -1.
+    /// Parses a square from the file and the rank characters.
+    ///
     /// # Examples  
     /// ```rust
-    /// use bitschess::BoardHelper;
-    /// assert_eq!(BoardHelper::chars_to_square('a', '1'), 0);
-    /// assert_eq!(BoardHelper::chars_to_square('B', '2'), 9);
+    /// use bitschess::{BoardHelper, Square, SquareParseError};
+    /// assert_eq!(BoardHelper::chars_to_square('a', '1'), Ok(Square::A1));
+    /// assert_eq!(BoardHelper::chars_to_square('B', '2'), Ok(Square::B2));
     /// 
-    /// assert_eq!(BoardHelper::chars_to_square('a', '9'), -1); // invalid
-    /// assert_eq!(BoardHelper::chars_to_square('i', '1'), -1); // invalid
-    /// assert_eq!(BoardHelper::chars_to_square('a', '0'), -1); // invalid
-    /// assert_eq!(BoardHelper::chars_to_square('h', '0'), -1); // invalid
+    /// assert_eq!(BoardHelper::chars_to_square('a', '9'), Err(SquareParseError::InvalidRank('9')));
+    /// assert_eq!(BoardHelper::chars_to_square('i', '1'), Err(SquareParseError::InvalidFile('i')));
+    /// assert_eq!(BoardHelper::chars_to_square('a', '0'), Err(SquareParseError::InvalidRank('0')));
     /// ```
+    ///
+    /// # Errors
+    /// If the `file` isn't from 'a' to 'h' or the `rank` isn't from '1' to '8'.
+    pub const fn chars_to_square(file: char, rank: char) -> Result<Square, SquareParseError> {
+        let file_idx = Self::file_to_idx(file);
+        if file_idx == -1 {
+            return Err(SquareParseError::InvalidFile(file));
+        }
+        let rank_idx = Self::rank_to_idx(rank);
+        if rank_idx == -1 {
+            return Err(SquareParseError::InvalidRank(rank));
+        }
+        Ok(Square::from_u32(Self::file_rank_to_idx(file_idx, rank_idx) as u32))
+    }
+
+    /// Constructs a square index from a text, only the first 2 characters are read.  
+    /// Invalid input will return -1.
+    #[must_use]
+    #[deprecated(note = "use `BoardHelper::text_to_square`, which returns a `Result`")]
+    pub const fn text_to_square_idx(uci_cmd: &str) -> i32 {
+        let bytes = uci_cmd.as_bytes();
+        if bytes.len() < 2 {
+            return -1;
+        }
+        match Self::chars_to_square(bytes[0] as char, bytes[1] as char) {
+            Ok(square) => { square as i32 }
+            Err(_) => { -1 }
+        }
+    }
+
+    /// Returns index for a rank and index.  
+    /// Invalid input will return -1.
     #[must_use]
     #[inline(always)]
-    pub const fn chars_to_square(file: char, rank: char) -> i32 {
-        let file = Self::file_to_idx(file);
-        let rank = Self::rank_to_idx(rank);
-        Self::file_rank_to_idx(file, rank)
+    #[deprecated(note = "use `BoardHelper::chars_to_square`, which returns a `Result`")]
+    pub const fn chars_to_square_idx(file: char, rank: char) -> i32 {
+        match Self::chars_to_square(file, rank) {
+            Ok(square) => { square as i32 }
+            Err(_) => { -1 }
+        }
     }
 
     /// Returns the file index from a [char].  
@@ -306,11 +534,11 @@ impl BoardHelper {
     
         let chars = uci_move.as_bytes();
 
-        if BoardHelper::chars_to_square(chars[0] as char, chars[1] as char) == -1 {
+        if BoardHelper::chars_to_square(chars[0] as char, chars[1] as char).is_err() {
             return false;
         }
 
-        if BoardHelper::chars_to_square(chars[2] as char, chars[3] as char) == -1 {
+        if BoardHelper::chars_to_square(chars[2] as char, chars[3] as char).is_err() {
             return false;
         }
 
@@ -329,12 +557,12 @@ mod tests {
 
     #[test]
     fn test_board_helper_text_to_square() {
-        assert_eq!(BoardHelper::text_to_square("a1"), Square::A1 as i32);
-        assert_eq!(BoardHelper::text_to_square("A4"), Square::A4 as i32);
-        assert_eq!(BoardHelper::text_to_square("h5"), Square::H5 as i32);
-        assert_eq!(BoardHelper::text_to_square("E4"), Square::E4 as i32);
-        assert_eq!(BoardHelper::text_to_square("h1"), Square::H1 as i32);
-        assert_eq!(BoardHelper::text_to_square("H7"), Square::H7 as i32);
+        assert_eq!(BoardHelper::text_to_square("a1"), Ok(Square::A1));
+        assert_eq!(BoardHelper::text_to_square("A4"), Ok(Square::A4));
+        assert_eq!(BoardHelper::text_to_square("h5"), Ok(Square::H5));
+        assert_eq!(BoardHelper::text_to_square("E4"), Ok(Square::E4));
+        assert_eq!(BoardHelper::text_to_square("h1"), Ok(Square::H1));
+        assert_eq!(BoardHelper::text_to_square("H7"), Ok(Square::H7));
     }
 
     #[test]
@@ -441,16 +669,26 @@ mod tests {
         assert_eq!(BoardHelper::square_to_chars(Square::H1 as i32), ('h', '1'));
     }
 
+    #[test]
+    #[allow(deprecated)]
+    fn test_board_helper_deprecated_square_indices() {
+        assert_eq!(BoardHelper::text_to_square_idx("e4"), Square::E4 as i32);
+        assert_eq!(BoardHelper::text_to_square_idx("e2e4"), Square::E2 as i32);
+        assert_eq!(BoardHelper::text_to_square_idx("e"), -1);
+        assert_eq!(BoardHelper::chars_to_square_idx('h', '8'), Square::H8 as i32);
+        assert_eq!(BoardHelper::chars_to_square_idx('h', '9'), -1);
+    }
+
     #[test]
     fn test_board_helper_chars_to_square() {
-        assert_eq!(BoardHelper::chars_to_square('A', '8'), Square::A8 as i32);
-        assert_eq!(BoardHelper::chars_to_square('b', '7'), Square::B7 as i32);
-        assert_eq!(BoardHelper::chars_to_square('C', '6'), Square::C6 as i32);
-        assert_eq!(BoardHelper::chars_to_square('d', '5'), Square::D5 as i32);
-        assert_eq!(BoardHelper::chars_to_square('E', '4'), Square::E4 as i32);
-        assert_eq!(BoardHelper::chars_to_square('f', '3'), Square::F3 as i32);
-        assert_eq!(BoardHelper::chars_to_square('G', '2'), Square::G2 as i32);
-        assert_eq!(BoardHelper::chars_to_square('h', '1'), Square::H1 as i32);
+        assert_eq!(BoardHelper::chars_to_square('A', '8'), Ok(Square::A8));
+        assert_eq!(BoardHelper::chars_to_square('b', '7'), Ok(Square::B7));
+        assert_eq!(BoardHelper::chars_to_square('C', '6'), Ok(Square::C6));
+        assert_eq!(BoardHelper::chars_to_square('d', '5'), Ok(Square::D5));
+        assert_eq!(BoardHelper::chars_to_square('E', '4'), Ok(Square::E4));
+        assert_eq!(BoardHelper::chars_to_square('f', '3'), Ok(Square::F3));
+        assert_eq!(BoardHelper::chars_to_square('G', '2'), Ok(Square::G2));
+        assert_eq!(BoardHelper::chars_to_square('h', '1'), Ok(Square::H1));
     }
 
     #[test]
@@ -490,4 +728,28 @@ mod tests {
         assert_eq!(BoardHelper::is_valid_uci_move("q2x5"), false);
         assert_eq!(BoardHelper::is_valid_uci_move("z2e2"), false);
     }
+
+    #[test]
+    fn test_square_conversions() {
+        for (idx, square) in Square::iter().enumerate() {
+            assert_eq!(Square::try_from(idx as i32), Ok(square));
+            assert_eq!(square.to_string(), BoardHelper::square_to_string(idx as i32));
+            assert_eq!(square.to_string().parse::<Square>(), Ok(square));
+            assert_eq!(Square::from_file_rank(square.file(), square.rank()), Some(square));
+        }
+        assert_eq!(Square::try_from(-1), Err(SquareError::OutOfRange(-1)));
+        assert_eq!("E4".parse::<Square>(), Ok(Square::E4));
+        assert_eq!("e44".parse::<Square>(), Err(SquareParseError::InvalidLength(3)));
+        assert_eq!("i1".parse::<Square>(), Err(SquareParseError::InvalidFile('i')));
+        assert_eq!(Square::INVALID.to_string(), "-");
+
+        assert_eq!(Square::A1 - 1, None);
+        assert_eq!(Square::A2 - 8, Some(Square::A1));
+        assert_eq!(Square::A5 - 1, Some(Square::H4));
+        assert_eq!(Square::INVALID + 1, None);
+        assert_eq!(Square::H8 - Square::A1, 63);
+        assert_eq!(Square::A4.offset(-1, 0), None);
+        assert_eq!(Square::B1.offset(1, 2), Some(Square::C3));
+        assert_eq!(Square::INVALID.offset(0, 0), None);
+    }
 }
\ No newline at end of file
@@ -1,7 +1,11 @@
 #![allow(dead_code)]
 
-use crate::board_helper::BoardHelper;
-use crate::piece::Piece;
+use crate::bitschess::board::state::BoardState;
+use crate::board_helper::{BoardHelper, Square};
+use crate::piece::{Piece, PieceColor, PieceType};
+
+use core::str::FromStr;
+use alloc::{format, string::String, vec::Vec};
 
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -13,15 +17,25 @@ pub enum MoveFlag {
     PromoteKnight = 4,
     PromoteBishop = 5,
     PromoteRook   = 6,
-    PromoteQueen  = 7 
+    PromoteQueen  = 7,
+    /// A piece from the hand placed on the board in crazyhouse, the source square holds the [PieceType] instead, see [Move::drop].
+    Drop          = 8,
 }
 
 impl MoveFlag {
     #[must_use]
     #[inline(always)]
     pub const fn from_u8(f: u8) -> Self {
-        unsafe {
-            std::mem::transmute(f & 0b111)
+        match f & 0b1111 {
+            1 => { Self::EnPassant }
+            2 => { Self::PawnTwoUp }
+            3 => { Self::Castle }
+            4 => { Self::PromoteKnight }
+            5 => { Self::PromoteBishop }
+            6 => { Self::PromoteRook }
+            7 => { Self::PromoteQueen }
+            8 => { Self::Drop }
+            _ => { Self::None }
         }
     }
 
@@ -36,6 +50,37 @@ impl MoveFlag {
     pub const fn eq_const(self, other: Self) -> bool {
         self.to_u8() == other.to_u8()
     }
+
+    /// Flag of a promotion to the `piece_type`, [None] if pawns can't promote to it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// assert_eq!(MoveFlag::promotion(PieceType::Rook), Some(MoveFlag::PromoteRook));
+    /// assert_eq!(MoveFlag::promotion(PieceType::King), None);
+    /// ```
+    #[must_use]
+    pub const fn promotion(piece_type: PieceType) -> Option<Self> {
+        match piece_type {
+            PieceType::Knight => { Some(MoveFlag::PromoteKnight) }
+            PieceType::Bishop => { Some(MoveFlag::PromoteBishop) }
+            PieceType::Rook => { Some(MoveFlag::PromoteRook) }
+            PieceType::Queen => { Some(MoveFlag::PromoteQueen) }
+            _ => { None }
+        }
+    }
+
+    /// The piece a pawn promotes to, [None] if the flag isn't a promotion.
+    #[must_use]
+    pub const fn promotion_piece(self) -> Option<PieceType> {
+        match self {
+            MoveFlag::PromoteKnight => { Some(PieceType::Knight) }
+            MoveFlag::PromoteBishop => { Some(PieceType::Bishop) }
+            MoveFlag::PromoteRook => { Some(PieceType::Rook) }
+            MoveFlag::PromoteQueen => { Some(PieceType::Queen) }
+            _ => { None }
+        }
+    }
 }
 
 /// # Move is represented with 16 bits
@@ -46,9 +91,42 @@ impl MoveFlag {
 ///    111111  
 ///    5432109876543210  
 /// (0bFFFFDDDDDDSSSSSS) -> S = source_square D = destination_square F = flag
+///
+/// Drops have the [PieceType] of the dropped piece in place of the source square.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Move(pub u16);
 
+/// Why a string couldn't be parsed into a [Move], see [Move::from_str].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveParseError {
+    /// UCI moves are 4 or 5 characters long, the value is the length of the input.
+    InvalidLength(usize),
+    /// The source square is not a valid square, e.g. "i2".
+    InvalidFromSquare,
+    /// The destination square is not a valid square, e.g. "a9".
+    InvalidToSquare,
+    /// The 5th character is not one of 'q', 'r', 'b' or 'n'.
+    InvalidPromotion(char),
+    /// The piece of a drop, e.g. "N@f3", is not one of 'P', 'N', 'B', 'R' or 'Q'.
+    InvalidDropPiece(char),
+}
+
+impl core::fmt::Display for MoveParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLength(len) => { write!(f, "expected 4 or 5 characters, got {len}") }
+            Self::InvalidFromSquare => { write!(f, "invalid source square") }
+            Self::InvalidToSquare => { write!(f, "invalid destination square") }
+            Self::InvalidPromotion(c) => { write!(f, "invalid promotion piece '{c}'") }
+            Self::InvalidDropPiece(c) => { write!(f, "invalid drop piece '{c}'") }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MoveParseError {}
+
 impl Move {
     /// Creates a new move.
     /// 
@@ -74,6 +152,104 @@ impl Move {
         Self(m)
     }
 
+    /// Same as [Move::new], but with typed squares.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let m = Move::from_squares(Square::E2, Square::E4, MoveFlag::PawnTwoUp);
+    /// assert_eq!(m, Move::new(12, 28, MoveFlag::PawnTwoUp));
+    /// assert_eq!((m.get_from_square(), m.get_to_square()), (Square::E2, Square::E4));
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn from_squares(from: Square, to: Square, flag: MoveFlag) -> Self {
+        Self::new(from as i32, to as i32, flag)
+    }
+
+    /// Castling on the king side, the king moving from e1 to g1 or from e8 to g8.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// assert_eq!(Move::castle_kingside(PieceColor::Black), Move::from_squares(Square::E8, Square::G8, MoveFlag::Castle));
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn castle_kingside(color: PieceColor) -> Self {
+        match color {
+            PieceColor::White => { Self::from_squares(Square::E1, Square::G1, MoveFlag::Castle) }
+            PieceColor::Black => { Self::from_squares(Square::E8, Square::G8, MoveFlag::Castle) }
+        }
+    }
+
+    /// Castling on the queen side, the king moving from e1 to c1 or from e8 to c8.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// assert_eq!(Move::castle_queenside(PieceColor::White).to_uci(), "e1c1");
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn castle_queenside(color: PieceColor) -> Self {
+        match color {
+            PieceColor::White => { Self::from_squares(Square::E1, Square::C1, MoveFlag::Castle) }
+            PieceColor::Black => { Self::from_squares(Square::E8, Square::C8, MoveFlag::Castle) }
+        }
+    }
+
+    /// A pawn moving from `from` to `to` and promoting to the `piece_type`, [None] if pawns can't promote to it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let m = Move::promotion(Square::B7, Square::A8, PieceType::Knight).expect("knights can be promoted to");
+    /// assert_eq!(m.to_uci(), "b7a8n");
+    /// assert_eq!(m.promotion_piece(), Some(PieceType::Knight));
+    /// assert_eq!(Move::promotion(Square::B7, Square::B8, PieceType::Pawn), None);
+    /// ```
+    #[must_use]
+    pub const fn promotion(from: Square, to: Square, piece_type: PieceType) -> Option<Self> {
+        match MoveFlag::promotion(piece_type) {
+            Some(flag) => { Some(Self::from_squares(from, to, flag)) }
+            None => { None }
+        }
+    }
+
+    /// The piece the move promotes to, [None] if it's not a promotion.
+    #[must_use]
+    #[inline(always)]
+    pub const fn promotion_piece(self) -> Option<PieceType> {
+        self.get_flag().promotion_piece()
+    }
+
+    /// Dropping a `piece_type` from the hand on the `to` square in crazyhouse, [None] for kings.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let m = Move::drop(PieceType::Knight, Square::F3).expect("knights can be dropped");
+    /// assert_eq!(m.to_uci(), "N@f3");
+    /// assert_eq!(m.dropped_piece(), Some(PieceType::Knight));
+    /// assert_eq!("N@f3".parse::<Move>(), Ok(m));
+    /// assert_eq!(Move::drop(PieceType::King, Square::F3), None);
+    /// ```
+    #[must_use]
+    pub const fn drop(piece_type: PieceType, to: Square) -> Option<Self> {
+        match piece_type {
+            PieceType::None | PieceType::King => { None }
+            _ => { Some(Self::new(piece_type as i32, to as i32, MoveFlag::Drop)) }
+        }
+    }
+
+    /// The piece dropped from the hand, [None] if the move isn't a drop.
+    #[must_use]
+    #[inline(always)]
+    pub const fn dropped_piece(self) -> Option<PieceType> {
+        if self.is_drop() { Some(PieceType::from_u8(self.get_from_idx() as u8)) } else { None }
+    }
+
     /// Returns the flag of the move.
     ///
     /// # Examples  
@@ -85,7 +261,7 @@ impl Move {
     #[must_use]
     #[inline(always)]
     pub const fn get_flag(self) -> MoveFlag {
-        let flags = (self.0 >> 12) & 0b111;
+        let flags = (self.0 >> 12) & 0b1111;
         MoveFlag::from_u8(flags as u8)
     }
 
@@ -117,6 +293,20 @@ impl Move {
         ((self.0 >> 6) & 0b111111) as i32
     }
 
+    /// Returns the source square as a [Square].
+    #[must_use]
+    #[inline(always)]
+    pub const fn get_from_square(self) -> Square {
+        Square::from_u32(self.get_from_idx() as u32)
+    }
+
+    /// Returns the destination square as a [Square].
+    #[must_use]
+    #[inline(always)]
+    pub const fn get_to_square(self) -> Square {
+        Square::from_u32(self.get_to_idx() as u32)
+    }
+
     /// Helper function to check if a move does en passant.
     /// # Examples  
     /// ```rust
@@ -149,6 +339,20 @@ impl Move {
         self.get_flag().eq_const(MoveFlag::PawnTwoUp)
     }
 
+    /// Helper function to check if a move promotes a pawn, to any piece.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_promotion(self) -> bool {
+        self.get_flag().promotion_piece().is_some()
+    }
+
+    /// Helper function to check if a move drops a piece from the hand.
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_drop(self) -> bool {
+        self.get_flag().eq_const(MoveFlag::Drop)
+    }
+
 
     /// Converts the move into a [UCI (Universal Chess Interface)](https://en.wikipedia.org/wiki/Universal_Chess_Interface)
     /// command.
@@ -156,8 +360,14 @@ impl Move {
     /// # Returns
     /// * basic: "e2e4" a piece moved from e2 to e4
     /// * promotion: "e7e8q" last character tells us the promotion piece. See [Piece::to_char].
+    /// * drop: "N@f3" a knight dropped from the hand on f3.
     #[must_use]
     pub fn to_uci(self) -> String {
+        if let Some(piece_type) = self.dropped_piece() {
+            let (trank, tfile) = BoardHelper::square_to_chars(self.get_to_idx());
+            return format!("{}@{trank}{tfile}", piece_type.to_char().to_ascii_uppercase());
+        }
+
         let (frank, ffile) = BoardHelper::square_to_chars(self.get_from_idx());
         let (trank, tfile) = BoardHelper::square_to_chars(self.get_to_idx());
         
@@ -180,6 +390,7 @@ impl Move {
     /// # Valid Inputs
     /// * basic: "e2e4" a piece moved from e2 to e4
     /// * promotion: "e7e8q" last character tells us the promotion piece. See [Piece::from_char].
+    /// * drop: "N@f3" a knight dropped from the hand on f3.
     /// 
     /// # Examples
     /// ```
@@ -194,7 +405,7 @@ impl Move {
     /// ```
     /// 
     /// # Panics
-    /// If `uci`'s length is not in range of 4..=5
+    /// If `uci`'s length is not in range of 4..=5, use [Move::from_str] to handle invalid input.
     /// 
     #[must_use]
     #[allow(dead_code)]
@@ -202,8 +413,22 @@ impl Move {
         assert!(uci.len() >= 4);
 
         let bytes = uci.as_bytes();
-        let from = BoardHelper::chars_to_square(bytes[0] as char, bytes[1] as char);
-        let to = BoardHelper::chars_to_square(bytes[2] as char, bytes[3] as char);
+        if bytes[1] == b'@' {
+            let to = match BoardHelper::chars_to_square(bytes[2] as char, bytes[3] as char) {
+                Ok(square) => { square }
+                Err(_) => { Square::INVALID }
+            };
+            return Self::new(PieceType::from_char(bytes[0] as char) as i32, to as i32, MoveFlag::Drop);
+        }
+
+        let from = match BoardHelper::chars_to_square(bytes[0] as char, bytes[1] as char) {
+            Ok(square) => { square }
+            Err(_) => { Square::INVALID }
+        };
+        let to = match BoardHelper::chars_to_square(bytes[2] as char, bytes[3] as char) {
+            Ok(square) => { square }
+            Err(_) => { Square::INVALID }
+        };
 
         // flags
         let mut flag = MoveFlag::None;
@@ -230,11 +455,62 @@ impl Move {
             }
         }
 
-        Self::new(from, to, flag)
+        Self::from_squares(from, to, flag)
+    }
+}
+
+impl core::fmt::Display for Move {
+    /// Formats the move in the UCI format, see [Move::to_uci].
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.to_uci())
+    }
+}
+
+impl FromStr for Move {
+    type Err = MoveParseError;
+
+    /// Parses a move in the UCI format, e.g. "e2e4", "e7e8q" or the drop "N@f3".
+    /// The flags besides promotions are not inferred, see [Move::from_uci].
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let m: Move = "e7e8q".parse().expect("valid move");
+    /// assert_eq!(m.get_flag(), MoveFlag::PromoteQueen);
+    /// assert_eq!(m.to_string(), "e7e8q");
+    /// 
+    /// assert_eq!("e2".parse::<Move>(), Err(MoveParseError::InvalidLength(2)));
+    /// assert_eq!("e2e9".parse::<Move>(), Err(MoveParseError::InvalidToSquare));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        if chars.len() != 4 && chars.len() != 5 {
+            return Err(MoveParseError::InvalidLength(chars.len()));
+        }
+
+        if chars[1] == '@' && chars.len() == 4 {
+            let to = BoardHelper::chars_to_square(chars[2], chars[3]).map_err(|_| MoveParseError::InvalidToSquare)?;
+            return Self::drop(PieceType::from_char(chars[0]), to).ok_or(MoveParseError::InvalidDropPiece(chars[0]));
+        }
+
+        let from = BoardHelper::chars_to_square(chars[0], chars[1]).map_err(|_| MoveParseError::InvalidFromSquare)?;
+        let to = BoardHelper::chars_to_square(chars[2], chars[3]).map_err(|_| MoveParseError::InvalidToSquare)?;
+
+        let flag = match chars.get(4).map(char::to_ascii_lowercase) {
+            None => { MoveFlag::None }
+            Some('n') => { MoveFlag::PromoteKnight }
+            Some('b') => { MoveFlag::PromoteBishop }
+            Some('r') => { MoveFlag::PromoteRook }
+            Some('q') => { MoveFlag::PromoteQueen }
+            Some(_) => { return Err(MoveParseError::InvalidPromotion(chars[4])); }
+        };
+
+        Ok(Self::from_squares(from, to, flag))
     }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReversibleMove {
     pub board_move: Move,
     pub captured: Piece,
@@ -244,12 +520,13 @@ pub struct ReversibleMove {
     pub en_passant_square: i32,
     pub castling: [bool; 4],
     pub half_move: u8,
-    pub repetition_saved: bool,
+    /// Zobrist hash before the move xor the hash after it, see [ReversibleMove::hash_delta].
+    pub hash_delta: u64,
 }
 
 impl ReversibleMove {
     #[must_use]
-    pub const fn new(board_move: Move, captured: Piece, en_passant_square: i32, castling: [bool; 4], half_move: u8, zobrist_hash: u64, repetition_saved: bool) -> Self { 
+    pub const fn new(board_move: Move, captured: Piece, en_passant_square: i32, castling: [bool; 4], half_move: u8, zobrist_hash: u64, hash_delta: u64) -> Self { 
         Self {
             board_move, 
             captured,
@@ -257,15 +534,50 @@ impl ReversibleMove {
             castling,
             half_move,
             zobrist_hash,
-            repetition_saved
+            hash_delta
         }
     }
+
+    /// The board state before the move, see [ChessBoard::unmake_move_with](crate::bitschess::board::ChessBoard::unmake_move_with).
+    #[must_use]
+    pub const fn state(&self) -> BoardState {
+        BoardState {
+            captured: self.captured,
+            en_passant_square: self.en_passant_square,
+            castling: self.castling,
+            half_move: self.half_move,
+            zobrist_hash: self.zobrist_hash,
+        }
+    }
+
+    /// The keys the move xored into the zobrist hash, xoring it with the hash before the move gives the hash after the move and vice versa.
+    /// Useful for keeping external state keyed by the hash up to date without recomputing it.
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// let before = board.zobrist_hash;
+    /// board.make_move_uci("g1f3").expect("legal move");
+    /// let last = board.get_move_history().last().expect("a move was made");
+    /// assert_eq!(before ^ last.hash_delta(), board.zobrist_hash);
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn hash_delta(&self) -> u64 {
+        self.hash_delta
+    }
 }
 
+/// Most legal moves in a chess position is 218 in this position:
+/// fen: R6R/3Q4/1Q4Q1/4Q3/2Q4Q/Q4Q2/pp1Q4/kBNN1KB1 w - -
+/// Drops in crazyhouse can add a few hundred more, every piece type in hand to almost every empty square.
+pub const MOVE_CONTAINER_CAPACITY: usize = 512;
+
+#[derive(Clone)]
 pub struct MoveContainer {
-    // Most legal moves in a chess position is 218 in this position:
-    // fen: R6R/3Q4/1Q4Q1/4Q3/2Q4Q/Q4Q2/pp1Q4/kBNN1KB1 w - -
-    moves: [Move; 218], 
+    moves: [Move; MOVE_CONTAINER_CAPACITY], 
     size: usize 
 }
 
@@ -317,13 +629,31 @@ impl IntoIterator for MoveContainer {
     }
 }
 
+impl core::fmt::Debug for MoveContainer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
 impl FromIterator<Move> for MoveContainer {
     fn from_iter<I: IntoIterator<Item=Move>>(iter: I) -> Self {
         let mut c = MoveContainer::new();
+        c.extend(iter);
+        c
+    }
+}
+
+impl Extend<Move> for MoveContainer {
+    fn extend<I: IntoIterator<Item=Move>>(&mut self, iter: I) {
         for i in iter {
-            c.push(i);
+            self.push(i);
         }
-        c
+    }
+}
+
+impl Default for MoveContainer {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -331,7 +661,7 @@ impl MoveContainer {
     #[inline(always)]
     pub fn new() -> Self {
         MoveContainer {
-            moves: [Move(0); 218],
+            moves: [Move(0); MOVE_CONTAINER_CAPACITY],
             size: 0
         }
     }
@@ -406,6 +736,47 @@ impl MoveContainer {
     pub fn is_empty(&self) -> bool {
         self.size == 0
     }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.size = 0;
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[Move] {
+        &self.moves[..self.size]
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub fn contains(&self, chess_move: Move) -> bool {
+        self.as_slice().contains(&chess_move)
+    }
+
+    /// Keeps only the moves for which `keep` returns true, in the same order. Filters in place without allocating.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// let mut moves = board.get_legal_moves();
+    /// moves.retain(|m| m.get_flag() == MoveFlag::PawnTwoUp);
+    /// assert_eq!(moves.len(), 8);
+    /// assert!(moves.iter().any(|m| m.to_uci() == "e2e4"));
+    /// ```
+    pub fn retain(&mut self, mut keep: impl FnMut(Move) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.size {
+            let chess_move = self.moves[i];
+            if keep(chess_move) {
+                self.moves[kept] = chess_move;
+                kept += 1;
+            }
+        }
+        self.size = kept;
+    }
 }
 
 #[cfg(test)]
@@ -445,4 +816,42 @@ mod tests {
         let m = Move::from_uci("e7e8n");
         assert_eq!(m.get_flag(), MoveFlag::PromoteKnight);
     }
+
+    #[test]
+    fn test_move_display_matches_uci() {
+        assert_eq!(Move::from_uci("e7e8n").to_string(), "e7e8n");
+        assert_eq!(format!("{}", Move::from_uci("g1f3")), "g1f3");
+    }
+
+    #[test]
+    fn test_move_from_str() {
+        assert_eq!("a2a4".parse::<Move>(), Ok(Move::from_uci("a2a4")));
+        assert_eq!("E7E8Q".parse::<Move>(), Ok(Move::from_uci("e7e8q")));
+    }
+
+    #[test]
+    fn test_move_from_str_errors() {
+        assert_eq!("".parse::<Move>(), Err(MoveParseError::InvalidLength(0)));
+        assert_eq!("e7e8qq".parse::<Move>(), Err(MoveParseError::InvalidLength(6)));
+        assert_eq!("i2e4".parse::<Move>(), Err(MoveParseError::InvalidFromSquare));
+        assert_eq!("e2e0".parse::<Move>(), Err(MoveParseError::InvalidToSquare));
+        assert_eq!("e7e8k".parse::<Move>(), Err(MoveParseError::InvalidPromotion('k')));
+        assert_eq!("ä2e4".parse::<Move>(), Err(MoveParseError::InvalidFromSquare));
+    }
+
+    #[test]
+    fn test_move_constructors() {
+        let mut board = crate::bitschess::board::ChessBoard::new();
+        board.parse_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").expect("valid fen");
+        let moves = board.get_legal_moves();
+        assert!(moves.contains(Move::castle_kingside(PieceColor::White)));
+        assert!(moves.contains(Move::castle_queenside(PieceColor::White)));
+        for piece_type in [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen] {
+            let m = Move::promotion(Square::B7, Square::A8, piece_type).expect("valid promotion");
+            assert!(moves.contains(m));
+            assert_eq!(m.promotion_piece(), Some(piece_type));
+        }
+        assert_eq!(Move::castle_kingside(PieceColor::White).promotion_piece(), None);
+        assert_eq!(Move::promotion(Square::B7, Square::A8, PieceType::None), None);
+    }
 }
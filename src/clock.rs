@@ -0,0 +1,312 @@
+//! Time controls and a chess clock for arbiters and GUIs, see [GameClock].
+//! The clock doesn't read the system time, it's advanced with [GameClock::tick] so it works the same in tests, replays and `no_std`.
+
+use alloc::vec::Vec;
+use core::str::FromStr;
+use core::time::Duration;
+
+use crate::piece::PieceColor;
+
+/// A period of a [TimeControl], e.g. 40 moves in 90 minutes with 30 seconds added per move.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeStage {
+    /// Moves each side has to make within the stage, [None] for the rest of the game.
+    pub moves: Option<u32>,
+    /// Time added to the clock at the start of the stage.
+    pub time: Duration,
+    /// Time added after every move (Fischer increment).
+    pub increment: Duration,
+    /// Time at the start of every move before the clock starts running (simple delay).
+    pub delay: Duration,
+}
+
+/// How much time the players have, written in PGN's "TimeControl" tag as seconds, e.g. "300+2" or "40/5400:1800+30".
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use core::time::Duration;
+/// let blitz: TimeControl = "300+2".parse().expect("valid time control");
+/// assert_eq!(blitz, TimeControl::Increment { base: Duration::from_secs(300), increment: Duration::from_secs(2) });
+///
+/// let classical: TimeControl = "40/5400+30:1800+30".parse().expect("valid time control");
+/// assert_eq!(classical.stage(0).moves, Some(40));
+/// assert_eq!(classical.stage(1).time, Duration::from_secs(1800));
+/// assert_eq!(classical.to_string(), "40/5400+30:1800+30");
+///
+/// assert_eq!("-".parse::<TimeControl>(), Err(TimeControlParseError::NoTimeControl));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeControl {
+    /// All moves in the given time.
+    SuddenDeath(Duration),
+    /// Fischer increment, the `increment` is added after every move.
+    Increment { base: Duration, increment: Duration },
+    /// Simple delay, the clock starts running only after the `delay` at every move.
+    /// PGN has no notation for delays, so the tag only has the `base`.
+    Delay { base: Duration, delay: Duration },
+    /// Stages played one after the other, the last one repeating if it has a move count.
+    Stages(Vec<TimeStage>),
+}
+
+/// Why a "TimeControl" tag couldn't be parsed, see [TimeControl::from_str].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeControlParseError {
+    /// The tag is "?" or "-", the time control is unknown or the game had none.
+    NoTimeControl,
+    /// The field at the index isn't "moves/seconds" or "seconds", optionally followed by "+increment".
+    /// Sandclock fields ("*180") are not supported.
+    InvalidField(usize),
+}
+
+impl core::fmt::Display for TimeControlParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NoTimeControl => { write!(f, "no time control") }
+            Self::InvalidField(idx) => { write!(f, "invalid time control field {}", idx + 1) }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TimeControlParseError {}
+
+impl TimeControl {
+    /// The stage at the `idx`, stages past the last one repeat the last one.
+    #[must_use]
+    pub fn stage(&self, idx: usize) -> TimeStage {
+        match self {
+            Self::SuddenDeath(time) => { TimeStage { time: *time, ..Default::default() } }
+            Self::Increment { base, increment } => { TimeStage { time: *base, increment: *increment, ..Default::default() } }
+            Self::Delay { base, delay } => { TimeStage { time: *base, delay: *delay, ..Default::default() } }
+            Self::Stages(stages) => { stages.get(idx).or(stages.last()).copied().unwrap_or_default() }
+        }
+    }
+
+    fn parse_stage(field: &str) -> Option<TimeStage> {
+        let (moves, rest) = match field.split_once('/') {
+            Some((moves, rest)) => { (Some(moves.parse().ok()?), rest) }
+            None => { (None, field) }
+        };
+        let (time, increment) = rest.split_once('+').unwrap_or((rest, "0"));
+        Some(TimeStage {
+            moves,
+            time: Duration::from_secs(time.parse().ok()?),
+            increment: Duration::from_secs(increment.parse().ok()?),
+            delay: Duration::ZERO,
+        })
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = TimeControlParseError;
+
+    /// Parses the value of PGN's "TimeControl" tag, a single field without a move count becomes
+    /// [TimeControl::SuddenDeath] or [TimeControl::Increment] and anything else [TimeControl::Stages].
+    fn from_str(tag: &str) -> Result<Self, Self::Err> {
+        let tag = tag.trim();
+        if tag == "?" || tag == "-" {
+            return Err(TimeControlParseError::NoTimeControl);
+        }
+
+        let stages = tag.split(':')
+            .enumerate()
+            .map(|(idx, field)| Self::parse_stage(field).ok_or(TimeControlParseError::InvalidField(idx)))
+            .collect::<Result<Vec<_>, _>>()?;
+        match stages[..] {
+            [TimeStage { moves: None, time, increment, .. }] if increment.is_zero() => { Ok(Self::SuddenDeath(time)) }
+            [TimeStage { moves: None, time, increment, .. }] => { Ok(Self::Increment { base: time, increment }) }
+            _ => { Ok(Self::Stages(stages)) }
+        }
+    }
+}
+
+impl core::fmt::Display for TimeControl {
+    /// Formats the time control as in PGN's "TimeControl" tag, in whole seconds.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::SuddenDeath(time) | Self::Delay { base: time, .. } => { write!(f, "{}", time.as_secs()) }
+            Self::Increment { base, increment } => { write!(f, "{}+{}", base.as_secs(), increment.as_secs()) }
+            Self::Stages(stages) => {
+                for (idx, stage) in stages.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ":")?;
+                    }
+                    if let Some(moves) = stage.moves {
+                        write!(f, "{moves}/")?;
+                    }
+                    write!(f, "{}", stage.time.as_secs())?;
+                    if !stage.increment.is_zero() {
+                        write!(f, "+{}", stage.increment.as_secs())?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Clocks of both players under a [TimeControl].
+/// Time passes for the side to move with [GameClock::tick] and [GameClock::press] ends its move,
+/// adding the increment and the time of the next stage.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use core::time::Duration;
+/// let mut clock = GameClock::new("60+1".parse().expect("valid time control"), PieceColor::White);
+/// clock.tick(Duration::from_secs(5));
+/// assert_eq!(clock.press(), Duration::from_secs(56));
+/// assert_eq!(clock.turn(), PieceColor::Black);
+///
+/// clock.tick(Duration::from_secs(61));
+/// assert_eq!(clock.flagged(), Some(PieceColor::Black));
+/// assert_eq!(clock.remaining(PieceColor::Black), Duration::ZERO);
+/// assert_eq!(clock.clock_times(), &[Duration::from_secs(56)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameClock {
+    control: TimeControl,
+    remaining: [Duration; 2],
+    /// Stage each side is in and the moves it has made within the stage.
+    stages: [(usize, u32); 2],
+    turn: PieceColor,
+    /// Time spent on the current move, the delay included.
+    move_time: Duration,
+    flagged: Option<PieceColor>,
+    /// Time left for the side which moved after each move.
+    clock_times: Vec<Duration>,
+}
+
+impl GameClock {
+    /// Both clocks set to the time of the first stage, the clock of the `turn` running first.
+    #[must_use]
+    pub fn new(control: TimeControl, turn: PieceColor) -> Self {
+        let time = control.stage(0).time;
+        Self {
+            control,
+            remaining: [time; 2],
+            stages: [(0, 0); 2],
+            turn,
+            move_time: Duration::ZERO,
+            flagged: None,
+            clock_times: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn time_control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    /// The side whose clock is running.
+    #[must_use]
+    #[inline(always)]
+    pub const fn turn(&self) -> PieceColor {
+        self.turn
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn remaining(&self, color: PieceColor) -> Duration {
+        self.remaining[color as usize]
+    }
+
+    /// The side which ran out of time, the clock stops once a side has.
+    #[must_use]
+    #[inline(always)]
+    pub const fn flagged(&self) -> Option<PieceColor> {
+        self.flagged
+    }
+
+    /// Time left for the side which moved after each move, oldest first, as written in the "%clk" comments of a PGN.
+    #[must_use]
+    #[inline(always)]
+    pub fn clock_times(&self) -> &[Duration] {
+        &self.clock_times
+    }
+
+    /// Moves left until the next stage for the `color`, [None] if it's in a stage lasting the rest of the game.
+    #[must_use]
+    pub fn moves_to_go(&self, color: PieceColor) -> Option<u32> {
+        let (stage, moves) = self.stages[color as usize];
+        self.control.stage(stage).moves.map(|stage_moves| stage_moves - moves)
+    }
+
+    /// Runs the clock of the side to move for the `elapsed` time, the time of the delay is free.
+    pub fn tick(&mut self, elapsed: Duration) {
+        if self.flagged.is_some() {
+            return;
+        }
+
+        let delay = self.control.stage(self.stages[self.turn as usize].0).delay;
+        let charged = elapsed.saturating_sub(delay.saturating_sub(self.move_time));
+        self.move_time += elapsed;
+
+        let remaining = &mut self.remaining[self.turn as usize];
+        if charged >= *remaining && !charged.is_zero() {
+            *remaining = Duration::ZERO;
+            self.flagged = Some(self.turn);
+        } else {
+            *remaining -= charged;
+        }
+    }
+
+    /// Ends the move of the side to move and starts the other side's clock.
+    /// Returns the time left for the side which moved, does nothing once a side has flagged.
+    pub fn press(&mut self) -> Duration {
+        let color = self.turn as usize;
+        if self.flagged.is_some() {
+            return self.remaining[color];
+        }
+
+        let (stage, moves) = &mut self.stages[color];
+        let current = self.control.stage(*stage);
+        self.remaining[color] += current.increment;
+        *moves += 1;
+        if current.moves == Some(*moves) {
+            *stage += 1;
+            *moves = 0;
+            self.remaining[color] += self.control.stage(*stage).time;
+        }
+
+        self.clock_times.push(self.remaining[color]);
+        self.turn = self.turn.flipped();
+        self.move_time = Duration::ZERO;
+        self.remaining[color]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_stages_and_delay() {
+        let control: TimeControl = "2/100:50+5".parse().expect("valid time control");
+        let mut clock = GameClock::new(control, PieceColor::White);
+        for _ in 0..4 {
+            clock.tick(Duration::from_secs(10));
+            clock.press();
+        }
+        // The second stage starts after 2 moves of each side
+        assert_eq!(clock.remaining(PieceColor::White), Duration::from_secs(130));
+        assert_eq!(clock.moves_to_go(PieceColor::Black), None);
+        clock.tick(Duration::from_secs(10));
+        assert_eq!(clock.press(), Duration::from_secs(125));
+
+        let mut clock = GameClock::new(TimeControl::Delay { base: Duration::from_secs(60), delay: Duration::from_secs(5) }, PieceColor::Black);
+        clock.tick(Duration::from_secs(3));
+        clock.tick(Duration::from_secs(3));
+        assert_eq!(clock.press(), Duration::from_secs(59));
+        assert_eq!(clock.remaining(PieceColor::White), Duration::from_secs(60));
+        assert_eq!(clock.time_control().to_string(), "60");
+
+        assert_eq!("40/5400:*60".parse::<TimeControl>(), Err(TimeControlParseError::InvalidField(1)));
+        assert_eq!("300+".parse::<TimeControl>(), Err(TimeControlParseError::InvalidField(0)));
+    }
+}
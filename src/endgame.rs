@@ -0,0 +1,430 @@
+//! Helpers for recognizing and reasoning about endgames.
+
+use crate::bitschess::bitboard::BitBoard;
+use crate::bitschess::board::ChessBoard;
+use crate::board_helper::BoardHelper;
+use crate::piece::{PieceColor, PieceType};
+
+/// Material values used for deciding which side is stronger, in pawns.
+const MATERIAL_VALUES: [u32; 5] = [1, 3, 3, 5, 9];
+/// Order of the pieces in a material label, after the king.
+const LABEL_ORDER: [PieceType; 5] = [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight, PieceType::Pawn];
+/// A side with more non-pawn material than this (a queen and a minor piece) is not considered to be in an endgame.
+const ENDGAME_MATERIAL_LIMIT: u32 = 12;
+
+/// Amount of each piece for both sides (kings excluded), indexed by `[color][piece_type.get_index()]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaterialKey {
+    pub counts: [[u8; 5]; 2],
+}
+
+impl MaterialKey {
+    #[must_use]
+    pub const fn count(&self, color: PieceColor, piece_type: PieceType) -> u8 {
+        self.counts[color as usize][piece_type.get_index()]
+    }
+
+    /// Material of a side in pawns, using the common 1, 3, 3, 5, 9 values.
+    #[must_use]
+    pub const fn material(&self, color: PieceColor) -> u32 {
+        let mut material = 0;
+        let mut i = 0;
+        while i < 5 {
+            material += self.counts[color as usize][i] as u32 * MATERIAL_VALUES[i];
+            i += 1;
+        }
+        material
+    }
+
+    /// Material of a side without pawns.
+    #[must_use]
+    pub const fn non_pawn_material(&self, color: PieceColor) -> u32 {
+        self.material(color) - self.counts[color as usize][0] as u32
+    }
+
+    /// The side with more material, white if equal.
+    #[must_use]
+    pub const fn stronger_side(&self) -> PieceColor {
+        if self.material(PieceColor::Black) > self.material(PieceColor::White) { PieceColor::Black } else { PieceColor::White }
+    }
+
+    /// Pieces of both sides excluding pawns.
+    #[must_use]
+    const fn pieces(&self, piece_type: PieceType) -> u8 {
+        self.counts[0][piece_type.get_index()] + self.counts[1][piece_type.get_index()]
+    }
+}
+
+impl core::fmt::Display for MaterialKey {
+    /// In the form of "KRPvKR", stronger side first.
+    fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let stronger = self.stronger_side();
+        for (idx, color) in [stronger, stronger.flipped()].into_iter().enumerate() {
+            if idx == 1 {
+                write!(formatter, "v")?;
+            }
+            write!(formatter, "K")?;
+            for piece_type in LABEL_ORDER {
+                let ch = piece_type.to_char().to_ascii_uppercase();
+                for _ in 0..self.count(color, piece_type) {
+                    write!(formatter, "{ch}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Type of an ending by the pieces left on the board, pawns are not considered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndgameKind {
+    /// Only kings left.
+    BareKings,
+    PawnEnding,
+    KnightEnding,
+    BishopEnding,
+    /// Both sides have a single bishop, moving on different colored squares.
+    OppositeColoredBishops,
+    /// Both knights and bishops, but nothing else.
+    MinorPieceEnding,
+    RookEnding,
+    QueenEnding,
+    /// Any other combination of pieces, e.g. a rook against a bishop.
+    Mixed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndgameClass {
+    pub kind: EndgameKind,
+    pub material: MaterialKey,
+}
+
+impl core::fmt::Display for EndgameClass {
+    fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(formatter, "{}", self.material)
+    }
+}
+
+/// Squares in front of the pawn on its own file, up to the promotion square.
+const fn front_span(square: i32, color: PieceColor) -> u64 {
+    let file = BoardHelper::get_file(square);
+    let mut span = 0u64;
+    let mut rank = BoardHelper::get_rank(square);
+    loop {
+        rank += if color as u8 == PieceColor::White as u8 { 1 } else { -1 };
+        if rank < 0 || rank > 7 {
+            return span;
+        }
+        span |= 1u64 << (rank * 8 + file);
+    }
+}
+
+/// Returns a bitboard of the passed pawns of `color` which can't be caught by the enemy king.
+/// Uses the rule of the square, with the pawn's own king protecting the promotion square giving one extra tempo.
+/// Only applies to positions where the opponent has nothing but a king and pawns, otherwise returns 0.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use bitschess::endgame::unstoppable_passer;
+/// let mut board = ChessBoard::new();
+/// board.parse_fen("8/8/8/1P6/5k2/8/8/K7 w - - 0 1").expect("valid fen");
+/// assert_eq!(unstoppable_passer(&board, PieceColor::White), 1u64 << Square::B5 as u32);
+/// // Black to move gets inside of the square
+/// board.parse_fen("8/8/8/1P6/5k2/8/8/K7 b - - 0 1").expect("valid fen");
+/// assert_eq!(unstoppable_passer(&board, PieceColor::White), 0);
+/// ```
+#[must_use]
+pub fn unstoppable_passer(board: &ChessBoard, color: PieceColor) -> u64 {
+    let enemy = color.flipped();
+    let enemy_pawns = board.bitboards[PieceType::Pawn.get_side_index(enemy)].0;
+    if board.side_bitboards[enemy as usize].0 & !enemy_pawns & !board.bitboards[PieceType::King.get_side_index(enemy)].0 != 0 {
+        return 0;
+    }
+
+    let all_pieces = board.side_bitboards[0].0 | board.side_bitboards[1].0;
+    let own_king = board.get_king_square(color);
+    let enemy_king = board.get_king_square(enemy);
+    let enemy_tempo = i32::from(board.get_turn() == enemy);
+
+    let mut unstoppable = 0u64;
+    let mut pawns = board.bitboards[PieceType::Pawn.get_side_index(color)].0;
+    while pawns != 0 {
+        let square = BoardHelper::pop_lsb(&mut pawns);
+        let span = front_span(square, color);
+
+        // Passed & nothing blocking the way
+        let file = BoardHelper::get_file(square);
+        let mut passed_mask = span;
+        if file > 0 { passed_mask |= front_span(square - 1, color); }
+        if file < 7 { passed_mask |= front_span(square + 1, color); }
+        if passed_mask & enemy_pawns != 0 || span & all_pieces != 0 {
+            continue;
+        }
+
+        let (promotion_rank, start_rank) = if color == PieceColor::White { (7, 1) } else { (0, 6) };
+        let promotion_square = promotion_rank * 8 + file;
+        let rank = BoardHelper::get_rank(square);
+        let mut pawn_distance = (promotion_rank - rank).abs();
+        if rank == start_rank {
+            pawn_distance -= 1;
+        }
+
+        // After promoting, the king could still capture the queen if it's unprotected.
+        let queen_protected = BitBoard::distance(own_king, promotion_square) == 1;
+        let king_distance = BitBoard::distance(enemy_king, promotion_square) - enemy_tempo;
+        if king_distance > pawn_distance || (queen_protected && king_distance == pawn_distance) {
+            unstoppable |= 1u64 << square;
+        }
+    }
+    unstoppable
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OppositionKind {
+    /// Kings on the same file or rank with one square between them.
+    Direct,
+    /// Kings on the same file or rank with 3 or 5 squares between them.
+    Distant,
+    /// Kings on the same diagonal with an odd number of squares between them.
+    Diagonal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opposition {
+    pub kind: OppositionKind,
+    /// The side which has the opposition, i.e. the side which is not to move.
+    pub holder: PieceColor,
+}
+
+/// Returns the opposition between the kings, `None` if the kings are not in opposition.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use bitschess::endgame::{opposition, Opposition, OppositionKind};
+/// let mut board = ChessBoard::new();
+/// board.parse_fen("8/8/4k3/8/4K3/4P3/8/8 b - - 0 1").expect("valid fen");
+/// assert_eq!(opposition(&board), Some(Opposition { kind: OppositionKind::Direct, holder: PieceColor::White }));
+/// ```
+#[must_use]
+pub fn opposition(board: &ChessBoard) -> Option<Opposition> {
+    let white_king = board.get_king_square(PieceColor::White);
+    let black_king = board.get_king_square(PieceColor::Black);
+    let files = (BoardHelper::get_file(white_king) - BoardHelper::get_file(black_king)).abs();
+    let ranks = (BoardHelper::get_rank(white_king) - BoardHelper::get_rank(black_king)).abs();
+
+    let kind = match (files, ranks) {
+        (0, gap) | (gap, 0) if gap == 2 => { OppositionKind::Direct }
+        (0, gap) | (gap, 0) if gap % 2 == 0 => { OppositionKind::Distant }
+        (files, ranks) if files == ranks && files % 2 == 0 => { OppositionKind::Diagonal }
+        _ => { return None; }
+    };
+    Some(Opposition { kind, holder: board.get_turn().flipped() })
+}
+
+/// Returns the key squares of a pawn of `color` standing on the `square`.
+/// If the attacking king reaches a key square, the pawn promotes regardless of who is to move.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use bitschess::endgame::key_squares;
+/// let squares = key_squares(Square::E4 as i32, PieceColor::White);
+/// assert_eq!(squares, (1u64 << Square::D6 as u32) | (1u64 << Square::E6 as u32) | (1u64 << Square::F6 as u32));
+/// ```
+#[must_use]
+pub const fn key_squares(square: i32, color: PieceColor) -> u64 {
+    let file = BoardHelper::get_file(square);
+    let white = color as u8 == PieceColor::White as u8;
+    // Computed from white's point of view and flipped for black
+    let rank = if white { BoardHelper::get_rank(square) } else { 7 - BoardHelper::get_rank(square) };
+
+    // Rook pawns can only be won by getting the king in front of the pawn
+    let squares = if file == 0 || file == 7 {
+        let key_file = if file == 0 { 1 } else { 6 };
+        (1u64 << (48 + key_file)) | (1u64 << (56 + key_file))
+    } else {
+        let (first_rank, last_rank) = match rank {
+            1..=3 => { (rank + 2, rank + 2) }
+            4 | 5 => { (rank + 1, rank + 2) }
+            6 => { (6, 7) }
+            _ => { return 0; }
+        };
+
+        let mut squares = 0u64;
+        let mut key_rank = first_rank;
+        while key_rank <= last_rank {
+            squares |= 0b111u64 << (key_rank * 8 + file - 1);
+            key_rank += 1;
+        }
+        squares & !(1u64 << (rank * 8 + file))
+    };
+    if white { squares } else { squares.swap_bytes() }
+}
+
+impl ChessBoard {
+    #[must_use]
+    pub const fn material_key(&self) -> MaterialKey {
+        let mut counts = [[0u8; 5]; 2];
+        let mut i = 0;
+        while i < 5 {
+            counts[0][i] = BoardHelper::count_bits(self.bitboards[i].0) as u8;
+            counts[1][i] = BoardHelper::count_bits(self.bitboards[6 + i].0) as u8;
+            i += 1;
+        }
+        MaterialKey { counts }
+    }
+
+    /// Classifies the position as a standard type of an ending, [None] if there's too much material left for an endgame.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// use bitschess::endgame::EndgameKind;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("8/8/4k3/2r5/8/3KP3/8/5R2 w - - 0 1").expect("valid fen");
+    /// let class = board.endgame_class().expect("is an endgame");
+    /// assert_eq!(class.kind, EndgameKind::RookEnding);
+    /// assert_eq!(class.to_string(), "KRPvKR");
+    ///
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// assert_eq!(board.endgame_class(), None);
+    /// ```
+    #[must_use]
+    pub fn endgame_class(&self) -> Option<EndgameClass> {
+        let material = self.material_key();
+        if material.non_pawn_material(PieceColor::White) > ENDGAME_MATERIAL_LIMIT || material.non_pawn_material(PieceColor::Black) > ENDGAME_MATERIAL_LIMIT {
+            return None;
+        }
+
+        let knights = material.pieces(PieceType::Knight);
+        let bishops = material.pieces(PieceType::Bishop);
+        let rooks = material.pieces(PieceType::Rook);
+        let queens = material.pieces(PieceType::Queen);
+        let pawns = material.pieces(PieceType::Pawn);
+
+        let kind = match (knights, bishops, rooks, queens) {
+            (0, 0, 0, 0) if pawns == 0 => { EndgameKind::BareKings }
+            (0, 0, 0, 0) => { EndgameKind::PawnEnding }
+            (_, 0, 0, 0) => { EndgameKind::KnightEnding }
+            (0, _, 0, 0) => {
+                let white_bishops = self.bitboards[PieceType::Bishop.get_side_index(PieceColor::White)].0;
+                let black_bishops = self.bitboards[PieceType::Bishop.get_side_index(PieceColor::Black)].0;
+                let single_bishops = BoardHelper::count_bits(white_bishops) == 1 && BoardHelper::count_bits(black_bishops) == 1;
+                if single_bishops && Self::is_light_square(BoardHelper::bitscan_forward(white_bishops)) != Self::is_light_square(BoardHelper::bitscan_forward(black_bishops)) {
+                    EndgameKind::OppositeColoredBishops
+                } else {
+                    EndgameKind::BishopEnding
+                }
+            }
+            (_, _, 0, 0) => { EndgameKind::MinorPieceEnding }
+            (0, 0, _, 0) => { EndgameKind::RookEnding }
+            (0, 0, 0, _) => { EndgameKind::QueenEnding }
+            _ => { EndgameKind::Mixed }
+        };
+
+        Some(EndgameClass { kind, material })
+    }
+
+    const fn is_light_square(square: i32) -> bool {
+        (BoardHelper::get_rank(square) + BoardHelper::get_file(square)) % 2 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_helper::Square;
+
+    fn _class(fen: &str) -> Option<EndgameClass> {
+        let mut board = ChessBoard::new();
+        board.parse_fen(fen).expect("valid fen");
+        board.endgame_class()
+    }
+
+    #[test]
+    fn test_endgame_class_labels() {
+        let class = _class("8/8/4k3/8/8/3K4/8/2BN4 w - - 0 1").expect("endgame");
+        assert_eq!(class.to_string(), "KBNvK");
+        assert_eq!(class.kind, EndgameKind::MinorPieceEnding);
+
+        // Stronger side first
+        let class = _class("8/8/4k3/3qp3/8/3K4/8/8 w - - 0 1").expect("endgame");
+        assert_eq!(class.to_string(), "KQPvK");
+        assert_eq!(class.kind, EndgameKind::QueenEnding);
+
+        let class = _class("8/8/4k3/8/8/3K4/8/8 w - - 0 1").expect("endgame");
+        assert_eq!(class.to_string(), "KvK");
+        assert_eq!(class.kind, EndgameKind::BareKings);
+    }
+
+    #[test]
+    fn test_endgame_class_kinds() {
+        assert_eq!(_class("8/5pp1/4k3/8/8/3K4/5PP1/8 w - - 0 1").expect("endgame").kind, EndgameKind::PawnEnding);
+        assert_eq!(_class("8/5pp1/4kb2/8/8/3K1B2/5PP1/8 w - - 0 1").expect("endgame").kind, EndgameKind::OppositeColoredBishops);
+        assert_eq!(_class("8/5pp1/4k1b1/8/8/3K1B2/5PP1/8 w - - 0 1").expect("endgame").kind, EndgameKind::BishopEnding);
+        assert_eq!(_class("8/5pp1/4k1n1/8/8/3KN3/5PP1/8 w - - 0 1").expect("endgame").kind, EndgameKind::KnightEnding);
+        assert_eq!(_class("8/5pp1/4k1b1/8/8/R2K4/5PP1/8 w - - 0 1").expect("endgame").kind, EndgameKind::Mixed);
+        assert_eq!(_class("r7/5pp1/4k1b1/8/8/R2K4/5PP1/7R w - - 0 1").expect("endgame").kind, EndgameKind::Mixed);
+        assert_eq!(_class("qr6/5pp1/4k1b1/8/8/R2K4/5PP1/7R w - - 0 1"), None);
+    }
+
+    #[test]
+    fn test_unstoppable_passer() {
+        let mut board = ChessBoard::new();
+
+        // Double move from the starting rank
+        board.parse_fen("8/8/8/8/8/6k1/P7/K7 w - - 0 1").expect("valid fen");
+        assert_eq!(unstoppable_passer(&board, PieceColor::White), 1u64 << Square::A2 as u32);
+
+        // Not passed
+        board.parse_fen("8/p7/8/1P6/8/8/5k2/K7 w - - 0 1").expect("valid fen");
+        assert_eq!(unstoppable_passer(&board, PieceColor::White), 0);
+
+        // Blocked by own king
+        board.parse_fen("1K6/8/8/1P6/8/8/5k2/8 w - - 0 1").expect("valid fen");
+        assert_eq!(unstoppable_passer(&board, PieceColor::White), 0);
+
+        // Own king protects the promotion square
+        board.parse_fen("8/K7/4k3/1P6/8/8/8/8 w - - 0 1").expect("valid fen");
+        assert_eq!(unstoppable_passer(&board, PieceColor::White), 1u64 << Square::B5 as u32);
+        board.parse_fen("8/8/K3k3/1P6/8/8/8/8 w - - 0 1").expect("valid fen");
+        assert_eq!(unstoppable_passer(&board, PieceColor::White), 0);
+
+        // Opponent has a piece
+        board.parse_fen("8/8/8/1P6/8/8/5kn1/K7 w - - 0 1").expect("valid fen");
+        assert_eq!(unstoppable_passer(&board, PieceColor::White), 0);
+
+        // Black pawns
+        board.parse_fen("k7/8/8/8/6p1/8/8/1K6 b - - 0 1").expect("valid fen");
+        assert_eq!(unstoppable_passer(&board, PieceColor::Black), 1u64 << Square::G4 as u32);
+    }
+
+    #[test]
+    fn test_opposition() {
+        let mut board = ChessBoard::new();
+
+        board.parse_fen("4k3/8/8/8/4K3/4P3/8/8 w - - 0 1").expect("valid fen");
+        assert_eq!(opposition(&board), Some(Opposition { kind: OppositionKind::Distant, holder: PieceColor::Black }));
+
+        board.parse_fen("8/8/8/1k6/8/3K4/4P3/8 w - - 0 1").expect("valid fen");
+        assert_eq!(opposition(&board), Some(Opposition { kind: OppositionKind::Diagonal, holder: PieceColor::Black }));
+
+        board.parse_fen("8/8/3k4/8/4K3/4P3/8/8 w - - 0 1").expect("valid fen");
+        assert_eq!(opposition(&board), None);
+        board.parse_fen("8/8/8/4k3/4K3/4P3/8/8 w - - 0 1").expect_err("kings are adjacent");
+    }
+
+    #[test]
+    fn test_key_squares() {
+        let bits = |squares: &[&str]| squares.iter().fold(0u64, |bits, sq| bits | (1u64 << BoardHelper::text_to_square(sq).expect("valid square") as u32));
+
+        assert_eq!(key_squares(Square::D2 as i32, PieceColor::White), bits(&["c4", "d4", "e4"]));
+        assert_eq!(key_squares(Square::D5 as i32, PieceColor::White), bits(&["c6", "d6", "e6", "c7", "d7", "e7"]));
+        assert_eq!(key_squares(Square::D7 as i32, PieceColor::White), bits(&["c7", "e7", "c8", "d8", "e8"]));
+        assert_eq!(key_squares(Square::A3 as i32, PieceColor::White), bits(&["b7", "b8"]));
+        assert_eq!(key_squares(Square::E5 as i32, PieceColor::Black), bits(&["d3", "e3", "f3"]));
+        assert_eq!(key_squares(Square::H4 as i32, PieceColor::Black), bits(&["g2", "g1"]));
+    }
+}
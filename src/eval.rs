@@ -0,0 +1,444 @@
+//! Classical hand-crafted evaluation: material, piece-square tables, pawn structure, king safety, mobility and outposts.
+//! Every term is a field of [EvalParams], so the weights can be tuned without touching the code.
+
+use crate::bitschess::bitboard::{BitBoard, A_FILE, KING_ATTACKS, NOT_A_FILE, NOT_H_FILE, PAWN_ATTACK_SPANS};
+use crate::bitschess::board::ChessBoard;
+use crate::board_helper::BoardHelper;
+use crate::piece::{PieceColor, PieceType};
+
+/// Game phase of the starting position, knights and bishops count as 1, rooks as 2 and queens as 4.
+const MAX_PHASE: i32 = 24;
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+
+/// Squares in front of a pawn on its own and the adjacent files, indexed by `[color][square]`.
+/// A pawn with none of the enemy pawns on these squares (and no own pawn in front of it) is passed.
+const PASSED_PAWN_MASKS: [[u64; 64]; 2] = {
+    let mut masks = [[0u64; 64]; 2];
+    let mut square = 0;
+    while square < 64 {
+        masks[0][square] = BitBoard::front_span(square as i32, PieceColor::White) | PAWN_ATTACK_SPANS[0][square];
+        masks[1][square] = BitBoard::front_span(square as i32, PieceColor::Black) | PAWN_ATTACK_SPANS[1][square];
+        square += 1;
+    }
+    masks
+};
+
+/// Squares around the king and the 3 squares in front of those, indexed by `[color][king_square]`.
+/// E.g. f1-h3 and f3-h3 for a white king on g1.
+pub const KING_ZONES: [[u64; 64]; 2] = {
+    let mut zones = [[0u64; 64]; 2];
+    let mut square = 0;
+    while square < 64 {
+        let ring = KING_ATTACKS[square] | (1u64 << square);
+        zones[0][square] = ring | (ring << 8);
+        zones[1][square] = ring | (ring >> 8);
+        square += 1;
+    }
+    zones
+};
+
+/// Weight of every king zone square attacked by a piece, indexed by [PieceType::get_index].
+pub const KING_ATTACK_WEIGHTS: [i32; 6] = [1, 2, 2, 3, 5, 0];
+
+/// Enemy pieces attacking the zone around a king, see [king_attackers_info].
+/// Arrays are indexed by [PieceType::get_index].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KingAttackersInfo {
+    /// Pieces of each type attacking at least one square of the zone.
+    pub attackers: [u32; 6],
+    /// Attacked zone squares times [KING_ATTACK_WEIGHTS], a square attacked by 2 pieces counts twice.
+    pub attack_weights: [i32; 6],
+}
+
+impl KingAttackersInfo {
+    #[must_use]
+    pub fn attacker_count(&self) -> u32 {
+        self.attackers.iter().sum()
+    }
+
+    #[must_use]
+    pub fn attack_weight(&self) -> i32 {
+        self.attack_weights.iter().sum()
+    }
+}
+
+/// Counts the pieces attacking the [KING_ZONES] zone around the king of `color` and how heavily they attack it.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use bitschess::eval::{king_attackers_info, KING_ATTACK_WEIGHTS};
+/// let mut board = ChessBoard::new();
+/// board.parse_fen("6k1/5ppp/8/6N1/8/8/5PPP/1Q4K1 w - - 0 1").expect("valid fen");
+/// let info = king_attackers_info(&board, PieceColor::Black);
+/// // The knight attacks f7 and h7, the queen attacks g6 and h7
+/// assert_eq!(info.attacker_count(), 2);
+/// assert_eq!(info.attack_weights[PieceType::Knight.get_index()], 2 * KING_ATTACK_WEIGHTS[PieceType::Knight.get_index()]);
+/// assert_eq!(info.attack_weights[PieceType::Queen.get_index()], 2 * KING_ATTACK_WEIGHTS[PieceType::Queen.get_index()]);
+/// assert_eq!(king_attackers_info(&board, PieceColor::White).attacker_count(), 0);
+/// ```
+#[must_use]
+pub fn king_attackers_info(board: &ChessBoard, color: PieceColor) -> KingAttackersInfo {
+    let zone = KING_ZONES[color as usize][board.get_king_square(color) as usize];
+    let enemy = color.flipped();
+    let mut info = KingAttackersInfo::default();
+    for piece_type in [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen] {
+        let idx = piece_type.get_index();
+        let mut pieces = board.bitboards[piece_type.get_side_index(enemy)].0;
+        while pieces != 0 {
+            let square = BoardHelper::pop_lsb(&mut pieces);
+            let attacked = (attacks(board, piece_type, square, enemy) & zone).count_ones() as i32;
+            if attacked > 0 {
+                info.attackers[idx] += 1;
+                info.attack_weights[idx] += attacked * KING_ATTACK_WEIGHTS[idx];
+            }
+        }
+    }
+    info
+}
+
+/// Weights of the evaluation terms in centipawns. Tables are indexed from white's point of view,
+/// a1 being 0 and h8 being 63, black pieces use the vertically flipped square.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalParams {
+    /// Indexed by [PieceType::get_index], the king's value is unused.
+    pub piece_values: [i32; 6],
+    /// Piece-square tables indexed by [PieceType::get_index], the king's table is used in the middlegame.
+    pub piece_square_tables: [[i32; 64]; 6],
+    /// King's piece-square table once most of the pieces are gone.
+    pub king_endgame_table: [i32; 64],
+    /// Penalty for every pawn behind another pawn of the same color on its file.
+    pub doubled_pawn: i32,
+    /// Penalty for a pawn without own pawns on the adjacent files.
+    pub isolated_pawn: i32,
+    /// Bonus for a passed pawn, indexed by the rank from the pawn's own side (0 is the first rank).
+    pub passed_pawn: [i32; 8],
+    /// Bonus for every own pawn on the two ranks in front of the king and its adjacent files, scaled down towards the endgame.
+    pub king_shield: i32,
+    /// Penalty for every attack on the squares around the king, scaled down towards the endgame.
+    pub king_attack: i32,
+    /// Bonus per reachable square not occupied by own pieces, indexed by [PieceType::get_index].
+    pub mobility: [i32; 6],
+    /// Bonus for a knight on an outpost, see [ChessBoard::outposts].
+    pub knight_outpost: i32,
+}
+
+impl Default for EvalParams {
+    /// Piece-square tables are from the "Simplified Evaluation Function" by Tomasz Michniewski.
+    fn default() -> Self {
+        Self {
+            piece_values: [100, 320, 330, 500, 900, 0],
+            piece_square_tables: [
+                flip_table([
+                     0,  0,  0,  0,  0,  0,  0,  0,
+                    50, 50, 50, 50, 50, 50, 50, 50,
+                    10, 10, 20, 30, 30, 20, 10, 10,
+                     5,  5, 10, 25, 25, 10,  5,  5,
+                     0,  0,  0, 20, 20,  0,  0,  0,
+                     5, -5,-10,  0,  0,-10, -5,  5,
+                     5, 10, 10,-20,-20, 10, 10,  5,
+                     0,  0,  0,  0,  0,  0,  0,  0,
+                ]),
+                flip_table([
+                    -50,-40,-30,-30,-30,-30,-40,-50,
+                    -40,-20,  0,  0,  0,  0,-20,-40,
+                    -30,  0, 10, 15, 15, 10,  0,-30,
+                    -30,  5, 15, 20, 20, 15,  5,-30,
+                    -30,  0, 15, 20, 20, 15,  0,-30,
+                    -30,  5, 10, 15, 15, 10,  5,-30,
+                    -40,-20,  0,  5,  5,  0,-20,-40,
+                    -50,-40,-30,-30,-30,-30,-40,-50,
+                ]),
+                flip_table([
+                    -20,-10,-10,-10,-10,-10,-10,-20,
+                    -10,  0,  0,  0,  0,  0,  0,-10,
+                    -10,  0,  5, 10, 10,  5,  0,-10,
+                    -10,  5,  5, 10, 10,  5,  5,-10,
+                    -10,  0, 10, 10, 10, 10,  0,-10,
+                    -10, 10, 10, 10, 10, 10, 10,-10,
+                    -10,  5,  0,  0,  0,  0,  5,-10,
+                    -20,-10,-10,-10,-10,-10,-10,-20,
+                ]),
+                flip_table([
+                     0,  0,  0,  0,  0,  0,  0,  0,
+                     5, 10, 10, 10, 10, 10, 10,  5,
+                    -5,  0,  0,  0,  0,  0,  0, -5,
+                    -5,  0,  0,  0,  0,  0,  0, -5,
+                    -5,  0,  0,  0,  0,  0,  0, -5,
+                    -5,  0,  0,  0,  0,  0,  0, -5,
+                    -5,  0,  0,  0,  0,  0,  0, -5,
+                     0,  0,  0,  5,  5,  0,  0,  0,
+                ]),
+                flip_table([
+                    -20,-10,-10, -5, -5,-10,-10,-20,
+                    -10,  0,  0,  0,  0,  0,  0,-10,
+                    -10,  0,  5,  5,  5,  5,  0,-10,
+                     -5,  0,  5,  5,  5,  5,  0, -5,
+                      0,  0,  5,  5,  5,  5,  0, -5,
+                    -10,  5,  5,  5,  5,  5,  0,-10,
+                    -10,  0,  5,  0,  0,  0,  0,-10,
+                    -20,-10,-10, -5, -5,-10,-10,-20,
+                ]),
+                flip_table([
+                    -30,-40,-40,-50,-50,-40,-40,-30,
+                    -30,-40,-40,-50,-50,-40,-40,-30,
+                    -30,-40,-40,-50,-50,-40,-40,-30,
+                    -30,-40,-40,-50,-50,-40,-40,-30,
+                    -20,-30,-30,-40,-40,-30,-30,-20,
+                    -10,-20,-20,-20,-20,-20,-20,-10,
+                     20, 20,  0,  0,  0,  0, 20, 20,
+                     20, 30, 10,  0,  0, 10, 30, 20,
+                ]),
+            ],
+            king_endgame_table: flip_table([
+                -50,-40,-30,-20,-20,-30,-40,-50,
+                -30,-20,-10,  0,  0,-10,-20,-30,
+                -30,-10, 20, 30, 30, 20,-10,-30,
+                -30,-10, 30, 40, 40, 30,-10,-30,
+                -30,-10, 30, 40, 40, 30,-10,-30,
+                -30,-10, 20, 30, 30, 20,-10,-30,
+                -30,-30,  0,  0,  0,  0,-30,-30,
+                -50,-30,-30,-30,-30,-30,-30,-50,
+            ]),
+            doubled_pawn: 15,
+            isolated_pawn: 15,
+            passed_pawn: [0, 10, 15, 25, 40, 65, 100, 0],
+            king_shield: 10,
+            king_attack: 8,
+            mobility: [0, 4, 4, 2, 1, 0],
+            knight_outpost: 20,
+        }
+    }
+}
+
+/// Tables are written rank 8 first so that they look like the board, the evaluation indexes them from a1.
+const fn flip_table(table: [i32; 64]) -> [i32; 64] {
+    let mut flipped = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        flipped[square] = table[square ^ 56];
+        square += 1;
+    }
+    flipped
+}
+
+/// Evaluates the position with the default [EvalParams], in centipawns from the point of view of the side to move.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use bitschess::eval::evaluate;
+/// let mut board = ChessBoard::new();
+/// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+/// assert_eq!(evaluate(&board), 0);
+///
+/// // White is a knight up, good for white and bad for black.
+/// board.parse_fen("rnbqkb1r/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").expect("valid fen");
+/// assert!(evaluate(&board) > 250);
+/// board.parse_fen("rnbqkb1r/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").expect("valid fen");
+/// assert!(evaluate(&board) < -250);
+/// ```
+#[must_use]
+pub fn evaluate(board: &ChessBoard) -> i32 {
+    EvalParams::default().evaluate(board)
+}
+
+impl EvalParams {
+    /// Evaluates the position with these weights, in centipawns from the point of view of the side to move.
+    #[must_use]
+    pub fn evaluate(&self, board: &ChessBoard) -> i32 {
+        let score = self.evaluate_side(board, PieceColor::White) - self.evaluate_side(board, PieceColor::Black);
+        if board.get_turn() == PieceColor::White { score } else { -score }
+    }
+
+    /// Every term for one side, the opponent's terms are not subtracted.
+    fn evaluate_side(&self, board: &ChessBoard, color: PieceColor) -> i32 {
+        let phase = game_phase(board);
+        let mut score = 0;
+
+        for piece_type in [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen, PieceType::King] {
+            let idx = piece_type.get_index();
+            let mut pieces = board.bitboards[piece_type.get_side_index(color)].0;
+            while pieces != 0 {
+                let square = BoardHelper::pop_lsb(&mut pieces);
+                let relative = relative_square(square, color);
+
+                score += self.piece_values[idx];
+                if piece_type == PieceType::King {
+                    let middlegame = self.piece_square_tables[idx][relative];
+                    let endgame = self.king_endgame_table[relative];
+                    score += (middlegame * phase + endgame * (MAX_PHASE - phase)) / MAX_PHASE;
+                } else {
+                    score += self.piece_square_tables[idx][relative];
+                }
+
+                score += self.mobility[idx] * mobility(board, piece_type, square, color);
+            }
+        }
+
+        score += self.knight_outpost * board.knight_outposts(color).count_ones() as i32;
+        score + self.pawn_structure(board, color) + self.king_safety(board, color) * phase / MAX_PHASE
+    }
+
+    fn pawn_structure(&self, board: &ChessBoard, color: PieceColor) -> i32 {
+        let own_pawns = board.bitboards[PieceType::Pawn.get_side_index(color)].0;
+        let enemy_pawns = board.bitboards[PieceType::Pawn.get_side_index(color.flipped())].0;
+        let mut score = 0;
+
+        for file in 0..8 {
+            let on_file = (own_pawns & (A_FILE << file)).count_ones() as i32;
+            if on_file > 1 {
+                score -= self.doubled_pawn * (on_file - 1);
+            }
+        }
+
+        let mut pawns = own_pawns;
+        while pawns != 0 {
+            let square = BoardHelper::pop_lsb(&mut pawns);
+            let file = BoardHelper::get_file(square);
+
+            let mut adjacent_files = 0u64;
+            if file > 0 {
+                adjacent_files |= A_FILE << (file - 1);
+            }
+            if file < 7 {
+                adjacent_files |= A_FILE << (file + 1);
+            }
+            if own_pawns & adjacent_files == 0 {
+                score -= self.isolated_pawn;
+            }
+
+            let front = PASSED_PAWN_MASKS[color as usize][square as usize];
+            if enemy_pawns & front == 0 && own_pawns & BitBoard::front_span(square, color) == 0 {
+                score += self.passed_pawn[relative_square(square, color) / 8];
+            }
+        }
+        score
+    }
+
+    fn king_safety(&self, board: &ChessBoard, color: PieceColor) -> i32 {
+        let king_square = board.get_king_square(color);
+        let king = 1u64 << king_square;
+        let zone = KING_ATTACKS[king_square as usize] | king;
+
+        // Own pawns in front of the king, e.g. f2, g2, h2, f3, g3 and h3 for a king on g1
+        let row = king | ((king << 1) & NOT_A_FILE) | ((king >> 1) & NOT_H_FILE);
+        let shield_squares = if color == PieceColor::White { (row << 8) | (row << 16) } else { (row >> 8) | (row >> 16) };
+        let shield = shield_squares & board.bitboards[PieceType::Pawn.get_side_index(color)].0;
+        let mut score = self.king_shield * shield.count_ones() as i32;
+
+        let enemy = color.flipped();
+        for piece_type in [PieceType::Pawn, PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen] {
+            let mut pieces = board.bitboards[piece_type.get_side_index(enemy)].0;
+            while pieces != 0 {
+                let square = BoardHelper::pop_lsb(&mut pieces);
+                let attacks = attacks(board, piece_type, square, enemy) & zone;
+                score -= self.king_attack * attacks.count_ones() as i32;
+            }
+        }
+        score
+    }
+}
+
+/// Remaining material scaled from [MAX_PHASE] (starting position) down to 0 (only kings and pawns).
+fn game_phase(board: &ChessBoard) -> i32 {
+    let mut phase = 0;
+    for piece_type in [PieceType::Knight, PieceType::Bishop, PieceType::Rook, PieceType::Queen] {
+        let count = board.bitboards[piece_type.get_side_index(PieceColor::White)].0.count_ones()
+            + board.bitboards[piece_type.get_side_index(PieceColor::Black)].0.count_ones();
+        phase += count as i32 * PHASE_WEIGHTS[piece_type.get_index()];
+    }
+    phase.min(MAX_PHASE)
+}
+
+#[inline(always)]
+const fn relative_square(square: i32, color: PieceColor) -> usize {
+    if color.eq_const(PieceColor::White) { square as usize } else { (square ^ 56) as usize }
+}
+
+/// Squares the piece attacks on the `board`, see [BitBoard::attacks].
+fn attacks(board: &ChessBoard, piece_type: PieceType, square: i32, color: PieceColor) -> u64 {
+    let occupied = board.side_bitboards[0].0 | board.side_bitboards[1].0;
+    BitBoard::attacks(piece_type, color, square, occupied)
+}
+
+/// Amount of squares the piece attacks which are not occupied by own pieces.
+fn mobility(board: &ChessBoard, piece_type: PieceType, square: i32, color: PieceColor) -> i32 {
+    (attacks(board, piece_type, square, color) & !board.side_bitboards[color as usize].0).count_ones() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitschess::board::fen::STARTPOS_FEN;
+    use crate::board_helper::Square;
+    use crate::bench::BENCH_POSITIONS;
+    use crate::testing::assert_eval_symmetry;
+
+    fn evaluate_fen(fen: &str) -> i32 {
+        let mut board = ChessBoard::new();
+        board.parse_fen(fen).expect("valid fen");
+        evaluate(&board)
+    }
+
+    #[test]
+    fn test_eval_symmetry() {
+        assert_eval_symmetry(&BENCH_POSITIONS, |board| {
+            if board.get_turn() == PieceColor::White { evaluate(board) } else { -evaluate(board) }
+        });
+        assert_eq!(evaluate_fen(STARTPOS_FEN), 0);
+    }
+
+    #[test]
+    fn test_eval_pawn_structure() {
+        let params = EvalParams::default();
+        let mut board = ChessBoard::new();
+
+        // Doubled and isolated pawns on the c-file, passed pawn on the h-file.
+        board.parse_fen("4k3/8/8/7P/8/2P5/2P5/4K3 w - - 0 1").expect("valid fen");
+        // The pawn on c2 is behind the one on c3, so it is not passed.
+        let expected = -params.doubled_pawn - 3 * params.isolated_pawn + params.passed_pawn[2] + params.passed_pawn[4];
+        assert_eq!(params.pawn_structure(&board, PieceColor::White), expected);
+
+        // Blocked by a pawn on the adjacent file, no longer passed.
+        board.parse_fen("4k3/6p1/8/7P/8/8/8/4K3 w - - 0 1").expect("valid fen");
+        assert_eq!(params.pawn_structure(&board, PieceColor::White), -params.isolated_pawn);
+        assert_eq!(params.pawn_structure(&board, PieceColor::Black), -params.isolated_pawn);
+    }
+
+    #[test]
+    fn test_eval_king_safety() {
+        let params = EvalParams::default();
+        let mut board = ChessBoard::new();
+        board.parse_fen("6k1/5ppp/8/8/8/8/5PPP/1Q4K1 w - - 0 1").expect("valid fen");
+        assert_eq!(params.king_safety(&board, PieceColor::White), 3 * params.king_shield);
+        // Queen on b1 attacks h7 next to the black king
+        assert_eq!(params.king_safety(&board, PieceColor::Black), 3 * params.king_shield - params.king_attack);
+
+        // Pawns which have advanced past the shield don't count
+        board.parse_fen("6k1/8/5ppp/8/8/7P/5P2/6K1 w - - 0 1").expect("valid fen");
+        assert_eq!(params.king_safety(&board, PieceColor::White), 2 * params.king_shield);
+        assert_eq!(params.king_safety(&board, PieceColor::Black), 3 * params.king_shield);
+    }
+
+    #[test]
+    fn test_king_attackers_info() {
+        assert_eq!(KING_ZONES[PieceColor::White as usize][Square::G1 as usize].count_ones(), 9);
+        assert_eq!(KING_ZONES[PieceColor::Black as usize][Square::G8 as usize].count_ones(), 9);
+        assert_eq!(KING_ZONES[PieceColor::White as usize][Square::E4 as usize].count_ones(), 12);
+
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        assert_eq!(king_attackers_info(&board, PieceColor::White), KingAttackersInfo::default());
+
+        // The rook attacks g1, g2 and g3, the pawn attacks g2 while e2 is outside the zone
+        board.parse_fen("6k1/6r1/8/8/8/5p2/8/6K1 w - - 0 1").expect("valid fen");
+        let info = king_attackers_info(&board, PieceColor::White);
+        let (pawn, rook) = (PieceType::Pawn.get_index(), PieceType::Rook.get_index());
+        assert_eq!(info.attackers[pawn], 1);
+        assert_eq!(info.attackers[rook], 1);
+        assert_eq!(info.attack_weights[pawn], KING_ATTACK_WEIGHTS[pawn]);
+        assert_eq!(info.attack_weights[rook], 3 * KING_ATTACK_WEIGHTS[rook]);
+        assert_eq!(info.attacker_count(), 2);
+    }
+}
@@ -0,0 +1,163 @@
+//! A small embedded corpus of famous games for documentation examples, benchmarks and tests.
+//!
+//! # Examples
+//! ```rust
+//! use bitschess::prelude::*;
+//! use bitschess::fixtures;
+//! let board = fixtures::replay("opera").expect("known game");
+//! assert!(board.is_check_mate());
+//! assert_eq!(board.get_move_history().len(), 33);
+//! ```
+
+use crate::bitschess::board::ChessBoard;
+use crate::bitschess::board::fen::STARTPOS_FEN;
+use crate::bitschess::board::pgn::Pgn;
+
+/// An embedded game, every game starts from the standard starting position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixture {
+    /// Short name used for looking the game up, e.g. "opera".
+    pub name: &'static str,
+    pub pgn: &'static str,
+}
+
+pub const GAMES: [Fixture; 5] = [
+    Fixture {
+        name: "opera",
+        pgn: r#"[Event "Casual game"]
+[Site "Paris FRA"]
+[Date "1858.??.??"]
+[White "Paul Morphy"]
+[Black "Duke Karl / Count Isouard"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 d6 3. d4 Bg4?! 4. dxe5 Bxf3 5. Qxf3 dxe5 6. Bc4 Nf6 7. Qb3 Qe7
+8. Nc3 c6 9. Bg5 b5?! 10. Nxb5! cxb5 11. Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7! Rxd7
+14. Rd1 Qe6 15. Bxd7+ Nxd7 16. Qb8+!! {The queen sacrifice clears the way for the rook.} Nxb8 17. Rd8# 1-0"#,
+    },
+    Fixture {
+        name: "immortal",
+        pgn: r#"[Event "London"]
+[Site "London ENG"]
+[Date "1851.06.21"]
+[White "Adolf Anderssen"]
+[Black "Lionel Kieseritzky"]
+[Result "1-0"]
+
+1. e4 e5 2. f4 exf4 3. Bc4 Qh4+ 4. Kf1 b5?! 5. Bxb5 Nf6 6. Nf3 Qh6 7. d3 Nh5
+8. Nh4 Qg5 9. Nf5 c6 10. g4 Nf6 11. Rg1! cxb5 12. h4 Qg6 13. h5 Qg5 14. Qf3 Ng8
+15. Bxf4 Qf6 16. Nc3 Bc5 17. Nd5 Qxb2 18. Bd6!! {Both rooks are left hanging.} Bxg1
+19. e5 Qxa1+ 20. Ke2 Na6 21. Nxg7+ Kd8 22. Qf6+!! Nxf6 23. Be7# 1-0"#,
+    },
+    Fixture {
+        name: "evergreen",
+        pgn: r#"[Event "Berlin"]
+[Site "Berlin GER"]
+[Date "1852.??.??"]
+[White "Adolf Anderssen"]
+[Black "Jean Dufresne"]
+[Result "1-0"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. b4 {The Evans Gambit.} Bxb4 5. c3 Ba5 6. d4 exd4
+7. O-O d3 8. Qb3 Qf6 9. e5 Qg6 10. Re1 Nge7 11. Ba3 b5 12. Qxb5 Rb8 13. Qa4 Bb6
+14. Nbd2 Bb7 15. Ne4 Qf5 16. Bxd3 Qh5 17. Nf6+ gxf6 18. exf6 Rg8 19. Rad1! Qxf3
+20. Rxe7+! Nxe7 21. Qxd7+!! Kxd7 22. Bf5+ Ke8 23. Bd7+ Kf8 24. Bxe7# 1-0"#,
+    },
+    Fixture {
+        name: "century",
+        pgn: r#"[Event "Third Rosenwald Trophy"]
+[Site "New York, NY USA"]
+[Date "1956.10.17"]
+[White "Donald Byrne"]
+[Black "Robert James Fischer"]
+[Result "0-1"]
+
+1. Nf3 Nf6 2. c4 g6 3. Nc3 Bg7 4. d4 O-O 5. Bf4 d5 6. Qb3 dxc4 7. Qxc4 c6 8. e4 Nbd7
+9. Rd1 Nb6 10. Qc5 Bg4 11. Bg5? Na4!! 12. Qa3 Nxc3 13. bxc3 Nxe4 14. Bxe7 Qb6
+15. Bc4 Nxc3 16. Bc5 Rfe8+ 17. Kf1 Be6!! {Fischer gives up his queen.} 18. Bxb6 Bxc4+
+19. Kg1 Ne2+ 20. Kf1 Nxd4+ 21. Kg1 Ne2+ 22. Kf1 Nc3+ 23. Kg1 axb6 24. Qb4 Ra4
+25. Qxb6 Nxd1 26. h3 Rxa2 27. Kh2 Nxf2 28. Re1 Rxe1 29. Qd8+ Bf8 30. Nxe1 Bd5
+31. Nf3 Ne4 32. Qb8 b5 33. h4 h5 34. Ne5 Kg7 35. Kg1 Bc5+ 36. Kf1 Ng3+ 37. Ke1 Bb4+
+38. Kd1 Bb3+ 39. Kc1 Ne2+ 40. Kb1 Nc3+ 41. Kc1 Rc2# 0-1"#,
+    },
+    Fixture {
+        name: "deep-blue",
+        pgn: r#"[Event "IBM Man-Machine, Game 6"]
+[Site "New York, NY USA"]
+[Date "1997.05.11"]
+[White "Deep Blue"]
+[Black "Garry Kasparov"]
+[Result "1-0"]
+
+1. e4 c6 2. d4 d5 3. Nc3 dxe4 4. Nxe4 Nd7 5. Ng5 Ngf6 6. Bd3 e6 7. N1f3 h6
+8. Nxe6! {A well known sacrifice, Kasparov mixed up the move order.} Qe7 9. O-O fxe6
+10. Bg6+ Kd8 11. Bf4 b5 12. a4 Bb7 13. Re1 Nd5 14. Bg3 Kc8 15. axb5 cxb5 16. Qd3 Bc6
+17. Bf5 exf5 18. Rxe7 Bxe7 19. c4 {Black resigned.} 1-0"#,
+    },
+];
+
+/// Parsed game of the fixture called `name`, [None] if there's no such fixture.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::fixtures;
+/// let pgn = fixtures::pgn("immortal").expect("known game");
+/// assert_eq!(pgn.get_tag("White"), Some(&String::from("Adolf Anderssen")));
+/// assert_eq!(pgn.get_moves().last(), Some(&String::from("Be7#")));
+/// assert!(fixtures::pgn("unknown").is_none());
+/// ```
+#[must_use]
+pub fn pgn(name: &str) -> Option<Pgn> {
+    let fixture = GAMES.iter().find(|fixture| fixture.name == name)?;
+    Some(Pgn::parse_string(fixture.pgn).expect("fixtures are valid pgn"))
+}
+
+/// A board with every move of the fixture called `name` made on it, [None] if there's no such fixture.
+/// The earlier positions of the game can be reached with [ChessBoard::unmake_to_ply].
+///
+/// # Examples
+/// ```rust
+/// use bitschess::fixtures;
+/// let mut board = fixtures::replay("deep-blue").expect("known game");
+/// assert!(!board.is_check_mate());
+/// // The position before 8. Nxe6
+/// let _ = board.unmake_to_ply(14).expect("the game is longer");
+/// assert_eq!(board.to_fen(), "r1bqkb1r/pp1n1pp1/2p1pn1p/6N1/3P4/3B1N2/PPP2PPP/R1BQK2R w KQkq - 0 8");
+/// ```
+#[must_use]
+pub fn replay(name: &str) -> Option<ChessBoard> {
+    let pgn = pgn(name)?;
+    let mut board = ChessBoard::new();
+    board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    for san in pgn.get_moves() {
+        board.make_move_pgn(san).expect("fixtures contain only legal moves");
+    }
+    Some(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixtures_replay() {
+        for fixture in GAMES {
+            let pgn = pgn(fixture.name).expect("known game");
+            let board = replay(fixture.name).expect("known game");
+            assert_eq!(board.get_move_history().len(), pgn.get_moves().len(), "{}", fixture.name);
+
+            // Every game ends either in a mate or a resignation
+            let mated = pgn.get_moves().last().is_some_and(|san| san.ends_with('#'));
+            assert_eq!(board.is_check_mate(), mated, "{}", fixture.name);
+            let expected_result = if board.get_turn() == crate::piece::PieceColor::White { "0-1" } else { "1-0" };
+            assert_eq!(pgn.get_tag("Result").map(String::as_str), Some(expected_result), "{}", fixture.name);
+        }
+    }
+
+    #[test]
+    fn test_fixtures_unique_names() {
+        for (i, fixture) in GAMES.iter().enumerate() {
+            assert!(GAMES[i + 1..].iter().all(|other| other.name != fixture.name));
+        }
+    }
+}
@@ -1,7 +1,10 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![allow(clippy::inline_always)]
 
 #![doc = include_str!("../README.md")]
 
+extern crate alloc;
+
 mod bitschess;
 
 mod board_helper;
@@ -13,12 +16,107 @@ pub use piece::*;
 mod chess_move;
 pub use chess_move::*;
 
+#[cfg(feature = "std")]
+mod uci;
+#[cfg(feature = "std")]
+pub use uci::*;
+
+#[cfg(feature = "std")]
+pub mod bench;
+
+pub mod clock;
+
+pub mod endgame;
+
+pub mod eval;
+
+pub mod move_ordering;
+
+#[cfg(feature = "std")]
+pub mod fixtures;
+
+#[cfg(feature = "std")]
+pub mod search;
+
+#[cfg(feature = "std")]
+pub mod mcts;
+
+#[cfg(feature = "std")]
+pub mod selfplay;
+
+pub mod solver;
+
+pub mod testing;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+/// Commonly used types, import everything with `use bitschess::prelude::*;`.
+///
+/// Items are re-exported here one by one and are kept stable between versions,
+/// the modules they live in are an implementation detail and may be moved around.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// let mut board = ChessBoard::new();
+/// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+/// let m: Move = "e2e4".parse().expect("valid uci");
+/// assert_eq!(board.make_move_uci(&m.to_string()), Ok(Move::new(Square::E2 as i32, Square::E4 as i32, MoveFlag::PawnTwoUp)));
+/// ```
 pub mod prelude {
-    pub use super::board_helper::*;
-    pub use super::bitschess::board::*;
-    pub use super::bitschess::board::fen::*;
-    pub use super::bitschess::bitboard::*;
-    pub use super::chess_move::*;
-    pub use super::piece::*;
+    // Board
+    pub use super::bitschess::board::{ChessBoard, DrawClaim, GamePosition, Positions, PromotionPolicy, CHESSBOARD_WIDTH};
+    pub use super::bitschess::board::builder::ChessBoardBuilder;
+    pub use super::bitschess::board::crazyhouse::Pockets;
+    pub use super::bitschess::board::diff::SquareChange;
+    pub use super::bitschess::board::edit::BoardEditor;
+    pub use super::bitschess::board::game_state::{GameResult, GameState};
+    #[cfg(feature = "json")]
+    pub use super::bitschess::board::json::JsonState;
+    pub use super::bitschess::board::material::PIECE_VALUES;
+    pub use super::bitschess::board::observer::BoardObserver;
+    pub use super::bitschess::board::position_key::PositionKey;
+    pub use super::bitschess::board::square_control::{SquareAttacker, SquareControl};
+    pub use super::bitschess::board::state::BoardState;
+    pub use super::bitschess::board::ui_adapter::BoardUiAdapter;
+    pub use super::bitschess::board::variant::{Variant, VariantKind};
+    pub use super::bitschess::bitboard::{BitBoard, Direction};
+    pub use super::board_helper::{BoardHelper, Square};
+
+    // Notation
+    pub use super::bitschess::board::fen::{FenParseOptions, FenWarning, STARTPOS_FEN, STARTPOS_FEN_BLACK};
+    #[cfg(feature = "std")]
+    pub use super::bitschess::board::notation::Notation;
+    #[cfg(feature = "std")]
+    pub use super::bitschess::board::pgn::{Annotation, Game, Pgn, PgnExportOptions, PgnParser};
+
+    // Clocks
+    pub use super::clock::{GameClock, TimeControl, TimeStage};
+
+    // Pieces & moves
+    pub use super::chess_move::{Move, MoveContainer, MoveFlag, ReversibleMove};
+    pub use super::bitschess::board::move_generation::{GenType, LegalMoveIter, MoveGenInfo, MoveGenerator, MoveStage, StagedMoveGen};
+    pub use super::piece::{Piece, PieceColor, PieceType};
+
+    // Containers
+    pub use super::bitschess::board::transposition_table::{ReplacementScheme, TranspositionEntry, TranspositionTable};
+
+    // Errors
+    pub use super::bitschess::board::MoveError;
+    pub use super::bitschess::board::builder::BoardBuildError;
+    pub use super::bitschess::board::fen::FenParsingError;
+    #[cfg(feature = "std")]
+    pub use super::bitschess::board::pgn::{PGNParserError, PgnImportError};
+    pub use super::chess_move::MoveParseError;
+    pub use super::clock::TimeControlParseError;
+    pub use super::board_helper::{SquareError, SquareParseError};
+}
+
+/// Internal building blocks of the move generator, no stability guarantees.
+#[doc(hidden)]
+pub mod internals {
+    pub use super::bitschess::bitboard;
+    pub use super::bitschess::board::{magics, move_generation, zobrist};
 }
 
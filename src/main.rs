@@ -1,36 +1,310 @@
 #![allow(clippy::inline_always)]
 
-mod bitschess;
-mod board_helper;
-mod chess_move;
-mod piece;
+use std::io::Read;
+use std::process::ExitCode;
+use std::sync::atomic::AtomicBool;
 
-use board_helper::BoardHelper;
-use bitschess::board::fen::STARTPOS_FEN;
-use bitschess::board::ChessBoard;
-use bitschess::bitboard::BitBoard;
+use bitschess::prelude::*;
+use bitschess::internals::move_generation::MoveGenerator;
+use bitschess::search::{search, SearchLimits};
+use bitschess::Uci;
 
-fn main() {
+const USAGE: &str = "\
+Usage: bitschess-bin [--json] [COMMAND] [OPTIONS]
+
+Commands:
+  repl                                 Interactive prompt (default), type 'help' for its commands
+  perft --depth <N> [--fen <FEN>]      Counts the leaf nodes of the move tree for every root move
+  analyze [--fen <FEN>] [--depth <N>]  Game state and legal moves, with '--depth' also the best move
+  pgn-to-fen [FILE] [--all]            FEN of the final position of every game in FILE or stdin,
+                                       with '--all' the FEN after every move
+  bench [--depth <N>]                  Search of a fixed set of positions to a depth
+  bench-pgn [FILE]                     Games imported per second from FILE or the built-in corpus
+  uci                                  Speaks the Universal Chess Interface protocol
+  help                                 Prints this message
+
+Options:
+  --json      Prints JSON objects instead of text
+  -h, --help  Prints this message";
+
+const REPL_HELP: &str = "\
+<move>             Makes a move in UCI (e2e4) or SAN (Nf3) notation, promotes to a queen by default
+undo               Takes back the last move
+board | fen | pgn  Prints the position
+moves <square>     Legal moves of the piece on the square
+checked <square>   If the square is attacked by the opponent
+go perft <depth>   Perft of the position
+analyse            Game state and legal moves
+bench [depth]      Search of a fixed set of positions to a depth
+attackmask | checkmask | pinmask
+cpu-ins            Instruction sets the binary was compiled with
+uci                Switches to the UCI protocol
+exit | quit";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Repl,
+    Perft { fen: String, depth: u32 },
+    Analyze { fen: String, depth: Option<u32> },
+    PgnToFen { path: Option<String>, all: bool },
+    Bench { depth: u32 },
+    BenchPgn { path: Option<String> },
+    Uci,
+    Help,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cli {
+    command: Command,
+    json: bool,
+}
+
+/// Parses the command line arguments, without the program name.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Cli, String> {
+    let mut json = false;
+    let mut help = false;
+    let mut command: Option<String> = None;
+    let mut fen: Option<String> = None;
+    let mut depth: Option<u32> = None;
+    let mut all = false;
+    let mut positional: Vec<String> = vec![];
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => { json = true; }
+            "-h" | "--help" => { help = true; }
+            "--all" => { all = true; }
+            "--fen" => {
+                fen = Some(args.next().ok_or("'--fen' needs a value")?);
+            }
+            "--depth" => {
+                let value = args.next().ok_or("'--depth' needs a value")?;
+                depth = Some(value.parse().map_err(|_| format!("invalid depth '{value}'"))?);
+            }
+            _ if arg.starts_with('-') => { return Err(format!("unknown option '{arg}'")); }
+            _ if command.is_none() => { command = Some(arg); }
+            _ => { positional.push(arg); }
+        }
+    }
+
+    let name = command.as_deref().unwrap_or("repl");
+    let command = match name {
+        _ if help => { return Ok(Cli { command: Command::Help, json }); }
+        "repl" => { Command::Repl }
+        "perft" => {
+            let depth = depth.take().ok_or("'perft' needs '--depth <N>'")?;
+            Command::Perft { fen: fen.take().unwrap_or_else(|| STARTPOS_FEN.to_string()), depth }
+        }
+        "analyze" | "analyse" => {
+            Command::Analyze { fen: fen.take().unwrap_or_else(|| STARTPOS_FEN.to_string()), depth: depth.take() }
+        }
+        "pgn-to-fen" => {
+            let path = if positional.len() <= 1 { positional.pop() } else { None };
+            Command::PgnToFen { path, all: core::mem::take(&mut all) }
+        }
+        "bench-pgn" => {
+            let path = if positional.len() <= 1 { positional.pop() } else { None };
+            Command::BenchPgn { path }
+        }
+        "bench" => { Command::Bench { depth: depth.take().unwrap_or(bitschess::bench::BENCH_DEPTH) } }
+        "uci" => { Command::Uci }
+        "help" => { Command::Help }
+        _ => { return Err(format!("unknown command '{name}'")); }
+    };
+
+    if fen.is_some() || depth.is_some() || all || !positional.is_empty() {
+        return Err(format!("unexpected arguments for '{name}'"));
+    }
+    Ok(Cli { command, json })
+}
+
+/// Escapes a string into a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => { escaped.push_str("\\\""); }
+            '\\' => { escaped.push_str("\\\\"); }
+            '\n' => { escaped.push_str("\\n"); }
+            '\r' => { escaped.push_str("\\r"); }
+            '\t' => { escaped.push_str("\\t"); }
+            // Rest of the control characters have no short escape
+            c if (c as u32) < 0x20 => { escaped.push_str(&format!("\\u{:04x}", c as u32)); }
+            _ => { escaped.push(c); }
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn json_moves(moves: &MoveContainer) -> String {
+    let moves: Vec<String> = moves.iter().map(|m| json_string(&m.to_uci())).collect();
+    format!("[{}]", moves.join(","))
+}
+
+fn board_from_fen(fen: &str) -> Result<ChessBoard, String> {
+    let mut board = ChessBoard::new();
+    board.parse_fen(fen).map_err(|e| format!("invalid fen '{fen}': {e:?}"))?;
+    Ok(board)
+}
+
+fn print_perft(board: &mut ChessBoard, depth: u32, json: bool) {
+    let begin = std::time::Instant::now();
+    let divide = if depth == 0 { vec![] } else { board.perft_divide(depth) };
+    let nodes: u64 = if depth == 0 { 1 } else { divide.iter().map(|(_, nodes)| nodes).sum() };
+    let duration = begin.elapsed();
+
+    if json {
+        let divide: Vec<String> = divide.iter().map(|(m, nodes)| format!("{}:{nodes}", json_string(&m.to_uci()))).collect();
+        println!("{{\"depth\":{depth},\"nodes\":{nodes},\"time_ms\":{},\"divide\":{{{}}}}}", duration.as_millis(), divide.join(","));
+    } else {
+        for (m, nodes) in &divide {
+            println!("{}: {nodes}", m.to_uci());
+        }
+        println!("\nNodes searched: {nodes}");
+        println!("perft took: {duration:?}");
+    }
+}
+
+fn print_bench(depth: u32, json: bool) {
+    let result = bitschess::bench::run(depth);
+    if json {
+        let nodes: Vec<String> = result.position_nodes.iter().map(u64::to_string).collect();
+        println!("{{\"depth\":{depth},\"nodes\":{},\"nps\":{},\"time_ms\":{},\"position_nodes\":[{}]}}", result.nodes, result.nps(), result.duration.as_millis(), nodes.join(","));
+    } else {
+        for (fen, nodes) in bitschess::bench::BENCH_POSITIONS.iter().zip(&result.position_nodes) {
+            println!("{fen}: {nodes}");
+        }
+        println!("===========================");
+        println!("Total time (ms) : {}", result.duration.as_millis());
+        println!("Nodes searched  : {}", result.nodes);
+        println!("Nodes/second    : {}", result.nps());
+    }
+}
+
+fn print_bench_pgn(path: Option<&str>, json: bool) -> Result<(), String> {
+    let contents = match path {
+        Some(path) => { std::fs::read_to_string(path).map_err(|e| format!("couldn't read '{path}': {e}"))? }
+        None => { bitschess::bench::pgn_corpus(bitschess::bench::PGN_BENCH_COPIES) }
+    };
+
+    let result = bitschess::bench::run_pgn(&contents);
+    if json {
+        println!(
+            "{{\"games\":{},\"moves\":{},\"errors\":{},\"time_ms\":{},\"games_per_second\":{}}}",
+            result.games, result.moves, result.errors, result.duration.as_millis(), result.games_per_second()
+        );
+    } else {
+        println!("Games imported  : {}", result.games);
+        println!("Moves played    : {}", result.moves);
+        println!("Invalid games   : {}", result.errors);
+        println!("Total time (ms) : {}", result.duration.as_millis());
+        println!("Games/second    : {}", result.games_per_second());
+    }
+    Ok(())
+}
+
+/// Game state and the legal moves, with a `depth` the position is also searched.
+fn print_analysis(board: &mut ChessBoard, depth: Option<u32>, json: bool) {
+    let turn = if board.get_turn() == PieceColor::White { "white" } else { "black" };
+    let in_check = board.is_king_in_check(board.get_turn());
+    let legal_moves = board.get_legal_moves();
+    let check_mate = in_check && legal_moves.is_empty();
+    let stale_mate = !in_check && legal_moves.is_empty();
+    let draw = board.can_claim_draw();
+    let result = depth.map(|depth| search(board, SearchLimits { depth: Some(depth), ..Default::default() }, &AtomicBool::new(false)));
+
+    if json {
+        let search = result.map_or(String::new(), |result| {
+            let best = result.best.map_or(String::from("null"), |m| json_string(&m.to_uci()));
+            let pv: Vec<String> = result.pv.iter().map(|m| json_string(&m.to_uci())).collect();
+            format!(",\"best\":{best},\"score\":{},\"pv\":[{}]", json_string(&result.score.to_string()), pv.join(","))
+        });
+        println!(
+            "{{\"fen\":{},\"turn\":\"{turn}\",\"in_check\":{in_check},\"checkmate\":{check_mate},\"stalemate\":{stale_mate},\"draw\":{draw},\"legal_moves\":{}{search}}}",
+            json_string(&board.to_fen()), json_moves(&legal_moves)
+        );
+    } else {
+        println!("FEN: {}", board.to_fen());
+        println!("turn: {turn}");
+        println!("in check: {in_check}");
+        println!("checkmate: {check_mate}");
+        println!("stalemate: {stale_mate}");
+        println!("draw: {draw}");
+        println!("legal moves ({}): {:?}", legal_moves.len(), legal_moves.iter().map(|m| m.to_uci()).collect::<Vec<String>>());
+        if let Some(result) = result {
+            println!("best move: {}", result.best.map_or(String::from("none"), |m| m.to_uci()));
+            println!("score: {}", result.score);
+            println!("pv: {}", result.pv.iter().map(|m| m.to_uci()).collect::<Vec<String>>().join(" "));
+        }
+    }
+}
+
+/// Prints the FEN of the final position of every game, or of every position with `all`.
+fn pgn_to_fen(path: Option<&str>, all: bool, json: bool) -> Result<(), String> {
+    let contents = match path {
+        Some(path) => { std::fs::read_to_string(path).map_err(|e| format!("couldn't read '{path}': {e}"))? }
+        None => {
+            let mut contents = String::new();
+            std::io::stdin().read_to_string(&mut contents).map_err(|e| format!("couldn't read stdin: {e}"))?;
+            contents
+        }
+    };
+
+    for (i, game) in PgnParser::new(&contents).enumerate() {
+        let game = game.map_err(|e| format!("game {}: {e:?}", i + 1))?;
+        let mut board = board_from_fen(game.starting_fen()).map_err(|e| format!("game {}: {e}", i + 1))?;
+
+        let mut fens = vec![board.to_fen()];
+        for san in game.get_moves() {
+            board.make_move_pgn(san).map_err(|e| format!("game {}: couldn't play '{san}': {e:?}", i + 1))?;
+            fens.push(board.to_fen());
+        }
+        if !all {
+            fens.drain(..fens.len() - 1);
+        }
+
+        if json {
+            let fens: Vec<String> = fens.iter().map(|fen| json_string(fen)).collect();
+            println!("{{\"game\":{},\"fens\":[{}]}}", i + 1, fens.join(","));
+        } else {
+            for fen in fens {
+                println!("{fen}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn repl(json_output: bool) {
     let mut board = ChessBoard::new();
-    println!("Welcome to BitChess' interface!");
-    
+    if !json_output {
+        println!("Welcome to BitChess' interface! Type 'help' for the commands.");
+    }
+
     board.parse_fen(STARTPOS_FEN).expect("valid fen");
 
-    loop {
-        let line = std::io::stdin().lines().next().expect("").expect("");
+    for line in std::io::stdin().lines() {
+        let Ok(line) = line else { break; };
+        let line = line.trim();
         let args: Vec<&str> = line.split(' ').collect();
 
-        if &line == "exit" {
+        if line == "exit" || line == "quit" {
             break;
         }
-        else if &line == "board" {
+        else if line.is_empty() {}
+        else if line == "help" {
+            println!("{REPL_HELP}");
+        }
+        else if line == "board" {
             println!("{board}");
         }
-        else if &line == "pgn" {
+        else if line == "pgn" {
             println!("{:?}", board.to_pgn());
             println!("{}", board.to_pgn().to_string());
         }
-        else if &line == "undo" {
+        else if line == "undo" {
             let m = board.unmake_move();
             if let Some(made_move) = m {
                 println!("undid {}", made_move.to_uci());
@@ -38,57 +312,67 @@ fn main() {
                 println!("nothing to undo");
             }
         }
-        else if args.len() == 2 && args[0] == "checked" {
-            if let Some(square_str) = args.last() {
-                let square = BoardHelper::text_to_square(&square_str[0..2]);
-                println!("is {square} checked?: {}", board.is_square_in_check(board.get_turn(), square));
-            }
+        else if args.len() == 2 && args[0] == "checked" && BoardHelper::text_to_square(args[1]).is_ok() {
+            let square = BoardHelper::text_to_square(args[1]).expect("valid square") as i32;
+            println!("is {square} checked?: {}", board.is_square_in_check(board.get_turn(), square));
         }
         else if args.len() == 3 && args[0] == "go" && args[1] == "perft" {
-            match args.last().expect(":^(").parse::<u32>() {
-                Ok(depth) => {
-                    let begin = std::time::Instant::now();
-                    board.perft(depth, true);
-                    let duration = begin.elapsed();
-
-                    println!("perft took: {duration:?}");
+            match args[2].parse::<u32>() {
+                Ok(depth) => { print_perft(&mut board, depth, json_output); }
+                Err(_) if json_output => {
+                    println!("{{\"error\":\"error while parsing numerical value\"}}");
                 }
                 Err(_) => {
                     println!("error while parsing numerical value");
                 }
             }
         }
-        else if args[0] == "attackmask" {
-            use crate::bitschess::board::move_generation::MoveGenerator;
+        else if args[0] == "bench" && args.len() <= 2 {
+            let depth = args.get(1).and_then(|d| d.parse::<u32>().ok()).unwrap_or(bitschess::bench::BENCH_DEPTH);
+            print_bench(depth, json_output);
+        }
+        else if line == "attackmask" {
             let atk = MoveGenerator::get_attack_mask(&board);
             println!("{}", BitBoard::pretty(atk));
         }
-        else if args[0] == "checkmask" {
-            use crate::bitschess::board::move_generation::MoveGenerator;
+        else if line == "checkmask" {
             let (double_check, all_pieces) = MoveGenerator::get_check_mask(&board);
             println!("double_check: {double_check}\n {}", BitBoard::pretty(all_pieces));
         }
-        else if args[0] == "pinmask" {
-            use crate::bitschess::board::move_generation::MoveGenerator;
+        else if line == "pinmask" {
             let (hv, d12) = MoveGenerator::get_pinned_mask(&board);
 
             println!("HorizontalVertical: \n{}", BitBoard::pretty(hv));
-            
+
             println!("Diagonal: \n{}", BitBoard::pretty(d12));
         }
-        else if args[0] == "fen" {
-            println!("FEN: {}", board.to_fen());
+        else if line == "fen" {
+            if json_output {
+                println!("{{\"fen\":{}}}", json_string(&board.to_fen()));
+            } else {
+                println!("FEN: {}", board.to_fen());
+            }
         }
-        else if args.len() == 2 && args[0] == "moves" {
-            if let Some(square_str) = args.last() {
-                let square = BoardHelper::text_to_square(&square_str[0..2]);
+        else if args.len() == 2 && args[0] == "moves" && BoardHelper::text_to_square(args[1]).is_ok() {
+            let square = BoardHelper::text_to_square(args[1]).expect("valid square") as i32;
+            if json_output {
+                let moves = board.get_legal_moves_for_square(square);
+                println!("{{\"square\":{},\"moves\":{}}}", json_string(&BoardHelper::square_to_string(square)), json_moves(&moves));
+            } else {
                 board.print_legal_moves_for_square(square);
             }
         }
-        else if &line == "quit" {
+        else if line == "analyse" || line == "analyze" {
+            print_analysis(&mut board, None, json_output);
+        }
+        else if line == "uci" {
+            // Hand over to the UCI protocol, GUIs send "uci" as their very first command.
+            let mut uci = Uci::new();
+            let _ = uci.execute(line, &mut std::io::stdout());
+            uci.run();
             return;
         }
-        else if &line == "cpu-ins" {
+        else if line == "cpu-ins" {
             println!("Allowed cpu instruction sets:");
             println!("\tAVX={}", cfg!(target_feature = "avx"));
             println!("\tAVX2={}", cfg!(target_feature = "avx2"));
@@ -99,12 +383,96 @@ fn main() {
             println!("\tSSE4.2={}", cfg!(target_feature = "sse4.2"));
             println!("\tBMI2={}", cfg!(target_feature = "bmi2"));
         }
-        else if BoardHelper::is_valid_uci_move(&line) && board.make_move_uci(&line).is_some() {}   
-        else if board.make_move_pgn(&line).is_some() {
+        else if board.make_move_uci_with(line, PromotionPolicy::Queen).is_ok() {}
+        else if board.make_move_pgn(line).is_ok() {
             println!("PGN: made move '{line}'");
         }
+        else if json_output {
+            println!("{{\"error\":{}}}", json_string(&format!("invalid command '{line}'")));
+        }
         else {
-            println!("invalid command :^(");
+            println!("invalid command '{line}', type 'help' for the commands");
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = match parse_args(std::env::args().skip(1)) {
+        Ok(cli) => { cli }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = match cli.command {
+        Command::Help => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Command::Repl => {
+            repl(cli.json);
+            Ok(())
+        }
+        Command::Uci => {
+            Uci::new().run();
+            Ok(())
+        }
+        Command::Bench { depth } => {
+            print_bench(depth, cli.json);
+            Ok(())
+        }
+        Command::Perft { fen, depth } => {
+            board_from_fen(&fen).map(|mut board| print_perft(&mut board, depth, cli.json))
+        }
+        Command::Analyze { fen, depth } => {
+            board_from_fen(&fen).map(|mut board| print_analysis(&mut board, depth, cli.json))
         }
+        Command::PgnToFen { path, all } => { pgn_to_fen(path.as_deref(), all, cli.json) }
+        Command::BenchPgn { path } => { print_bench_pgn(path.as_deref(), cli.json) }
+    };
+
+    match result {
+        Ok(()) => { ExitCode::SUCCESS }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, String> {
+        parse_args(args.iter().map(|arg| String::from(*arg)))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&[]), Ok(Cli { command: Command::Repl, json: false }));
+        assert_eq!(
+            parse(&["perft", "--depth", "3", "--fen", "8/8/8/8/8/8/8/K6k w - - 0 1", "--json"]),
+            Ok(Cli { command: Command::Perft { fen: String::from("8/8/8/8/8/8/8/K6k w - - 0 1"), depth: 3 }, json: true })
+        );
+        assert_eq!(parse(&["pgn-to-fen", "games.pgn", "--all"]).map(|cli| cli.command), Ok(Command::PgnToFen { path: Some(String::from("games.pgn")), all: true }));
+        assert_eq!(parse(&["bench", "--help"]).map(|cli| cli.command), Ok(Command::Help));
+        assert_eq!(parse(&["bench-pgn"]).map(|cli| cli.command), Ok(Command::BenchPgn { path: None }));
+
+        assert!(parse(&["perft"]).is_err());
+        assert!(parse(&["perft", "--depth", "x"]).is_err());
+        assert!(parse(&["bench", "--fen", STARTPOS_FEN]).is_err());
+        assert!(parse(&["pgn-to-fen", "a.pgn", "b.pgn"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+        assert!(parse(&["play"]).is_err());
+    }
+
+    #[test]
+    fn test_json_string_escapes() {
+        assert_eq!(json_string("e2e4"), "\"e2e4\"");
+        assert_eq!(json_string("a \"b\" \\ c"), "\"a \\\"b\\\" \\\\ c\"");
+        assert_eq!(json_string("\n\r\t"), "\"\\n\\r\\t\"");
+        assert_eq!(json_string("\u{0}\u{8}\u{1b}\u{1f} \u{7f}ä"), "\"\\u0000\\u0008\\u001b\\u001f \u{7f}ä\"");
     }
 }
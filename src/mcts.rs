@@ -0,0 +1,332 @@
+//! Monte-Carlo tree search (UCT), an alternative to the alpha-beta [search](crate::search).
+//! Takes the same [SearchLimits] and returns the same [SearchResult], so the two can be compared on equal terms.
+//!
+//! Leaves are valued by an [Evaluator], either by playing random moves until the game ends ([RandomPlayout])
+//! or by any function mapping a position into a win probability.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::bitschess::board::ChessBoard;
+use crate::chess_move::Move;
+use crate::search::{Score, SearchLimits, SearchResult, MATE_SCORE, MAX_DEPTH};
+
+/// Simulations run when neither a node nor a time limit is given.
+pub const DEFAULT_SIMULATIONS: u64 = 10_000;
+/// Win probabilities are converted into centipawns with `400 * log10(p / (1 - p))`, capped to this.
+const MAX_CENTIPAWNS: i32 = MATE_SCORE / 2;
+
+/// Values positions for the tree search.
+pub trait Evaluator {
+    /// Expected outcome for the side to move, from 0.0 (loss) to 1.0 (win), 0.5 being a draw.
+    /// The `board` may be changed, but has to be restored before returning.
+    fn evaluate(&mut self, board: &mut ChessBoard) -> f64;
+}
+
+/// Any `FnMut(&mut ChessBoard) -> f64` can be used as a value function.
+impl<F: FnMut(&mut ChessBoard) -> f64> Evaluator for F {
+    fn evaluate(&mut self, board: &mut ChessBoard) -> f64 {
+        self(board)
+    }
+}
+
+/// Plays random legal moves until the game ends. Playouts longer than `max_plies` are cut short
+/// and valued with the static [evaluation](crate::eval::evaluate) instead.
+pub struct RandomPlayout {
+    pub max_plies: u32,
+    rng: fastrand::Rng,
+}
+
+impl RandomPlayout {
+    /// The moves are picked deterministically from the `seed`.
+    #[must_use]
+    pub fn new(max_plies: u32, seed: u64) -> Self {
+        Self { max_plies, rng: fastrand::Rng::with_seed(seed) }
+    }
+}
+
+impl Default for RandomPlayout {
+    fn default() -> Self {
+        Self::new(200, 0)
+    }
+}
+
+impl Evaluator for RandomPlayout {
+    fn evaluate(&mut self, board: &mut ChessBoard) -> f64 {
+        let mut plies = 0;
+        let value = loop {
+            if let Some(value) = terminal_value(board) {
+                break value;
+            }
+            if plies == self.max_plies {
+                break win_probability(crate::eval::evaluate(board));
+            }
+
+            let moves = board.get_legal_moves();
+            let m = moves.get(self.rng.usize(..moves.len())).expect("not a terminal position");
+            board.make_move(m, true);
+            plies += 1;
+        };
+
+        for _ in 0..plies {
+            let _ = board.unmake_move();
+        }
+        // Every ply flips the point of view
+        if plies % 2 == 0 { value } else { 1.0 - value }
+    }
+}
+
+/// Tuning of the tree search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsOptions {
+    /// The exploration constant `c` of UCT, higher values try the less promising moves more often.
+    pub exploration: f64,
+}
+
+impl Default for MctsOptions {
+    fn default() -> Self {
+        Self { exploration: core::f64::consts::SQRT_2 }
+    }
+}
+
+struct Node {
+    /// Move leading to this node, `None` for the root.
+    m: Option<Move>,
+    parent: usize,
+    /// Children are stored next to each other, empty until expanded.
+    children: core::ops::Range<usize>,
+    expanded: bool,
+    /// Set for positions where the game has ended, from the point of view of the side to move.
+    terminal: Option<f64>,
+    visits: u32,
+    /// Sum of the results from the point of view of the side which made the move leading here.
+    total: f64,
+}
+
+impl Node {
+    const fn new(m: Option<Move>, parent: usize) -> Self {
+        Self { m, parent, children: 0..0, expanded: false, terminal: None, visits: 0, total: 0.0 }
+    }
+
+    fn mean(&self) -> f64 {
+        if self.visits == 0 { 0.0 } else { self.total / f64::from(self.visits) }
+    }
+}
+
+/// Same as [mcts_with], with the default [MctsOptions] and [RandomPlayout].
+///
+/// # Examples
+/// ```rust
+/// use std::sync::atomic::AtomicBool;
+/// use bitschess::prelude::*;
+/// use bitschess::search::{SearchLimits, Score};
+/// use bitschess::mcts::mcts;
+/// let mut board = ChessBoard::new();
+/// board.parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").expect("valid fen");
+/// let limits = SearchLimits { nodes: Some(2000), ..Default::default() };
+/// let result = mcts(&mut board, limits, &AtomicBool::new(false));
+/// assert_eq!(result.best.map(|m| m.to_uci()), Some(String::from("a1a8")));
+/// assert_eq!(result.score, Score::mate_in(1));
+/// ```
+pub fn mcts(board: &mut ChessBoard, limits: SearchLimits, stop: &AtomicBool) -> SearchResult {
+    mcts_with(board, limits, MctsOptions::default(), &mut RandomPlayout::default(), stop)
+}
+
+/// Runs simulations until one of the `limits` is hit or `stop` is set from another thread.
+/// `limits.nodes` is the amount of simulations and `limits.depth` caps the depth of the tree,
+/// without a node or time limit [DEFAULT_SIMULATIONS] simulations are run.
+///
+/// The best move is the most visited one, its score is the average result converted into centipawns.
+pub fn mcts_with(board: &mut ChessBoard, limits: SearchLimits, options: MctsOptions, evaluator: &mut impl Evaluator, stop: &AtomicBool) -> SearchResult {
+    mcts_with_policy(board, limits, options, evaluator, stop).0
+}
+
+/// Same as [mcts_with], but also returns the visit counts of every legal move at the root,
+/// e.g. as the policy target for training a value and policy network. Empty if the search was stopped before the first simulation.
+///
+/// # Examples
+/// ```rust
+/// use std::sync::atomic::AtomicBool;
+/// use bitschess::prelude::*;
+/// use bitschess::search::SearchLimits;
+/// use bitschess::mcts::{mcts_with_policy, MctsOptions, RandomPlayout};
+/// let mut board = ChessBoard::new();
+/// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+/// let limits = SearchLimits { nodes: Some(100), ..Default::default() };
+/// let (result, policy) = mcts_with_policy(&mut board, limits, MctsOptions::default(), &mut RandomPlayout::default(), &AtomicBool::new(false));
+/// assert_eq!(policy.len(), 20);
+/// // The first simulation only expands the root
+/// assert_eq!(policy.iter().map(|(_, visits)| u64::from(*visits)).sum::<u64>(), result.nodes - 1);
+/// ```
+pub fn mcts_with_policy(board: &mut ChessBoard, limits: SearchLimits, options: MctsOptions, evaluator: &mut impl Evaluator, stop: &AtomicBool) -> (SearchResult, Vec<(Move, u32)>) {
+    let start = Instant::now();
+    let mut result = SearchResult::default();
+    if board.legal_moves_iter().next().is_none() {
+        result.score = if board.is_king_in_check(board.get_turn()) { Score::mated_in(0) } else { Score::DRAW };
+        return (result, vec![]);
+    }
+
+    let simulations = match (limits.nodes, limits.movetime) {
+        (None, None) => { Some(DEFAULT_SIMULATIONS) }
+        (nodes, _) => { nodes }
+    };
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+
+    let mut tree = vec![Node::new(None, 0)];
+    let mut depth_reached = 0;
+    while simulations.is_none_or(|simulations| result.nodes < simulations) {
+        if stop.load(Ordering::Relaxed) {
+            result.aborted = true;
+            break;
+        }
+        if limits.movetime.is_some_and(|movetime| start.elapsed() >= movetime) {
+            break;
+        }
+
+        // Selection
+        let mut node = 0;
+        let mut depth = 0;
+        while tree[node].expanded && tree[node].terminal.is_none() {
+            node = select_child(&tree, node, options.exploration);
+            board.make_move(tree[node].m.expect("only the root has no move"), true);
+            depth += 1;
+        }
+        depth_reached = depth_reached.max(depth);
+
+        // Expansion, the value is from the point of view of the side to move at the node
+        let value = if let Some(value) = tree[node].terminal {
+            value
+        } else if let Some(value) = terminal_value(board) {
+            tree[node].terminal = Some(value);
+            value
+        } else {
+            if depth < max_depth {
+                let first = tree.len();
+                tree.extend(board.get_legal_moves().into_iter().map(|m| Node::new(Some(m), node)));
+                tree[node].children = first..tree.len();
+                tree[node].expanded = true;
+            }
+            evaluator.evaluate(board).clamp(0.0, 1.0)
+        };
+
+        // Backpropagation
+        let mut value = 1.0 - value;
+        loop {
+            tree[node].visits += 1;
+            tree[node].total += value;
+            if node == 0 {
+                break;
+            }
+            let _ = board.unmake_move();
+            node = tree[node].parent;
+            value = 1.0 - value;
+        }
+        result.nodes += 1;
+    }
+
+    // Principal variation follows the most visited moves
+    let mut node = 0;
+    while let Some(child) = tree[node].children.clone().max_by_key(|child| tree[*child].visits) {
+        if tree[child].visits == 0 {
+            break;
+        }
+        result.pv.push(tree[child].m.expect("only the root has no move"));
+        node = child;
+    }
+    result.best = result.pv.first().copied().or_else(|| board.legal_moves_iter().next());
+
+    let best = tree[0].children.clone().max_by_key(|child| tree[*child].visits);
+    result.score = match best.map(|best| &tree[best]) {
+        // A terminal loss for the opponent is always a checkmate, other endings are valued 0.5
+        Some(best) if best.terminal == Some(0.0) => { Score::mate_in(1) }
+        Some(best) if best.visits > 0 => { Score(centipawns(best.mean())) }
+        _ => { Score::DRAW }
+    };
+    result.depth = depth_reached;
+
+    let policy = tree[0].children.clone()
+        .map(|child| (tree[child].m.expect("only the root has no move"), tree[child].visits))
+        .collect();
+    (result, policy)
+}
+
+/// Upper confidence bound applied to trees, unvisited children are always tried first.
+fn select_child(tree: &[Node], node: usize, exploration: f64) -> usize {
+    let parent_visits = f64::from(tree[node].visits.max(1)).ln();
+    tree[node].children.clone().max_by(|a, b| {
+        let uct = |child: usize| {
+            let child = &tree[child];
+            if child.visits == 0 {
+                return f64::INFINITY;
+            }
+            child.mean() + exploration * (parent_visits / f64::from(child.visits)).sqrt()
+        };
+        uct(*a).total_cmp(&uct(*b))
+    }).expect("expanded nodes have children")
+}
+
+/// Result of a finished game for the side to move, `None` if the game continues.
+fn terminal_value(board: &ChessBoard) -> Option<f64> {
+    if board.legal_moves_iter().next().is_none() {
+        return Some(if board.is_king_in_check(board.get_turn()) { 0.0 } else { 0.5 });
+    }
+    if board.is_insufficient_material() || board.can_claim_draw() {
+        return Some(0.5);
+    }
+    None
+}
+
+/// Logistic curve, 400 centipawns up is roughly a 90% chance to win.
+fn win_probability(centipawns: i32) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-f64::from(centipawns) / 400.0))
+}
+
+fn centipawns(win_probability: f64) -> i32 {
+    let p = win_probability.clamp(1e-6, 1.0 - 1e-6);
+    ((400.0 * (p / (1.0 - p)).log10()) as i32).clamp(-MAX_CENTIPAWNS, MAX_CENTIPAWNS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitschess::board::fen::STARTPOS_FEN;
+
+    #[test]
+    fn test_mcts_value_function() {
+        let mut board = ChessBoard::new();
+        // Queen on d5 is hanging to the pawn on e4
+        board.parse_fen("4k3/8/8/3q4/4P3/8/8/4K3 w - - 0 1").expect("valid fen");
+        let before = board.clone();
+
+        let mut value = |board: &mut ChessBoard| win_probability(crate::eval::evaluate(board));
+        let limits = SearchLimits { nodes: Some(300), ..Default::default() };
+        let result = mcts_with(&mut board, limits, MctsOptions::default(), &mut value, &AtomicBool::new(false));
+
+        assert_eq!(result.best.map(|m| m.to_uci()), Some(String::from("e4d5")));
+        assert_eq!(result.nodes, 300);
+        assert!(result.score.0 > 0);
+        assert!(!result.aborted);
+        assert_eq!(board, before);
+    }
+
+    #[test]
+    fn test_mcts_limits() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+
+        let result = mcts(&mut board, SearchLimits::default(), &AtomicBool::new(true));
+        assert!(result.aborted);
+        assert_eq!(result.nodes, 0);
+        assert!(result.best.is_some());
+
+        let limits = SearchLimits { nodes: Some(100), depth: Some(1), ..Default::default() };
+        let result = mcts(&mut board, limits, &AtomicBool::new(false));
+        assert_eq!(result.depth, 1);
+        assert_eq!(result.pv.len(), 1);
+        assert_eq!(board.to_fen(), STARTPOS_FEN);
+
+        board.parse_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").expect("valid fen");
+        let result = mcts(&mut board, limits, &AtomicBool::new(false));
+        assert_eq!((result.best, result.score), (None, Score::DRAW));
+    }
+}
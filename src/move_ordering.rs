@@ -0,0 +1,199 @@
+//! Move ordering heuristics for alpha-beta searches: most valuable victim - least valuable attacker for captures,
+//! and killer moves and the history heuristic for quiet moves.
+//! The [search](crate::search) orders its moves with these, engines of their own can combine them as they like.
+
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::bitschess::board::ChessBoard;
+use crate::bitschess::board::material::PIECE_VALUES;
+use crate::chess_move::Move;
+
+/// Scores of the [HistoryTable] stay within `-MAX_HISTORY..=MAX_HISTORY`.
+pub const MAX_HISTORY: i32 = 16384;
+
+/// Most valuable victim - least valuable attacker score of the `m`, made in the `board` before the move.
+/// Captures score above 0 (capturing a queen with a pawn the highest), other moves score 0.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use bitschess::move_ordering::score_mvv_lva;
+/// let board = ChessBoard::from_fen("4k3/8/8/3q4/4P3/2N5/8/4K3 w - - 0 1").expect("valid fen");
+/// let pawn_takes_queen = score_mvv_lva(&board, Move::from_uci("e4d5"));
+/// let knight_takes_queen = score_mvv_lva(&board, Move::from_uci("c3d5"));
+/// assert!(pawn_takes_queen > knight_takes_queen && knight_takes_queen > 0);
+/// assert_eq!(score_mvv_lva(&board, Move::from_uci("e4e5")), 0);
+/// ```
+#[must_use]
+pub const fn score_mvv_lva(board: &ChessBoard, m: Move) -> i32 {
+    match board.captured_piece(m) {
+        Some(victim) => {
+            let attacker = board.moved_piece(m).get_piece_type();
+            10 * PIECE_VALUES[victim.get_piece_type() as usize] - PIECE_VALUES[attacker as usize]
+        }
+        None => { 0 }
+    }
+}
+
+/// Quiet moves which caused a beta cutoff, 2 per ply, the latest first.
+/// Siblings of a node often get refuted by the same move, so these are tried right after the captures.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use bitschess::move_ordering::KillerMoves;
+/// let mut killers = KillerMoves::new();
+/// killers.store(3, Move::from_uci("g1f3"));
+/// killers.store(3, Move::from_uci("b1c3"));
+/// killers.store(3, Move::from_uci("b1c3"));
+/// assert_eq!(killers.slot(3, Move::from_uci("b1c3")), Some(0));
+/// assert_eq!(killers.slot(3, Move::from_uci("g1f3")), Some(1));
+/// assert_eq!(killers.slot(2, Move::from_uci("g1f3")), None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KillerMoves {
+    plies: Vec<[Option<Move>; 2]>,
+}
+
+impl KillerMoves {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { plies: Vec::new() }
+    }
+
+    /// Room for `plies` plies up front, storing a killer deeper than that grows the table.
+    #[must_use]
+    pub fn with_plies(plies: usize) -> Self {
+        Self { plies: alloc::vec![[None; 2]; plies] }
+    }
+
+    /// The killers of the `ply`, the latest first.
+    #[must_use]
+    #[inline(always)]
+    pub fn get(&self, ply: u32) -> [Option<Move>; 2] {
+        self.plies.get(ply as usize).copied().unwrap_or([None; 2])
+    }
+
+    /// Which slot the `m` is in at the `ply`, 0 being the latest killer.
+    #[must_use]
+    #[inline(always)]
+    pub fn slot(&self, ply: u32, m: Move) -> Option<usize> {
+        self.get(ply).iter().position(|killer| *killer == Some(m))
+    }
+
+    /// Makes the `m` the latest killer of the `ply`, the previous one moves to the second slot.
+    pub fn store(&mut self, ply: u32, m: Move) {
+        let ply = ply as usize;
+        if ply >= self.plies.len() {
+            self.plies.resize(ply + 1, [None; 2]);
+        }
+
+        let killers = &mut self.plies[ply];
+        if killers[0] != Some(m) {
+            killers[1] = killers[0];
+            killers[0] = Some(m);
+        }
+    }
+
+    /// Forgets every killer, e.g. before searching a new position.
+    pub fn clear(&mut self) {
+        self.plies.fill([None; 2]);
+    }
+}
+
+/// How often quiet moves caused a beta cutoff, indexed by the side to move and the move's from and to squares.
+/// Deeper cutoffs weigh more, and the scores are pulled towards 0 as they grow, so they stay within [MAX_HISTORY].
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use bitschess::move_ordering::HistoryTable;
+/// let board = ChessBoard::from_fen(STARTPOS_FEN).expect("valid fen");
+/// let mut history = HistoryTable::new();
+/// history.reward(&board, Move::from_uci("g1f3"), 4);
+/// history.penalize(&board, Move::from_uci("b1c3"), 4);
+/// assert!(history.get(&board, Move::from_uci("g1f3")) > 0);
+/// assert!(history.get(&board, Move::from_uci("b1c3")) < 0);
+/// assert_eq!(history.get(&board, Move::from_uci("e2e4")), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HistoryTable {
+    scores: Box<[[[i32; 64]; 64]; 2]>,
+}
+
+impl Default for HistoryTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HistoryTable {
+    #[must_use]
+    pub fn new() -> Self {
+        Self { scores: Box::new([[[0; 64]; 64]; 2]) }
+    }
+
+    /// Score of the `m` for the side to move in the `board`.
+    /// Drops are keyed by the dropped piece instead of the from square.
+    #[must_use]
+    #[inline(always)]
+    pub fn get(&self, board: &ChessBoard, m: Move) -> i32 {
+        self.scores[board.get_turn() as usize][m.get_from_idx() as usize][m.get_to_idx() as usize]
+    }
+
+    /// The `m` caused a beta cutoff in a search of `depth` plies, call before the move is made.
+    pub fn reward(&mut self, board: &ChessBoard, m: Move, depth: u32) {
+        self.update(board, m, Self::bonus(depth));
+    }
+
+    /// The `m` was searched before the move which caused a beta cutoff without causing one itself, call before the move is made.
+    pub fn penalize(&mut self, board: &ChessBoard, m: Move, depth: u32) {
+        self.update(board, m, -Self::bonus(depth));
+    }
+
+    /// Halves every score, so the history of earlier searches weighs less than that of the next one.
+    pub fn age(&mut self) {
+        self.scores.iter_mut().flatten().flatten().for_each(|score| *score /= 2);
+    }
+
+    pub fn clear(&mut self) {
+        *self.scores = [[[0; 64]; 64]; 2];
+    }
+
+    #[inline(always)]
+    fn bonus(depth: u32) -> i32 {
+        depth.saturating_mul(depth).min(MAX_HISTORY as u32) as i32
+    }
+
+    fn update(&mut self, board: &ChessBoard, m: Move, bonus: i32) {
+        let score = &mut self.scores[board.get_turn() as usize][m.get_from_idx() as usize][m.get_to_idx() as usize];
+        *score += bonus - *score * bonus.abs() / MAX_HISTORY;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_stays_bounded() {
+        let board = ChessBoard::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").expect("valid fen");
+        let m = Move::from_uci("e1e2");
+        let mut history = HistoryTable::new();
+        for _ in 0..1000 {
+            history.reward(&board, m, 200);
+        }
+        assert_eq!(history.get(&board, m), MAX_HISTORY);
+
+        history.penalize(&board, m, 10);
+        assert!(history.get(&board, m) < MAX_HISTORY);
+        history.age();
+        assert!(history.get(&board, m) < MAX_HISTORY / 2);
+
+        // Black's moves are kept apart
+        let black = ChessBoard::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 1").expect("valid fen");
+        assert_eq!(history.get(&black, m), 0);
+        history.clear();
+        assert_eq!(history.get(&board, m), 0);
+    }
+}
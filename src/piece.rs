@@ -1,3 +1,5 @@
+use alloc::format;
+
 /// # Type  
 /// Represents the type of a chess piece.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -17,6 +19,7 @@ pub enum PieceType {
 /// * [PieceColor::White] = 0
 /// * [PieceColor::Black] = 1
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum PieceColor {
     White = 0,
@@ -38,7 +41,7 @@ impl PieceColor {
     #[inline(always)]
     pub const fn from_u8(val: u8) -> Self {
         unsafe { 
-            std::mem::transmute(val & 0b1)
+            core::mem::transmute(val & 0b1)
         }
     }
 
@@ -58,7 +61,7 @@ impl PieceColor {
         unsafe {
             // Works, because PieceColor uses only 1 bit.
             // 0b1^1 -> 0b0, 0b0^1 -> 0b1
-            std::mem::transmute(self as u8 ^ 0b1)
+            core::mem::transmute(self as u8 ^ 0b1)
         }
     }
 
@@ -215,7 +218,7 @@ impl PieceType {
     #[inline(always)]
     pub const fn from_u8(val: u8) -> Self {
         unsafe { 
-            std::mem::transmute(val & 0b111)
+            core::mem::transmute(val & 0b111)
         }
     }
 
@@ -230,6 +233,7 @@ impl PieceType {
 /// * bits 3-6 are unused.
 /// * bit 7 is used for color (0 is white, 1 is black)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece(pub u8);
 
 impl Piece {
@@ -303,6 +307,24 @@ impl Piece {
         ((self.0 >> 7) & 0b1) == 1
     }
 
+    /// The same piece type of the other color, a [Piece::is_none] piece stays as is.
+    ///
+    /// # Examples  
+    /// ```rust
+    /// use bitschess::Piece;
+    /// assert_eq!(Piece::from_char('N').color_flipped(), Piece::from_char('n'));
+    /// assert_eq!(Piece::from_char('q').color_flipped(), Piece::from_char('Q'));
+    /// assert_eq!(Piece::new(0).color_flipped(), Piece::new(0));
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn color_flipped(self) -> Self {
+        if self.is_none() {
+            return self;
+        }
+        Self(self.0 ^ (0b1 << 7))
+    }
+
     /// # Examples  
     /// ```rust
     /// use bitschess::Piece;
@@ -429,10 +451,22 @@ impl Piece {
         piece
     }
 
+    /// Constructs a [Piece] of the `piece_type` and the `color`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::{Piece, PieceColor, PieceType};
+    /// assert_eq!(Piece::from_type(PieceType::Knight, PieceColor::Black), Piece::from_char('n'));
+    /// ```
+    #[must_use]
+    #[inline(always)]
+    pub const fn from_type(piece_type: PieceType, color: PieceColor) -> Self {
+        Self::new((piece_type as u8 & 0b111) | (color as u8) << 7)
+    }
 }
 
-impl std::fmt::Display for Piece {
-    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Piece {
+    fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         formatter.pad(format!("{{piece: {:?}, color: {:?}}}", self.get_piece_type(), self.get_color()).as_str())
     }
 }
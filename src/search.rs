@@ -0,0 +1,885 @@
+//! Iterative deepening principal variation search with a material only evaluation.
+//! The search can always be interrupted (node budget, time limit or an external stop flag)
+//! and still returns the best move of the last completed iteration.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::bitschess::board::ChessBoard;
+use crate::bitschess::board::move_generation::{GenType, MoveGenerator};
+use crate::bitschess::board::transposition_table::{ReplacementScheme, TranspositionTable};
+use crate::bitschess::board::zobrist::{ZOBRIST_KEYS, ZOBRIST_TURN};
+use crate::chess_move::{Move, MoveContainer};
+use crate::move_ordering::{score_mvv_lva, KillerMoves};
+use crate::piece::PieceType;
+
+/// Score of checkmating at the root, a mate in `ply` plies is `MATE_SCORE - ply`.
+pub const MATE_SCORE: i32 = 30000;
+pub const MAX_DEPTH: u32 = 64;
+/// Every score at least this far from zero is a mate score.
+const MATE_THRESHOLD: i32 = MATE_SCORE - 2 * MAX_DEPTH as i32;
+
+const INFINITY: i32 = 32000;
+/// How often (in nodes) the stop flag and the clock are polled.
+const POLL_INTERVAL: u64 = 1024;
+/// Plies a [SearchStack] has room for by default, the main search and a quiescence search of the same length.
+const STACK_PLIES: usize = 2 * MAX_DEPTH as usize;
+/// Most legal moves in any chess position, see [MoveContainer].
+const MAX_MOVES: usize = 218;
+
+/// Centipawns from the point of view of the side to move, or a forced mate as [MATE_SCORE] minus the distance in plies.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::search::Score;
+/// assert_eq!(Score::mate_in(3).to_string(), "mate 2");
+/// assert_eq!(Score::mated_in(4).to_string(), "mate -2");
+/// assert_eq!(Score(-35).to_string(), "cp -35");
+/// assert!(Score::mate_in(1) > Score::mate_in(3));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Score(pub i32);
+
+impl Score {
+    pub const DRAW: Self = Self(0);
+
+    /// The side to move mates in `ply` plies.
+    #[must_use]
+    #[inline(always)]
+    pub const fn mate_in(ply: u32) -> Self {
+        Self(MATE_SCORE - ply as i32)
+    }
+
+    /// The side to move gets mated in `ply` plies.
+    #[must_use]
+    #[inline(always)]
+    pub const fn mated_in(ply: u32) -> Self {
+        Self(-MATE_SCORE + ply as i32)
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn is_mate(self) -> bool {
+        self.0.abs() >= MATE_THRESHOLD
+    }
+
+    /// Full moves until mate as in UCI's `score mate`, negative if the side to move gets mated.
+    #[must_use]
+    pub const fn mate_moves(self) -> Option<i32> {
+        if !self.is_mate() {
+            return None;
+        }
+        let plies = MATE_SCORE - self.0.abs();
+        Some(if self.0 > 0 { (plies + 1) / 2 } else { -plies / 2 })
+    }
+
+    /// Mate scores are relative to the root, but a transposition table entry can be found at any ply.
+    /// Converts the score to be relative to the position at `ply` for storing.
+    #[must_use]
+    pub const fn to_tt(self, ply: u32) -> Self {
+        if self.0 >= MATE_THRESHOLD { Self(self.0 + ply as i32) }
+        else if self.0 <= -MATE_THRESHOLD { Self(self.0 - ply as i32) }
+        else { self }
+    }
+
+    /// Inverse of [Score::to_tt].
+    #[must_use]
+    pub const fn from_tt(self, ply: u32) -> Self {
+        if self.0 >= MATE_THRESHOLD { Self(self.0 - ply as i32) }
+        else if self.0 <= -MATE_THRESHOLD { Self(self.0 + ply as i32) }
+        else { self }
+    }
+}
+
+impl std::fmt::Display for Score {
+    /// Formats the score as in UCI's `info score`, e.g. "cp 35" or "mate -2".
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.mate_moves() {
+            Some(moves) => { write!(f, "mate {moves}") }
+            None => { write!(f, "cp {}", self.0) }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The score is at least this much (fail-high).
+    Lower,
+    /// The score is at most this much (fail-low).
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SearchEntry {
+    score: Score,
+    bound: Bound,
+    best: Option<Move>,
+}
+
+/// When to stop searching, the search stops at whichever limit is hit first.
+/// Without any limits the search only stops at [MAX_DEPTH] or when the stop flag is raised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    /// Hard node budget, the search never visits more nodes than this.
+    pub nodes: Option<u64>,
+    pub movetime: Option<Duration>,
+}
+
+/// Search techniques which can be toggled, mainly for comparing their effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Principal variation search, every move after the first one is searched with a null window first.
+    pub pvs: bool,
+    /// Half width of the aspiration window around the previous iteration's score, `None` always uses a full window.
+    /// The window is doubled on every fail-high or fail-low.
+    pub aspiration_window: Option<i32>,
+    /// Late move reductions, quiet moves late in the move ordering are first searched with a reduced depth.
+    pub lmr: bool,
+    /// Amount of moves searched at full depth before reducing.
+    pub lmr_min_moves: usize,
+    /// Plies the late moves are reduced by.
+    pub lmr_reduction: u32,
+    /// Quiet moves are skipped at depths 1 and 2 when the static evaluation is further than the margin below alpha.
+    pub futility_pruning: bool,
+    /// Margin per ply of remaining depth, in centipawns.
+    pub futility_margin: i32,
+    /// If passing the turn still fails high, the position is pruned. Disabled in check and with only pawns left.
+    pub null_move_pruning: bool,
+    /// Plies the null move search is reduced by, in addition to the passed turn.
+    pub null_move_reduction: u32,
+    /// Size of the transposition table in kilobytes, 0 disables it.
+    pub hash_size_kb: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            pvs: true,
+            aspiration_window: Some(50),
+            lmr: true,
+            lmr_min_moves: 3,
+            lmr_reduction: 1,
+            futility_pruning: true,
+            futility_margin: 200,
+            null_move_pruning: true,
+            null_move_reduction: 2,
+            hash_size_kb: 4 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SearchResult {
+    /// `None` only if there are no legal moves.
+    pub best: Option<Move>,
+    pub pv: Vec<Move>,
+    pub score: Score,
+    pub nodes: u64,
+    /// Depth of the last completed iteration, 0 if not even the first one was completed.
+    pub depth: u32,
+    /// The search was stopped by the node budget, time limit or stop flag before reaching the depth limit.
+    pub aborted: bool,
+}
+
+/// Scratch space of a single ply of the search.
+#[derive(Debug, Clone)]
+struct Frame {
+    /// Moves of the node in the generation order.
+    generated: MoveContainer,
+    /// Moves of the node with their ordering scores, best first.
+    moves: Vec<(i32, Move)>,
+    /// Principal variation from this node on.
+    pv: Vec<Move>,
+}
+
+impl Frame {
+    fn new() -> Self {
+        Self { generated: MoveContainer::new(), moves: Vec::with_capacity(MAX_MOVES), pv: Vec::with_capacity(STACK_PLIES) }
+    }
+}
+
+/// Scratch buffers of the search, the move lists, principal variations and killer moves of every ply.
+/// Everything is allocated up front and reused by every node, so the search doesn't allocate while running
+/// and its memory usage is known beforehand. Each search thread needs its own stack, which can be kept around between searches.
+///
+/// # Examples
+/// ```rust
+/// use std::sync::atomic::AtomicBool;
+/// use bitschess::prelude::*;
+/// use bitschess::search::{search_with_stack, SearchLimits, SearchOptions, SearchStack};
+/// let mut board = ChessBoard::new();
+/// let mut stack = SearchStack::new();
+/// let limits = SearchLimits { depth: Some(3), ..Default::default() };
+/// for fen in [STARTPOS_FEN, "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1"] {
+///     board.parse_fen(fen).expect("valid fen");
+///     let result = search_with_stack(&mut board, limits, SearchOptions::default(), &mut stack, &AtomicBool::new(false));
+///     assert!(result.best.is_some());
+/// }
+/// assert_eq!(stack.plies(), 128);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearchStack {
+    frames: Vec<Frame>,
+    /// Quiet moves which caused a beta cutoff, tried right after the captures.
+    killers: KillerMoves,
+}
+
+impl Default for SearchStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchStack {
+    /// Room for [MAX_DEPTH] plies of search followed by as long a quiescence search.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_plies(STACK_PLIES)
+    }
+
+    /// Room for `plies` plies, a search reaching deeper than that grows the stack.
+    #[must_use]
+    pub fn with_plies(plies: usize) -> Self {
+        Self { frames: (0..plies).map(|_| Frame::new()).collect(), killers: KillerMoves::with_plies(plies) }
+    }
+
+    /// Amount of plies the stack has room for.
+    #[must_use]
+    #[inline(always)]
+    pub fn plies(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn frame(&mut self, ply: u32) -> &mut Frame {
+        let ply = ply as usize;
+        if ply >= self.frames.len() {
+            self.frames.resize_with(ply + 1, Frame::new);
+        }
+        &mut self.frames[ply]
+    }
+}
+
+struct Searcher<'a> {
+    limits: SearchLimits,
+    options: SearchOptions,
+    stop: &'a AtomicBool,
+    stack: &'a mut SearchStack,
+    start: Instant,
+    nodes: u64,
+    aborted: bool,
+    root_hint: Option<Move>,
+    table: Option<TranspositionTable<SearchEntry>>,
+    /// Hashes of the positions on the path from the root to the current node, excluding the current one.
+    path: Vec<u64>,
+}
+
+/// Searches the position until one of the `limits` is hit or `stop` is set from another thread.
+///
+/// # Examples
+/// ```rust
+/// use std::sync::atomic::AtomicBool;
+/// use bitschess::prelude::*;
+/// use bitschess::search::{search, SearchLimits, Score};
+/// let mut board = ChessBoard::new();
+/// board.parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").expect("valid fen");
+/// let limits = SearchLimits { depth: Some(3), ..Default::default() };
+/// let result = search(&mut board, limits, &AtomicBool::new(false));
+/// assert_eq!(result.best.map(|m| m.to_uci()), Some(String::from("a1a8")));
+/// assert_eq!(result.score, Score::mate_in(1));
+/// ```
+pub fn search(board: &mut ChessBoard, limits: SearchLimits, stop: &AtomicBool) -> SearchResult {
+    search_with_options(board, limits, SearchOptions::default(), stop)
+}
+
+/// Same as [search], but with the given [SearchOptions] instead of the defaults.
+pub fn search_with_options(board: &mut ChessBoard, limits: SearchLimits, options: SearchOptions, stop: &AtomicBool) -> SearchResult {
+    search_with_stack(board, limits, options, &mut SearchStack::new(), stop)
+}
+
+/// Same as [search_with_options], but uses the scratch buffers of the `stack` instead of allocating new ones.
+pub fn search_with_stack(board: &mut ChessBoard, limits: SearchLimits, options: SearchOptions, stack: &mut SearchStack, stop: &AtomicBool) -> SearchResult {
+    stack.killers.clear();
+    let mut searcher = Searcher::new(limits, options, stack, stop);
+    let mut result = SearchResult::default();
+
+    let moves = board.get_legal_moves();
+    let Some(first) = moves.get(0) else {
+        result.score = if board.is_king_in_check(board.get_turn()) { Score::mated_in(0) } else { Score::DRAW };
+        return result;
+    };
+    // Guarantees a move even if the first iteration doesn't finish.
+    result.best = Some(first);
+
+    let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+    for depth in 1..=max_depth {
+        let score = match options.aspiration_window {
+            Some(window) if depth > 1 => { searcher.aspiration(board, depth, result.score.0, window) }
+            _ => { searcher.negamax(board, depth, 0, -INFINITY, INFINITY, false) }
+        };
+        if searcher.aborted {
+            break;
+        }
+
+        let pv = &searcher.stack.frame(0).pv;
+        searcher.root_hint = pv.first().copied();
+        result.best = searcher.root_hint;
+        result.pv.clone_from(pv);
+        result.score = Score(score);
+        result.depth = depth;
+    }
+
+    result.nodes = searcher.nodes;
+    result.aborted = searcher.aborted;
+    result
+}
+
+/// How hard a position is to play for the side to move, see [complexity].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complexity {
+    /// Standard deviation of the legal moves' scores in centipawns, mate scores are capped.
+    pub eval_spread: f64,
+    /// Amount of legal moves.
+    pub branching: usize,
+    /// Share of the legal moves which are captures, promotions or give a check.
+    pub tactic_density: f64,
+    /// The above combined into a single score from 0 (nothing to think about) to 100.
+    pub score: f64,
+}
+
+/// Scores beyond this (e.g. mates) count as this much in [Complexity::eval_spread].
+const COMPLEXITY_SCORE_CAP: i32 = 1000;
+
+/// Estimates how difficult the position is by searching every legal move to `depth` plies.
+/// Positions where the moves' scores differ a lot (only a few moves hold), with many legal moves
+/// and lots of tactical moves score higher. Useful for picking training positions of a suitable difficulty.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use bitschess::search::complexity;
+/// let mut board = ChessBoard::new();
+/// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+/// let opening = complexity(&mut board, 2);
+/// assert_eq!(opening.branching, 20);
+///
+/// // Queen is hanging and there are captures and checks everywhere
+/// board.parse_fen("r1b1k2r/ppp2ppp/2n5/3qp3/1b1P4/2N2N2/PPP2PPP/R1BQKB1R w KQkq - 0 1").expect("valid fen");
+/// assert!(complexity(&mut board, 2).score > opening.score);
+/// ```
+pub fn complexity(board: &mut ChessBoard, depth: u32) -> Complexity {
+    let moves = board.get_legal_moves();
+    if moves.is_empty() {
+        return Complexity::default();
+    }
+
+    let stop = AtomicBool::new(false);
+    let options = SearchOptions { hash_size_kb: 1024, ..Default::default() };
+    let mut stack = SearchStack::new();
+    let mut searcher = Searcher::new(SearchLimits::default(), options, &mut stack, &stop);
+    let depth = depth.clamp(1, MAX_DEPTH);
+
+    let mut scores = Vec::with_capacity(moves.len());
+    let mut tactical = 0;
+    for m in moves.iter().copied() {
+        let noisy = !board.is_quiet(m);
+        searcher.make_move(board, m);
+        if noisy || board.is_king_in_check(board.get_turn()) {
+            tactical += 1;
+        }
+        let score = -searcher.negamax(board, depth - 1, 1, -INFINITY, INFINITY, true);
+        searcher.unmake_move(board);
+        scores.push(f64::from(score.clamp(-COMPLEXITY_SCORE_CAP, COMPLEXITY_SCORE_CAP)));
+    }
+
+    let count = scores.len() as f64;
+    let mean = scores.iter().sum::<f64>() / count;
+    let eval_spread = (scores.iter().map(|score| (score - mean).powi(2)).sum::<f64>() / count).sqrt();
+    let tactic_density = f64::from(tactical) / count;
+
+    // Weighted so that each part saturates at a typical sharp middlegame value.
+    let score = 50.0 * (eval_spread / 500.0).min(1.0)
+        + 30.0 * tactic_density
+        + 20.0 * (count / 50.0).min(1.0);
+    Complexity { eval_spread, branching: moves.len(), tactic_density, score }
+}
+
+/// Null move pruning is unsafe in pawn endgames where zugzwang is common.
+const fn has_non_pawn_material(board: &ChessBoard) -> bool {
+    let us = board.get_turn();
+    let pieces = board.side_bitboards[us as usize].0
+        & !board.bitboards[PieceType::Pawn.get_side_index(us)].0
+        & !board.bitboards[PieceType::King.get_side_index(us)].0;
+    pieces != 0
+}
+
+/// Passes the turn, returns the en passant square to restore with [unmake_null_move].
+fn make_null_move(board: &mut ChessBoard) -> i32 {
+    let en_passant = board.en_passant;
+    board.en_passant = -1;
+    board.turn = board.turn.flipped();
+    board.zobrist_hash ^= ZOBRIST_KEYS[ZOBRIST_TURN];
+    board.clear_legal_moves_cache();
+    en_passant
+}
+
+fn unmake_null_move(board: &mut ChessBoard, en_passant: i32) {
+    board.en_passant = en_passant;
+    board.turn = board.turn.flipped();
+    board.zobrist_hash ^= ZOBRIST_KEYS[ZOBRIST_TURN];
+    board.clear_legal_moves_cache();
+}
+
+/// Material balance from the point of view of the side to move.
+fn evaluate(board: &ChessBoard) -> i32 {
+    let us = board.get_turn();
+    board.material(us) - board.material(us.flipped())
+}
+
+impl<'a> Searcher<'a> {
+    fn new(limits: SearchLimits, options: SearchOptions, stack: &'a mut SearchStack, stop: &'a AtomicBool) -> Self {
+        let table = (options.hash_size_kb > 0).then(|| TranspositionTable::new(options.hash_size_kb, ReplacementScheme::DepthPreferred));
+        let path = Vec::with_capacity(stack.plies());
+        Self { limits, options, stop, stack, start: Instant::now(), nodes: 0, aborted: false, root_hint: None, table, path }
+    }
+
+    /// Also marks the search as aborted, after which every node returns immediately.
+    fn should_stop(&mut self) -> bool {
+        if self.aborted {
+            return true;
+        }
+
+        if self.limits.nodes.is_some_and(|budget| self.nodes >= budget) {
+            self.aborted = true;
+        }
+        else if self.nodes.is_multiple_of(POLL_INTERVAL) {
+            self.aborted = self.stop.load(Ordering::Relaxed)
+                || self.limits.movetime.is_some_and(|movetime| self.start.elapsed() >= movetime);
+        }
+        self.aborted
+    }
+
+    /// A single repetition is scored as a draw, as whatever avoided it the first time can be played again.
+    /// Covers positions repeated within the search tree and positions which have already occurred in the game.
+    fn is_repetition(&self, board: &ChessBoard) -> bool {
+        if board.repetition_count() >= 2 {
+            return true;
+        }
+
+        // Only positions with the same side to move since the last irreversible move (or null move) can repeat
+        self.path.iter().rev()
+            .take(board.half_move as usize)
+            .take_while(|hash| **hash != 0)
+            .skip(1).step_by(2)
+            .any(|hash| *hash == board.zobrist_hash)
+    }
+
+    fn make_move(&mut self, board: &mut ChessBoard, m: Move) {
+        self.path.push(board.zobrist_hash);
+        board.make_move(m, true);
+    }
+
+    fn unmake_move(&mut self, board: &mut ChessBoard) {
+        let _ = board.unmake_move();
+        self.path.pop();
+    }
+
+    /// Searches the root with a window around the `guess`, widening it until the score falls inside of it.
+    fn aspiration(&mut self, board: &mut ChessBoard, depth: u32, guess: i32, window: i32) -> i32 {
+        let mut delta = window.max(1);
+        let mut alpha = (guess - delta).max(-INFINITY);
+        let mut beta = (guess + delta).min(INFINITY);
+        loop {
+            let score = self.negamax(board, depth, 0, alpha, beta, false);
+            if self.aborted {
+                return 0;
+            }
+
+            if score <= alpha {
+                alpha = (alpha - delta).max(-INFINITY);
+            }
+            else if score >= beta {
+                beta = (beta + delta).min(INFINITY);
+            }
+            else {
+                return score;
+            }
+            delta *= 2;
+        }
+    }
+
+    /// Generates the moves of the `gen_type` into the frame of the `ply`, returns the amount of moves.
+    fn generate_moves(&mut self, board: &ChessBoard, ply: u32, gen_type: GenType) -> usize {
+        let generated = &mut self.stack.frame(ply).generated;
+        MoveGenerator::generate_into(board, generated, gen_type);
+        generated.len()
+    }
+
+    /// Orders the generated moves of the `ply`, returns the amount of moves.
+    /// The `hint` (e.g. the previous best move) goes first, then captures by most valuable victim - least valuable attacker and then the killer moves.
+    fn order_moves(&mut self, board: &ChessBoard, ply: u32, hint: Option<Move>) -> usize {
+        let killers = self.stack.killers.get(ply);
+        let Frame { generated, moves, .. } = self.stack.frame(ply);
+        moves.clear();
+        moves.extend(generated.iter().map(|&m| {
+            let mvv_lva = score_mvv_lva(board, m);
+            let score = if hint == Some(m) {
+                INFINITY
+            } else if mvv_lva > 0 {
+                mvv_lva
+            } else if let Some(slot) = killers.iter().position(|killer| *killer == Some(m)) {
+                -(slot as i32)
+            } else {
+                -INFINITY
+            };
+            (score, m)
+        }));
+        // Stable and without a heap allocated buffer for lists this short
+        moves.sort_by_key(|(score, _)| -score);
+        moves.len()
+    }
+
+    /// The principal variation of the `ply` becomes `m` followed by the principal variation of the next ply.
+    fn update_pv(&mut self, ply: u32, m: Move) {
+        let ply = ply as usize;
+        let (frames, children) = self.stack.frames.split_at_mut(ply + 1);
+        let pv = &mut frames[ply].pv;
+        pv.clear();
+        pv.push(m);
+        if let Some(child) = children.first() {
+            pv.extend_from_slice(&child.pv);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(&mut self, board: &mut ChessBoard, depth: u32, ply: u32, mut alpha: i32, beta: i32, allow_null: bool) -> i32 {
+        self.stack.frame(ply).pv.clear();
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        let in_check = board.is_king_in_check(board.get_turn());
+        if ply > 0 {
+            // Checkmate takes precedence over the 50-move rule
+            if board.half_move >= 100 {
+                return if in_check && board.legal_moves_iter().next().is_none() { Score::mated_in(ply).0 } else { Score::DRAW.0 };
+            }
+            if self.is_repetition(board) {
+                return Score::DRAW.0;
+            }
+        }
+        if depth == 0 {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        // Cutoffs only outside of the principal variation, so the PV doesn't get cut short
+        let entry = self.table.as_ref().and_then(|table| table.probe(board.zobrist_with_ep())).copied();
+        if let Some(entry) = entry {
+            let score = entry.data.score.from_tt(ply).0;
+            if ply > 0 && beta - alpha == 1 && u32::from(entry.depth) >= depth {
+                match entry.data.bound {
+                    Bound::Exact => { return score.clamp(alpha, beta); }
+                    Bound::Lower if score >= beta => { return beta; }
+                    Bound::Upper if score <= alpha => { return alpha; }
+                    _ => {}
+                }
+            }
+        }
+
+        if self.generate_moves(board, ply, GenType::All) == 0 {
+            return if in_check { Score::mated_in(ply).0 } else { Score::DRAW.0 };
+        }
+        let original_alpha = alpha;
+
+        if allow_null && self.options.null_move_pruning && !in_check && depth > self.options.null_move_reduction && has_non_pawn_material(board) {
+            self.path.push(0);
+            let en_passant = make_null_move(board);
+            let score = -self.negamax(board, depth - 1 - self.options.null_move_reduction, ply + 1, -beta, -beta + 1, false);
+            unmake_null_move(board, en_passant);
+            self.path.pop();
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                return beta;
+            }
+        }
+
+        let futile = self.options.futility_pruning && !in_check && depth <= 2
+            && evaluate(board) + self.options.futility_margin * depth as i32 <= alpha;
+
+        let hint = if ply == 0 { self.root_hint } else { entry.and_then(|entry| entry.data.best) };
+        let count = self.order_moves(board, ply, hint);
+        for i in 0..count {
+            let m = self.stack.frames[ply as usize].moves[i].1;
+            let quiet = board.is_quiet(m);
+            self.make_move(board, m);
+            let gives_check = board.is_king_in_check(board.get_turn());
+
+            if futile && i > 0 && quiet && !gives_check {
+                self.unmake_move(board);
+                continue;
+            }
+
+            let score = if i == 0 {
+                -self.negamax(board, depth - 1, ply + 1, -beta, -alpha, true)
+            } else {
+                let reduction = if self.options.lmr && i >= self.options.lmr_min_moves && depth >= 3 && quiet && !in_check && !gives_check {
+                    self.options.lmr_reduction.min(depth - 2)
+                } else {
+                    0
+                };
+
+                // Cheaper searches first, every one which fails to prove the move worse than alpha is redone more thoroughly.
+                let mut score = alpha + 1;
+                if reduction > 0 {
+                    score = -self.negamax(board, depth - 1 - reduction, ply + 1, -alpha - 1, -alpha, true);
+                }
+                if score > alpha && self.options.pvs {
+                    score = -self.negamax(board, depth - 1, ply + 1, -alpha - 1, -alpha, true);
+                }
+                if score > alpha && (score < beta || !self.options.pvs) {
+                    score = -self.negamax(board, depth - 1, ply + 1, -beta, -alpha, true);
+                }
+                score
+            };
+            self.unmake_move(board);
+            if self.aborted {
+                return 0;
+            }
+
+            if score > alpha {
+                alpha = score;
+                self.update_pv(ply, m);
+                if alpha >= beta {
+                    if quiet {
+                        self.stack.killers.store(ply, m);
+                    }
+                    break;
+                }
+            }
+        }
+
+        if let Some(table) = &mut self.table {
+            let bound = if alpha >= beta { Bound::Lower } else if alpha > original_alpha { Bound::Exact } else { Bound::Upper };
+            let data = SearchEntry { score: Score(alpha).to_tt(ply), bound, best: self.stack.frames[ply as usize].pv.first().copied() };
+            table.store(board.zobrist_with_ep(), depth as u8, data);
+        }
+        alpha
+    }
+
+    fn quiescence(&mut self, board: &mut ChessBoard, ply: u32, mut alpha: i32, beta: i32) -> i32 {
+        let stand_pat = evaluate(board);
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        self.generate_moves(board, ply, GenType::Captures);
+        let count = self.order_moves(board, ply, None);
+        for i in 0..count {
+            let m = self.stack.frames[ply as usize].moves[i].1;
+            if self.should_stop() {
+                return 0;
+            }
+            self.nodes += 1;
+
+            board.make_move(m, true);
+            let score = -self.quiescence(board, ply + 1, -beta, -alpha);
+            let _ = board.unmake_move();
+            if self.aborted {
+                return 0;
+            }
+
+            if score > alpha {
+                alpha = score;
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+        alpha
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitschess::board::fen::STARTPOS_FEN;
+
+    #[test]
+    fn test_search_node_budget() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        let limits = SearchLimits { nodes: Some(500), ..Default::default() };
+        let result = search(&mut board, limits, &AtomicBool::new(false));
+
+        assert!(result.aborted);
+        assert!(result.nodes <= 500);
+        assert!(result.depth >= 1);
+        assert!(board.get_legal_moves().iter().any(|m| Some(*m) == result.best));
+        assert_eq!(board.to_fen(), STARTPOS_FEN);
+    }
+
+    #[test]
+    fn test_search_stopped_before_first_iteration() {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        let result = search(&mut board, SearchLimits::default(), &AtomicBool::new(true));
+
+        assert!(result.aborted);
+        assert_eq!(result.depth, 0);
+        assert!(result.best.is_some());
+    }
+
+    #[test]
+    fn test_complexity() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").expect("valid fen");
+        let before = board.clone();
+        let back_rank = complexity(&mut board, 2);
+        assert_eq!(board, before);
+        assert_eq!(back_rank.branching, 17);
+        // Only a1a8 mates
+        assert!(back_rank.eval_spread > 0.0);
+        assert!(back_rank.tactic_density > 0.0 && back_rank.tactic_density < 1.0);
+        assert!((0.0..=100.0).contains(&back_rank.score));
+
+        board.parse_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").expect("valid fen");
+        assert_eq!(complexity(&mut board, 2), Complexity::default());
+    }
+
+    #[test]
+    fn test_search_wins_material() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("4k3/8/8/3q4/8/8/3R4/3RK3 w - - 0 1").expect("valid fen");
+        let limits = SearchLimits { depth: Some(2), ..Default::default() };
+        let result = search(&mut board, limits, &AtomicBool::new(false));
+
+        assert!(!result.aborted);
+        assert_eq!(result.depth, 2);
+        assert_eq!(result.best.map(Move::to_uci), Some(String::from("d2d5")));
+        assert_eq!(result.pv.first(), result.best.as_ref());
+    }
+
+    #[test]
+    fn test_search_no_legal_moves() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").expect("valid fen");
+        let result = search(&mut board, SearchLimits::default(), &AtomicBool::new(false));
+        assert_eq!(result.best, None);
+        assert_eq!(result.score, Score::DRAW);
+    }
+
+    #[test]
+    fn test_search_options_same_score() {
+        let no_pruning = SearchOptions { lmr: false, futility_pruning: false, null_move_pruning: false, hash_size_kb: 0, ..Default::default() };
+        let plain = SearchOptions { pvs: false, aspiration_window: None, ..no_pruning };
+        let aspiration_only = SearchOptions { aspiration_window: Some(10), ..plain };
+        let limits = SearchLimits { depth: Some(3), ..Default::default() };
+
+        let mut board = ChessBoard::new();
+        for fen in crate::bench::BENCH_POSITIONS {
+            board.parse_fen(fen).expect("valid fen");
+            let expected = search_with_options(&mut board, limits, plain, &AtomicBool::new(false));
+            for options in [aspiration_only, no_pruning] {
+                let result = search_with_options(&mut board, limits, options, &AtomicBool::new(false));
+                assert_eq!(result.score, expected.score, "{fen} {options:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_pruning_toggles() {
+        let no_pruning = SearchOptions { lmr: false, futility_pruning: false, null_move_pruning: false, ..Default::default() };
+        let limits = SearchLimits { depth: Some(4), ..Default::default() };
+
+        let mut board = ChessBoard::new();
+        board.parse_fen(crate::bench::BENCH_POSITIONS[6]).expect("valid fen");
+        let full = search_with_options(&mut board, limits, no_pruning, &AtomicBool::new(false));
+        for options in [
+            SearchOptions { lmr: true, ..no_pruning },
+            SearchOptions { futility_pruning: true, ..no_pruning },
+            SearchOptions { null_move_pruning: true, ..no_pruning },
+            SearchOptions::default(),
+        ] {
+            let pruned = search_with_options(&mut board, limits, options, &AtomicBool::new(false));
+            assert!(pruned.nodes < full.nodes, "{options:?}");
+            assert!(pruned.best.is_some());
+        }
+        assert_eq!(board.to_fen(), crate::bench::BENCH_POSITIONS[6]);
+    }
+
+    #[test]
+    fn test_search_mate_distance() {
+        let mut board = ChessBoard::new();
+        // Mate in 2: 1. Ra7 Kc8 2. Ra8#
+        board.parse_fen("1k6/8/1K6/8/8/8/8/R7 w - - 0 1").expect("valid fen");
+        for hash_size_kb in [0, 1024] {
+            let options = SearchOptions { hash_size_kb, ..Default::default() };
+            let limits = SearchLimits { depth: Some(5), ..Default::default() };
+            let result = search_with_options(&mut board, limits, options, &AtomicBool::new(false));
+            assert_eq!(result.score, Score::mate_in(3));
+            assert_eq!(result.score.mate_moves(), Some(2));
+        }
+
+        // Getting mated
+        board.parse_fen("k7/8/1K6/8/8/8/8/7R b - - 0 1").expect("valid fen");
+        let result = search(&mut board, SearchLimits { depth: Some(4), ..Default::default() }, &AtomicBool::new(false));
+        assert_eq!(result.score, Score::mated_in(2));
+        assert_eq!(result.score.to_string(), "mate -1");
+    }
+
+    #[test]
+    fn test_search_stack_reuse() {
+        let limits = SearchLimits { depth: Some(4), ..Default::default() };
+        let mut board = ChessBoard::new();
+        // A small stack grows when the quiescence search reaches deeper
+        let mut stack = SearchStack::with_plies(1);
+        for fen in crate::bench::BENCH_POSITIONS {
+            board.parse_fen(fen).expect("valid fen");
+            let fresh = search(&mut board, limits, &AtomicBool::new(false));
+            let reused = search_with_stack(&mut board, limits, SearchOptions::default(), &mut stack, &AtomicBool::new(false));
+            assert_eq!(reused, fresh, "{fen}");
+        }
+        assert!(stack.plies() > 4);
+    }
+
+    #[test]
+    fn test_score_tt_conversion() {
+        let score = Score::mate_in(7);
+        assert_eq!(score.to_tt(4), Score::mate_in(3));
+        assert_eq!(score.to_tt(4).from_tt(4), score);
+        assert_eq!(Score::mated_in(6).to_tt(2).from_tt(2), Score::mated_in(6));
+        assert_eq!(Score(150).to_tt(10), Score(150));
+    }
+
+    #[test]
+    fn test_search_perpetual_check() {
+        // Black is up a rook, but 1. Qe8+ Kh7 2. Qh5+ Kg8 3. Qe8+ is a perpetual
+        let mut board = ChessBoard::new();
+        board.parse_fen("6k1/r5p1/8/8/8/8/q7/4Q2K w - - 0 1").expect("valid fen");
+        let result = search(&mut board, SearchLimits { depth: Some(6), ..Default::default() }, &AtomicBool::new(false));
+        assert_eq!(result.score, Score::DRAW);
+        assert_eq!(result.best.map(Move::to_uci), Some(String::from("e1e8")));
+
+        // Black to move can prevent the perpetual
+        board.parse_fen("6k1/r5p1/8/8/8/8/q7/4Q2K b - - 0 1").expect("valid fen");
+        let result = search(&mut board, SearchLimits { depth: Some(6), ..Default::default() }, &AtomicBool::new(false));
+        assert!(result.score > Score(500));
+    }
+
+    #[test]
+    fn test_search_repetition_with_game_history() {
+        // The position after 1... Kh7 2. Qh5+ Kg8 already occurred in the game, so 3. Qe8+ repeats it
+        let mut board = ChessBoard::new();
+        board.parse_fen("4Q1k1/r5p1/8/8/8/8/q7/7K b - - 0 1").expect("valid fen");
+        for m in ["g8h7", "e8h5", "h7g8", "h5e8", "g8h7", "e8h5", "h7g8"] {
+            board.make_move_uci(m).expect("legal move");
+        }
+        let result = search(&mut board, SearchLimits { depth: Some(1), ..Default::default() }, &AtomicBool::new(false));
+        assert_eq!(result.score, Score::DRAW);
+    }
+}
@@ -0,0 +1,289 @@
+//! Self-play with the [Monte-Carlo tree search](crate::mcts), recording training data for AlphaZero style learning.
+//!
+//! Every position of a game becomes a [TrainingSample] of (position planes, visit count policy, game outcome),
+//! handed to a [SampleSink] once the game has finished and the outcome is known.
+//! [BinaryWriter] stores the samples in a compact binary format, which [read_sample] reads back.
+
+use std::io::{self, Read, Write};
+use std::sync::atomic::AtomicBool;
+
+use crate::bitschess::board::ChessBoard;
+use crate::bitschess::board::game_state::GameResult;
+use crate::board_helper::Square;
+use crate::chess_move::Move;
+use crate::mcts::{mcts_with_policy, Evaluator, MctsOptions};
+use crate::piece::PieceColor;
+use crate::search::SearchLimits;
+
+/// 12 piece bitboards (white pawns to black king), en passant square, rooks with castling rights and side to move.
+pub const PLANES: usize = 15;
+
+/// One position of a self-play game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingSample {
+    /// Bitboards from white's point of view, see [PLANES]. The side to move plane is full if white is to move.
+    pub planes: [u64; PLANES],
+    /// Share of the search's visits for each legal move, sums to 1.
+    pub policy: Vec<(Move, f32)>,
+    /// Final result for the side to move: 1 won, 0 drawn and -1 lost.
+    pub outcome: i8,
+}
+
+impl TrainingSample {
+    /// Encodes the position into [PLANES] bitboards, the policy and outcome are left empty.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// use bitschess::selfplay::TrainingSample;
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen(STARTPOS_FEN).expect("valid fen");
+    /// let planes = TrainingSample::from_board(&board).planes;
+    /// assert_eq!(planes[0], 0xFF00); // white pawns
+    /// assert_eq!(planes[13].count_ones(), 4); // every rook can castle
+    /// assert_eq!(planes[14], !0); // white to move
+    /// ```
+    #[must_use]
+    pub fn from_board(board: &ChessBoard) -> Self {
+        let mut planes = [0u64; PLANES];
+        for (plane, bitboard) in planes.iter_mut().zip(board.bitboards) {
+            *plane = bitboard.into();
+        }
+        if board.en_passant != -1 {
+            planes[12] = 1u64 << board.en_passant;
+        }
+        for (rights, rook) in board.castling_rights.iter().zip([Square::H1, Square::A1, Square::H8, Square::A8]) {
+            if *rights {
+                planes[13] |= 1u64 << rook as u32;
+            }
+        }
+        planes[14] = if board.get_turn() == PieceColor::White { !0 } else { 0 };
+        Self { planes, policy: vec![], outcome: 0 }
+    }
+}
+
+/// Receives the samples of every finished self-play game.
+pub trait SampleSink {
+    /// # Errors
+    /// If the sample couldn't be stored.
+    fn record(&mut self, sample: &TrainingSample) -> io::Result<()>;
+}
+
+impl SampleSink for Vec<TrainingSample> {
+    fn record(&mut self, sample: &TrainingSample) -> io::Result<()> {
+        self.push(sample.clone());
+        Ok(())
+    }
+}
+
+/// Writes the samples one after another, all numbers are little endian:
+/// * [PLANES] `u64` bitboards.
+/// * `i8` outcome.
+/// * `u16` amount of moves in the policy, followed by a `u16` [Move] and `f32` probability for each.
+pub struct BinaryWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> BinaryWriter<W> {
+    pub const fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> SampleSink for BinaryWriter<W> {
+    fn record(&mut self, sample: &TrainingSample) -> io::Result<()> {
+        for plane in sample.planes {
+            self.writer.write_all(&plane.to_le_bytes())?;
+        }
+        self.writer.write_all(&sample.outcome.to_le_bytes())?;
+
+        let moves = u16::try_from(sample.policy.len()).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+        self.writer.write_all(&moves.to_le_bytes())?;
+        for (m, probability) in &sample.policy {
+            self.writer.write_all(&m.0.to_le_bytes())?;
+            self.writer.write_all(&probability.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the next sample written by a [BinaryWriter], `None` at the end of the input.
+///
+/// # Errors
+/// If reading fails or the input ends in the middle of a sample.
+pub fn read_sample(reader: &mut impl Read) -> io::Result<Option<TrainingSample>> {
+    let mut planes = [0u64; PLANES];
+    let mut bytes = [0u8; 8];
+    for (idx, plane) in planes.iter_mut().enumerate() {
+        // Only the end of the input before the first byte of a sample is a clean end
+        if idx == 0 {
+            let read = reader.read(&mut bytes[..1])?;
+            if read == 0 {
+                return Ok(None);
+            }
+            reader.read_exact(&mut bytes[1..])?;
+        } else {
+            reader.read_exact(&mut bytes)?;
+        }
+        *plane = u64::from_le_bytes(bytes);
+    }
+
+    let mut outcome = [0u8; 1];
+    reader.read_exact(&mut outcome)?;
+    let mut count = [0u8; 2];
+    reader.read_exact(&mut count)?;
+
+    let mut policy = vec![];
+    for _ in 0..u16::from_le_bytes(count) {
+        let mut m = [0u8; 2];
+        let mut probability = [0u8; 4];
+        reader.read_exact(&mut m)?;
+        reader.read_exact(&mut probability)?;
+        policy.push((Move(u16::from_le_bytes(m)), f32::from_le_bytes(probability)));
+    }
+    Ok(Some(TrainingSample { planes, policy, outcome: i8::from_le_bytes(outcome) }))
+}
+
+/// Plays games against itself with the tree search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SelfPlay {
+    /// Limits of the search of every move.
+    pub limits: SearchLimits,
+    pub options: MctsOptions,
+    /// For this many plies from the start the move is picked randomly in proportion to the visit counts,
+    /// after that the most visited move is played. Keeps the games varied.
+    pub sampling_plies: u32,
+    /// Games longer than this are adjudicated as draws.
+    pub max_plies: u32,
+}
+
+impl Default for SelfPlay {
+    fn default() -> Self {
+        Self {
+            limits: SearchLimits { nodes: Some(800), ..Default::default() },
+            options: MctsOptions::default(),
+            sampling_plies: 30,
+            max_plies: 400,
+        }
+    }
+}
+
+impl SelfPlay {
+    /// Plays a game from the position of the `board` and hands every position to the `sink` after the game is over.
+    /// The moves are picked with the `seed`, the board is left at the final position.
+    ///
+    /// # Errors
+    /// If the `sink` fails to record a sample.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bitschess::prelude::*;
+    /// use bitschess::mcts::RandomPlayout;
+    /// use bitschess::search::SearchLimits;
+    /// use bitschess::selfplay::{SelfPlay, TrainingSample};
+    /// let mut board = ChessBoard::new();
+    /// board.parse_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").expect("valid fen");
+    /// let selfplay = SelfPlay { limits: SearchLimits { nodes: Some(500), ..Default::default() }, sampling_plies: 0, ..Default::default() };
+    ///
+    /// let mut samples: Vec<TrainingSample> = vec![];
+    /// let result = selfplay.play_game(&mut board, &mut RandomPlayout::default(), &mut samples, 1).expect("stored in memory");
+    /// assert_eq!(result, GameResult::WhiteWins);
+    /// assert_eq!(samples.len(), 1); // a1a8 mates right away
+    /// assert_eq!(samples[0].outcome, 1);
+    /// ```
+    pub fn play_game(&self, board: &mut ChessBoard, evaluator: &mut impl Evaluator, sink: &mut impl SampleSink, seed: u64) -> io::Result<GameResult> {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let stop = AtomicBool::new(false);
+        let mut samples: Vec<(TrainingSample, PieceColor)> = vec![];
+
+        let mut plies = 0;
+        let result = loop {
+            if let Some(result) = board.get_game_state().result() {
+                break result;
+            }
+            // Both players claim a draw as soon as they can
+            if plies >= self.max_plies || board.can_claim_draw() {
+                break GameResult::Draw;
+            }
+
+            let (_, visits) = mcts_with_policy(board, self.limits, self.options, evaluator, &stop);
+            let total = visits.iter().map(|(_, visits)| f64::from(*visits)).sum::<f64>().max(1.0);
+            let m = if plies < self.sampling_plies {
+                let mut pick = rng.f64() * total;
+                visits.iter().find(|(_, visits)| {
+                    pick -= f64::from(*visits);
+                    pick < 0.0
+                }).or(visits.last())
+            } else {
+                visits.iter().max_by_key(|(_, visits)| *visits)
+            }.map(|(m, _)| *m).expect("the game isn't over");
+
+            let mut sample = TrainingSample::from_board(board);
+            sample.policy = visits.iter().map(|(m, visits)| (*m, (f64::from(*visits) / total) as f32)).collect();
+            samples.push((sample, board.get_turn()));
+
+            board.make_move(m, true);
+            plies += 1;
+        };
+
+        let winner = match result {
+            GameResult::WhiteWins => { Some(PieceColor::White) }
+            GameResult::BlackWins => { Some(PieceColor::Black) }
+            GameResult::Draw => { None }
+        };
+        for (mut sample, turn) in samples {
+            sample.outcome = match winner {
+                Some(winner) if winner == turn => { 1 }
+                Some(_) => { -1 }
+                None => { 0 }
+            };
+            sink.record(&sample)?;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcts::RandomPlayout;
+
+    #[test]
+    fn test_selfplay_binary_roundtrip() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("4k3/8/8/8/8/8/3Q4/4K3 w - - 0 1").expect("valid fen");
+        let selfplay = SelfPlay {
+            limits: SearchLimits { nodes: Some(50), ..Default::default() },
+            sampling_plies: 4,
+            max_plies: 6,
+            ..Default::default()
+        };
+
+        let mut samples: Vec<TrainingSample> = vec![];
+        let result = selfplay.play_game(&mut board.clone(), &mut RandomPlayout::default(), &mut samples, 7).expect("stored in memory");
+        assert_eq!(result, GameResult::Draw);
+        assert_eq!(samples.len(), 6);
+        assert!(samples.iter().all(|sample| sample.outcome == 0));
+        assert!(samples.iter().all(|sample| (sample.policy.iter().map(|(_, p)| p).sum::<f32>() - 1.0).abs() < 1e-4));
+        assert_eq!(samples[0].planes, TrainingSample::from_board(&board).planes);
+
+        // Same seed, same game
+        let mut writer = BinaryWriter::new(vec![]);
+        selfplay.play_game(&mut board, &mut RandomPlayout::default(), &mut writer, 7).expect("stored in memory");
+        let bytes = writer.into_inner();
+        let mut reader = bytes.as_slice();
+        let mut read = vec![];
+        while let Some(sample) = read_sample(&mut reader).expect("valid data") {
+            read.push(sample);
+        }
+        assert_eq!(read, samples);
+
+        // Truncated data is an error, not the end
+        assert!(read_sample(&mut &bytes[..10]).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! Checkmate solver for puzzles, proves a forced mate without the full [search](crate::search).
+//!
+//! The attacking side only tries checking moves and the defending side tries all of its replies,
+//! so mates starting with a quiet move aren't found. Draws by repetition or the fifty-move rule are ignored.
+
+use alloc::vec::Vec;
+
+use crate::bitschess::board::ChessBoard;
+use crate::chess_move::Move;
+
+/// Finds the shortest forced mate for the side to move within `max_plies` half moves, counting the moves of both sides.
+/// Returns the moves from the first check to the mate, the defender always picking the reply which delays the mate the longest.
+/// The `board` is left as it was.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::prelude::*;
+/// use bitschess::solver::find_mate;
+/// let mut board = ChessBoard::new();
+/// // Queen sacrifice followed by a smothered mate
+/// board.parse_fen("5rrk/6pp/7N/8/8/1Q6/8/6K1 w - - 0 1").expect("valid fen");
+/// let line = find_mate(&mut board, 3).expect("mate in 2");
+/// let line: Vec<String> = line.iter().map(|m| m.to_uci()).collect();
+/// assert_eq!(line, ["b3g8", "f8g8", "h6f7"]);
+///
+/// assert_eq!(find_mate(&mut board, 1), None);
+/// ```
+#[must_use]
+pub fn find_mate(board: &mut ChessBoard, max_plies: u32) -> Option<Vec<Move>> {
+    (1..=max_plies).step_by(2).find_map(|plies| {
+        let mut line = mate_in(board, plies)?;
+        line.reverse();
+        Some(line)
+    })
+}
+
+/// Attacker to move, a checking move after which every reply still gets mated within `plies`.
+/// The line is returned in reverse, so moves can be pushed when unwinding.
+fn mate_in(board: &mut ChessBoard, plies: u32) -> Option<Vec<Move>> {
+    for m in board.get_legal_moves().iter().copied() {
+        board.make_move(m, true);
+        if !board.is_king_in_check(board.get_turn()) {
+            let _ = board.unmake_move();
+            continue;
+        }
+
+        let line = if board.legal_moves_iter().next().is_none() {
+            Some(Vec::new())
+        } else if plies >= 3 {
+            defend(board, plies - 1)
+        } else {
+            None
+        };
+        let _ = board.unmake_move();
+
+        if let Some(mut line) = line {
+            line.push(m);
+            return Some(line);
+        }
+    }
+    None
+}
+
+/// Defender to move and in check, every reply has to be mated within `plies`.
+/// The reply needing the longest mate is returned.
+fn defend(board: &mut ChessBoard, plies: u32) -> Option<Vec<Move>> {
+    let mut longest: Option<Vec<Move>> = None;
+    for reply in board.get_legal_moves().iter().copied() {
+        board.make_move(reply, true);
+        let line = (1..plies).step_by(2).find_map(|plies| mate_in(board, plies));
+        let _ = board.unmake_move();
+
+        let mut line = line?;
+        line.push(reply);
+        if longest.as_ref().is_none_or(|longest| line.len() > longest.len()) {
+            longest = Some(line);
+        }
+    }
+    longest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitschess::board::fen::STARTPOS_FEN;
+
+    #[test]
+    fn test_find_mate() {
+        let mut board = ChessBoard::new();
+        board.parse_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").expect("valid fen");
+        let line = find_mate(&mut board, 5).expect("mate in 1");
+        assert_eq!(line, [Move::from_uci("h1h8")]);
+        assert_eq!(board.to_fen(), "k7/8/1K6/8/8/8/8/7R w - - 0 1");
+
+        // Already mated, nothing to find
+        board.parse_fen("k6R/8/1K6/8/8/8/8/8 b - - 1 1").expect("valid fen");
+        assert_eq!(find_mate(&mut board, 5), None);
+
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        assert_eq!(find_mate(&mut board, 3), None);
+        assert_eq!(board.to_fen(), STARTPOS_FEN);
+    }
+
+    #[test]
+    fn test_find_mate_interposition() {
+        // The bishop can block the check, but the knight protects the capturing rook
+        let mut board = ChessBoard::new();
+        board.parse_fen("7k/8/5N1K/8/8/1b6/8/R7 w - - 0 1").expect("valid fen");
+        let line = find_mate(&mut board, 5).expect("mate in 2");
+        assert_eq!(line, [Move::from_uci("a1a8"), Move::from_uci("b3g8"), Move::from_uci("a8g8")]);
+        for m in &line {
+            board.make_move(*m, false);
+        }
+        assert!(board.is_check_mate());
+    }
+}
@@ -0,0 +1,179 @@
+//! Test utilities for code built on top of the [ChessBoard], mainly catching evaluation bugs.
+
+use crate::bitschess::bitboard::BitBoard;
+use crate::bitschess::board::ChessBoard;
+use crate::chess_move::Move;
+use crate::piece::PieceColor;
+use alloc::{format, string::{String, ToString}, vec, vec::Vec};
+
+/// Swaps the colors of every piece and flips the board vertically, adjusting the turn, castling rights and en passant square.
+/// The resulting position is the same position from the opponent's point of view.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::testing::color_flipped_fen;
+/// assert_eq!(
+///     color_flipped_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 1"),
+///     "rnbqkbnr/pppp1ppp/8/4p3/8/8/PPPPPPPP/RNBQKBNR w Qk e6 0 1"
+/// );
+/// ```
+#[must_use]
+pub fn color_flipped_fen(fen: &str) -> String {
+    let mut args = fen.split_whitespace();
+    let mut flipped: Vec<String> = vec![];
+
+    // Position
+    let position = args.next().unwrap_or("");
+    let ranks: Vec<String> = position.split('/').rev().map(|rank| {
+        rank.chars().map(|c| if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() }).collect()
+    }).collect();
+    flipped.push(ranks.join("/"));
+
+    // Turn
+    flipped.push(if args.next() == Some("b") { "w" } else { "b" }.to_string());
+
+    // Castling rights
+    let castling = args.next().unwrap_or("-");
+    let mut castling_flipped: String = ['k', 'q', 'K', 'Q'].iter()
+        .filter(|c| castling.contains(**c))
+        .map(|c| if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() })
+        .collect();
+    if castling_flipped.is_empty() {
+        castling_flipped.push('-');
+    }
+    flipped.push(castling_flipped);
+
+    // En passant
+    let en_passant = args.next().unwrap_or("-");
+    flipped.push(match en_passant.as_bytes() {
+        [file, b'3'] => { format!("{}6", *file as char) }
+        [file, b'6'] => { format!("{}3", *file as char) }
+        _ => { String::from("-") }
+    });
+
+    // Half & Full -moves
+    flipped.extend(args.map(String::from));
+    flipped.join(" ")
+}
+
+/// Asserts `eval(position) == -eval(color_flipped(position))` for every position.
+/// The evaluation is expected to be from white's point of view.
+///
+/// # Panics
+/// If the evaluation is not symmetric for some position.
+#[track_caller]
+pub fn assert_eval_symmetry(positions: &[&str], eval: impl Fn(&ChessBoard) -> i32) {
+    let mut board = ChessBoard::new();
+    for fen in positions {
+        board.parse_fen(fen).expect("valid fen");
+        let score = eval(&board);
+
+        let flipped = board.color_flipped();
+        let flipped_score = eval(&flipped);
+
+        assert_eq!(score, -flipped_score, "asymmetric evaluation\n'{fen}': {score}\n'{}': {flipped_score}", flipped.to_fen());
+    }
+}
+
+/// Checks that the incrementally updated parts of the board (bitboards and the zobrist hash) are
+/// equal to the ones computed from scratch.
+///
+/// # Panics
+/// If some part of the incremental state differs.
+#[track_caller]
+pub fn assert_incremental_state(board: &ChessBoard) {
+    let mut bitboards = [BitBoard::EMPTY; 12];
+    let mut side_bitboards = [BitBoard::EMPTY; 2];
+    for (square, piece) in board.board.iter().enumerate() {
+        if piece.is_none() {
+            continue;
+        }
+        bitboards[piece.get_piece_index()] |= BitBoard::from_square(square as i32);
+        side_bitboards[piece.get_color() as usize] |= BitBoard::from_square(square as i32);
+    }
+
+    assert_eq!(board.bitboards, bitboards, "bitboards desynced\n{board}");
+    assert_eq!(board.side_bitboards, side_bitboards, "side bitboards desynced\n{board}");
+    assert_eq!(board.zobrist_hash, board.create_zobrist_hash(), "zobrist hash desynced\n{board}");
+}
+
+/// Walks every move sequence up to `depth` plies and asserts after every make and unmake that:
+/// * the board's incremental state matches the from-scratch computation (see [assert_incremental_state]).
+/// * `incremental` (e.g. an eval accumulator maintained by the caller) equals `from_scratch`.
+/// * unmaking a move restores the exact same position.
+///
+/// # Panics
+/// If any of the conditions doesn't hold.
+#[track_caller]
+pub fn assert_make_unmake_consistency<T: PartialEq + core::fmt::Debug>(
+    board: &mut ChessBoard,
+    depth: u32,
+    incremental: &mut impl FnMut(&ChessBoard, Option<Move>) -> T,
+    from_scratch: &impl Fn(&ChessBoard) -> T,
+) {
+    if depth == 0 {
+        return;
+    }
+
+    for m in board.get_legal_moves() {
+        let before = board.clone();
+
+        board.make_move(m, true);
+        assert_incremental_state(board);
+        assert_eq!(incremental(board, Some(m)), from_scratch(board), "incremental state differs after making '{}'\n{board}", m.to_uci());
+
+        assert_make_unmake_consistency(board, depth - 1, incremental, from_scratch);
+
+        let _ = board.unmake_move();
+        assert_incremental_state(board);
+        assert_eq!(board, &before, "unmaking '{}' didn't restore the position", m.to_uci());
+        assert_eq!(incremental(board, None), from_scratch(board), "incremental state differs after unmaking '{}'\n{board}", m.to_uci());
+    }
+}
+
+/// Sum of piece values from white's point of view, handy as a minimal evaluation for tests.
+#[must_use]
+pub fn material_balance(board: &ChessBoard) -> i32 {
+    board.material(PieceColor::White) - board.material(PieceColor::Black)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bench::BENCH_POSITIONS;
+
+    #[test]
+    fn test_color_flipped_fen_twice_is_identity() {
+        for fen in BENCH_POSITIONS.iter().chain(["rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"].iter()) {
+            assert_eq!(color_flipped_fen(&color_flipped_fen(fen)), *fen);
+        }
+    }
+
+    #[test]
+    fn test_eval_symmetry_material() {
+        assert_eval_symmetry(&BENCH_POSITIONS, material_balance);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_eval_symmetry_catches_side_to_move_bug() {
+        // Forgot to negate the score for black
+        assert_eval_symmetry(&BENCH_POSITIONS, |board| {
+            material_balance(board) + if board.get_turn() == PieceColor::White { 10 } else { 0 }
+        });
+    }
+
+    #[test]
+    fn test_make_unmake_consistency_fresh_board() {
+        // The hash and the bitboards kept up to date by make and unmake, against a board parsed from the FEN
+        let incrementally_updated = |board: &ChessBoard| (board.zobrist_hash, board.bitboards, board.side_bitboards);
+        let from_scratch = |board: &ChessBoard| {
+            let fresh = ChessBoard::from_fen(&board.to_fen()).expect("valid fen");
+            (fresh.zobrist_hash, fresh.bitboards, fresh.side_bitboards)
+        };
+        for fen in BENCH_POSITIONS {
+            let mut board = ChessBoard::from_fen(fen).expect("valid fen");
+            assert_make_unmake_consistency(&mut board, 2, &mut |board, _| incrementally_updated(board), &from_scratch);
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::io::{BufRead, Write};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use crate::bitschess::board::ChessBoard;
+use crate::bitschess::board::fen::STARTPOS_FEN;
+use crate::chess_move::Move;
+use crate::piece::PieceColor;
+use crate::search::{search_with_stack, SearchLimits, SearchOptions, SearchStack};
+
+/// Depth searched by a plain `go` without any limits, searches are synchronous so `go infinite` couldn't be stopped.
+const DEFAULT_GO_DEPTH: u32 = 4;
+
+/// # Universal Chess Interface frontend
+/// Implements the [UCI protocol](https://www.wbec-ridderkerk.nl/html/UCIProtocol.html) on top of [ChessBoard]
+/// so BitsChess can be plugged into GUIs like Cute Chess or Arena.
+///
+/// Supported commands: `uci`, `isready`, `ucinewgame`, `position [startpos | fen <fen>] [moves ...]`,
+/// `go perft <depth>`, `go [depth <d>] [nodes <n>] [movetime <ms>] [wtime <ms>] [btime <ms>] [winc <ms>] [binc <ms>] [movestogo <n>]`,
+/// `d`, `stop` and `quit`.
+///
+/// # Examples
+/// ```rust
+/// use bitschess::Uci;
+/// let mut uci = Uci::new();
+/// let mut out = Vec::new();
+/// uci.execute("position startpos moves e2e4", &mut out).unwrap();
+/// uci.execute("isready", &mut out).unwrap();
+/// assert_eq!(String::from_utf8(out).unwrap(), "readyok\n");
+/// ```
+pub struct Uci {
+    board: ChessBoard,
+    /// Reused by every `go`, so searching doesn't allocate.
+    stack: SearchStack,
+}
+
+impl Default for Uci {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Uci {
+    #[must_use]
+    pub fn new() -> Self {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        Self { board, stack: SearchStack::new() }
+    }
+
+    #[must_use]
+    #[inline(always)]
+    pub const fn get_board(&self) -> &ChessBoard {
+        &self.board
+    }
+
+    /// Reads commands from stdin until `quit` is received or stdin is closed.
+    pub fn run(&mut self) {
+        let stdin = std::io::stdin();
+        let mut stdout = std::io::stdout();
+
+        for line in stdin.lock().lines() {
+            let Ok(line) = line else { break; };
+            match self.execute(&line, &mut stdout) {
+                Ok(true) => { let _ = stdout.flush(); }
+                _ => { break; }
+            }
+        }
+    }
+
+    /// Executes a single UCI command and writes the response into `out`.
+    /// Returns `Ok(false)` when the engine should quit.
+    pub fn execute(&mut self, command: &str, out: &mut impl Write) -> std::io::Result<bool> {
+        let args: Vec<&str> = command.split_whitespace().collect();
+        let Some(&name) = args.first() else {
+            return Ok(true);
+        };
+
+        match name {
+            "uci" => {
+                writeln!(out, "id name BitsChess {}", env!("CARGO_PKG_VERSION"))?;
+                writeln!(out, "id author {}", env!("CARGO_PKG_AUTHORS"))?;
+                writeln!(out, "uciok")?;
+            }
+            "isready" => {
+                writeln!(out, "readyok")?;
+            }
+            "ucinewgame" => {
+                self.board.parse_fen(STARTPOS_FEN).expect("valid fen");
+            }
+            "position" => {
+                self.position(&args[1..], out)?;
+            }
+            "go" => {
+                self.go(&args[1..], out)?;
+            }
+            "d" => {
+                writeln!(out, "{}", self.board)?;
+                writeln!(out, "Fen: {}", self.board.to_fen())?;
+            }
+            // Searches are synchronous, so there's never anything to stop.
+            "stop" => {}
+            "quit" => {
+                return Ok(false);
+            }
+            _ => {
+                writeln!(out, "info string unknown command '{command}'")?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// position [startpos | fen <fen>] [moves <move1> ... <movei>]
+    fn position(&mut self, args: &[&str], out: &mut impl Write) -> std::io::Result<()> {
+        let moves_idx = args.iter().position(|arg| *arg == "moves").unwrap_or(args.len());
+        let fen = match args.first() {
+            Some(&"startpos") => { STARTPOS_FEN.to_string() }
+            Some(&"fen") => { args[1..moves_idx].join(" ") }
+            _ => {
+                writeln!(out, "info string expected 'startpos' or 'fen'")?;
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = self.board.parse_fen(&fen) {
+            writeln!(out, "info string invalid fen '{fen}': {e:?}")?;
+            self.board.parse_fen(STARTPOS_FEN).expect("valid fen");
+            return Ok(());
+        }
+
+        for uci_move in args.iter().skip(moves_idx + 1) {
+            if let Err(e) = self.board.make_move_uci(uci_move) {
+                writeln!(out, "info string illegal move '{uci_move}': {e:?}")?;
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// go perft <depth> | go [depth <d>] [nodes <n>] [movetime <ms>] [wtime <ms>] [btime <ms>] [winc <ms>] [binc <ms>] [movestogo <n>]
+    fn go(&mut self, args: &[&str], out: &mut impl Write) -> std::io::Result<()> {
+        if args.first() == Some(&"perft") {
+            let Some(Ok(depth)) = args.get(1).map(|d| d.parse::<u32>()) else {
+                writeln!(out, "info string expected a depth for perft")?;
+                return Ok(());
+            };
+            return self.perft_divide(depth, out);
+        }
+
+        let limits = self.parse_search_limits(args);
+        let begin = std::time::Instant::now();
+        let result = search_with_stack(&mut self.board, limits, SearchOptions::default(), &mut self.stack, &AtomicBool::new(false));
+
+        let pv: Vec<String> = result.pv.iter().map(|m| m.to_uci()).collect();
+        writeln!(
+            out, "info depth {} score {} nodes {} time {} pv {}",
+            result.depth, result.score, result.nodes, begin.elapsed().as_millis(), pv.join(" ")
+        )?;
+        writeln!(out, "bestmove {}", result.best.map_or(String::from("0000"), Move::to_uci))?;
+        Ok(())
+    }
+
+    fn parse_search_limits(&self, args: &[&str]) -> SearchLimits {
+        let value = |name: &str| {
+            args.iter().position(|arg| *arg == name).and_then(|i| args.get(i + 1)).and_then(|v| v.parse::<u64>().ok())
+        };
+
+        let mut limits = SearchLimits {
+            depth: value("depth").map(|d| d as u32),
+            nodes: value("nodes"),
+            movetime: value("movetime").map(Duration::from_millis),
+        };
+
+        // Spend an even share of the remaining time on each move
+        let (time, increment) = if self.board.get_turn() == PieceColor::White { ("wtime", "winc") } else { ("btime", "binc") };
+        if let Some(time_left) = value(time) {
+            let moves_to_go = value("movestogo").unwrap_or(30).max(1);
+            let allocated = time_left / moves_to_go + value(increment).unwrap_or(0) / 2;
+            let allocated = Duration::from_millis(allocated.min(time_left.saturating_sub(50)).max(1));
+            limits.movetime = Some(limits.movetime.map_or(allocated, |movetime| movetime.min(allocated)));
+        }
+
+        if limits == SearchLimits::default() {
+            limits.depth = Some(DEFAULT_GO_DEPTH);
+        }
+        limits
+    }
+
+    fn perft_divide(&mut self, depth: u32, out: &mut impl Write) -> std::io::Result<()> {
+        let mut total = 0u64;
+        if depth > 0 {
+            for (m, nodes) in self.board.perft_divide(depth) {
+                writeln!(out, "{}: {}", m.to_uci(), nodes)?;
+                total += nodes;
+            }
+        }
+        writeln!(out)?;
+        writeln!(out, "Nodes searched: {total}")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn _execute(uci: &mut Uci, command: &str) -> String {
+        let mut out = Vec::new();
+        uci.execute(command, &mut out).expect("writing to a vec");
+        String::from_utf8(out).expect("valid utf8")
+    }
+
+    #[test]
+    fn test_uci_handshake() {
+        let mut uci = Uci::new();
+        assert!(_execute(&mut uci, "uci").ends_with("uciok\n"));
+        assert_eq!(_execute(&mut uci, "isready"), "readyok\n");
+    }
+
+    #[test]
+    fn test_uci_position_startpos_moves() {
+        let mut uci = Uci::new();
+        _execute(&mut uci, "position startpos moves e2e4 e7e5 g1f3");
+        assert_eq!(uci.get_board().to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+    }
+
+    #[test]
+    fn test_uci_position_fen_moves() {
+        let mut uci = Uci::new();
+        _execute(&mut uci, "position fen 4k3/8/8/5Pp1/8/8/8/4K3 w - g6 0 1 moves f5g6");
+        assert_eq!(uci.get_board().to_fen(), "4k3/8/6P1/8/8/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn test_uci_position_illegal_move() {
+        let mut uci = Uci::new();
+        let response = _execute(&mut uci, "position startpos moves e2e5");
+        assert_eq!(response, "info string illegal move 'e2e5': IllegalMove\n");
+        assert_eq!(uci.get_board().to_fen(), STARTPOS_FEN);
+    }
+
+    #[test]
+    fn test_uci_go_perft() {
+        let mut uci = Uci::new();
+        let response = _execute(&mut uci, "go perft 3");
+        assert!(response.contains("e2e4: 600\n"));
+        assert!(response.ends_with("Nodes searched: 8902\n"));
+    }
+
+    #[test]
+    fn test_uci_go_bestmove_is_legal() {
+        let mut uci = Uci::new();
+        let response = _execute(&mut uci, "go");
+        let best = response.lines().last().and_then(|line| line.strip_prefix("bestmove ")).expect("bestmove");
+        assert!(uci.board.get_legal_moves().iter().any(|m| m.to_uci() == best));
+    }
+
+    #[test]
+    fn test_uci_go_depth_finds_mate() {
+        let mut uci = Uci::new();
+        _execute(&mut uci, "position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        let response = _execute(&mut uci, "go depth 3");
+        assert!(response.starts_with("info depth 3 score mate 1 "));
+        assert!(response.ends_with("bestmove a1a8\n"));
+    }
+
+    #[test]
+    fn test_uci_go_nodes() {
+        let mut uci = Uci::new();
+        let response = _execute(&mut uci, "go nodes 100 wtime 1000 btime 1000");
+        let nodes: u64 = response.split_whitespace().skip_while(|arg| *arg != "nodes").nth(1).expect("nodes").parse().expect("number");
+        assert!(nodes <= 100);
+        assert!(response.contains("bestmove "));
+    }
+
+    #[test]
+    fn test_uci_quit() {
+        let mut uci = Uci::new();
+        let mut out = Vec::new();
+        assert!(!uci.execute("quit", &mut out).unwrap());
+    }
+}
@@ -0,0 +1,107 @@
+//! JavaScript bindings for running the board in a browser, built with [wasm-bindgen](https://rustwasm.github.io/docs/wasm-bindgen/).
+//!
+//! ```js
+//! import init, { WasmBoard } from "./pkg/bitschess.js";
+//! await init();
+//! const board = WasmBoard.fromFen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+//! console.log(board.san("g1f3")); // "Nf3"
+//! board.makeMove("g1f3");
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+use crate::bitschess::board::{ChessBoard, PromotionPolicy};
+use crate::bitschess::board::fen::STARTPOS_FEN;
+use crate::chess_move::Move;
+
+/// A [ChessBoard] exposed to JavaScript, moves are passed in the UCI format ("e2e4", "e7e8q").
+#[wasm_bindgen]
+pub struct WasmBoard {
+    board: ChessBoard,
+}
+
+#[wasm_bindgen]
+impl WasmBoard {
+    /// A board in the starting position.
+    #[wasm_bindgen(constructor)]
+    #[must_use]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> WasmBoard {
+        let mut board = ChessBoard::new();
+        board.parse_fen(STARTPOS_FEN).expect("valid fen");
+        WasmBoard { board }
+    }
+
+    /// # Errors
+    /// If the FEN is not a valid position.
+    #[wasm_bindgen(js_name = fromFen)]
+    pub fn from_fen(fen: &str) -> Result<WasmBoard, JsError> {
+        let board = ChessBoard::from_fen(fen).map_err(|e| JsError::new(&format!("invalid fen: {e:?}")))?;
+        Ok(WasmBoard { board })
+    }
+
+    #[wasm_bindgen(js_name = toFen)]
+    #[must_use]
+    pub fn to_fen(&self) -> String {
+        self.board.to_fen()
+    }
+
+    /// Legal moves in the UCI format.
+    #[wasm_bindgen(js_name = legalMoves)]
+    #[must_use]
+    pub fn legal_moves(&self) -> Vec<String> {
+        self.board.get_legal_moves().iter().map(|m| m.to_uci()).collect()
+    }
+
+    /// Promotions without the piece, e.g. "e7e8", promote to a queen.
+    ///
+    /// # Errors
+    /// If the move is not legal.
+    #[wasm_bindgen(js_name = makeMove)]
+    pub fn make_move(&mut self, uci: &str) -> Result<(), JsError> {
+        self.board.make_move_uci_with(uci, PromotionPolicy::Queen).map_err(|e| JsError::new(&format!("{uci}: {e:?}")))?;
+        Ok(())
+    }
+
+    /// Takes back the last move, returns false if no moves have been made.
+    #[wasm_bindgen(js_name = unmakeMove)]
+    pub fn unmake_move(&mut self) -> bool {
+        self.board.unmake_move().is_some()
+    }
+
+    /// Converts a legal move from the UCI format into the SAN format, e.g. "g1f3" -> "Nf3".
+    ///
+    /// # Errors
+    /// If the move is not legal.
+    pub fn san(&self, uci: &str) -> Result<String, JsError> {
+        let m = self.find_legal_move(uci).ok_or_else(|| JsError::new(&format!("{uci}: illegal move")))?;
+        Ok(self.board.move_to_san(m))
+    }
+}
+
+impl WasmBoard {
+    fn find_legal_move(&self, uci: &str) -> Option<Move> {
+        let uci = uci.to_ascii_lowercase();
+        self.board.get_legal_moves().into_iter().find(|m| m.to_uci() == uci)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only the successful paths, errors need a JavaScript runtime.
+    #[test]
+    fn test_wasm_board() {
+        let mut board = WasmBoard::new();
+        assert_eq!(board.legal_moves().len(), 20);
+        assert_eq!(board.san("g1f3").ok(), Some(String::from("Nf3")));
+        assert!(board.make_move("g1f3").is_ok());
+        assert!(board.make_move("e7e5").is_ok());
+        assert_eq!(board.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/8/5N2/PPPPPPPP/RNBQKB1R w KQkq e6 0 2");
+        assert!(board.unmake_move());
+
+        let Ok(board) = WasmBoard::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1") else { panic!("valid fen") };
+        assert_eq!(board.san("e1c1").ok(), Some(String::from("O-O-O")));
+    }
+}